@@ -25,6 +25,8 @@ async fn main() -> Result<(), RedisError> {
     tls: None,
     // whether to enable tracing
     tracing: false,
+    // use the defaults for everything else
+    ..Default::default()
   };
   // configure exponential backoff when reconnecting, starting at 100 ms, and doubling each time up to 30 sec.
   let policy = ReconnectPolicy::new_exponential(0, 100, 30_000, 2);
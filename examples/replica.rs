@@ -0,0 +1,42 @@
+use fred::prelude::*;
+use fred::replica::{self, Config, Event};
+use futures::stream::StreamExt;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::main]
+async fn main() -> Result<(), RedisError> {
+  pretty_env_logger::init();
+
+  let replica_jh = tokio::spawn(async move {
+    let config = Config::default();
+    let mut replica_stream = replica::run(config).await?;
+
+    while let Some(event) = replica_stream.next().await {
+      match event? {
+        Event::FullResync { replication_id, offset } => println!("Full resync from {} at {}", replication_id, offset),
+        Event::Rdb(chunk) => println!("Recv {} RDB bytes", chunk.len()),
+        Event::Command(command) => println!("{}", command),
+        _ => {}
+      };
+    }
+
+    Ok::<(), RedisError>(())
+  });
+
+  let config = RedisConfig::default();
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  if let Err(error) = client.wait_for_connect().await {
+    println!("Client failed to connect with error: {:?}", error);
+  }
+
+  for idx in 0..50 {
+    let _: () = client.set("foo", idx, Some(Expiration::EX(10)), None, false).await?;
+  }
+  let _ = client.quit().await?;
+
+  sleep(Duration::from_secs(1)).await;
+  replica_jh.abort();
+  Ok(())
+}
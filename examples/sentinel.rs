@@ -19,8 +19,10 @@ async fn main() -> Result<(), RedisError> {
       username: None,
       #[cfg(feature = "sentinel-auth")]
       password: None,
+      // sentinel nodes use the same TLS settings as the Redis servers unless the `sentinel-auth` feature is enabled
+      #[cfg(all(feature = "sentinel-auth", any(feature = "enable-tls", feature = "enable-rustls")))]
+      tls: None,
     },
-    // sentinels should use the same TLS settings as the Redis servers
     ..Default::default()
   };
 
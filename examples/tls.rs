@@ -12,6 +12,7 @@ async fn main() -> Result<(), RedisError> {
       max_protocol_version: None,
       disable_built_in_roots: false,
       use_sni: true,
+      ..TlsConfig::default()
     }),
     ..RedisConfig::default()
   };
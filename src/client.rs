@@ -1,30 +1,53 @@
 use crate::commands;
 use crate::error::{RedisError, RedisErrorKind};
 use crate::modules::inner::{MultiPolicy, RedisClientInner};
+use crate::modules::pubsub;
 use crate::modules::response::RedisResponse;
 use crate::multiplexer::commands as multiplexer_commands;
 use crate::multiplexer::utils as multiplexer_utils;
+use crate::protocol::utils as protocol_utils;
 use crate::types::*;
 use crate::utils;
-use futures::Stream;
-use std::collections::HashMap;
+use futures::{future, Stream, StreamExt};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryInto;
 use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::unbounded_channel;
-use tokio::time::interval as tokio_interval;
+use tokio::sync::watch;
+use tokio::time::{interval as tokio_interval, sleep};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 #[cfg(feature = "metrics")]
 use crate::modules::metrics::Stats;
+#[cfg(feature = "serde-json")]
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Utility functions used by the client that may also be useful to callers.
 pub mod util {
   pub use crate::utils::f64_to_redis_string;
   pub use crate::utils::redis_string_to_f64;
-  pub use redis_protocol::redis_keyslot;
+  pub use crate::utils::redis_keyslot;
+
+  /// Read the [hash tag](https://redis.io/topics/cluster-spec#keys-hash-tags) from a key, if any.
+  ///
+  /// Only the hash tag is hashed when computing a key's hash slot, so keys with the same hash tag always map to the same
+  /// hash slot.
+  pub fn hash_tag(key: &str) -> Option<&str> {
+    let start = key.find('{')?;
+    let end = key[start + 1..].find('}')?;
+
+    if end == 0 {
+      None
+    } else {
+      Some(&key[start + 1..start + 1 + end])
+    }
+  }
 
   /// Calculate the SHA1 hash output as a hex string. This is provided for clients that use the Lua interface to manage their own script caches.
   pub fn sha1_hash(input: &str) -> String {
@@ -73,6 +96,23 @@ impl TransactionClient {
   /// If the underlying connection closes while a transaction is in process the client will abort the transaction by
   /// returning a `Canceled` error to the caller of any pending intermediate command, as well as this one. It's up to
   /// the caller to retry transactions as needed.
+  ///
+  /// If the server rejected any of the queued commands this will return an `InvalidCommand` error describing the `EXECABORT` response.
+  ///
+  /// The results can be converted into a `Vec<RedisValue>` or into a tuple with one type per queued command.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+  ///   let trx = client.multi(true).await?;
+  ///   let _: () = trx.incr("foo").await?;
+  ///   let _: () = trx.set("bar", "baz", None, None, false).await?;
+  ///   let _: () = trx.get("wibble").await?;
+  ///
+  ///   let (foo, bar, wibble): (i64, String, Option<f64>) = trx.exec().await?;
+  ///   Ok(())
+  /// }
+  /// ```
   pub async fn exec<R>(mut self) -> Result<R, RedisError>
   where
     R: RedisResponse,
@@ -81,6 +121,18 @@ impl TransactionClient {
     commands::server::exec(&self.client.inner).await?.convert()
   }
 
+  /// Executes all previously queued commands in a transaction, returning a result for each command in the order the commands were queued.
+  ///
+  /// Unlike [exec](Self::exec), an error from one command (such as a `WRONGTYPE` error) does not hide the results of the other commands.
+  /// The outer `Result` contains errors that affect the entire transaction, such as an `EXECABORT` error or a `Canceled` error if the
+  /// transaction was aborted due to a `WATCH`ed key.
+  ///
+  /// <https://redis.io/commands/exec>
+  pub async fn exec_all(mut self) -> Result<Vec<Result<RedisValue, RedisError>>, RedisError> {
+    self.finished = true;
+    commands::server::exec_all(&self.client.inner).await
+  }
+
   /// Flushes all previously queued commands in a transaction and restores the connection state to normal.
   ///
   /// <https://redis.io/commands/discard>
@@ -113,7 +165,102 @@ impl<'a> From<&'a Arc<RedisClientInner>> for TransactionClient {
   }
 }
 
+/// A wrapping struct for commands buffered in a pipeline.
+///
+/// Commands sent while the pipeline is active resolve immediately with [RedisValue::Queued] and are written to the
+/// server together when calling [all](Self::all) or [try_all](Self::try_all). Dropping the pipeline without calling
+/// either function discards any buffered commands.
+pub struct Pipeline {
+  client: RedisClient,
+  finished: bool,
+}
+
+impl fmt::Display for Pipeline {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "[Pipeline {}: {}]", self.client.inner.id, self.client.state())
+  }
+}
+
+impl Drop for Pipeline {
+  fn drop(&mut self) {
+    if !self.finished && self.len() > 0 {
+      warn!(
+        "{}: Dropping pipeline without sending buffered commands!",
+        self.inner.client_name()
+      );
+    }
+  }
+}
+
+impl Pipeline {
+  /// Send all buffered commands to the server, returning the results of each command in the order the commands were buffered.
+  ///
+  /// The results are converted from an array with one value per command, so callers can read them as a `Vec<RedisValue>` or as
+  /// a tuple with one type per command. The first error returned by any command is returned instead of the results.
+  pub async fn all<R>(mut self) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+  {
+    self.finished = true;
+    let mut values = Vec::new();
+    for result in utils::send_pipeline(&self.client.inner).await?.into_iter() {
+      values.push(protocol_utils::frame_to_results(result?)?);
+    }
+
+    RedisValue::Array(values).convert()
+  }
+
+  /// Send all buffered commands to the server, returning a result for each command in the order the commands were buffered.
+  ///
+  /// Unlike [all](Self::all), an error from one command does not hide the results of the other commands.
+  pub async fn try_all(mut self) -> Result<Vec<Result<RedisValue, RedisError>>, RedisError> {
+    self.finished = true;
+    Ok(
+      utils::send_pipeline(&self.client.inner)
+        .await?
+        .into_iter()
+        .map(|result| result.and_then(protocol_utils::frame_to_results))
+        .collect(),
+    )
+  }
+
+  /// Read the number of commands buffered in the pipeline.
+  pub fn len(&self) -> usize {
+    self.inner.pipeline.as_ref().map(|c| c.read().len()).unwrap_or(0)
+  }
+}
+
+impl Deref for Pipeline {
+  type Target = RedisClient;
+
+  fn deref(&self) -> &Self::Target {
+    &self.client
+  }
+}
+
+#[doc(hidden)]
+impl<'a> From<&'a Arc<RedisClientInner>> for Pipeline {
+  fn from(inner: &'a Arc<RedisClientInner>) -> Self {
+    Pipeline {
+      client: RedisClient::from(inner),
+      finished: false,
+    }
+  }
+}
+
 /// A Redis client struct.
+///
+/// ## Dropping Commands
+///
+/// The futures returned by each command can be dropped at any time without affecting other commands:
+///
+/// * Commands that have not been written to the server are removed from the command queue.
+/// * Responses to commands that were already written to the server are read and discarded, so the connection stays in sync.
+/// * Blocking commands that were written to the server will continue to block the connection until they finish unless
+///   [unblock_on_drop](crate::types::RedisConfig::unblock_on_drop) is enabled, in which case the connection is unblocked on
+///   the backchannel. Blocking commands that were still queued are skipped without unblocking the connection.
+/// * Blocking stream reads on a dedicated connection, enabled via
+///   [dedicated_blocking_streams](crate::types::RedisConfig::dedicated_blocking_streams), close the dedicated connection.
 #[derive(Clone)]
 pub struct RedisClient {
   pub(crate) inner: Arc<RedisClientInner>,
@@ -177,7 +324,9 @@ impl RedisClient {
       if let Err(ref e) = result {
         multiplexer_utils::emit_connect_error(&inner, e);
       }
-      utils::set_client_state(&inner.state, ClientState::Disconnected);
+      if utils::read_client_state(&inner.state) != ClientState::Closed {
+        utils::set_client_state(&inner, ClientState::Disconnected);
+      }
       result
     })
   }
@@ -205,6 +354,8 @@ impl RedisClient {
   /// Return a future that will ping the server on an interval.
   ///
   /// If the underlying connection closes or `PING` returns an error this will break the interval and this function will need to be called again.
+  ///
+  /// Each `PING` is sent ahead of any other queued commands so that a deep command queue does not delay the health check.
   pub async fn enable_heartbeat(&self, interval: Duration) -> Result<(), RedisError> {
     let mut interval = tokio_interval(interval);
     loop {
@@ -219,7 +370,11 @@ impl RedisClient {
       if self.state() != ClientState::Connected {
         break;
       }
-      let _ = self.ping().await?;
+      let options = Options {
+        priority: true,
+        ..Default::default()
+      };
+      let _ = utils::with_command_options(options, self.ping()).await?;
     }
 
     Ok(())
@@ -237,6 +392,41 @@ impl RedisClient {
     utils::set_atomic(&self.inner.redeliver_count, 0)
   }
 
+  /// Return a future that will trim each of the provided streams with `XTRIM` on an interval.
+  ///
+  /// Errors trimming a stream are logged and do not break the interval. Ticks are skipped while the client is inside a
+  /// transaction or reconnecting. If the client disconnects or closes this will break the interval and this function
+  /// will need to be called again.
+  pub async fn enable_stream_trimming<P>(&self, interval: Duration, policies: Vec<P>) -> Result<(), RedisError>
+  where
+    P: Into<StreamTrimPolicy>,
+  {
+    let policies = policies.into_iter().map(|p| p.into()).collect();
+    commands::streams::trim_on_interval(&self.inner, interval, policies).await
+  }
+
+  /// Read the number of pubsub messages dropped because a listener's buffer was full.
+  ///
+  /// See [pubsub_buffer](crate::types::RedisConfig::pubsub_buffer) for more information.
+  pub fn read_pubsub_dropped_count(&self) -> usize {
+    utils::read_atomic(&self.inner.pubsub_dropped)
+  }
+
+  /// Read and reset the number of pubsub messages dropped because a listener's buffer was full.
+  pub fn take_pubsub_dropped_count(&self) -> usize {
+    utils::set_atomic(&self.inner.pubsub_dropped, 0)
+  }
+
+  /// Read the number of stream entries removed by [enable_stream_trimming](Self::enable_stream_trimming).
+  pub fn read_trimmed_count(&self) -> usize {
+    utils::read_atomic(&self.inner.trimmed_count)
+  }
+
+  /// Read and reset the number of stream entries removed by [enable_stream_trimming](Self::enable_stream_trimming).
+  pub fn take_trimmed_count(&self) -> usize {
+    utils::set_atomic(&self.inner.trimmed_count, 0)
+  }
+
   /// Read the state of the underlying connection(s).
   ///
   /// If running against a cluster the underlying state will reflect the state of the least healthy connection, if any.
@@ -249,11 +439,96 @@ impl RedisClient {
     *self.inner.state.read() == ClientState::Connected
   }
 
+  /// Listen for changes to the state of the underlying connection(s).
+  ///
+  /// The receiver starts with the current state and is notified each time the state changes, which can be used to drive readiness probes
+  /// or supervisors without polling the server with `PING`. Intermediate states may be skipped if the receiver falls behind.
+  pub fn watch_state(&self) -> watch::Receiver<ClientState> {
+    self.inner.state_tx.subscribe()
+  }
+
+  /// Read the server version, if known.
+  ///
+  /// The version is read via `INFO` the first time the client connects to each server. When connected to a cluster this is
+  /// the oldest version of any node. Commands that require a newer server version will return an `Unsupported` error
+  /// without being sent to the server.
+  pub fn server_version(&self) -> Option<Version> {
+    self.inner.server_version.read().clone()
+  }
+
+  /// Read the socket address used for the most recent connection to each server, keyed by the `host:port` of the server.
+  ///
+  /// When a host resolves to multiple addresses the client will try each of them in order, alternating between IPv6 and
+  /// IPv4 addresses. See [set_connection_attempt_timeout_ms](crate::globals::set_connection_attempt_timeout_ms) for more information.
+  pub fn connected_addresses(&self) -> HashMap<String, SocketAddr> {
+    self.inner.connected_addrs.read().clone()
+  }
+
   /// Read the cached state of the cluster used for routing commands to the correct cluster nodes.
   pub fn cached_cluster_state(&self) -> Option<ClusterKeyCache> {
     self.inner.cluster_state.read().clone()
   }
 
+  /// Read the `host:port` of the primary node that owns the provided hash slot in the cached cluster state.
+  pub fn cached_slot_owner(&self, slot: u16) -> Option<Arc<String>> {
+    self
+      .inner
+      .cluster_state
+      .read()
+      .as_ref()
+      .and_then(|state| state.get_server(slot).map(|range| range.server.clone()))
+  }
+
+  /// Group the provided keys by their [hash slot](https://redis.io/topics/cluster-spec#keys-distribution-model), preserving
+  /// the order of the keys within each group.
+  ///
+  /// Each group can be used in a multi-key command without a `CROSSSLOT` error.
+  pub fn split_by_slot<K>(&self, keys: K) -> BTreeMap<u16, Vec<RedisKey>>
+  where
+    K: Into<MultipleKeys>,
+  {
+    let mut out: BTreeMap<u16, Vec<RedisKey>> = BTreeMap::new();
+    for key in keys.into().inner().into_iter() {
+      out.entry(key.cluster_hash()).or_insert_with(Vec::new).push(key);
+    }
+
+    out
+  }
+
+  /// Rebuild the cached cluster state from `CLUSTER SLOTS`, falling back to `CLUSTER NODES` on older servers.
+  ///
+  /// The client already does this when a connection closes or the cluster reports a change that cannot be handled by
+  /// updating a single hash slot, so this is only necessary when callers know the cluster topology has changed.
+  pub async fn sync_cluster(&self) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::cluster::sync_cluster(&self.inner).await
+  }
+
+  /// Return a future that will refresh the cached cluster state on an interval.
+  ///
+  /// Ticks are skipped while the client is inside a transaction. If the underlying connection closes or the cluster
+  /// state cannot be read this will break the interval and this function will need to be called again.
+  pub async fn enable_cluster_refresh(&self, interval: Duration) -> Result<(), RedisError> {
+    utils::check_clustered(&self.inner)?;
+    let mut interval = tokio_interval(interval);
+    loop {
+      interval.tick().await;
+
+      if utils::is_locked_some(&self.inner.multi_block) {
+        let inner = &self.inner;
+        _debug!(inner, "Skip cluster refresh while inside transaction.");
+        continue;
+      }
+
+      if self.state() != ClientState::Connected {
+        break;
+      }
+      let _ = commands::cluster::sync_cluster(&self.inner).await?;
+    }
+
+    Ok(())
+  }
+
   /// Read latency metrics across all commands.
   ///
   /// This metric reflects the total latency experienced by callers, including time spent waiting in memory to be written and network latency.
@@ -316,11 +591,41 @@ impl RedisClient {
     self.inner.res_size_stats.write().take_metrics()
   }
 
+  /// Read cumulative network statistics for the connection to each server, keyed by the resolved `ip:port` address of the server.
+  ///
+  /// These statistics are not reset when the client reconnects.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  pub fn read_connection_stats(&self) -> HashMap<String, ConnectionStats> {
+    self
+      .inner
+      .connection_stats
+      .read()
+      .iter()
+      .map(|(server, counters)| (server.clone(), counters.read()))
+      .collect()
+  }
+
   /// Read the number of buffered commands that have not yet been sent to the server.
   pub fn command_queue_len(&self) -> usize {
     utils::read_atomic(&self.inner.cmd_buffer_len)
   }
 
+  /// Listen for connection events.
+  ///
+  /// The stream receives `Ok(())` each time the client connects or reconnects, and an error each time a connection attempt fails
+  /// and the client gives up or waits to try again. Unlike [wait_for_connect](Self::wait_for_connect) this does not emit an event if the
+  /// client is already connected.
+  ///
+  /// Similar to [on_reconnect](Self::on_reconnect) and [on_error](Self::on_error), this function does not need to be called again if the
+  /// connection closes.
+  pub fn on_connect(&self) -> impl Stream<Item = Result<(), RedisError>> {
+    let (tx, rx) = unbounded_channel();
+    self.inner.connection_tx.write().push_back(tx);
+
+    UnboundedReceiverStream::new(rx)
+  }
+
   /// Listen for reconnection notifications.
   ///
   /// This function can be used to receive notifications whenever the client successfully reconnects in order to select the right database again, re-subscribe to channels, etc.
@@ -346,12 +651,32 @@ impl RedisClient {
 
   /// Listen for `(channel, message)` tuples on the publish-subscribe interface. **Keyspace events are not sent on this interface.**
   ///
-  /// If the connection to the Redis server closes for any reason this function does not need to be called again. Messages will start appearing on the original stream after [subscribe](Self::subscribe) is called again.
+  /// If the connection to the Redis server closes for any reason this function does not need to be called again. The client will subscribe
+  /// to the same channels and patterns after reconnecting and messages will start appearing on the original stream.
   pub fn on_message(&self) -> impl Stream<Item = (String, RedisValue)> {
-    let (tx, rx) = unbounded_channel();
+    let (tx, rx) = pubsub::listener(&self.inner);
     self.inner.message_tx.write().push_back(tx);
 
-    UnboundedReceiverStream::new(rx)
+    rx
+  }
+
+  /// Listen for `(channel, message)` tuples on a single channel, or on the channels matching a pattern passed to [psubscribe](Self::psubscribe).
+  ///
+  /// This behaves like [on_message](Self::on_message) except that messages on other channels are filtered out, so each consumer can
+  /// read its own stream. The client must still subscribe to the channel or pattern separately.
+  pub fn message_stream<S>(&self, channel: S) -> impl Stream<Item = (String, RedisValue)>
+  where
+    S: Into<String>,
+  {
+    let filter = channel.into();
+    let inner = self.inner.clone();
+
+    self.on_message().filter(move |(channel, _)| {
+      let matches = *channel == filter
+        || (inner.subscriptions.read().patterns.contains(&filter)
+          && utils::glob_match(filter.as_bytes(), channel.as_bytes()));
+      future::ready(matches)
+    })
   }
 
   /// Listen for keyspace and keyevent notifications on the publish subscribe interface.
@@ -362,12 +687,40 @@ impl RedisClient {
   ///
   /// <https://redis.io/topics/notifications>
   pub fn on_keyspace_event(&self) -> impl Stream<Item = KeyspaceEvent> {
-    let (tx, rx) = unbounded_channel();
+    let (tx, rx) = pubsub::listener(&self.inner);
     self.inner.keyspace_tx.write().push_back(tx);
 
+    rx
+  }
+
+  /// Listen for out-of-band RESP3 push messages, such as pubsub messages and client tracking invalidations.
+  ///
+  /// Push messages that aren't part of the publish-subscribe interface are only sent to these streams. This requires
+  /// the client to use RESP3. See [version](crate::types::RedisConfig::version).
+  ///
+  /// If the connection to the Redis server closes for any reason this function does not need to be called again.
+  pub fn on_push(&self) -> impl Stream<Item = PushMessage> {
+    let (tx, rx) = unbounded_channel();
+    self.inner.push_tx.write().push_back(tx);
+
     UnboundedReceiverStream::new(rx)
   }
 
+  /// Listen for messages, keyspace notifications, and gaps in delivery on the publish-subscribe interface.
+  ///
+  /// When the connection closes and the client reconnects it subscribes again to the tracked channels and patterns.
+  /// Once every subscription has been restored a [GapDetected](crate::types::PubsubEvent::GapDetected) event is
+  /// emitted describing the window in which messages may have been missed, followed by a
+  /// [Restored](crate::types::PubsubEvent::Restored) event.
+  ///
+  /// If the connection to the Redis server closes for any reason this function does not need to be called again.
+  pub fn on_pubsub_event(&self) -> impl Stream<Item = PubsubEvent> {
+    let (tx, rx) = pubsub::listener(&self.inner);
+    self.inner.pubsub_tx.write().push_back(tx);
+
+    rx
+  }
+
   /// Whether or not the client is using a clustered Redis deployment.
   pub fn is_clustered(&self) -> bool {
     utils::is_clustered(&self.inner.config)
@@ -377,6 +730,9 @@ impl RedisClient {
   /// not when the connection has been fully closed. Some time after this future resolves the future returned by [connect](Self::connect)
   /// will resolve which indicates that the connection has been fully closed.
   ///
+  /// Before sending `QUIT` the client stops accepting new commands and waits for any buffered or in-flight commands to finish, up
+  /// to the [quit timeout](crate::globals::get_quit_timeout_ms). Commands sent while the client is closing return a `Canceled` error.
+  ///
   /// This function will also close all error, pubsub message, and reconnection event streams.
   pub async fn quit(&self) -> Result<(), RedisError> {
     commands::server::quit(&self.inner).await
@@ -390,6 +746,48 @@ impl RedisClient {
     commands::server::shutdown(&self.inner, flags).await
   }
 
+  /// Create a new client connected to the provided primary node in the cluster, identified by its `host:port`.
+  ///
+  /// This can be used to run commands that apply to a single server, such as `CONFIG`, `MEMORY`, or `CLIENT LIST`,
+  /// against a specific cluster node. The returned client uses its own connection, and callers should call `quit` on it
+  /// when finished.
+  pub async fn with_cluster_node<S>(&self, server: S) -> Result<RedisClient, RedisError>
+  where
+    S: AsRef<str>,
+  {
+    commands::cluster::connect_cluster_node(&self.inner, server.as_ref()).await
+  }
+
+  /// Run the provided function concurrently against each primary node in the cluster, returning the results keyed by the
+  /// `host:port` of each node.
+  ///
+  /// Each function call receives a client connected only to that node. The clients are created with
+  /// [split_cluster](Self::split_cluster) and are all closed after every function call finishes. If any connection or
+  /// call returns an error the first error is returned.
+  pub async fn each_cluster_node<F, Fut, R>(&self, func: F) -> Result<HashMap<String, R>, RedisError>
+  where
+    F: Fn(RedisClient) -> Fut,
+    Fut: Future<Output = Result<R, RedisError>>,
+  {
+    let clients = self.split_cluster().await?;
+    let func = &func;
+    let calls = clients.iter().map(|client| async move {
+      let server = match client.inner.config.read().server.hosts().first() {
+        Some((host, port)) => format!("{}:{}", host, port),
+        None => return Err(RedisError::new(RedisErrorKind::Unknown, "Missing cluster node host.")),
+      };
+      let _ = client.connect(None);
+      let _ = client.wait_for_connect().await?;
+
+      func(client.clone()).await.map(|result| (server, result))
+    });
+    // run every call to completion so that each client can be closed, even if an earlier call fails
+    let results = future::join_all(calls).await;
+    let _ = future::join_all(clients.iter().map(|client| client.quit())).await;
+
+    results.into_iter().collect()
+  }
+
   /// Split a clustered Redis client into a list of centralized clients - one for each primary node in the cluster.
   ///
   /// Some Redis commands are not designed to work with hash slots against a clustered deployment. For example,
@@ -460,6 +858,70 @@ impl RedisClient {
     commands::server::dbsize(&self.inner).await?.convert()
   }
 
+  /// Block the current client until all previous write commands are acknowledged by at least `numreplicas` replicas, or until
+  /// `timeout` milliseconds elapse, returning the number of replicas that acknowledged the writes.
+  ///
+  /// When used against a cluster this will run on a random node. Callers should use [write_with_ack](Self::write_with_ack) instead.
+  ///
+  /// <https://redis.io/commands/wait>
+  pub async fn wait<R>(&self, numreplicas: i64, timeout: i64) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::server::wait(&self.inner, numreplicas, timeout, None).await?.convert()
+  }
+
+  /// Run a write command and then call [WAIT](https://redis.io/commands/wait) on the same connection, returning the result of the
+  /// write and the number of replicas that acknowledged it.
+  ///
+  /// The `key` is used to route `WAIT` to the node that received the write when used against a cluster, and should be the key
+  /// modified by the `write` future. If fewer than `min_replicas` replicas acknowledge the write within `timeout` milliseconds a
+  /// `NotEnoughReplicas` error is returned. Note that the write is not rolled back in this case.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+  ///   let (_, replicas): ((), i64) = client
+  ///     .write_with_ack("foo", 1, 1000, client.set("foo", "bar", None, None, false))
+  ///     .await?;
+  ///
+  ///   println!("Write acknowledged by {} replicas", replicas);
+  ///   Ok(())
+  /// }
+  /// ```
+  pub async fn write_with_ack<R, K, F>(
+    &self,
+    key: K,
+    min_replicas: i64,
+    timeout: i64,
+    write: F,
+  ) -> Result<(R, i64), RedisError>
+  where
+    K: Into<RedisKey>,
+    F: Future<Output = Result<R, RedisError>>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    let hash_slot = key.into().cluster_hash();
+
+    let result = write.await?;
+    let replicas = commands::server::wait(&self.inner, min_replicas, timeout, Some(hash_slot))
+      .await?
+      .convert::<i64>()?;
+
+    if replicas < min_replicas {
+      Err(RedisError::new(
+        RedisErrorKind::NotEnoughReplicas,
+        format!(
+          "Write acknowledged by {} replicas, but {} were required.",
+          replicas, min_replicas
+        ),
+      ))
+    } else {
+      Ok((result, replicas))
+    }
+  }
+
   /// Run a custom command that is not yet supported via another interface on this client. This is most useful when interacting with third party modules or extensions.
   ///
   /// This interface makes some assumptions about the nature of the provided command:
@@ -472,6 +934,10 @@ impl RedisClient {
   ///
   /// Callers should use the re-exported [redis_keyslot](crate::client::util::redis_keyslot) function to hash the command's key, if necessary.
   ///
+  /// When the client uses RESP3 the response keeps the RESP3 type information, so maps, sets, doubles, and booleans are
+  /// returned as the corresponding `RedisValue` variants. Use [into_resp2](crate::types::RedisValue::into_resp2) to read the
+  /// RESP2 equivalent.
+  ///
   /// Callers that find themselves using this interface for commands that are not a part of a third party extension should file an issue
   /// to add the command to the list of supported commands. This interface should be used with caution as it may break the automatic pipeline
   /// features in the client if command flags are not properly configured.
@@ -486,6 +952,94 @@ impl RedisClient {
       .convert()
   }
 
+  /// Run the provided future with the provided options applied to any commands it sends.
+  ///
+  /// If the deadline passes or the cancellation token is triggered then commands that were not yet written to the server are
+  /// dropped and in-flight commands return a `Canceled` error. Responses to in-flight commands are read and discarded when they arrive.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// # use fred::types::{CancellationToken, Options};
+  /// # use std::time::{Duration, Instant};
+  /// async fn example(client: &RedisClient, token: CancellationToken) -> Result<(), RedisError> {
+  ///   let options = Options {
+  ///     deadline: Some(Instant::now() + Duration::from_millis(100)),
+  ///     cancel: Some(token),
+  ///     ..Default::default()
+  ///   };
+  ///
+  ///   let value: Option<String> = client.with_options(options, client.get("foo")).await?;
+  ///   Ok(())
+  /// }
+  /// ```
+  pub async fn with_options<F>(&self, options: Options, ft: F) -> F::Output
+  where
+    F: Future,
+  {
+    utils::with_command_options(options, ft).await
+  }
+
+  /// Register a policy used to find the hash slot for a [custom](Self::custom) command when running against a cluster.
+  ///
+  /// The policy is used when the `hash_slot` on the [CustomCommand](crate::types::CustomCommand) is `None`. Command names are
+  /// case-insensitive, and registering a policy for the same command again will replace the previous policy.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// # use fred::types::{CustomCommand, KeyPosition};
+  /// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+  ///   // the key is the second argument to `MODULE.CMD`
+  ///   client.set_custom_routing("MODULE.CMD", KeyPosition::Index(1));
+  ///
+  ///   let cmd = CustomCommand {
+  ///     cmd: "MODULE.CMD",
+  ///     hash_slot: None,
+  ///     is_blocking: false,
+  ///   };
+  ///   let _: () = client.custom(cmd, vec!["arg", "key"]).await?;
+  ///   Ok(())
+  /// }
+  /// ```
+  pub fn set_custom_routing<S, R>(&self, cmd: S, routing: R)
+  where
+    S: AsRef<str>,
+    R: ClusterRouting,
+  {
+    self
+      .inner
+      .custom_routing
+      .write()
+      .insert(cmd.as_ref().to_uppercase(), Arc::new(routing));
+  }
+
+  /// Register a hook that runs before each command is sent and after each response is received.
+  ///
+  /// Hooks run in the order they were added. This can be used for custom metrics, audit logging, or rewriting command arguments.
+  pub fn add_command_hook<H>(&self, hook: H)
+  where
+    H: CommandHook,
+  {
+    self.inner.command_hooks.write().push(Arc::new(hook));
+  }
+
+  /// Register a callback that runs when the client stops trying to reconnect.
+  ///
+  /// This happens when the reconnect policy runs out of attempts or when the client sees one of the
+  /// [unrecoverable_errors](crate::types::RedisConfig::unrecoverable_errors) while reconnecting. Callbacks run on the reconnection
+  /// task after pending commands fail and the client moves to the [Closed](crate::types::ClientState::Closed) state, so applications
+  /// can exit or switch to a fallback store.
+  pub fn on_unrecoverable<F>(&self, func: F)
+  where
+    F: Fn(&RedisError) + Send + Sync + 'static,
+  {
+    self.inner.unrecoverable_callbacks.write().push(Arc::new(func));
+  }
+
+  /// Remove all command hooks from the client.
+  pub fn clear_command_hooks(&self) {
+    self.inner.command_hooks.write().clear();
+  }
+
   /// Subscribe to a channel on the PubSub interface, returning the number of channels to which the client is subscribed.
   ///
   /// Any messages received before [on_message](Self::on_message) is called will be discarded, so it's usually best to call [on_message](Self::on_message)
@@ -533,6 +1087,16 @@ impl RedisClient {
     commands::pubsub::punsubscribe(&self.inner, patterns).await
   }
 
+  /// Read the channels to which the client is subscribed.
+  pub fn tracked_channels(&self) -> BTreeSet<String> {
+    self.inner.subscriptions.read().channels.clone()
+  }
+
+  /// Read the patterns to which the client is subscribed.
+  pub fn tracked_patterns(&self) -> BTreeSet<String> {
+    self.inner.subscriptions.read().patterns.clone()
+  }
+
   /// Publish a message on the PubSub interface, returning the number of clients that received the message.
   ///
   /// <https://redis.io/commands/publish>
@@ -548,25 +1112,86 @@ impl RedisClient {
       .convert()
   }
 
-  /// Enter a MULTI block, executing subsequent commands as a transaction.
+  /// Subscribe to a sharded channel, returning the number of sharded channels to which the client is subscribed on the node that owns the channel.
   ///
-  /// <https://redis.io/commands/multi>
+  /// In a cluster the command is sent to the node that owns the channel's hash slot. If the slot later moves the server will
+  /// unsubscribe the client and the client will automatically subscribe again on the new owner.
   ///
-  /// The `abort_on_error` flag indicates whether the client should automatically abort the transaction when an error is received from a command within the transaction.
+  /// <https://redis.io/commands/ssubscribe>
+  pub async fn ssubscribe<S>(&self, channel: S) -> Result<usize, RedisError>
+  where
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::pubsub::ssubscribe(&self.inner, channel).await
+  }
+
+  /// Unsubscribe from a sharded channel, returning the number of sharded channels to which the client is subscribed on the node that owns the channel.
   ///
-  /// See <https://redis.io/topics/transactions#errors-inside-a-transaction> for more information. If this flag is `false` then the caller will need to `exec` or `discard`
-  /// the transaction before either retrying or moving on to new commands outside the transaction.
-  pub async fn multi(&self, abort_on_error: bool) -> Result<TransactionClient, RedisError> {
-    if utils::is_clustered(&self.inner.config) {
-      let policy = MultiPolicy {
-        hash_slot: None,
-        abort_on_error,
-        sent_multi: false,
-      };
+  /// <https://redis.io/commands/sunsubscribe>
+  pub async fn sunsubscribe<S>(&self, channel: S) -> Result<usize, RedisError>
+  where
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::pubsub::sunsubscribe(&self.inner, channel).await
+  }
 
-      if !utils::check_and_set_none(&self.inner.multi_block, policy) {
-        return Err(RedisError::new(
-          RedisErrorKind::InvalidCommand,
+  /// Publish a message on a sharded channel, returning the number of clients that received the message.
+  ///
+  /// <https://redis.io/commands/spublish>
+  pub async fn spublish<R, S, V>(&self, channel: S, message: V) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    S: Into<String>,
+    V: TryInto<RedisValue>,
+    V::Error: Into<RedisError>,
+  {
+    commands::pubsub::spublish(&self.inner, channel, to!(message)?)
+      .await?
+      .convert()
+  }
+
+  /// Read the sharded channels to which the client is subscribed.
+  pub fn tracked_shard_channels(&self) -> BTreeSet<String> {
+    self.inner.subscriptions.read().shard_channels.clone()
+  }
+
+  /// Enter a MULTI block, executing subsequent commands as a transaction.
+  ///
+  /// <https://redis.io/commands/multi>
+  ///
+  /// The `abort_on_error` flag indicates whether the client should automatically abort the transaction when an error is received from a command within the transaction.
+  ///
+  /// See <https://redis.io/topics/transactions#errors-inside-a-transaction> for more information. If this flag is `false` then the caller will need to `exec` or `discard`
+  /// the transaction before either retrying or moving on to new commands outside the transaction.
+  ///
+  /// Commands inside the transaction resolve with [RedisValue::Queued] once the server queues them. If the server rejects a command while
+  /// `abort_on_error` is `true` then any subsequent commands in the transaction will return a `Canceled` error without being sent to the server,
+  /// and `EXEC` will return the server's `EXECABORT` error.
+  ///
+  /// When the client is clustered the entire transaction is sent to the node that owns the hash slot of the first command with a key.
+  /// Commands with keys that map to a different hash slot, or commands without a key that are sent before the hash slot is known, will
+  /// return an error without being sent to the server.
+  pub async fn multi(&self, abort_on_error: bool) -> Result<TransactionClient, RedisError> {
+    if self.inner.pipeline.is_some() {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidCommand,
+        "Cannot start a transaction while building a pipeline.",
+      ));
+    }
+
+    if utils::is_clustered(&self.inner.config) {
+      let policy = MultiPolicy {
+        hash_slot: None,
+        abort_on_error,
+        sent_multi: false,
+        aborted: false,
+      };
+
+      if !utils::check_and_set_none(&self.inner.multi_block, policy) {
+        return Err(RedisError::new(
+          RedisErrorKind::InvalidCommand,
           "Client is already within a MULTI transaction.",
         ));
       }
@@ -578,6 +1203,7 @@ impl RedisClient {
         hash_slot: None,
         abort_on_error,
         sent_multi: true,
+        aborted: false,
       };
       if !utils::check_and_set_none(&self.inner.multi_block, policy) {
         return Err(RedisError::new(
@@ -597,6 +1223,49 @@ impl RedisClient {
     utils::is_locked_some(&self.inner.multi_block)
   }
 
+  /// Start buffering commands in a pipeline.
+  ///
+  /// Commands sent on the client while the returned [Pipeline] is active are not sent to the server until the pipeline's
+  /// [all](Pipeline::all) or [try_all](Pipeline::try_all) function is called, at which point they are all written to the
+  /// socket before flushing. This avoids paying the round trip latency for each command when loading data in bulk.
+  ///
+  /// Only commands sent through the returned pipeline are buffered. Commands sent on this client, its clones, or other
+  /// pipelines are sent normally, so each task can build its own pipeline.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// # use std::collections::HashMap;
+  /// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+  ///   let pipeline = client.pipeline()?;
+  ///   let _: () = pipeline.incr("foo").await?;
+  ///   let _: () = pipeline.hgetall("bar").await?;
+  ///
+  ///   let (foo, bar): (i64, HashMap<String, String>) = pipeline.all().await?;
+  ///   Ok(())
+  /// }
+  /// ```
+  pub fn pipeline(&self) -> Result<Pipeline, RedisError> {
+    if utils::is_locked_some(&self.inner.multi_block) {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidCommand,
+        "Cannot build a pipeline within a MULTI transaction.",
+      ));
+    }
+    if self.inner.pipeline.is_some() {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidCommand,
+        "Client is already building a pipeline.",
+      ));
+    }
+
+    Ok(Pipeline::from(&self.inner.with_pipeline()))
+  }
+
+  /// Whether or not this client is a [Pipeline] handle that buffers the commands sent through it.
+  pub fn in_pipeline(&self) -> bool {
+    self.inner.pipeline.is_some()
+  }
+
   /// Force the client to abort any in-flight transactions.
   ///
   /// The `Drop` trait on the [TransactionClient] is not async and so callers that accidentally drop the transaction
@@ -624,6 +1293,82 @@ impl RedisClient {
     commands::keys::unwatch(&self.inner).await
   }
 
+  /// Run an optimistic transaction against the provided keys, retrying whenever `EXEC` aborts because a watched key changed.
+  ///
+  /// On each attempt the keys are `WATCH`ed and the function is called with this client. The function should read any values it
+  /// needs, start the transaction with [multi](Self::multi), queue the transaction's commands, and return the [TransactionClient]
+  /// without calling `EXEC`. This function then calls `EXEC` and converts the results, or calls the function again if the
+  /// transaction was aborted.
+  ///
+  /// If the function returns an error the keys are unwatched, any transaction it started is discarded, and the error is returned.
+  /// Aborted transactions are retried after a short, increasing delay. If the transaction is aborted `max_attempts` times a
+  /// `Canceled` error is returned.
+  ///
+  /// Note: `WATCH` state belongs to the connection, which is shared by every task using this client or its clones. Other
+  /// tasks that call `WATCH`, `UNWATCH`, `MULTI`, or `EXEC` on the same client while this function runs will change the
+  /// watched keys, so callers should use a separate client for each concurrent optimistic transaction.
+  ///
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// async fn incr_by_two(client: &RedisClient) -> Result<(), RedisError> {
+  ///   client
+  ///     .transact_with_watch("foo", 10, |client| async move {
+  ///       let current: i64 = client.get::<Option<i64>, _>("foo").await?.unwrap_or(0);
+  ///       let trx = client.multi(true).await?;
+  ///       let _: () = trx.set("foo", current + 2, None, None, false).await?;
+  ///       Ok(trx)
+  ///     })
+  ///     .await
+  /// }
+  /// ```
+  pub async fn transact_with_watch<K, F, Fut, R>(
+    &self,
+    keys: K,
+    max_attempts: u32,
+    mut func: F,
+  ) -> Result<R, RedisError>
+  where
+    K: Into<MultipleKeys>,
+    F: FnMut(RedisClient) -> Fut,
+    Fut: Future<Output = Result<TransactionClient, RedisError>>,
+    R: RedisResponse,
+  {
+    let keys = keys.into().inner();
+
+    for attempt in 0 .. max_attempts {
+      if attempt > 0 {
+        let delay = cmp::min(10 << cmp::min(attempt, 7), 1000);
+        sleep(Duration::from_millis(utils::add_jitter(delay, 10))).await;
+      }
+
+      let _ = self.watch(keys.iter().cloned().collect::<MultipleKeys>()).await?;
+
+      let trx = match func(self.clone()).await {
+        Ok(trx) => trx,
+        Err(e) => {
+          if self.in_transaction() {
+            let _ = self.force_discard_transaction().await;
+          }
+          let _ = self.unwatch().await;
+          return Err(e);
+        }
+      };
+
+      let result: RedisValue = trx.exec().await?;
+      if result.is_null() {
+        debug!("{}: Retrying transaction after a watched key changed.", self.inner.id);
+        continue;
+      }
+
+      return result.convert();
+    }
+
+    Err(RedisError::new(
+      RedisErrorKind::Canceled,
+      format!("Transaction aborted after {} attempts.", max_attempts),
+    ))
+  }
+
   /// Delete the keys in all databases.
   ///
   /// <https://redis.io/commands/flushall>
@@ -848,6 +1593,16 @@ impl RedisClient {
     commands::client::unblock_self(&self.inner, flag).await
   }
 
+  /// Close the blocked connection with `CLIENT KILL`, forcing the client to reconnect according to its reconnect policy.
+  ///
+  /// This can be used to recover a connection that cannot be interrupted with [unblock_self](Self::unblock_self).
+  ///
+  /// Note: this command is sent on a backchannel connection and will work even when the main connection is blocked.
+  pub async fn kill_blocked_connection(&self) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::client::kill_blocked_connection(&self.inner).await
+  }
+
   // ------------- CLUSTER -----------
 
   /// Advances the cluster config epoch.
@@ -898,11 +1653,19 @@ impl RedisClient {
   /// CLUSTER SLOTS returns details about which cluster slots map to which Redis instances.
   ///
   /// <https://redis.io/commands/cluster-slots>
-  pub async fn cluster_slots(&self) -> Result<RedisValue, RedisError> {
+  pub async fn cluster_slots(&self) -> Result<Vec<ClusterSlotRange>, RedisError> {
     utils::disallow_during_transaction(&self.inner)?;
     commands::cluster::cluster_slots(&self.inner).await
   }
 
+  /// CLUSTER SHARDS returns details about the shards of the cluster, including the health of each node.
+  ///
+  /// <https://redis.io/commands/cluster-shards>
+  pub async fn cluster_shards(&self) -> Result<Vec<ClusterShard>, RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::cluster::cluster_shards(&self.inner).await
+  }
+
   /// CLUSTER INFO provides INFO style information about Redis Cluster vital parameters.
   ///
   /// <https://redis.io/commands/cluster-info>
@@ -922,6 +1685,24 @@ impl RedisClient {
     commands::cluster::cluster_add_slots(&self.inner, slots).await
   }
 
+  /// Assign the provided inclusive ranges of hash slots to the node receiving the command.
+  ///
+  /// Use [with_cluster_node](Self::with_cluster_node) to send this to a specific node in the cluster.
+  ///
+  /// <https://redis.io/commands/cluster-addslotsrange>
+  pub async fn cluster_add_slots_range(&self, ranges: Vec<(u16, u16)>) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::cluster::cluster_add_slots_range(&self.inner, ranges).await
+  }
+
+  /// Remove the provided inclusive ranges of hash slots from the node receiving the command.
+  ///
+  /// <https://redis.io/commands/cluster-delslotsrange>
+  pub async fn cluster_del_slots_range(&self, ranges: Vec<(u16, u16)>) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::cluster::cluster_del_slots_range(&self.inner, ranges).await
+  }
+
   /// The command returns the number of failure reports for the specified node.
   ///
   /// <https://redis.io/commands/cluster-count-failure-reports>
@@ -1057,6 +1838,8 @@ impl RedisClient {
 
   /// CLUSTER SETSLOT is responsible of changing the state of a hash slot in the receiving node in different ways.
   ///
+  /// Use [with_cluster_node](Self::with_cluster_node) to send this to a specific node in the cluster.
+  ///
   /// <https://redis.io/commands/cluster-setslot>
   pub async fn cluster_setslot(&self, slot: u16, state: ClusterSetSlotState) -> Result<(), RedisError> {
     utils::disallow_during_transaction(&self.inner)?;
@@ -1300,6 +2083,9 @@ impl RedisClient {
 
   /// Serialize the value stored at `key` in a Redis-specific format and return it as bulk string.
   ///
+  /// The payload is always returned as a `Bytes` value, or `Null` if the key does not exist. See [decode](crate::dump::decode)
+  /// to read the payload without a server.
+  ///
   /// <https://redis.io/commands/dump>
   pub async fn dump<K>(&self, key: K) -> Result<RedisValue, RedisError>
   where
@@ -1310,12 +2096,14 @@ impl RedisClient {
 
   /// Create a key associated with a value that is obtained by deserializing the provided serialized value
   ///
+  /// The payload can be the value returned by [dump](Self::dump) or the raw bytes as a `Bytes` or `&[u8]`.
+  ///
   /// <https://redis.io/commands/restore>
-  pub async fn restore<K>(
+  pub async fn restore<K, V>(
     &self,
     key: K,
     ttl: i64,
-    serialized: RedisValue,
+    serialized: V,
     replace: bool,
     absttl: bool,
     idletime: Option<i64>,
@@ -1323,10 +2111,66 @@ impl RedisClient {
   ) -> Result<RedisValue, RedisError>
   where
     K: Into<RedisKey>,
+    V: Into<RedisValue>,
   {
     commands::keys::restore(&self.inner, key, ttl, serialized, replace, absttl, idletime, frequency).await
   }
 
+  /// Atomically transfer one or more keys from the server to the database `db` on the destination server.
+  ///
+  /// A single key is sent with the positional form of the command and multiple keys are sent with the `KEYS` form. The
+  /// command returns `OK`, or `NOKEY` if none of the keys exist. In a cluster all the keys must map to the same hash slot.
+  ///
+  /// <https://redis.io/commands/migrate>
+  pub async fn migrate<R, H, K>(
+    &self,
+    host: H,
+    port: u16,
+    keys: K,
+    db: u8,
+    timeout_ms: u64,
+    copy: bool,
+    replace: bool,
+    auth: Option<MigrateAuth>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    H: Into<String>,
+    K: Into<MultipleKeys>,
+  {
+    commands::keys::migrate(&self.inner, host, port, keys, db, timeout_ms, copy, replace, auth)
+      .await?
+      .convert()
+  }
+
+  /// Sort the elements in the list, set, or sorted set at `key`.
+  ///
+  /// If a `STORE` destination is provided the command returns the number of elements in the destination list, otherwise
+  /// it returns the sorted elements.
+  ///
+  /// <https://redis.io/commands/sort>
+  pub async fn sort<R, K>(&self, key: K, options: SortOptions) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::sort(&self.inner, key, options).await?.convert()
+  }
+
+  /// Read-only variant of [sort](Self::sort). The options must not include a `STORE` destination.
+  ///
+  /// The server treats `SORT_RO` as a read-only command, but this client still sends it to the primary node that owns the
+  /// key. This requires Redis 7 or later.
+  ///
+  /// <https://redis.io/commands/sort_ro>
+  pub async fn sort_ro<R, K>(&self, key: K, options: SortOptions) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::sort_ro(&self.inner, key, options).await?.convert()
+  }
+
   /// Set a value with optional NX|XX, EX|PX|EXAT|PXAT|KEEPTTL, and GET arguments.
   ///
   /// <https://redis.io/commands/set>
@@ -1361,8 +2205,92 @@ impl RedisClient {
     commands::keys::get(&self.inner, key).await?.convert()
   }
 
+  /// Serialize `value` to JSON and write it with `SET`, with the same optional NX|XX and EX|PX|EXAT|PXAT|KEEPTTL arguments as
+  /// [set](Self::set).
+  ///
+  /// <https://redis.io/commands/set>
+  #[cfg(feature = "serde-json")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "serde-json")))]
+  pub async fn json_set<R, K, T>(
+    &self,
+    key: K,
+    value: &T,
+    expire: Option<Expiration>,
+    options: Option<SetOptions>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    T: Serialize + ?Sized,
+  {
+    let value = serde_json::to_string(value)?;
+    commands::keys::set(&self.inner, key.into(), value.into(), expire, options, false)
+      .await?
+      .convert()
+  }
+
+  /// Read a value with `GET` and deserialize it from JSON, returning `None` if the key does not exist.
+  ///
+  /// <https://redis.io/commands/get>
+  #[cfg(feature = "serde-json")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "serde-json")))]
+  pub async fn json_get<T, K>(&self, key: K) -> Result<Option<T>, RedisError>
+  where
+    T: DeserializeOwned,
+    K: Into<RedisKey>,
+  {
+    match commands::keys::get(&self.inner, key).await? {
+      RedisValue::Null => Ok(None),
+      RedisValue::String(s) => Ok(Some(serde_json::from_str(&s)?)),
+      RedisValue::Bytes(b) => Ok(Some(serde_json::from_slice(&b)?)),
+      _ => Err(RedisError::new_parse("Expected string value for JSON.")),
+    }
+  }
+
+  /// Read a value from the client-side cache, falling back to `GET` and caching the result on a cache miss.
+  ///
+  /// Cached values are removed when the server sends an invalidation message for the key. This requires the client to
+  /// be configured with [tracking](crate::types::RedisConfig::tracking).
+  ///
+  /// With [OptIn](crate::types::TrackingMode::OptIn) tracking the `GET` is sent with the
+  /// [caching](crate::types::Options::caching) option set to `true`. Values read with the `caching` option set to
+  /// `false` are not cached. In [Bcast](crate::types::TrackingMode::Bcast) mode keys that do not match any of the
+  /// prefixes are read with `GET` and are not cached.
+  ///
+  /// <https://redis.io/topics/client-side-caching>
+  pub async fn cached_get<R, K>(&self, key: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::cached_get(&self.inner, key).await?.convert()
+  }
+
+  /// Listen for keys invalidated by the server when [tracking](crate::types::RedisConfig::tracking) is enabled.
+  ///
+  /// In broadcast mode this includes every modified key that matches one of the configured prefixes. Messages that
+  /// invalidate the entire keyspace, such as after `FLUSHALL`, clear the cache but are not sent to these streams.
+  ///
+  /// If the connection to the Redis server closes for any reason this function does not need to be called again.
+  pub fn on_invalidation(&self) -> impl Stream<Item = RedisKey> {
+    UnboundedReceiverStream::new(self.inner.cache.add_listener())
+  }
+
+  /// Read the hit, miss, invalidation, and eviction counts for the client-side cache.
+  pub fn cache_stats(&self) -> CacheStats {
+    self.inner.cache.stats()
+  }
+
+  /// Remove all values from the client-side cache.
+  pub fn clear_cache(&self) {
+    self.inner.cache.clear();
+  }
+
   /// Returns the substring of the string value stored at `key` with offsets `start` and `end` (both inclusive).
   ///
+  /// Offsets past the end of the string are limited to the length of the string, and a missing key is treated as an empty
+  /// string.
+  ///
   /// Note: Command formerly called SUBSTR in Redis verison <=2.0.
   ///
   /// <https://redis.io/commands/getrange>
@@ -1376,8 +2304,11 @@ impl RedisClient {
 
   /// Overwrites part of the string stored at `key`, starting at the specified `offset`, for the entire length of `value`.
   ///
+  /// If the key does not exist, or the string is shorter than `offset`, the string is padded with zero bytes up to
+  /// `offset` before `value` is written. Returns the length of the string after it was modified.
+  ///
   /// <https://redis.io/commands/setrange>
-  pub async fn setrange<R, K, V>(&self, key: K, offset: u32, value: V) -> Result<R, RedisError>
+  pub async fn setrange<R, K, V>(&self, key: K, offset: usize, value: V) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
@@ -1389,6 +2320,65 @@ impl RedisClient {
       .convert()
   }
 
+  /// Append `value` to the string stored at `key`, creating the key if it does not exist.
+  ///
+  /// Returns the length of the string after the append operation.
+  ///
+  /// <https://redis.io/commands/append>
+  pub async fn append<R, K, V>(&self, key: K, value: V) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    V: TryInto<RedisValue>,
+    V::Error: Into<RedisError>,
+  {
+    commands::keys::append(&self.inner, key, to!(value)?).await?.convert()
+  }
+
+  /// Returns the longest common subsequence of the string values stored at `key1` and `key2`.
+  ///
+  /// <https://redis.io/commands/lcs>
+  pub async fn lcs<R, K1, K2>(&self, key1: K1, key2: K2) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K1: Into<RedisKey>,
+    K2: Into<RedisKey>,
+  {
+    commands::keys::lcs(&self.inner, key1, key2, false).await?.convert()
+  }
+
+  /// Returns the length of the longest common subsequence of the string values stored at `key1` and `key2`.
+  ///
+  /// <https://redis.io/commands/lcs>
+  pub async fn lcs_len<R, K1, K2>(&self, key1: K1, key2: K2) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K1: Into<RedisKey>,
+    K2: Into<RedisKey>,
+  {
+    commands::keys::lcs(&self.inner, key1, key2, true).await?.convert()
+  }
+
+  /// Returns the position of each substring in the longest common subsequence of the string values stored at `key1`
+  /// and `key2`, ignoring substrings shorter than `min_match_len`.
+  ///
+  /// If `with_match_len` is `true` each match will include the length of the substring.
+  ///
+  /// <https://redis.io/commands/lcs>
+  pub async fn lcs_idx<K1, K2>(
+    &self,
+    key1: K1,
+    key2: K2,
+    min_match_len: Option<u64>,
+    with_match_len: bool,
+  ) -> Result<LcsMatches, RedisError>
+  where
+    K1: Into<RedisKey>,
+    K2: Into<RedisKey>,
+  {
+    commands::keys::lcs_idx(&self.inner, key1, key2, min_match_len, with_match_len).await
+  }
+
   /// Atomically sets `key` to `value` and returns the old value stored at `key`.
   ///
   /// Returns an error if `key` does not hold string value. Returns nil if `key` does not exist.
@@ -1439,6 +2429,19 @@ impl RedisClient {
     commands::keys::del(&self.inner, keys).await?.convert()
   }
 
+  /// Removes the specified keys like [del](Self::del), but reclaims the memory used by the keys in a background thread.
+  ///
+  /// Returns the number of keys removed.
+  ///
+  /// <https://redis.io/commands/unlink>
+  pub async fn unlink<R, K>(&self, keys: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<MultipleKeys>,
+  {
+    commands::keys::unlink(&self.inner, keys).await?.convert()
+  }
+
   /// Returns the values of all specified keys. For every key that does not hold a string value or does not exist, the special value nil is returned.
   ///
   /// <https://redis.io/commands/mget>
@@ -1558,100 +2561,234 @@ impl RedisClient {
     commands::keys::pttl(&self.inner, key).await?.convert()
   }
 
-  /// Remove the existing timeout on a key, turning the key from volatile (a key with an expiration)
-  /// to persistent (a key that will never expire as no timeout is associated).
+  /// Returns the internal encoding of the value stored at `key`, or `nil` if the key does not exist.
   ///
-  /// Returns a boolean value describing whether or not the timeout was removed.
+  /// The result can be converted into an [ObjectEncoding](crate::types::ObjectEncoding).
   ///
-  /// <https://redis.io/commands/persist>
-  pub async fn persist<R, K>(&self, key: K) -> Result<R, RedisError>
+  /// <https://redis.io/commands/object-encoding>
+  pub async fn object_encoding<R, K>(&self, key: K) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
   {
-    commands::keys::persist(&self.inner, key).await?.convert()
+    commands::keys::object_encoding(&self.inner, key).await?.convert()
   }
 
-  /// Set a timeout on key. After the timeout has expired, the key will be automatically deleted.
+  /// Returns the logarithmic access frequency counter of the value stored at `key`, or `nil` if the key does not
+  /// exist.
   ///
-  /// Returns a boolean value describing whether or not the timeout was added.
+  /// The server returns an error unless the `maxmemory-policy` is set to an LFU policy.
   ///
-  /// <https://redis.io/commands/expire>
-  pub async fn expire<R, K>(&self, key: K, seconds: i64) -> Result<R, RedisError>
+  /// <https://redis.io/commands/object-freq>
+  pub async fn object_freq<R, K>(&self, key: K) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
   {
-    commands::keys::expire(&self.inner, key, seconds).await?.convert()
+    commands::keys::object_freq(&self.inner, key).await?.convert()
   }
 
-  /// Set a timeout on a key based on a UNIX timestamp.
+  /// Returns the number of seconds since the value stored at `key` was last read or written, or `nil` if the key does not
+  /// exist.
   ///
-  /// Returns a boolean value describing whether or not the timeout was added.
+  /// The result can be converted into a `Duration`. The server returns an error if the `maxmemory-policy` is set to an
+  /// LFU policy.
   ///
-  /// <https://redis.io/commands/expireat>
-  pub async fn expire_at<R, K>(&self, key: K, timestamp: i64) -> Result<R, RedisError>
+  /// <https://redis.io/commands/object-idletime>
+  pub async fn object_idletime<R, K>(&self, key: K) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
   {
-    commands::keys::expire_at(&self.inner, key, timestamp).await?.convert()
+    commands::keys::object_idletime(&self.inner, key).await?.convert()
   }
 
-  /// Returns number of keys that exist from the `keys` arguments.
+  /// Returns the number of references to the value stored at `key`, or `nil` if the key does not exist.
   ///
-  /// <https://redis.io/commands/exists>
-  pub async fn exists<R, K>(&self, keys: K) -> Result<R, RedisError>
+  /// <https://redis.io/commands/object-refcount>
+  pub async fn object_refcount<R, K>(&self, key: K) -> Result<R, RedisError>
   where
     R: RedisResponse,
-    K: Into<MultipleKeys>,
+    K: Into<RedisKey>,
   {
-    commands::keys::exists(&self.inner, keys).await?.convert()
+    commands::keys::object_refcount(&self.inner, key).await?.convert()
   }
 
-  // ----------- HASHES ------------------
-
-  /// Removes the specified fields from the hash stored at `key`.
+  /// Remove the existing timeout on a key, turning the key from volatile (a key with an expiration)
+  /// to persistent (a key that will never expire as no timeout is associated).
   ///
-  /// <https://redis.io/commands/hdel>
-  pub async fn hdel<R, K, F>(&self, key: K, fields: F) -> Result<R, RedisError>
+  /// Returns a boolean value describing whether or not the timeout was removed.
+  ///
+  /// <https://redis.io/commands/persist>
+  pub async fn persist<R, K>(&self, key: K) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
-    F: Into<MultipleKeys>,
   {
-    commands::hashes::hdel(&self.inner, key, fields).await?.convert()
+    commands::keys::persist(&self.inner, key).await?.convert()
   }
 
-  /// Returns if `field` is an existing field in the hash stored at `key`.
+  /// Set a timeout on key. After the timeout has expired, the key will be automatically deleted.
   ///
-  /// <https://redis.io/commands/hexists>
-  pub async fn hexists<R, K, F>(&self, key: K, field: F) -> Result<R, RedisError>
+  /// Returns a boolean value describing whether or not the timeout was added. The timeout can be an integer number of
+  /// seconds or a `Duration`, which is rounded up to the nearest second.
+  ///
+  /// <https://redis.io/commands/expire>
+  pub async fn expire<R, K, S>(&self, key: K, seconds: S, options: Option<ExpireOptions>) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
-    F: Into<RedisKey>,
+    S: TryInto<Seconds>,
+    S::Error: Into<RedisError>,
   {
-    commands::hashes::hexists(&self.inner, key, field).await?.convert()
+    let seconds: Seconds = to!(seconds)?;
+    commands::keys::expire(&self.inner, key, seconds.0, options)
+      .await?
+      .convert()
   }
 
-  /// Returns the value associated with `field` in the hash stored at `key`.
+  /// Set a timeout on a key based on a UNIX timestamp.
   ///
-  /// <https://redis.io/commands/hget>
-  pub async fn hget<R, K, F>(&self, key: K, field: F) -> Result<R, RedisError>
+  /// Returns a boolean value describing whether or not the timeout was added. The timestamp can be an integer number of
+  /// seconds, a `SystemTime`, or a `chrono::DateTime` with the `chrono` feature.
+  ///
+  /// <https://redis.io/commands/expireat>
+  pub async fn expire_at<R, K, T>(
+    &self,
+    key: K,
+    timestamp: T,
+    options: Option<ExpireOptions>,
+  ) -> Result<R, RedisError>
   where
     R: RedisResponse,
     K: Into<RedisKey>,
-    F: Into<RedisKey>,
+    T: Into<Timestamp>,
   {
-    commands::hashes::hget(&self.inner, key, field).await?.convert()
+    commands::keys::expire_at(&self.inner, key, timestamp.into().0, options)
+      .await?
+      .convert()
   }
 
-  /// Returns all fields and values of the hash stored at `key`.
+  /// Set a timeout on key in milliseconds.
   ///
-  /// <https://redis.io/commands/hgetall>
-  pub async fn hgetall<R, K>(&self, key: K) -> Result<R, RedisError>
-  where
+  /// Returns a boolean value describing whether or not the timeout was added.
+  ///
+  /// <https://redis.io/commands/pexpire>
+  pub async fn pexpire<R, K>(
+    &self,
+    key: K,
+    milliseconds: i64,
+    options: Option<ExpireOptions>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::pexpire(&self.inner, key, milliseconds, options)
+      .await?
+      .convert()
+  }
+
+  /// Set a timeout on a key based on a UNIX timestamp in milliseconds.
+  ///
+  /// Returns a boolean value describing whether or not the timeout was added.
+  ///
+  /// <https://redis.io/commands/pexpireat>
+  pub async fn pexpire_at<R, K>(
+    &self,
+    key: K,
+    timestamp: i64,
+    options: Option<ExpireOptions>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::pexpire_at(&self.inner, key, timestamp, options)
+      .await?
+      .convert()
+  }
+
+  /// Returns the absolute UNIX timestamp, in seconds, at which the key will expire.
+  ///
+  /// Returns `-1` if the key exists but has no expiration, or `-2` if the key does not exist.
+  ///
+  /// <https://redis.io/commands/expiretime>
+  pub async fn expire_time<R, K>(&self, key: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::expire_time(&self.inner, key).await?.convert()
+  }
+
+  /// Returns the absolute UNIX timestamp, in milliseconds, at which the key will expire.
+  ///
+  /// Returns `-1` if the key exists but has no expiration, or `-2` if the key does not exist.
+  ///
+  /// <https://redis.io/commands/pexpiretime>
+  pub async fn pexpire_time<R, K>(&self, key: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::keys::pexpire_time(&self.inner, key).await?.convert()
+  }
+
+  /// Returns number of keys that exist from the `keys` arguments.
+  ///
+  /// <https://redis.io/commands/exists>
+  pub async fn exists<R, K>(&self, keys: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<MultipleKeys>,
+  {
+    commands::keys::exists(&self.inner, keys).await?.convert()
+  }
+
+  // ----------- HASHES ------------------
+
+  /// Removes the specified fields from the hash stored at `key`.
+  ///
+  /// <https://redis.io/commands/hdel>
+  pub async fn hdel<R, K, F>(&self, key: K, fields: F) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    F: Into<MultipleKeys>,
+  {
+    commands::hashes::hdel(&self.inner, key, fields).await?.convert()
+  }
+
+  /// Returns if `field` is an existing field in the hash stored at `key`.
+  ///
+  /// <https://redis.io/commands/hexists>
+  pub async fn hexists<R, K, F>(&self, key: K, field: F) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    F: Into<RedisKey>,
+  {
+    commands::hashes::hexists(&self.inner, key, field).await?.convert()
+  }
+
+  /// Returns the value associated with `field` in the hash stored at `key`.
+  ///
+  /// <https://redis.io/commands/hget>
+  pub async fn hget<R, K, F>(&self, key: K, field: F) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    F: Into<RedisKey>,
+  {
+    commands::hashes::hget(&self.inner, key, field).await?.convert()
+  }
+
+  /// Returns all fields and values of the hash stored at `key`.
+  ///
+  /// <https://redis.io/commands/hgetall>
+  pub async fn hgetall<R, K>(&self, key: K) -> Result<R, RedisError>
+  where
     R: RedisResponse,
     K: Into<RedisKey>,
   {
@@ -3073,6 +4210,120 @@ impl RedisClient {
       .convert()
   }
 
+  // ------------- BITMAPS ----------------
+
+  /// Set or clear the bit at `offset` in the string value stored at `key`, returning the previous value of the bit.
+  ///
+  /// The string is padded with zero bytes if `offset` is past the end of the string.
+  ///
+  /// <https://redis.io/commands/setbit>
+  pub async fn setbit<R, K>(&self, key: K, offset: usize, value: bool) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::setbit(&self.inner, key, offset, value)
+      .await?
+      .convert()
+  }
+
+  /// Returns the bit value at `offset` in the string value stored at `key`.
+  ///
+  /// Offsets past the end of the string, or a missing key, are treated as zero bits.
+  ///
+  /// <https://redis.io/commands/getbit>
+  pub async fn getbit<R, K>(&self, key: K, offset: usize) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::getbit(&self.inner, key, offset).await?.convert()
+  }
+
+  /// Count the number of set bits in a string, optionally within the inclusive `start` and `end` range.
+  ///
+  /// The range is measured in bytes unless `unit` is `BitUnit::Bit`. A unit can only be provided with a range.
+  ///
+  /// <https://redis.io/commands/bitcount>
+  pub async fn bitcount<R, K>(
+    &self,
+    key: K,
+    range: Option<(i64, i64)>,
+    unit: Option<BitUnit>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::bitcount(&self.inner, key, range, unit)
+      .await?
+      .convert()
+  }
+
+  /// Return the position of the first bit set to 1, or 0 if `bit` is `false`, in a string.
+  ///
+  /// The range is measured in bytes unless `unit` is `BitUnit::Bit`. An `end` offset requires a `start` offset, and a
+  /// unit requires an `end` offset.
+  ///
+  /// <https://redis.io/commands/bitpos>
+  pub async fn bitpos<R, K>(
+    &self,
+    key: K,
+    bit: bool,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: Option<BitUnit>,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::bitpos(&self.inner, key, bit, start, end, unit)
+      .await?
+      .convert()
+  }
+
+  /// Perform a bitwise operation between the `sources` and store the result in `dest`, returning the length of the
+  /// resulting string.
+  ///
+  /// In a cluster the destination and source keys must map to the same hash slot.
+  ///
+  /// <https://redis.io/commands/bitop>
+  pub async fn bitop<R, D, S>(&self, operation: BitOperation, dest: D, sources: S) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    D: Into<RedisKey>,
+    S: Into<MultipleKeys>,
+  {
+    commands::bitmaps::bitop(&self.inner, operation, dest, sources)
+      .await?
+      .convert()
+  }
+
+  /// Run a sequence of `GET`, `SET`, and `INCRBY` operations on the integer fields in the string stored at `key`.
+  ///
+  /// Returns one value for each operation, or `None` for operations that were not applied due to the `FAIL` overflow
+  /// policy.
+  ///
+  /// <https://redis.io/commands/bitfield>
+  pub async fn bitfield<K>(&self, key: K, ops: BitFieldOps) -> Result<Vec<Option<i64>>, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::bitfield(&self.inner, key, ops).await?.convert()
+  }
+
+  /// Read-only variant of [bitfield](Self::bitfield) that can run on replicas. The operations must only include
+  /// `GET`.
+  ///
+  /// <https://redis.io/commands/bitfield_ro>
+  pub async fn bitfield_ro<K>(&self, key: K, ops: BitFieldOps) -> Result<Vec<Option<i64>>, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    commands::bitmaps::bitfield_ro(&self.inner, key, ops).await?.convert()
+  }
+
   // -------------- LUA ------------------
 
   /// Load a script into the scripts cache, without executing it. After the specified command is loaded into the script cache it will be callable using EVALSHA with the correct SHA1 digest of the script.
@@ -3095,6 +4346,8 @@ impl RedisClient {
 
   /// Kills the currently executing Lua script, assuming no write operation was yet performed by the script.
   ///
+  /// Note: this command is sent on a backchannel connection and will work even when the main connection is blocked.
+  ///
   /// <https://redis.io/commands/script-kill>
   pub async fn script_kill(&self) -> Result<(), RedisError> {
     utils::disallow_during_transaction(&self.inner)?;
@@ -3102,6 +4355,8 @@ impl RedisClient {
   }
 
   /// A clustered variant of the [script_kill](Self::script_kill) command that issues the command to all primary nodes in the cluster.
+  ///
+  /// Note: this command is sent on a backchannel connection to each node.
   pub async fn script_kill_cluster(&self) -> Result<(), RedisError> {
     utils::disallow_during_transaction(&self.inner)?;
     commands::lua::script_kill_cluster(&self.inner).await
@@ -3174,82 +4429,753 @@ impl RedisClient {
       .convert()
   }
 
-  // --------------- SCANNING ---------------
+  /// Evaluates a script cached on the server side by its SHA1 digest, reading the keys and arguments from a [ScriptArgs](crate::types::ScriptArgs) builder.
+  ///
+  /// <https://redis.io/commands/evalsha>
+  pub async fn evalsha_with_args<R, S>(&self, hash: S, args: ScriptArgs) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    let (keys, args) = args.into_parts();
+    commands::lua::evalsha(&self.inner, hash, keys, args).await?.convert()
+  }
 
-  /// Incrementally iterate over a set of keys matching the `pattern` argument, returning `count` results per page, if specified.
+  /// Evaluate a Lua script on the server, reading the keys and arguments from a [ScriptArgs](crate::types::ScriptArgs) builder.
   ///
-  /// The scan operation can be canceled by dropping the returned stream.
+  /// In a cluster the script is sent to the node that owns the first key, or to a random node if there are no keys. All keys must
+  /// belong to the same node.
   ///
-  /// Note: scanning data in a cluster can be tricky. To make this easier this function supports [hash tags](https://redis.io/topics/cluster-spec#keys-hash-tags) in the
-  /// `pattern` so callers can direct scanning operations to specific nodes in the cluster. Callers can also use [split_cluster](Self::split_cluster) with this function if
-  /// hash tags are not used in the keys that should be scanned.
+  /// ```rust no_run
+  /// # use fred::prelude::*;
+  /// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+  ///   let args = ScriptArgs::new().key("foo").arg(10);
+  ///   let value: i64 = client.eval_with_args("return redis.call('INCRBY', KEYS[1], ARGV[1])", args).await?;
+  ///   Ok(())
+  /// }
+  /// ```
   ///
-  /// <https://redis.io/commands/scan>
-  pub fn scan<P>(
-    &self,
-    pattern: P,
-    count: Option<u32>,
-    r#type: Option<ScanType>,
-  ) -> impl Stream<Item = Result<ScanResult, RedisError>>
+  /// <https://redis.io/commands/eval>
+  pub async fn eval_with_args<R, S>(&self, script: S, args: ScriptArgs) -> Result<R, RedisError>
   where
-    P: Into<String>,
+    R: RedisResponse,
+    S: Into<String>,
   {
-    commands::scan::scan(&self.inner, pattern, count, r#type)
+    utils::disallow_during_transaction(&self.inner)?;
+    let (keys, args) = args.into_parts();
+    commands::lua::eval(&self.inner, script, keys, args).await?.convert()
   }
 
-  /// Incrementally iterate over pages of the hash map stored at `key`, returning `count` results per page, if specified.
+  // -------------- FUNCTIONS ------------------
+
+  /// Invoke a function.
   ///
-  /// <https://redis.io/commands/hscan>
-  pub fn hscan<K, P>(
-    &self,
-    key: K,
-    pattern: P,
-    count: Option<u32>,
-  ) -> impl Stream<Item = Result<HScanResult, RedisError>>
+  /// In a cluster the function is sent to the node that owns the first key, or to a random node if there are no keys.
+  ///
+  /// <https://redis.io/commands/fcall>
+  pub async fn fcall<R, F, K, V>(&self, func: F, keys: K, args: V) -> Result<R, RedisError>
   where
-    K: Into<RedisKey>,
-    P: Into<String>,
+    R: RedisResponse,
+    F: Into<String>,
+    K: Into<MultipleKeys>,
+    V: TryInto<MultipleValues>,
+    V::Error: Into<RedisError>,
   {
-    commands::scan::hscan(&self.inner, key, pattern, count)
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::fcall(&self.inner, func, keys, to!(args)?, false)
+      .await?
+      .convert()
   }
 
-  /// Incrementally iterate over pages of the set stored at `key`, returning `count` results per page, if specified.
+  /// A read-only variant of [fcall](Self::fcall) that can only invoke functions registered with the `no-writes` flag.
   ///
-  /// <https://redis.io/commands/sscan>
-  pub fn sscan<K, P>(
-    &self,
-    key: K,
-    pattern: P,
-    count: Option<u32>,
-  ) -> impl Stream<Item = Result<SScanResult, RedisError>>
+  /// <https://redis.io/commands/fcall_ro>
+  pub async fn fcall_ro<R, F, K, V>(&self, func: F, keys: K, args: V) -> Result<R, RedisError>
   where
-    K: Into<RedisKey>,
-    P: Into<String>,
+    R: RedisResponse,
+    F: Into<String>,
+    K: Into<MultipleKeys>,
+    V: TryInto<MultipleValues>,
+    V::Error: Into<RedisError>,
   {
-    commands::scan::sscan(&self.inner, key, pattern, count)
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::fcall(&self.inner, func, keys, to!(args)?, true)
+      .await?
+      .convert()
   }
 
-  /// Incrementally iterate over pages of the sorted set stored at `key`, returning `count` results per page, if specified.
+  /// Delete a library and all its functions.
   ///
-  /// <https://redis.io/commands/zscan>
-  pub fn zscan<K, P>(
-    &self,
-    key: K,
-    pattern: P,
-    count: Option<u32>,
-  ) -> impl Stream<Item = Result<ZScanResult, RedisError>>
+  /// <https://redis.io/commands/function-delete>
+  pub async fn function_delete<S>(&self, library_name: S) -> Result<(), RedisError>
   where
-    K: Into<RedisKey>,
-    P: Into<String>,
+    S: Into<String>,
   {
-    commands::scan::zscan(&self.inner, key, pattern, count)
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_delete(&self.inner, library_name).await
   }
 
-  // --------------- STREAMS ----------------
-}
+  /// A clustered variant of [function_delete](Self::function_delete) that deletes the library on all primary nodes in the cluster.
+  pub async fn function_delete_cluster<S>(&self, library_name: S) -> Result<(), RedisError>
+  where
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_delete_cluster(&self.inner, library_name).await
+  }
 
-#[cfg(test)]
-mod tests {
+  /// Return the serialized payload of loaded libraries, which can be restored with [function_restore](Self::function_restore).
+  ///
+  /// <https://redis.io/commands/function-dump>
+  pub async fn function_dump<R>(&self) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_dump(&self.inner).await?.convert()
+  }
+
+  /// Kill the function that is currently executing, assuming no write operation was yet performed by the function.
+  ///
+  /// Note: this command is sent on a backchannel connection and will work even when the main connection is blocked.
+  ///
+  /// <https://redis.io/commands/function-kill>
+  pub async fn function_kill(&self) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_kill(&self.inner).await
+  }
+
+  /// A clustered variant of the [function_kill](Self::function_kill) command that issues the command to all primary nodes in the cluster.
+  ///
+  /// Note: this command is sent on a backchannel connection to each node.
+  pub async fn function_kill_cluster(&self) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_kill_cluster(&self.inner).await
+  }
+
+  /// Delete all the libraries.
+  ///
+  /// <https://redis.io/commands/function-flush>
+  pub async fn function_flush(&self, r#async: bool) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_flush(&self.inner, r#async).await
+  }
+
+  /// A clustered variant of [function_flush](Self::function_flush) that deletes all the libraries on all primary nodes in the cluster.
+  pub async fn function_flush_cluster(&self, r#async: bool) -> Result<(), RedisError> {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_flush_cluster(&self.inner, r#async).await
+  }
+
+  /// Return information about the functions and libraries, optionally filtering libraries by a glob-style pattern.
+  ///
+  /// <https://redis.io/commands/function-list>
+  pub async fn function_list<R, S>(&self, library_pattern: Option<S>, with_code: bool) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_list(&self.inner, library_pattern.map(|s| s.into()), with_code)
+      .await?
+      .convert()
+  }
+
+  /// Load a library to Redis, returning the library name.
+  ///
+  /// <https://redis.io/commands/function-load>
+  pub async fn function_load<R, S>(&self, replace: bool, code: S) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_load(&self.inner, replace, code)
+      .await?
+      .convert()
+  }
+
+  /// A clustered variant of [function_load](Self::function_load) that loads the library on all primary nodes in the cluster.
+  ///
+  /// The library name is read from the metadata line at the start of `code`.
+  pub async fn function_load_cluster<R, S>(&self, replace: bool, code: S) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    S: Into<String>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_load_cluster(&self.inner, replace, code)
+      .await?
+      .convert()
+  }
+
+  /// Restore libraries from the serialized payload returned by [function_dump](Self::function_dump).
+  ///
+  /// <https://redis.io/commands/function-restore>
+  pub async fn function_restore<B>(&self, serialized: B, policy: FnPolicy) -> Result<(), RedisError>
+  where
+    B: Into<RedisValue>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_restore(&self.inner, serialized.into(), policy).await
+  }
+
+  /// A clustered variant of [function_restore](Self::function_restore) that restores the libraries on all primary nodes in the cluster.
+  pub async fn function_restore_cluster<B>(&self, serialized: B, policy: FnPolicy) -> Result<(), RedisError>
+  where
+    B: Into<RedisValue>,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_restore_cluster(&self.inner, serialized.into(), policy).await
+  }
+
+  /// Return information about the function that's currently running and the available execution engines.
+  ///
+  /// <https://redis.io/commands/function-stats>
+  pub async fn function_stats<R>(&self) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+  {
+    utils::disallow_during_transaction(&self.inner)?;
+    commands::lua::function_stats(&self.inner).await?.convert()
+  }
+
+  // --------------- SCANNING ---------------
+
+  /// Incrementally iterate over a set of keys matching the `pattern` argument, returning `count` results per page, if specified.
+  ///
+  /// The scan operation can be canceled by dropping the returned stream.
+  ///
+  /// Note: scanning data in a cluster can be tricky. To make this easier this function supports [hash tags](https://redis.io/topics/cluster-spec#keys-hash-tags) in the
+  /// `pattern` so callers can direct scanning operations to specific nodes in the cluster. Callers can also use [split_cluster](Self::split_cluster) with this function if
+  /// hash tags are not used in the keys that should be scanned, or [scan_cluster](Self::scan_cluster) to scan every node.
+  ///
+  /// <https://redis.io/commands/scan>
+  pub fn scan<P>(
+    &self,
+    pattern: P,
+    count: Option<u32>,
+    r#type: Option<ScanType>,
+  ) -> impl Stream<Item = Result<ScanResult, RedisError>>
+  where
+    P: Into<String>,
+  {
+    commands::scan::scan(&self.inner, pattern, count, r#type)
+  }
+
+  /// Incrementally iterate over the keys matching the `pattern` argument, returning each key individually rather than pages of keys.
+  ///
+  /// The next page is requested as soon as a page is received, and up to `count` keys (or 10 if `count` is `None`) are
+  /// buffered while waiting for the caller to read them. The scan stops when the returned stream is dropped.
+  ///
+  /// See [scan](Self::scan) for more information on scanning in a cluster.
+  ///
+  /// <https://redis.io/commands/scan>
+  pub fn scan_buffered<P>(
+    &self,
+    pattern: P,
+    count: Option<u32>,
+    r#type: Option<ScanType>,
+  ) -> impl Stream<Item = Result<RedisKey, RedisError>>
+  where
+    P: Into<String>,
+  {
+    commands::scan::scan_buffered(&self.inner, pattern, count, r#type)
+  }
+
+  /// Incrementally iterate over the keys matching the `pattern` argument on every primary node in the cluster.
+  ///
+  /// Each primary node is scanned with its own cursor, and the pages from all nodes are interleaved on the returned
+  /// stream, which closes once every node has been scanned. As with [scan](Self::scan) the caller must call `next` on
+  /// each page to continue scanning the node that produced it.
+  ///
+  /// If the cluster topology changes during the scan the pages for a node are routed with a different hash slot owned by
+  /// that node. If a node stops serving hash slots entirely the scan restarts on the new owner, so some keys may be
+  /// returned more than once.
+  ///
+  /// <https://redis.io/commands/scan>
+  pub fn scan_cluster<P>(
+    &self,
+    pattern: P,
+    count: Option<u32>,
+    r#type: Option<ScanType>,
+  ) -> impl Stream<Item = Result<ScanResult, RedisError>>
+  where
+    P: Into<String>,
+  {
+    commands::scan::scan_cluster(&self.inner, pattern, count, r#type)
+  }
+
+  /// Incrementally iterate over pages of the hash map stored at `key`, returning `count` results per page, if specified.
+  ///
+  /// <https://redis.io/commands/hscan>
+  pub fn hscan<K, P>(
+    &self,
+    key: K,
+    pattern: P,
+    count: Option<u32>,
+  ) -> impl Stream<Item = Result<HScanResult, RedisError>>
+  where
+    K: Into<RedisKey>,
+    P: Into<String>,
+  {
+    commands::scan::hscan(&self.inner, key, pattern, count)
+  }
+
+  /// Incrementally iterate over pages of the set stored at `key`, returning `count` results per page, if specified.
+  ///
+  /// <https://redis.io/commands/sscan>
+  pub fn sscan<K, P>(
+    &self,
+    key: K,
+    pattern: P,
+    count: Option<u32>,
+  ) -> impl Stream<Item = Result<SScanResult, RedisError>>
+  where
+    K: Into<RedisKey>,
+    P: Into<String>,
+  {
+    commands::scan::sscan(&self.inner, key, pattern, count)
+  }
+
+  /// Incrementally iterate over pages of the sorted set stored at `key`, returning `count` results per page, if specified.
+  ///
+  /// <https://redis.io/commands/zscan>
+  pub fn zscan<K, P>(
+    &self,
+    key: K,
+    pattern: P,
+    count: Option<u32>,
+  ) -> impl Stream<Item = Result<ZScanResult, RedisError>>
+  where
+    K: Into<RedisKey>,
+    P: Into<String>,
+  {
+    commands::scan::zscan(&self.inner, key, pattern, count)
+  }
+
+  // --------------- STREAMS ----------------
+
+  /// Appends the specified stream entry to the stream at the specified key, returning the ID of the new entry.
+  ///
+  /// If `nomkstream` is true and the stream does not exist then no entry is added and `nil` is returned.
+  ///
+  /// <https://redis.io/commands/xadd>
+  pub async fn xadd<R, K, I, V>(
+    &self,
+    key: K,
+    nomkstream: bool,
+    cap: Option<XCap>,
+    id: I,
+    fields: V,
+  ) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    I: Into<XID>,
+    V: Into<RedisMap>,
+  {
+    commands::streams::xadd(&self.inner, key, nomkstream, cap, id.into(), fields.into())
+      .await?
+      .convert()
+  }
+
+  /// Returns the number of entries inside a stream.
+  ///
+  /// <https://redis.io/commands/xlen>
+  pub async fn xlen<R, K>(&self, key: K) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+  {
+    commands::streams::xlen(&self.inner, key).await?.convert()
+  }
+
+  /// Returns the stream entries matching the provided range of IDs, with `-` and `+` representing the minimum and
+  /// maximum possible IDs.
+  ///
+  /// <https://redis.io/commands/xrange>
+  pub async fn xrange<K, S, E>(
+    &self,
+    key: K,
+    start: S,
+    end: E,
+    count: Option<u64>,
+  ) -> Result<Vec<StreamEntry>, RedisError>
+  where
+    K: Into<RedisKey>,
+    S: Into<XID>,
+    E: Into<XID>,
+  {
+    commands::streams::xrange(&self.inner, key, start.into(), end.into(), count).await
+  }
+
+  /// Similar to `XRANGE`, but returns the entries in reverse order, starting from the `end` ID.
+  ///
+  /// <https://redis.io/commands/xrevrange>
+  pub async fn xrevrange<K, E, S>(
+    &self,
+    key: K,
+    end: E,
+    start: S,
+    count: Option<u64>,
+  ) -> Result<Vec<StreamEntry>, RedisError>
+  where
+    K: Into<RedisKey>,
+    E: Into<XID>,
+    S: Into<XID>,
+  {
+    commands::streams::xrevrange(&self.inner, key, end.into(), start.into(), count).await
+  }
+
+  /// Read entries with an ID greater than the provided ID from one or more streams, returning the entries read from
+  /// each stream by key.
+  ///
+  /// If `block` is provided the server will wait up to that many milliseconds for new entries. An empty map is returned
+  /// if no entries are available before the timeout.
+  ///
+  /// <https://redis.io/commands/xread>
+  pub async fn xread<K, I>(
+    &self,
+    count: Option<u64>,
+    block: Option<u64>,
+    keys: K,
+    ids: I,
+  ) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError>
+  where
+    K: Into<MultipleKeys>,
+    I: Into<MultipleIDs>,
+  {
+    commands::streams::xread(&self.inner, count, block, keys.into(), ids.into()).await
+  }
+
+  /// Create a new consumer group associated with a stream, starting from the provided ID.
+  ///
+  /// <https://redis.io/commands/xgroup-create>
+  pub async fn xgroup_create<K, G, I>(&self, key: K, group: G, id: I, mkstream: bool) -> Result<(), RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    I: Into<XID>,
+  {
+    commands::streams::xgroup_create(&self.inner, key, group, id.into(), mkstream).await
+  }
+
+  /// Create a consumer in the consumer group, returning the number of consumers created.
+  ///
+  /// <https://redis.io/commands/xgroup-createconsumer>
+  pub async fn xgroup_createconsumer<R, K, G, C>(&self, key: K, group: G, consumer: C) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+  {
+    commands::streams::xgroup_createconsumer(&self.inner, key, group, consumer)
+      .await?
+      .convert()
+  }
+
+  /// Delete a consumer from the consumer group, returning the number of pending entries the consumer had.
+  ///
+  /// <https://redis.io/commands/xgroup-delconsumer>
+  pub async fn xgroup_delconsumer<R, K, G, C>(&self, key: K, group: G, consumer: C) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+  {
+    commands::streams::xgroup_delconsumer(&self.inner, key, group, consumer)
+      .await?
+      .convert()
+  }
+
+  /// Destroy a consumer group, returning the number of destroyed groups.
+  ///
+  /// <https://redis.io/commands/xgroup-destroy>
+  pub async fn xgroup_destroy<R, K, G>(&self, key: K, group: G) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    G: Into<String>,
+  {
+    commands::streams::xgroup_destroy(&self.inner, key, group).await?.convert()
+  }
+
+  /// Set the last delivered ID for a consumer group.
+  ///
+  /// <https://redis.io/commands/xgroup-setid>
+  pub async fn xgroup_setid<K, G, I>(&self, key: K, group: G, id: I) -> Result<(), RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    I: Into<XID>,
+  {
+    commands::streams::xgroup_setid(&self.inner, key, group, id.into()).await
+  }
+
+  /// A variant of `XREAD` that reads entries on behalf of a consumer in a consumer group, returning the entries read
+  /// from each stream by key.
+  ///
+  /// Use [XID::NewInGroup] (`>`) to read entries never delivered to other consumers, or another ID to read the
+  /// consumer's pending entries.
+  ///
+  /// <https://redis.io/commands/xreadgroup>
+  pub async fn xreadgroup<G, C, K, I>(
+    &self,
+    group: G,
+    consumer: C,
+    count: Option<u64>,
+    block: Option<u64>,
+    noack: bool,
+    keys: K,
+    ids: I,
+  ) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError>
+  where
+    G: Into<String>,
+    C: Into<String>,
+    K: Into<MultipleKeys>,
+    I: Into<MultipleIDs>,
+  {
+    commands::streams::xreadgroup(
+      &self.inner,
+      group,
+      consumer,
+      count,
+      block,
+      noack,
+      keys.into(),
+      ids.into(),
+    )
+    .await
+  }
+
+  /// Remove one or more entries from the pending entries list of a consumer group, returning the number of entries
+  /// acknowledged.
+  ///
+  /// <https://redis.io/commands/xack>
+  pub async fn xack<R, K, G, I>(&self, key: K, group: G, ids: I) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    G: Into<String>,
+    I: Into<MultipleIDs>,
+  {
+    commands::streams::xack(&self.inner, key, group, ids.into())
+      .await?
+      .convert()
+  }
+
+  /// Read a summary of the pending entries in a consumer group.
+  ///
+  /// <https://redis.io/commands/xpending>
+  pub async fn xpending<K, G>(&self, key: K, group: G) -> Result<XPendingSummary, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+  {
+    commands::streams::xpending(&self.inner, key, group).await
+  }
+
+  /// Read the pending entries in a consumer group within the provided range of IDs, optionally filtered by the
+  /// minimum idle time in milliseconds and the consumer that owns the entries.
+  ///
+  /// <https://redis.io/commands/xpending#extended-form-of-xpending>
+  pub async fn xpending_ext<K, G, S, E>(
+    &self,
+    key: K,
+    group: G,
+    idle: Option<u64>,
+    start: S,
+    end: E,
+    count: u64,
+    consumer: Option<String>,
+  ) -> Result<Vec<XPendingEntry>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    S: Into<XID>,
+    E: Into<XID>,
+  {
+    commands::streams::xpending_ext(&self.inner, key, group, idle, start.into(), end.into(), count, consumer).await
+  }
+
+  /// Change the ownership of pending entries idle for at least `min_idle_time` milliseconds to `consumer`, returning
+  /// the claimed entries.
+  ///
+  /// <https://redis.io/commands/xclaim>
+  pub async fn xclaim<K, G, C, I>(
+    &self,
+    key: K,
+    group: G,
+    consumer: C,
+    min_idle_time: u64,
+    ids: I,
+    options: XClaimOptions,
+  ) -> Result<Vec<StreamEntry>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+    I: Into<MultipleIDs>,
+  {
+    commands::streams::xclaim(&self.inner, key, group, consumer, min_idle_time, ids.into(), options).await
+  }
+
+  /// A variant of `XCLAIM` with the `JUSTID` argument that returns only the IDs of the claimed entries, without
+  /// incrementing their delivery count.
+  ///
+  /// <https://redis.io/commands/xclaim>
+  pub async fn xclaim_justid<K, G, C, I>(
+    &self,
+    key: K,
+    group: G,
+    consumer: C,
+    min_idle_time: u64,
+    ids: I,
+    options: XClaimOptions,
+  ) -> Result<Vec<String>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+    I: Into<MultipleIDs>,
+  {
+    commands::streams::xclaim_justid(&self.inner, key, group, consumer, min_idle_time, ids.into(), options).await
+  }
+
+  /// Claim up to `count` pending entries idle for at least `min_idle_time` milliseconds, scanning the pending entries
+  /// list from the `start` ID.
+  ///
+  /// Callers can recover the entries of dead consumers by calling this again with the returned `next_id` until the
+  /// result [is_complete](crate::types::XAutoClaimResult::is_complete).
+  ///
+  /// <https://redis.io/commands/xautoclaim>
+  pub async fn xautoclaim<K, G, C, S>(
+    &self,
+    key: K,
+    group: G,
+    consumer: C,
+    min_idle_time: u64,
+    start: S,
+    count: Option<u64>,
+  ) -> Result<XAutoClaimResult<StreamEntry>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+    S: Into<XID>,
+  {
+    commands::streams::xautoclaim(&self.inner, key, group, consumer, min_idle_time, start.into(), count).await
+  }
+
+  /// A variant of `XAUTOCLAIM` with the `JUSTID` argument that returns only the IDs of the claimed entries.
+  ///
+  /// <https://redis.io/commands/xautoclaim>
+  pub async fn xautoclaim_justid<K, G, C, S>(
+    &self,
+    key: K,
+    group: G,
+    consumer: C,
+    min_idle_time: u64,
+    start: S,
+    count: Option<u64>,
+  ) -> Result<XAutoClaimResult<String>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+    C: Into<String>,
+    S: Into<XID>,
+  {
+    commands::streams::xautoclaim_justid(&self.inner, key, group, consumer, min_idle_time, start.into(), count).await
+  }
+
+  /// Read information about the stream stored at `key`.
+  ///
+  /// <https://redis.io/commands/xinfo-stream>
+  pub async fn xinfo_stream<K>(&self, key: K) -> Result<StreamInfo, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    commands::streams::xinfo_stream(&self.inner, key).await
+  }
+
+  /// Read detailed information about the stream stored at `key`, including its entries, consumer groups, and pending
+  /// entries.
+  ///
+  /// The server limits the number of entries and pending entries returned to 10 unless `count` is provided. A `count`
+  /// of `0` returns all entries.
+  ///
+  /// <https://redis.io/commands/xinfo-stream>
+  pub async fn xinfo_stream_full<K>(&self, key: K, count: Option<u64>) -> Result<StreamInfoFull, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    commands::streams::xinfo_stream_full(&self.inner, key, count).await
+  }
+
+  /// Read information about the consumer groups associated with the stream stored at `key`.
+  ///
+  /// <https://redis.io/commands/xinfo-groups>
+  pub async fn xinfo_groups<K>(&self, key: K) -> Result<Vec<GroupInfo>, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    commands::streams::xinfo_groups(&self.inner, key).await
+  }
+
+  /// Read information about the consumers in a consumer group.
+  ///
+  /// <https://redis.io/commands/xinfo-consumers>
+  pub async fn xinfo_consumers<K, G>(&self, key: K, group: G) -> Result<Vec<ConsumerInfo>, RedisError>
+  where
+    K: Into<RedisKey>,
+    G: Into<String>,
+  {
+    commands::streams::xinfo_consumers(&self.inner, key, group).await
+  }
+
+  /// Trim the stream by evicting older entries, returning the number of entries removed.
+  ///
+  /// <https://redis.io/commands/xtrim>
+  pub async fn xtrim<R, K, C>(&self, key: K, cap: C) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+    K: Into<RedisKey>,
+    C: Into<XCap>,
+  {
+    commands::streams::xtrim(&self.inner, key, cap.into()).await?.convert()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_read_hash_tags() {
+    assert_eq!(util::hash_tag("{user1000}.following"), Some("user1000"));
+    assert_eq!(util::hash_tag("foo{}{bar}"), None);
+    assert_eq!(util::hash_tag("foo{{bar}}"), Some("{bar"));
+    assert_eq!(util::hash_tag("foo{bar}{zap}"), Some("bar"));
+    assert_eq!(util::hash_tag("foo"), None);
+    assert_eq!(util::hash_tag("foo{bar"), None);
+  }
+
+  #[test]
+  fn should_split_keys_by_slot() {
+    let client = RedisClient::new(RedisConfig::default());
+    let groups = client.split_by_slot(vec!["{1}a", "foo", "{1}b", "bar"]);
+
+    assert_eq!(
+      groups.get(&util::redis_keyslot("1")),
+      Some(&vec![RedisKey::from("{1}a"), RedisKey::from("{1}b")])
+    );
+    assert_eq!(groups.get(&util::redis_keyslot("foo")), Some(&vec![RedisKey::from("foo")]));
+    assert_eq!(groups.values().map(|keys| keys.len()).sum::<usize>(), 4);
+  }
 
   #[cfg(feature = "sha1-support")]
   #[test]
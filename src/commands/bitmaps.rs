@@ -0,0 +1,205 @@
+use super::*;
+use crate::error::*;
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::*;
+use crate::protocol::utils as protocol_utils;
+use crate::types::*;
+use crate::utils;
+use std::convert::TryInto;
+use std::sync::Arc;
+
+pub async fn setbit<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  offset: usize,
+  value: bool,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let value: i64 = if value { 1 } else { 0 };
+    Ok((RedisCommandKind::Setbit, vec![key.into(), offset.try_into()?, value.into()]))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn getbit<K>(inner: &Arc<RedisClientInner>, key: K, offset: usize) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::GetBit, vec![key.into(), offset.try_into()?]))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitcount<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  range: Option<(i64, i64)>,
+  unit: Option<BitUnit>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if range.is_none() && unit.is_some() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "A range is required with BYTE or BIT.",
+    ));
+  }
+
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(4);
+    args.push(key.into());
+
+    if let Some((start, end)) = range {
+      args.push(start.into());
+      args.push(end.into());
+    }
+    if let Some(unit) = unit {
+      args.push(unit.to_str().into());
+    }
+
+    Ok((RedisCommandKind::BitCount, args))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitpos<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  bit: bool,
+  start: Option<i64>,
+  end: Option<i64>,
+  unit: Option<BitUnit>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if start.is_none() && end.is_some() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "A start offset is required with an end offset.",
+    ));
+  }
+  if end.is_none() && unit.is_some() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "An end offset is required with BYTE or BIT.",
+    ));
+  }
+
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(5);
+    args.push(key.into());
+    args.push(if bit { 1 } else { 0 }.into());
+
+    if let Some(start) = start {
+      args.push(start.into());
+    }
+    if let Some(end) = end {
+      args.push(end.into());
+    }
+    if let Some(unit) = unit {
+      args.push(unit.to_str().into());
+    }
+
+    Ok((RedisCommandKind::BitPos, args))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitop<D, S>(
+  inner: &Arc<RedisClientInner>,
+  operation: BitOperation,
+  dest: D,
+  sources: S,
+) -> Result<RedisValue, RedisError>
+where
+  D: Into<RedisKey>,
+  S: Into<MultipleKeys>,
+{
+  let (dest, sources) = (dest.into(), sources.into());
+  utils::check_empty_keys(&sources)?;
+  if operation == BitOperation::Not && sources.len() != 1 {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITOP NOT requires exactly one source key.",
+    ));
+  }
+
+  let sources = sources.inner();
+  if utils::is_clustered(&inner.config) {
+    let slot = dest.cluster_hash();
+    if sources.iter().any(|source| source.cluster_hash() != slot) {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidArgument,
+        "BITOP destination and source keys must map to the same hash slot.",
+      ));
+    }
+  }
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(2 + sources.len());
+    args.push(operation.to_str().into());
+    args.push(dest.into());
+
+    for source in sources.into_iter() {
+      args.push(source.into());
+    }
+    Ok((RedisCommandKind::BitOp, args))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitfield<K>(inner: &Arc<RedisClientInner>, key: K, ops: BitFieldOps) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = vec![key.into()];
+    args.extend(ops.into_args()?);
+
+    Ok((RedisCommandKind::BitField, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_array(frame)
+}
+
+pub async fn bitfield_ro<K>(inner: &Arc<RedisClientInner>, key: K, ops: BitFieldOps) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if !ops.is_read_only() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITFIELD_RO only supports GET operations.",
+    ));
+  }
+
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = vec![key.into()];
+    args.extend(ops.into_args()?);
+
+    Ok((RedisCommandKind::BitFieldRo, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_array(frame)
+}
@@ -1,11 +1,18 @@
 use super::*;
+use crate::client::RedisClient;
+use crate::error::{RedisError, RedisErrorKind};
 use crate::modules::inner::RedisClientInner;
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
 use crate::types::*;
 use crate::utils;
+use futures::future::BoxFuture;
+use futures::StreamExt;
 use std::sync::Arc;
 
+static TRACKING: &'static str = "TRACKING";
+static INVALIDATION_CHANNEL: &'static str = "__redis__:invalidate";
+
 value_cmd!(client_id, ClientID);
 value_cmd!(client_info, ClientInfo);
 
@@ -135,7 +142,174 @@ where
   protocol_utils::frame_to_single_result(frame)
 }
 
+pub async fn kill_blocked_connection(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  let connection_id = utils::read_blocked_connection_id(inner).await?;
+  let frame = utils::backchannel_request_response(inner, move || {
+    Ok((RedisCommandKind::ClientKill, vec![ID.into(), connection_id.into()]))
+  })
+  .await?;
+
+  let _ = protocol_utils::frame_to_single_result(frame)?;
+  Ok(())
+}
+
 pub async fn unblock_self(inner: &Arc<RedisClientInner>, flag: Option<ClientUnblockFlag>) -> Result<(), RedisError> {
   let flag = flag.unwrap_or(ClientUnblockFlag::Error);
   utils::interrupt_blocked_connection(inner, flag).await
 }
+
+/// Find the `host:port` used to connect to the server, which may differ from the socket address used by the codec.
+fn connected_server(inner: &Arc<RedisClientInner>, server: &str) -> String {
+  inner
+    .connected_addrs
+    .read()
+    .iter()
+    .find(|(_, addr)| format!("{}:{}", addr.ip(), addr.port()) == server)
+    .map(|(name, _)| name.clone())
+    .unwrap_or_else(|| server.to_owned())
+}
+
+/// Read the hash slot used to route commands to the provided server, if the client is clustered.
+fn server_hash_slot(inner: &Arc<RedisClientInner>, server: &str) -> Option<u16> {
+  inner.cluster_state.read().as_ref().and_then(|state| {
+    state
+      .slots()
+      .iter()
+      .find(|slot| slot.server.as_str() == server)
+      .map(|slot| slot.start)
+  })
+}
+
+/// Point `CLIENT TRACKING` on the connection to `server` at a new invalidation connection after it reconnects.
+async fn redirect_tracking(inner: &Arc<RedisClientInner>, server: &str, id: i64) -> Result<(), RedisError> {
+  let tracking = match inner.config.read().tracking.clone() {
+    Some(tracking) => tracking,
+    None => return Ok(()),
+  };
+  let hash_slot = if utils::is_clustered(&inner.config) {
+    match server_hash_slot(inner, server) {
+      Some(slot) => Some(slot),
+      None => {
+        return Err(RedisError::new(
+          RedisErrorKind::Cluster,
+          format!("Failed to find hash slot for {}", server),
+        ))
+      }
+    }
+  } else {
+    None
+  };
+
+  let mut args = vec![TRACKING.into()];
+  args.append(&mut protocol_utils::tracking_args(&tracking, true, Some(id)));
+  let cmd = CustomCommand {
+    cmd: "CLIENT",
+    hash_slot,
+    is_blocking: false,
+  };
+
+  inner.cache.clear();
+  let _ = server::custom(inner, cmd, args).await?;
+  Ok(())
+}
+
+/// Forward messages on the invalidation channel to the owner's cache, and redirect tracking on the owner's connection
+/// when the invalidation connection reconnects.
+fn spawn_invalidation_tasks(inner: &Arc<RedisClientInner>, client: &RedisClient, server: String) {
+  let owner = Arc::downgrade(inner);
+  let mut messages = client.on_message();
+  tokio::spawn(async move {
+    while let Some((channel, message)) = messages.next().await {
+      if channel != INVALIDATION_CHANNEL {
+        continue;
+      }
+
+      match owner.upgrade() {
+        Some(inner) => inner.cache.invalidate_value(&message),
+        None => break,
+      };
+    }
+  });
+
+  let owner = Arc::downgrade(inner);
+  let mut reconnections = client.on_reconnect();
+  tokio::spawn(async move {
+    while let Some(client) = reconnections.next().await {
+      let inner = match owner.upgrade() {
+        Some(inner) => inner,
+        None => break,
+      };
+
+      let result = match client.client_id::<i64>().await {
+        Ok(id) => redirect_tracking(&inner, &server, id).await,
+        Err(e) => Err(e),
+      };
+      if let Err(e) = result {
+        _warn!(inner, "Failed to redirect tracking for {}: {:?}", server, e);
+      }
+    }
+  });
+}
+
+/// Read the ID of the connection that receives invalidation messages for the provided server, connecting and
+/// subscribing to the invalidation channel if needed.
+///
+/// With `RESP2` the server sends invalidation messages to another connection subscribed to the `__redis__:invalidate`
+/// channel. Each server connection uses its own invalidation connection so that the messages are routed to the cache
+/// that owns the tracked keys.
+///
+/// The future is boxed since connecting the invalidation client awaits the connection futures that call this function.
+pub fn invalidation_client_id<'a>(
+  inner: &'a Arc<RedisClientInner>,
+  server: &'a str,
+) -> BoxFuture<'a, Result<i64, RedisError>> {
+  Box::pin(async move {
+    let server = connected_server(inner, server);
+    let existing = inner.invalidation_clients.read().get(&server).cloned();
+    if let Some(client) = existing {
+      if client.is_connected() {
+        return client.client_id().await;
+      }
+    }
+
+    let mut config = inner.config.read().clone();
+    config.tracking = None;
+    config.version = RespVersion::RESP2;
+    config.dedicated_blocking_streams = false;
+    config.pubsub_buffer = None;
+    match config.server {
+      ServerConfig::Clustered { .. } | ServerConfig::Sentinel { .. } => {
+        let server = Arc::new(server.clone());
+        let (host, port) = protocol_utils::server_to_parts(&server)?;
+        config.server = ServerConfig::new_centralized(host, port);
+      }
+      _ => {}
+    };
+
+    let client = RedisClient::new(config);
+    _debug!(inner, "Creating invalidation connection {} for {}", client.id(), server);
+    let _ = client.connect(inner.policy.read().clone());
+    client.wait_for_connect().await?;
+    // `on_reconnect` streams also receive the initial connection event, so the tasks start after connecting
+    spawn_invalidation_tasks(inner, &client, server.clone());
+    let _ = client.subscribe(INVALIDATION_CHANNEL).await?;
+
+    let old = inner.invalidation_clients.write().insert(server, client.clone());
+    if let Some(old) = old {
+      tokio::spawn(async move {
+        let _ = old.quit().await;
+      });
+    }
+    client.client_id().await
+  })
+}
+
+/// Close the connections used to receive invalidation messages.
+pub fn close_invalidation_clients(inner: &Arc<RedisClientInner>) {
+  for (_, client) in inner.invalidation_clients.write().drain() {
+    _debug!(inner, "Closing invalidation connection {}", client.id());
+    tokio::spawn(async move {
+      let _ = client.quit().await;
+    });
+  }
+}
@@ -1,4 +1,6 @@
 use super::*;
+use crate::client::RedisClient;
+use crate::error::RedisErrorKind;
 use crate::modules::inner::RedisClientInner;
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
@@ -6,13 +8,54 @@ use crate::types::*;
 use crate::utils;
 use std::convert::TryInto;
 use std::sync::Arc;
+use tokio::sync::oneshot::channel as oneshot_channel;
 
 value_cmd!(cluster_bumpepoch, ClusterBumpEpoch);
 ok_cmd!(cluster_flushslots, ClusterFlushSlots);
 value_cmd!(cluster_myid, ClusterMyID);
 value_cmd!(cluster_nodes, ClusterNodes);
 ok_cmd!(cluster_saveconfig, ClusterSaveConfig);
-values_cmd!(cluster_slots, ClusterSlots);
+
+pub async fn cluster_slots(inner: &Arc<RedisClientInner>) -> Result<Vec<ClusterSlotRange>, RedisError> {
+  // the server reports an empty host for nodes that use the same host as the node answering the command, so clustered
+  // clients pick the node up front in order to know its host
+  let random_slot = inner.cluster_state.read().as_ref().and_then(|state| state.random_slot());
+  let (kind, args, default_host) = match random_slot {
+    Some(slot) if utils::is_clustered(&inner.config) => {
+      let host = match slot.server.rsplit_once(':') {
+        Some((host, _)) => host.to_owned(),
+        None => slot.server.as_str().to_owned(),
+      };
+      let kind = RedisCommandKind::_Custom(CustomCommand {
+        cmd: "CLUSTER",
+        hash_slot: Some(slot.start),
+        is_blocking: false,
+      });
+
+      (kind, vec!["SLOTS".into()], host)
+    }
+    _ => {
+      let host = inner
+        .config
+        .read()
+        .server
+        .hosts()
+        .first()
+        .map(|(host, _)| host.to_string())
+        .unwrap_or_default();
+
+      (RedisCommandKind::ClusterSlots, vec![], host)
+    }
+  };
+
+  let frame = utils::request_response(inner, move || Ok((kind, args))).await?;
+  protocol_utils::parse_cluster_slots(frame, &default_host)
+}
+
+pub async fn cluster_shards(inner: &Arc<RedisClientInner>) -> Result<Vec<ClusterShard>, RedisError> {
+  let frame = utils::request_response(inner, || Ok((RedisCommandKind::ClusterShards, vec![]))).await?;
+  protocol_utils::parse_cluster_shards(frame)
+}
 
 pub async fn cluster_info(inner: &Arc<RedisClientInner>) -> Result<ClusterInfo, RedisError> {
   let frame = utils::request_response(inner, || Ok((RedisCommandKind::ClusterInfo, vec![]))).await?;
@@ -39,6 +82,36 @@ where
   protocol_utils::expect_ok(&response)
 }
 
+fn slot_range_args(ranges: Vec<(u16, u16)>) -> Vec<RedisValue> {
+  let mut args = Vec::with_capacity(ranges.len() * 2);
+  for (start, end) in ranges.into_iter() {
+    args.push(start.into());
+    args.push(end.into());
+  }
+
+  args
+}
+
+pub async fn cluster_add_slots_range(inner: &Arc<RedisClientInner>, ranges: Vec<(u16, u16)>) -> Result<(), RedisError> {
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::ClusterAddSlotsRange, slot_range_args(ranges)))
+  })
+  .await?;
+
+  let response = protocol_utils::frame_to_single_result(frame)?;
+  protocol_utils::expect_ok(&response)
+}
+
+pub async fn cluster_del_slots_range(inner: &Arc<RedisClientInner>, ranges: Vec<(u16, u16)>) -> Result<(), RedisError> {
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::ClusterDelSlotsRange, slot_range_args(ranges)))
+  })
+  .await?;
+
+  let response = protocol_utils::frame_to_single_result(frame)?;
+  protocol_utils::expect_ok(&response)
+}
+
 pub async fn cluster_count_failure_reports<N>(
   inner: &Arc<RedisClientInner>,
   node_id: N,
@@ -202,3 +275,63 @@ pub async fn cluster_setslot(
   let response = protocol_utils::frame_to_single_result(frame)?;
   protocol_utils::expect_ok(&response)
 }
+
+pub async fn sync_cluster(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+  let (tx, rx) = oneshot_channel();
+  let command = RedisCommand::new(RedisCommandKind::_SyncCluster, vec![], Some(tx));
+  let _ = utils::send_command(inner, command)?;
+
+  let _ = rx.await??;
+  Ok(())
+}
+
+/// Read the `host:port` of each primary node in the cached cluster state.
+pub fn cached_primary_nodes(inner: &Arc<RedisClientInner>) -> Result<Vec<Arc<String>>, RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  match inner.cluster_state.read().as_ref() {
+    Some(state) => Ok(state.unique_main_nodes()),
+    None => Err(RedisError::new(RedisErrorKind::Cluster, "Missing cluster state.")),
+  }
+}
+
+/// Create a new centralized client connected to the provided primary node in the cluster.
+pub async fn connect_cluster_node(inner: &Arc<RedisClientInner>, server: &str) -> Result<RedisClient, RedisError> {
+  if !cached_primary_nodes(inner)?.iter().any(|node| node.as_str() == server) {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      format!("Unknown cluster node: {}", server),
+    ));
+  }
+
+  connect_node(inner, server).await
+}
+
+/// Create a new centralized client connected to the provided `host:port`, using the config from the provided client.
+///
+/// Unlike [connect_cluster_node] this does not check that the server owns any hash slots.
+pub async fn connect_node(inner: &Arc<RedisClientInner>, server: &str) -> Result<RedisClient, RedisError> {
+  let mut parts = server.rsplitn(2, ":");
+  let (port, host) = match (parts.next(), parts.next()) {
+    (Some(port), Some(host)) => (port.parse::<u16>()?, host.to_owned()),
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidArgument,
+        format!("Invalid host/port for {}", server),
+      ))
+    }
+  };
+  let mut config = utils::read_locked(&inner.config);
+  config.server = ServerConfig::Centralized { host, port };
+
+  _debug!(inner, "Connecting to cluster node {}", server);
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  if let Err(e) = client.wait_for_connect().await {
+    let _ = client.quit().await;
+    return Err(e);
+  }
+
+  Ok(client)
+}
@@ -116,7 +116,7 @@ where
   })
   .await?;
 
-  protocol_utils::frame_to_results(frame)
+  protocol_utils::frame_to_array(frame)
 }
 
 pub async fn hmset<K, V>(inner: &Arc<RedisClientInner>, key: K, values: V) -> Result<RedisValue, RedisError>
@@ -5,6 +5,7 @@ use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
 use crate::types::*;
 use crate::utils;
+use futures::future::try_join_all;
 use std::convert::TryInto;
 use std::sync::Arc;
 
@@ -17,6 +18,49 @@ where
   one_arg_values_cmd(inner, RedisCommandKind::Get, key.into().into()).await
 }
 
+pub async fn cached_get<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  let (optin, tracked) = match inner.config.read().tracking {
+    Some(ref config) => (
+      config.mode == TrackingMode::OptIn,
+      protocol_utils::is_tracked_key(config, &key),
+    ),
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::Config,
+        "Client tracking is not enabled.",
+      ))
+    }
+  };
+  if !tracked {
+    // the server will not send invalidation messages for keys outside the broadcast prefixes
+    return get(inner, key).await;
+  }
+  if let Some(value) = inner.cache.get(&key) {
+    return Ok(value);
+  }
+
+  let mut options = utils::read_command_options().unwrap_or_default();
+  if options.caching == Some(false) {
+    // the server will not track the key, so the value cannot be cached safely
+    return get(inner, key).await;
+  }
+
+  let epoch = inner.cache.epoch();
+  let value = if optin {
+    // the multiplexer writes `CLIENT CACHING yes` immediately before the `GET` on the same connection
+    options.caching = Some(true);
+    utils::with_command_options(options, get(inner, key.clone())).await?
+  } else {
+    get(inner, key.clone()).await?
+  };
+  inner.cache.insert(epoch, key, value.clone());
+  Ok(value)
+}
+
 pub async fn set(
   inner: &Arc<RedisClientInner>,
   key: RedisKey,
@@ -51,16 +95,56 @@ pub async fn set(
   protocol_utils::frame_to_single_result(frame)
 }
 
-pub async fn del<K>(inner: &Arc<RedisClientInner>, keys: K) -> Result<RedisValue, RedisError>
+async fn del_keys(
+  inner: &Arc<RedisClientInner>,
+  kind: RedisCommandKind,
+  keys: Vec<RedisKey>,
+) -> Result<RedisValue, RedisError> {
+  let args: Vec<RedisValue> = keys.into_iter().map(|k| k.into()).collect();
+  let frame = utils::request_response(inner, move || Ok((kind, args))).await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+/// Send a `DEL` or `UNLINK` command, splitting the keys into one command per hash slot if needed.
+async fn del_multiple_keys<K>(
+  inner: &Arc<RedisClientInner>,
+  kind: fn() -> RedisCommandKind,
+  keys: K,
+) -> Result<RedisValue, RedisError>
 where
   K: Into<MultipleKeys>,
 {
   let keys = keys.into();
   utils::check_empty_keys(&keys)?;
+  let keys = keys.inner();
 
-  let args: Vec<RedisValue> = keys.inner().drain(..).map(|k| k.into()).collect();
-  let frame = utils::request_response(inner, move || Ok((RedisCommandKind::Del, args))).await?;
-  protocol_utils::frame_to_single_result(frame)
+  if let Some(groups) = utils::group_cross_slot_keys(inner, keys.iter().map(|key| key.cluster_hash())) {
+    let calls = groups
+      .iter()
+      .map(|group| del_keys(inner, kind(), group.iter().map(|idx| keys[*idx].clone()).collect()));
+
+    let mut deleted = 0;
+    for result in try_join_all(calls).await?.into_iter() {
+      deleted += result.as_i64().unwrap_or(0);
+    }
+    Ok(deleted.into())
+  } else {
+    del_keys(inner, kind(), keys).await
+  }
+}
+
+pub async fn del<K>(inner: &Arc<RedisClientInner>, keys: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<MultipleKeys>,
+{
+  del_multiple_keys(inner, || RedisCommandKind::Del, keys).await
+}
+
+pub async fn unlink<K>(inner: &Arc<RedisClientInner>, keys: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<MultipleKeys>,
+{
+  del_multiple_keys(inner, || RedisCommandKind::Unlink, keys).await
 }
 
 pub async fn incr<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
@@ -130,6 +214,41 @@ where
   one_arg_value_cmd(inner, RedisCommandKind::Pttl, key.into().into()).await
 }
 
+pub async fn object_encoding<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::ObjectEncoding, key.into().into()).await
+}
+
+pub async fn object_freq<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::ObjectFreq, key.into().into()).await
+}
+
+pub async fn object_idletime<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::ObjectIdleTime, key.into().into()).await
+}
+
+pub async fn object_refcount<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::ObjectRefCount, key.into().into()).await
+}
+
+pub async fn r#type<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::Type, key.into().into()).await
+}
+
 pub async fn persist<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
 where
   K: Into<RedisKey>,
@@ -137,30 +256,96 @@ where
   one_arg_value_cmd(inner, RedisCommandKind::Persist, key.into().into()).await
 }
 
-pub async fn expire<K>(inner: &Arc<RedisClientInner>, key: K, seconds: i64) -> Result<RedisValue, RedisError>
+async fn expire_cmd<K>(
+  inner: &Arc<RedisClientInner>,
+  kind: RedisCommandKind,
+  key: K,
+  value: i64,
+  options: Option<ExpireOptions>,
+) -> Result<RedisValue, RedisError>
 where
   K: Into<RedisKey>,
 {
   let key = key.into();
+  if let Some(ref options) = options {
+    let feature = format!("{} {}", kind.to_str_debug(), options.to_str());
+    let _ = utils::check_min_server_version(inner, &feature, &Version::new(7, 0, 0))?;
+  }
 
   let frame = utils::request_response(inner, move || {
-    Ok((RedisCommandKind::Expire, vec![key.into(), seconds.into()]))
+    let mut args = Vec::with_capacity(3);
+    args.push(key.into());
+    args.push(value.into());
+    if let Some(options) = options {
+      args.push(options.to_str().into());
+    }
+
+    Ok((kind, args))
   })
   .await?;
   protocol_utils::frame_to_single_result(frame)
 }
 
-pub async fn expire_at<K>(inner: &Arc<RedisClientInner>, key: K, timestamp: i64) -> Result<RedisValue, RedisError>
+pub async fn expire<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  seconds: i64,
+  options: Option<ExpireOptions>,
+) -> Result<RedisValue, RedisError>
 where
   K: Into<RedisKey>,
 {
-  let key = key.into();
+  expire_cmd(inner, RedisCommandKind::Expire, key, seconds, options).await
+}
 
-  let frame = utils::request_response(inner, move || {
-    Ok((RedisCommandKind::ExpireAt, vec![key.into(), timestamp.into()]))
-  })
-  .await?;
-  protocol_utils::frame_to_single_result(frame)
+pub async fn expire_at<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  timestamp: i64,
+  options: Option<ExpireOptions>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  expire_cmd(inner, RedisCommandKind::ExpireAt, key, timestamp, options).await
+}
+
+pub async fn pexpire<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  milliseconds: i64,
+  options: Option<ExpireOptions>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  expire_cmd(inner, RedisCommandKind::Pexpire, key, milliseconds, options).await
+}
+
+pub async fn pexpire_at<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  timestamp: i64,
+  options: Option<ExpireOptions>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  expire_cmd(inner, RedisCommandKind::Pexpireat, key, timestamp, options).await
+}
+
+pub async fn expire_time<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::ExpireTime, key.into().into()).await
+}
+
+pub async fn pexpire_time<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::PexpireTime, key.into().into()).await
 }
 
 pub async fn exists<K>(inner: &Arc<RedisClientInner>, keys: K) -> Result<RedisValue, RedisError>
@@ -188,14 +373,21 @@ pub async fn dump<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue
 where
   K: Into<RedisKey>,
 {
-  one_arg_values_cmd(inner, RedisCommandKind::Dump, key.into().into()).await
+  let key = key.into();
+  let frame = utils::request_response(inner, move || Ok((RedisCommandKind::Dump, vec![key.into()]))).await?;
+
+  match frame {
+    // the payload is moved into a `Bytes` value even if it happens to be valid UTF8
+    Frame::BulkString(payload) => Ok(RedisValue::Bytes(payload)),
+    frame => protocol_utils::frame_to_single_result(frame),
+  }
 }
 
-pub async fn restore<K>(
+pub async fn restore<K, V>(
   inner: &Arc<RedisClientInner>,
   key: K,
   ttl: i64,
-  serialized: RedisValue,
+  serialized: V,
   replace: bool,
   absttl: bool,
   idletime: Option<i64>,
@@ -203,13 +395,14 @@ pub async fn restore<K>(
 ) -> Result<RedisValue, RedisError>
 where
   K: Into<RedisKey>,
+  V: Into<RedisValue>,
 {
   let key = key.into();
   let frame = utils::request_response(inner, move || {
     let mut args = Vec::with_capacity(9);
     args.push(key.into());
     args.push(ttl.into());
-    args.push(serialized);
+    args.push(serialized.into());
 
     if replace {
       args.push(REPLACE.into());
@@ -233,6 +426,103 @@ where
   protocol_utils::frame_to_results(frame)
 }
 
+pub async fn migrate<H, K>(
+  inner: &Arc<RedisClientInner>,
+  host: H,
+  port: u16,
+  keys: K,
+  db: u8,
+  timeout: u64,
+  copy: bool,
+  replace: bool,
+  auth: Option<MigrateAuth>,
+) -> Result<RedisValue, RedisError>
+where
+  H: Into<String>,
+  K: Into<MultipleKeys>,
+{
+  let (host, keys) = (host.into(), keys.into());
+  if keys.len() == 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "At least one key is required.",
+    ));
+  }
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(keys.len() + 11);
+    args.push(host.into());
+    args.push(port.into());
+    let keys = if keys.len() == 1 {
+      args.push(keys.inner().pop().unwrap().into());
+      None
+    } else {
+      args.push("".into());
+      Some(keys)
+    };
+    args.push(db.into());
+    args.push(timeout.try_into()?);
+
+    if copy {
+      args.push(COPY.into());
+    }
+    if replace {
+      args.push(REPLACE.into());
+    }
+    if let Some(auth) = auth {
+      args.extend(auth.into_args());
+    }
+    if let Some(keys) = keys {
+      args.push(KEYS.into());
+      args.extend(keys.inner().into_iter().map(|k| k.into()));
+    }
+
+    Ok((RedisCommandKind::Migrate, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn sort<K>(inner: &Arc<RedisClientInner>, key: K, options: SortOptions) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = vec![key.into()];
+    args.extend(options.into_args());
+
+    Ok((RedisCommandKind::Sort, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn sort_ro<K>(inner: &Arc<RedisClientInner>, key: K, options: SortOptions) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if options.has_store() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "SORT_RO does not support STORE.",
+    ));
+  }
+
+  let key = key.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = vec![key.into()];
+    args.extend(options.into_args());
+
+    Ok((RedisCommandKind::SortRo, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
 pub async fn getrange<K>(
   inner: &Arc<RedisClientInner>,
   key: K,
@@ -258,7 +548,7 @@ where
 pub async fn setrange<K>(
   inner: &Arc<RedisClientInner>,
   key: K,
-  offset: u32,
+  offset: usize,
   value: RedisValue,
 ) -> Result<RedisValue, RedisError>
 where
@@ -267,12 +557,84 @@ where
   let key = key.into();
 
   let frame = utils::request_response(inner, move || {
-    Ok((RedisCommandKind::Setrange, vec![key.into(), offset.into(), value]))
+    Ok((RedisCommandKind::Setrange, vec![key.into(), offset.try_into()?, value]))
+  })
+  .await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn append<K>(inner: &Arc<RedisClientInner>, key: K, value: RedisValue) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  args_value_cmd(inner, RedisCommandKind::Append, vec![key.into().into(), value]).await
+}
+
+pub async fn lcs<K1, K2>(
+  inner: &Arc<RedisClientInner>,
+  key1: K1,
+  key2: K2,
+  len: bool,
+) -> Result<RedisValue, RedisError>
+where
+  K1: Into<RedisKey>,
+  K2: Into<RedisKey>,
+{
+  let (key1, key2) = (key1.into(), key2.into());
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(3);
+    args.push(key1.into());
+    args.push(key2.into());
+    if len {
+      args.push(LEN.into());
+    }
+
+    Ok((RedisCommandKind::Lcs, args))
   })
   .await?;
+
   protocol_utils::frame_to_single_result(frame)
 }
 
+pub async fn lcs_idx<K1, K2>(
+  inner: &Arc<RedisClientInner>,
+  key1: K1,
+  key2: K2,
+  min_match_len: Option<u64>,
+  with_match_len: bool,
+) -> Result<LcsMatches, RedisError>
+where
+  K1: Into<RedisKey>,
+  K2: Into<RedisKey>,
+{
+  let (key1, key2) = (key1.into(), key2.into());
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(6);
+    args.push(key1.into());
+    args.push(key2.into());
+    args.push(IDX.into());
+    if let Some(min_match_len) = min_match_len {
+      args.push(MINMATCHLEN.into());
+      args.push(min_match_len.try_into()?);
+    }
+    if with_match_len {
+      args.push(WITHMATCHLEN.into());
+    }
+
+    Ok((RedisCommandKind::Lcs, args))
+  })
+  .await?;
+
+  if let Frame::Array(frames) = frame {
+    protocol_utils::parse_lcs_matches(&frames)
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected array response.",
+    ))
+  }
+}
+
 pub async fn getset<K>(inner: &Arc<RedisClientInner>, key: K, value: RedisValue) -> Result<RedisValue, RedisError>
 where
   K: Into<RedisKey>,
@@ -294,25 +656,67 @@ where
   one_arg_value_cmd(inner, RedisCommandKind::Strlen, key.into().into()).await
 }
 
+async fn mget_keys(inner: &Arc<RedisClientInner>, keys: Vec<RedisKey>) -> Result<RedisValue, RedisError> {
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(keys.len());
+
+    for key in keys.into_iter() {
+      args.push(key.into());
+    }
+
+    Ok((RedisCommandKind::Mget, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_array(frame)
+}
+
 pub async fn mget<K>(inner: &Arc<RedisClientInner>, keys: K) -> Result<RedisValue, RedisError>
 where
   K: Into<MultipleKeys>,
 {
   let keys = keys.into();
   utils::check_empty_keys(&keys)?;
+  let keys = keys.inner();
+
+  if let Some(groups) = utils::group_cross_slot_keys(inner, keys.iter().map(|key| key.cluster_hash())) {
+    let calls = groups
+      .iter()
+      .map(|group| mget_keys(inner, group.iter().map(|idx| keys[*idx].clone()).collect()));
+    let results = try_join_all(calls).await?;
+
+    // put the values back in the order of the original keys
+    let mut out = vec![RedisValue::Null; keys.len()];
+    for (group, result) in groups.iter().zip(results.into_iter()) {
+      let values = match result {
+        RedisValue::Array(values) => values,
+        value => vec![value],
+      };
+
+      for (idx, value) in group.iter().zip(values.into_iter()) {
+        out[*idx] = value;
+      }
+    }
+    Ok(RedisValue::Array(out))
+  } else {
+    mget_keys(inner, keys).await
+  }
+}
 
+async fn mset_values(inner: &Arc<RedisClientInner>, values: Vec<(String, RedisValue)>) -> Result<RedisValue, RedisError> {
   let frame = utils::request_response(inner, move || {
-    let mut args = Vec::with_capacity(keys.len());
+    let mut args = Vec::with_capacity(values.len() * 2);
 
-    for key in keys.inner().into_iter() {
+    for (key, value) in values.into_iter() {
       args.push(key.into());
+      args.push(value);
     }
 
-    Ok((RedisCommandKind::Mget, args))
+    Ok((RedisCommandKind::Mset, args))
   })
   .await?;
 
-  protocol_utils::frame_to_results(frame)
+  protocol_utils::frame_to_single_result(frame)
 }
 
 pub async fn mset<V>(inner: &Arc<RedisClientInner>, values: V) -> Result<RedisValue, RedisError>
@@ -326,20 +730,18 @@ where
       "Values cannot be empty.",
     ));
   }
+  let values: Vec<(String, RedisValue)> = values.inner().into_iter().collect();
 
-  let frame = utils::request_response(inner, move || {
-    let mut args = Vec::with_capacity(values.len() * 2);
-
-    for (key, value) in values.inner().into_iter() {
-      args.push(key.into());
-      args.push(value);
-    }
-
-    Ok((RedisCommandKind::Mset, args))
-  })
-  .await?;
+  if let Some(groups) = utils::group_cross_slot_keys(inner, values.iter().map(|(key, _)| redis_keyslot(key))) {
+    let calls = groups
+      .iter()
+      .map(|group| mset_values(inner, group.iter().map(|idx| values[*idx].clone()).collect()));
 
-  protocol_utils::frame_to_single_result(frame)
+    let mut results = try_join_all(calls).await?;
+    Ok(results.pop().unwrap_or(RedisValue::Null))
+  } else {
+    mset_values(inner, values).await
+  }
 }
 
 pub async fn msetnx<V>(inner: &Arc<RedisClientInner>, values: V) -> Result<RedisValue, RedisError>
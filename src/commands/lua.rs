@@ -20,13 +20,7 @@ pub fn check_key_slot(inner: &Arc<RedisClientInner>, keys: &Vec<RedisKey>) -> Re
 
     let (mut cmd_server, mut cmd_slot) = (None, None);
     for key in keys.iter() {
-      let key_slot = match key.as_str() {
-        Some(k) => redis_protocol::redis_keyslot(k),
-        None => {
-          let key_str = String::from_utf8_lossy(key.as_bytes());
-          redis_protocol::redis_keyslot(&key_str)
-        }
-      };
+      let key_slot = key.cluster_hash();
 
       if let Some(slot) = cluster_state.get_server(key_slot) {
         if let Some(ref cmd_server) = cmd_server {
@@ -78,18 +72,47 @@ where
   Ok(hash.into())
 }
 
-ok_cmd!(script_kill, ScriptKill);
+/// Send a `SCRIPT KILL` or `FUNCTION KILL` command on the backchannel, since the main connection is blocked by the script.
+async fn kill_script(inner: &Arc<RedisClientInner>, kind: RedisCommandKind) -> Result<(), RedisError> {
+  let frame = utils::backchannel_request_response(inner, move || Ok((kind, vec![]))).await?;
 
-pub async fn script_kill_cluster(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  let response = protocol_utils::frame_to_single_result(frame)?;
+  protocol_utils::expect_ok(&response)
+}
+
+/// Send a `SCRIPT KILL` or `FUNCTION KILL` command to each primary node in the cluster.
+async fn kill_script_cluster(inner: &Arc<RedisClientInner>, kind: fn() -> RedisCommandKind) -> Result<(), RedisError> {
   let _ = utils::check_clustered(inner)?;
+  let servers = match &*inner.cluster_state.read() {
+    Some(state) => state.unique_main_nodes(),
+    None => return Err(RedisError::new(RedisErrorKind::Cluster, "Invalid cluster state.")),
+  };
+
+  // the main connection to a node running a script is blocked, so each node is sent the command on the backchannel
+  let mut error = None;
+  for server in servers.iter() {
+    let result = utils::backchannel_server_request_response(inner, server, || Ok((kind(), vec![])))
+      .await
+      .and_then(protocol_utils::frame_to_single_result)
+      .and_then(|response| protocol_utils::expect_ok(&response));
+
+    if let Err(e) = result {
+      error = error.or(Some(e));
+    }
+  }
 
-  let (tx, rx) = oneshot_channel();
-  let kind = RedisCommandKind::_ScriptKillCluster(AllNodesResponse::new(tx));
-  let command = RedisCommand::new(kind, vec![], None);
-  let _ = utils::send_command(inner, command)?;
-  let _ = rx.await??;
+  match error {
+    Some(e) => Err(e),
+    None => Ok(()),
+  }
+}
 
-  Ok(())
+pub async fn script_kill(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  kill_script(inner, RedisCommandKind::ScriptKill).await
+}
+
+pub async fn script_kill_cluster(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  kill_script_cluster(inner, || RedisCommandKind::ScriptKill).await
 }
 
 pub async fn script_flush(inner: &Arc<RedisClientInner>, r#async: bool) -> Result<(), RedisError> {
@@ -223,3 +246,196 @@ where
 
   protocol_utils::frame_to_results(frame)
 }
+
+pub async fn fcall<F, K>(
+  inner: &Arc<RedisClientInner>,
+  func: F,
+  keys: K,
+  cmd_args: MultipleValues,
+  read_only: bool,
+) -> Result<RedisValue, RedisError>
+where
+  F: Into<String>,
+  K: Into<MultipleKeys>,
+{
+  let (func, keys) = (func.into(), keys.into().inner());
+  let custom_key_slot = check_key_slot(inner, &keys)?;
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(2 + keys.len() + cmd_args.len());
+    args.push(func.into());
+    args.push(keys.len().try_into()?);
+
+    for key in keys.into_iter() {
+      args.push(key.into());
+    }
+    for arg in cmd_args.inner().into_iter() {
+      args.push(arg);
+    }
+
+    let kind = if read_only {
+      RedisCommandKind::FcallRO(custom_key_slot)
+    } else {
+      RedisCommandKind::Fcall(custom_key_slot)
+    };
+    Ok((kind, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn function_delete<S>(inner: &Arc<RedisClientInner>, library_name: S) -> Result<(), RedisError>
+where
+  S: Into<String>,
+{
+  one_arg_ok_cmd(inner, RedisCommandKind::FunctionDelete, library_name.into().into()).await
+}
+
+pub async fn function_delete_cluster<S>(inner: &Arc<RedisClientInner>, library_name: S) -> Result<(), RedisError>
+where
+  S: Into<String>,
+{
+  let _ = utils::check_clustered(inner)?;
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionDeleteCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, vec![library_name.into().into()], None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+pub async fn function_dump(inner: &Arc<RedisClientInner>) -> Result<RedisValue, RedisError> {
+  let frame = utils::request_response(inner, || Ok((RedisCommandKind::FunctionDump, vec![]))).await?;
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn function_kill(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  kill_script(inner, RedisCommandKind::FunctionKill).await
+}
+
+pub async fn function_kill_cluster(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
+  kill_script_cluster(inner, || RedisCommandKind::FunctionKill).await
+}
+
+pub async fn function_flush(inner: &Arc<RedisClientInner>, r#async: bool) -> Result<(), RedisError> {
+  let arg = if r#async { ASYNC } else { SYNC };
+  args_ok_cmd(inner, RedisCommandKind::FunctionFlush, vec![arg.into()]).await
+}
+
+pub async fn function_flush_cluster(inner: &Arc<RedisClientInner>, r#async: bool) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionFlushCluster(AllNodesResponse::new(tx));
+  let arg = if r#async { ASYNC } else { SYNC };
+  let command = RedisCommand::new(kind, vec![arg.into()], None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+pub async fn function_list(
+  inner: &Arc<RedisClientInner>,
+  library_pattern: Option<String>,
+  with_code: bool,
+) -> Result<RedisValue, RedisError> {
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(3);
+    if let Some(pattern) = library_pattern {
+      args.push(LIBRARYNAME.into());
+      args.push(pattern.into());
+    }
+    if with_code {
+      args.push(WITHCODE.into());
+    }
+
+    Ok((RedisCommandKind::FunctionList, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn function_load<S>(inner: &Arc<RedisClientInner>, replace: bool, code: S) -> Result<RedisValue, RedisError>
+where
+  S: Into<String>,
+{
+  let code = code.into();
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(2);
+    if replace {
+      args.push(REPLACE.into());
+    }
+    args.push(code.into());
+
+    Ok((RedisCommandKind::FunctionLoad, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn function_load_cluster<S>(
+  inner: &Arc<RedisClientInner>,
+  replace: bool,
+  code: S,
+) -> Result<RedisValue, RedisError>
+where
+  S: Into<String>,
+{
+  let _ = utils::check_clustered(inner)?;
+  let code = code.into();
+  let library_name = utils::function_library_name(&code)?;
+
+  let mut args = Vec::with_capacity(2);
+  if replace {
+    args.push(REPLACE.into());
+  }
+  args.push(code.into());
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionLoadCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, args, None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(library_name.into())
+}
+
+pub async fn function_restore(
+  inner: &Arc<RedisClientInner>,
+  serialized: RedisValue,
+  policy: FnPolicy,
+) -> Result<(), RedisError> {
+  args_ok_cmd(
+    inner,
+    RedisCommandKind::FunctionRestore,
+    vec![serialized, policy.to_str().into()],
+  )
+  .await
+}
+
+pub async fn function_restore_cluster(
+  inner: &Arc<RedisClientInner>,
+  serialized: RedisValue,
+  policy: FnPolicy,
+) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionRestoreCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, vec![serialized, policy.to_str().into()], None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+pub async fn function_stats(inner: &Arc<RedisClientInner>) -> Result<RedisValue, RedisError> {
+  let frame = utils::request_response(inner, || Ok((RedisCommandKind::FunctionStats, vec![]))).await?;
+  protocol_utils::frame_to_results(frame)
+}
@@ -36,6 +36,13 @@ pub static REV: &'static str = "REV";
 pub static ABSTTL: &'static str = "ABSTTL";
 pub static IDLE_TIME: &'static str = "IDLETIME";
 pub static FREQ: &'static str = "FREQ";
+pub static COPY: &'static str = "COPY";
+pub static KEYS: &'static str = "KEYS";
+pub static IDX: &'static str = "IDX";
+pub static MINMATCHLEN: &'static str = "MINMATCHLEN";
+pub static WITHMATCHLEN: &'static str = "WITHMATCHLEN";
+pub static LIBRARYNAME: &'static str = "LIBRARYNAME";
+pub static WITHCODE: &'static str = "WITHCODE";
 
 /// Macro to generate a command function that takes no arguments and expects an OK response - returning `()` to the caller.
 macro_rules! ok_cmd(
@@ -139,6 +146,7 @@ pub async fn args_ok_cmd(
 }
 
 pub mod acl;
+pub mod bitmaps;
 pub mod client;
 pub mod cluster;
 pub mod config;
@@ -1,59 +1,56 @@
 use super::*;
 use crate::error::*;
-use crate::modules::inner::RedisClientInner;
+use crate::modules::inner::{RedisClientInner, Subscriptions};
+use crate::multiplexer::utils as multiplexer_utils;
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
 use crate::types::*;
 use crate::utils;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-pub async fn subscribe<S>(inner: &Arc<RedisClientInner>, channel: S) -> Result<usize, RedisError>
-where
-  S: Into<String>,
-{
-  // note: if this ever changes to take in more than one channel then some additional work must be done
-  // in the multiplexer to associate multiple responses with a single request
-  let results = one_arg_values_cmd(inner, RedisCommandKind::Subscribe, channel.into().into()).await?;
-
+fn parse_subscription_count(results: RedisValue, command: &'static str) -> Result<usize, RedisError> {
   // last value in the array is number of channels
   if let RedisValue::Array(mut values) = results {
-    values
-      .pop()
-      .and_then(|c| c.as_u64())
-      .map(|c| c as usize)
-      .ok_or(RedisError::new(
+    values.pop().and_then(|c| c.as_usize()).ok_or_else(|| {
+      RedisError::new(
         RedisErrorKind::ProtocolError,
-        "Invalid SUBSCRIBE response.",
-      ))
+        format!("Invalid {} response.", command),
+      )
+    })
   } else {
     Err(RedisError::new(
       RedisErrorKind::ProtocolError,
-      "Invalid SUBSCRIBE response.",
+      format!("Invalid {} response.", command),
     ))
   }
 }
 
+pub async fn subscribe<S>(inner: &Arc<RedisClientInner>, channel: S) -> Result<usize, RedisError>
+where
+  S: Into<String>,
+{
+  let channel = channel.into();
+  // note: if this ever changes to take in more than one channel then some additional work must be done
+  // in the multiplexer to associate multiple responses with a single request
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Subscribe, channel.clone().into()).await?;
+  inner.subscriptions.write().channels.insert(channel);
+
+  parse_subscription_count(results, "SUBSCRIBE")
+}
+
 pub async fn unsubscribe<S>(inner: &Arc<RedisClientInner>, channel: S) -> Result<usize, RedisError>
 where
   S: Into<String>,
 {
   // note: if this ever changes to take in more than one channel then some additional work must be done
   // in the multiplexer to associate multiple responses with a single request
-  let results = one_arg_values_cmd(inner, RedisCommandKind::Unsubscribe, channel.into().into()).await?;
+  let channel = channel.into();
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Unsubscribe, channel.clone().into()).await?;
+  inner.subscriptions.write().channels.remove(&channel);
 
-  // last value in the array is number of channels
-  if let RedisValue::Array(mut values) = results {
-    values.pop().and_then(|c| c.as_usize()).ok_or(RedisError::new(
-      RedisErrorKind::ProtocolError,
-      "Invalid UNSUBSCRIBE response.",
-    ))
-  } else {
-    Err(RedisError::new(
-      RedisErrorKind::ProtocolError,
-      "Invalid UNSUBSCRIBE response.",
-    ))
-  }
+  parse_subscription_count(results, "UNSUBSCRIBE")
 }
 
 pub async fn publish<S>(
@@ -73,11 +70,58 @@ where
   protocol_utils::frame_to_single_result(frame)
 }
 
+pub async fn ssubscribe<S>(inner: &Arc<RedisClientInner>, channel: S) -> Result<usize, RedisError>
+where
+  S: Into<String>,
+{
+  let channel = channel.into();
+  // the channel is the first argument so the command is routed to the node that owns the channel's hash slot
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Ssubscribe, channel.clone().into()).await?;
+  inner.subscriptions.write().shard_channels.insert(channel);
+
+  parse_subscription_count(results, "SSUBSCRIBE")
+}
+
+pub async fn sunsubscribe<S>(inner: &Arc<RedisClientInner>, channel: S) -> Result<usize, RedisError>
+where
+  S: Into<String>,
+{
+  let channel = channel.into();
+  // stop tracking the channel first so the response isn't mistaken for a server-initiated unsubscribe after a slot migration
+  inner.subscriptions.write().shard_channels.remove(&channel);
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Sunsubscribe, channel.into()).await?;
+
+  parse_subscription_count(results, "SUNSUBSCRIBE")
+}
+
+pub async fn spublish<S>(
+  inner: &Arc<RedisClientInner>,
+  channel: S,
+  message: RedisValue,
+) -> Result<RedisValue, RedisError>
+where
+  S: Into<String>,
+{
+  let channel = channel.into();
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::Spublish, vec![channel.into(), message]))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
 pub async fn psubscribe<S>(inner: &Arc<RedisClientInner>, patterns: S) -> Result<Vec<usize>, RedisError>
 where
   S: Into<MultipleStrings>,
 {
-  let patterns = patterns.into();
+  let tracked: Vec<String> = patterns
+    .into()
+    .inner()
+    .into_iter()
+    .map(|pattern| pattern.as_str_lossy().into_owned())
+    .collect();
+  let patterns = tracked.clone();
   let frame = utils::request_response(inner, move || {
     let kind = RedisCommandKind::Psubscribe(ResponseKind::Multiple {
       count: patterns.len(),
@@ -85,7 +129,7 @@ where
     });
     let mut args = Vec::with_capacity(patterns.len());
 
-    for pattern in patterns.inner().into_iter() {
+    for pattern in patterns.into_iter() {
       args.push(pattern.into());
     }
 
@@ -94,6 +138,7 @@ where
   .await?;
 
   let result = protocol_utils::frame_to_results(frame)?;
+  inner.subscriptions.write().patterns.extend(tracked);
   if let RedisValue::Array(values) = result {
     utils::pattern_pubsub_counts(values)
   } else {
@@ -108,7 +153,13 @@ pub async fn punsubscribe<S>(inner: &Arc<RedisClientInner>, patterns: S) -> Resu
 where
   S: Into<MultipleStrings>,
 {
-  let patterns = patterns.into();
+  let tracked: Vec<String> = patterns
+    .into()
+    .inner()
+    .into_iter()
+    .map(|pattern| pattern.as_str_lossy().into_owned())
+    .collect();
+  let patterns = tracked.clone();
   let frame = utils::request_response(inner, move || {
     let kind = RedisCommandKind::Punsubscribe(ResponseKind::Multiple {
       count: patterns.len(),
@@ -116,7 +167,7 @@ where
     });
     let mut args = Vec::with_capacity(patterns.len());
 
-    for pattern in patterns.inner().into_iter() {
+    for pattern in patterns.into_iter() {
       args.push(pattern.into());
     }
 
@@ -125,6 +176,16 @@ where
   .await?;
 
   let result = protocol_utils::frame_to_results(frame)?;
+  {
+    let mut subscriptions = inner.subscriptions.write();
+    if tracked.is_empty() {
+      subscriptions.patterns.clear();
+    } else {
+      for pattern in tracked.iter() {
+        subscriptions.patterns.remove(pattern);
+      }
+    }
+  }
   if let RedisValue::Array(values) = result {
     utils::pattern_pubsub_counts(values)
   } else {
@@ -134,3 +195,46 @@ where
     ))
   }
 }
+
+/// Subscribe again to the tracked channels, patterns, and sharded channels, emitting a `GapDetected` event starting
+/// at `disconnected_at` and a `Restored` event when finished.
+pub async fn resubscribe(
+  inner: &Arc<RedisClientInner>,
+  disconnected_at: Option<SystemTime>,
+) -> Result<(), RedisError> {
+  let subscriptions = inner.subscriptions.read().clone();
+  if subscriptions == Subscriptions::default() {
+    if let Some(from) = disconnected_at {
+      multiplexer_utils::emit_pubsub_gap(inner, from);
+    }
+    return Ok(());
+  }
+
+  _debug!(
+    inner,
+    "Restoring {} channels, {} patterns, and {} sharded channels.",
+    subscriptions.channels.len(),
+    subscriptions.patterns.len(),
+    subscriptions.shard_channels.len()
+  );
+  for channel in subscriptions.channels.iter() {
+    let _ = subscribe(inner, channel.to_owned()).await?;
+  }
+  if !subscriptions.patterns.is_empty() {
+    let patterns: Vec<String> = subscriptions.patterns.iter().cloned().collect();
+    let _ = psubscribe(inner, patterns).await?;
+  }
+  for channel in subscriptions.shard_channels.iter() {
+    let _ = ssubscribe(inner, channel.to_owned()).await?;
+  }
+
+  if let Some(from) = disconnected_at {
+    multiplexer_utils::emit_pubsub_gap(inner, from);
+  }
+  multiplexer_utils::emit_pubsub_event(inner, PubsubEvent::Restored {
+    channels: subscriptions.channels,
+    patterns: subscriptions.patterns,
+    shard_channels: subscriptions.shard_channels,
+  });
+  Ok(())
+}
@@ -4,12 +4,15 @@ use crate::modules::inner::RedisClientInner;
 use crate::protocol::types::*;
 use crate::types::*;
 use crate::utils;
-use futures::stream::{Stream, TryStreamExt};
+use futures::stream::{Stream, StreamExt, TryStreamExt};
+use std::cmp;
 use std::sync::Arc;
-use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
-use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio::sync::mpsc::{channel, unbounded_channel, UnboundedSender};
+use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 
 static STARTING_CURSOR: &'static str = "0";
+/// The number of keys buffered by `scan_buffered` when `count` is not provided, matching the server's default `COUNT`.
+const DEFAULT_SCAN_BUFFER: usize = 10;
 
 fn values_args(key: RedisKey, pattern: String, count: Option<u32>) -> Vec<RedisValue> {
   let mut args = Vec::with_capacity(6);
@@ -79,6 +82,7 @@ where
   let err_tx = tx.clone();
   let scan = KeyScanInner {
     key_slot,
+    server: None,
     tx,
     cursor: STARTING_CURSOR.into(),
   };
@@ -93,6 +97,112 @@ where
   UnboundedReceiverStream::new(rx)
 }
 
+pub fn scan_buffered<S>(
+  inner: &Arc<RedisClientInner>,
+  pattern: S,
+  count: Option<u32>,
+  r#type: Option<ScanType>,
+) -> impl Stream<Item = Result<RedisKey, RedisError>>
+where
+  S: Into<String>,
+{
+  let buffer = cmp::max(count.map(|c| c as usize).unwrap_or(DEFAULT_SCAN_BUFFER), 1);
+  let (tx, rx) = channel(buffer);
+  let pattern: String = pattern.into();
+  let mut pages = scan(inner, pattern, count, r#type);
+
+  let _ = tokio::spawn(async move {
+    while let Some(page) = pages.next().await {
+      let mut page = match page {
+        Ok(page) => page,
+        Err(e) => {
+          let _ = tx.send(Err(e)).await;
+          break;
+        }
+      };
+
+      // request the next page before sending the keys so it arrives while the caller reads the current page
+      let keys = page.take_results().unwrap_or_default();
+      if let Err(e) = page.next() {
+        let _ = tx.send(Err(e)).await;
+        break;
+      }
+      for key in keys.into_iter() {
+        if let Err(_) = tx.send(Ok(key)).await {
+          // the caller dropped the stream, and dropping the scan stream stops the scan after the next page
+          return;
+        }
+      }
+    }
+  });
+
+  ReceiverStream::new(rx)
+}
+
+pub fn scan_cluster<S>(
+  inner: &Arc<RedisClientInner>,
+  pattern: S,
+  count: Option<u32>,
+  r#type: Option<ScanType>,
+) -> impl Stream<Item = Result<ScanResult, RedisError>>
+where
+  S: Into<String>,
+{
+  let (tx, rx) = unbounded_channel();
+  if let Err(e) = utils::disallow_during_transaction(inner).and_then(|_| utils::check_clustered(inner)) {
+    early_error(&tx, e);
+    return UnboundedReceiverStream::new(rx);
+  }
+  let cluster_state = match inner.cluster_state.read().clone() {
+    Some(state) => state,
+    None => {
+      early_error(
+        &tx,
+        RedisError::new(RedisErrorKind::Cluster, "Missing cluster state."),
+      );
+      return UnboundedReceiverStream::new(rx);
+    }
+  };
+
+  let pattern = pattern.into();
+  let mut args = Vec::with_capacity(7);
+  args.push(STARTING_CURSOR.into());
+  args.push(MATCH.into());
+  args.push(pattern.into());
+
+  if let Some(count) = count {
+    args.push(COUNT.into());
+    args.push(count.into());
+  }
+  if let Some(r#type) = r#type {
+    args.push(TYPE.into());
+    args.push(r#type.to_str().into());
+  }
+
+  // scan each primary node separately, using a hash slot owned by the node to route each page to it
+  for server in cluster_state.unique_main_nodes().into_iter() {
+    let key_slot = match cluster_state.slots().iter().find(|range| range.server == server) {
+      Some(range) => range.start,
+      None => continue,
+    };
+    _debug!(inner, "Scanning cluster node {} with hash slot {}", server, key_slot);
+
+    let scan = KeyScanInner {
+      key_slot: Some(key_slot),
+      server: Some(server),
+      tx: tx.clone(),
+      cursor: STARTING_CURSOR.into(),
+    };
+    let cmd = RedisCommand::new(RedisCommandKind::Scan(scan), args.clone(), None);
+    if let Err(e) = utils::send_command(inner, cmd) {
+      early_error(&tx, e);
+      break;
+    }
+  }
+
+  UnboundedReceiverStream::new(rx)
+}
+
 pub fn hscan<K, P>(
   inner: &Arc<RedisClientInner>,
   key: K,
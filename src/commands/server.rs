@@ -14,12 +14,19 @@ pub async fn quit(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
   _debug!(inner, "Closing Redis connection with Quit command.");
   utils::interrupt_reconnect_sleep(inner);
 
-  utils::set_client_state(&inner.state, ClientState::Disconnecting);
+  let was_connected = utils::read_client_state(&inner.state) == ClientState::Connected;
+  utils::set_client_state(&inner, ClientState::Disconnecting);
+  if was_connected {
+    utils::wait_for_drain(inner).await;
+  }
   let _ = utils::request_response(&inner, || Ok((RedisCommandKind::Quit, vec![]))).await;
+  streams::close_dedicated_clients(inner);
+  client::close_invalidation_clients(inner);
+  utils::take_subscriptions(inner);
 
   // close anything left over from previous connections or reconnection attempts
   utils::shutdown_listeners(&inner);
-  utils::set_client_state(&inner.state, ClientState::Disconnected);
+  utils::set_client_state(&inner, ClientState::Disconnected);
 
   Ok(())
 }
@@ -28,8 +35,8 @@ pub async fn shutdown(inner: &Arc<RedisClientInner>, flags: Option<ShutdownFlags
   _debug!(inner, "Shutting down server.");
   utils::interrupt_reconnect_sleep(inner);
 
-  utils::set_client_state(&inner.state, ClientState::Disconnecting);
-  let _ = utils::request_response(&inner, move || {
+  utils::set_client_state(&inner, ClientState::Disconnecting);
+  let result = utils::request_response(&inner, move || {
     let args = if let Some(flags) = flags {
       vec![flags.to_str().into()]
     } else {
@@ -38,10 +45,14 @@ pub async fn shutdown(inner: &Arc<RedisClientInner>, flags: Option<ShutdownFlags
 
     Ok((RedisCommandKind::Shutdown, args))
   })
-  .await?;
+  .await;
+  // the dedicated connections point at the same server, so close them even if the response is an error
+  streams::close_dedicated_clients(inner);
+  client::close_invalidation_clients(inner);
+  let _ = result?;
 
   utils::shutdown_listeners(&inner);
-  utils::set_client_state(&inner.state, ClientState::Disconnected);
+  utils::set_client_state(&inner, ClientState::Disconnected);
 
   Ok(())
 }
@@ -96,8 +107,10 @@ pub async fn ping(inner: &Arc<RedisClientInner>) -> Result<RedisValue, RedisErro
 pub async fn select(inner: &Arc<RedisClientInner>, db: u8) -> Result<RedisValue, RedisError> {
   _debug!(inner, "Selecting database {}", db);
   let frame = utils::request_response(inner, || Ok((RedisCommandKind::Select, vec![db.into()]))).await?;
+  let result = protocol_utils::frame_to_single_result(frame)?;
 
-  protocol_utils::frame_to_single_result(frame)
+  utils::set_selected_database(inner, db);
+  Ok(result)
 }
 
 pub async fn info(inner: &Arc<RedisClientInner>, section: Option<InfoKind>) -> Result<RedisValue, RedisError> {
@@ -125,6 +138,11 @@ pub async fn exec(inner: &Arc<RedisClientInner>) -> Result<RedisValue, RedisErro
   protocol_utils::frame_to_results(frame)
 }
 
+pub async fn exec_all(inner: &Arc<RedisClientInner>) -> Result<Vec<Result<RedisValue, RedisError>>, RedisError> {
+  let frame = utils::request_response(inner, move || Ok((RedisCommandKind::Exec, vec![]))).await?;
+  protocol_utils::frame_to_transaction_results(frame)
+}
+
 pub async fn discard(inner: &Arc<RedisClientInner>) -> Result<(), RedisError> {
   let frame = utils::request_response(inner, move || Ok((RedisCommandKind::Discard, vec![]))).await?;
   let _ = protocol_utils::frame_to_single_result(frame)?;
@@ -173,7 +191,37 @@ pub async fn custom(
   cmd: CustomCommand,
   args: Vec<RedisValue>,
 ) -> Result<RedisValue, RedisError> {
-  args_values_cmd(inner, RedisCommandKind::_Custom(cmd), args).await
+  let mut cmd = cmd;
+  if cmd.hash_slot.is_none() && utils::is_clustered(&inner.config) {
+    let routing = inner.custom_routing.read().get(&cmd.cmd.to_uppercase()).cloned();
+
+    if let Some(routing) = routing {
+      cmd.hash_slot = routing.hash_slot(&args)?;
+    }
+  }
+
+  let frame = utils::typed_request_response(inner, move || Ok((RedisCommandKind::_Custom(cmd), args))).await?;
+  protocol_utils::response_frame_to_results(frame)
+}
+
+pub async fn wait(
+  inner: &Arc<RedisClientInner>,
+  numreplicas: i64,
+  timeout: i64,
+  hash_slot: Option<u16>,
+) -> Result<RedisValue, RedisError> {
+  // route the command to the node that received the previous write, which is only possible with a custom command
+  let kind = if hash_slot.is_some() && utils::is_clustered(&inner.config) {
+    RedisCommandKind::_Custom(CustomCommand {
+      cmd: "WAIT",
+      hash_slot,
+      is_blocking: true,
+    })
+  } else {
+    RedisCommandKind::Wait
+  };
+
+  args_value_cmd(inner, kind, vec![numreplicas.into(), timeout.into()]).await
 }
 
 value_cmd!(dbsize, DBSize);
@@ -1,11 +1,781 @@
+use super::*;
+use crate::client::RedisClient;
+use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::*;
+use crate::protocol::utils as protocol_utils;
+use crate::types::*;
+use crate::utils;
+use redis_protocol::resp2::types::Frame as ProtocolFrame;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval as tokio_interval;
 
+static NOMKSTREAM: &'static str = "NOMKSTREAM";
+static BLOCK: &'static str = "BLOCK";
+static STREAMS: &'static str = "STREAMS";
+static MKSTREAM: &'static str = "MKSTREAM";
+static GROUP: &'static str = "GROUP";
+static NOACK: &'static str = "NOACK";
+static IDLE: &'static str = "IDLE";
+static TIME: &'static str = "TIME";
+static RETRYCOUNT: &'static str = "RETRYCOUNT";
+static JUSTID: &'static str = "JUSTID";
+static FULL: &'static str = "FULL";
 
+pub async fn xadd<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  nomkstream: bool,
+  cap: Option<XCap>,
+  id: XID,
+  fields: RedisMap,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
 
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(8 + (fields.len() * 2));
+    args.push(key.into());
 
+    if nomkstream {
+      args.push(NOMKSTREAM.into());
+    }
+    if let Some(cap) = cap {
+      args.append(&mut cap.into_args());
+    }
+    args.push(id.into_value());
 
+    for (field, value) in fields.inner().into_iter() {
+      args.push(field.into());
+      args.push(value);
+    }
 
+    Ok((RedisCommandKind::Xadd, args))
+  })
+  .await?;
 
+  protocol_utils::frame_to_single_result(frame)
+}
 
+pub async fn xlen<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  one_arg_value_cmd(inner, RedisCommandKind::Xlen, key.into().into()).await
+}
 
+async fn range_cmd(
+  inner: &Arc<RedisClientInner>,
+  kind: RedisCommandKind,
+  key: RedisKey,
+  first: XID,
+  second: XID,
+  count: Option<u64>,
+) -> Result<Vec<StreamEntry>, RedisError> {
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(5);
+    args.push(key.into());
+    args.push(first.into_value());
+    args.push(second.into_value());
 
+    if let Some(count) = count {
+      args.push(COUNT.into());
+      args.push(count.to_string().into());
+    }
 
+    Ok((kind, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_stream_entries(frame)
+}
+
+pub async fn xrange<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  start: XID,
+  end: XID,
+  count: Option<u64>,
+) -> Result<Vec<StreamEntry>, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  range_cmd(inner, RedisCommandKind::Xrange, key.into(), start, end, count).await
+}
+
+pub async fn xrevrange<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  end: XID,
+  start: XID,
+  count: Option<u64>,
+) -> Result<Vec<StreamEntry>, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  range_cmd(inner, RedisCommandKind::Xrevrange, key.into(), end, start, count).await
+}
+
+fn check_stream_ids(keys: &Vec<RedisKey>, ids: &Vec<XID>) -> Result<(), RedisError> {
+  if keys.is_empty() || keys.len() != ids.len() {
+    Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "Expected one ID for each stream key.",
+    ))
+  } else {
+    Ok(())
+  }
+}
+
+/// A client checked out of the pool of dedicated connections for blocking stream reads.
+///
+/// The client is returned to the pool when the read finishes. If the read's future is dropped while the connection is
+/// blocked the client is closed instead.
+struct DedicatedClientGuard {
+  inner: Arc<RedisClientInner>,
+  client: Option<RedisClient>,
+  finished: bool,
+}
+
+impl Drop for DedicatedClientGuard {
+  fn drop(&mut self) {
+    if let Some(client) = self.client.take() {
+      let parent_connected = utils::read_client_state(&self.inner.state) == ClientState::Connected;
+
+      if self.finished && parent_connected && client.is_connected() {
+        self.inner.stream_clients.write().push(client);
+      } else {
+        let inner = &self.inner;
+        _debug!(inner, "Closing dedicated stream connection {}", client.id());
+        // the guard can be dropped outside of a runtime, in which case the connection closes with the runtime
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+          handle.spawn(async move {
+            let _ = client.quit().await;
+          });
+        }
+      }
+    }
+  }
+}
+
+fn use_dedicated_client(inner: &Arc<RedisClientInner>, block: Option<u64>) -> bool {
+  block.is_some() && inner.config.read().dedicated_blocking_streams && inner.multi_block.read().is_none()
+}
+
+async fn checkout_dedicated_client(inner: &Arc<RedisClientInner>) -> Result<DedicatedClientGuard, RedisError> {
+  let idle = inner.stream_clients.write().pop();
+
+  let client = match idle {
+    Some(client) => client,
+    None => {
+      let mut config = inner.config.read().clone();
+      config.dedicated_blocking_streams = false;
+      config.tracking = None;
+
+      let client = RedisClient::new(config);
+      _debug!(inner, "Creating dedicated stream connection {}", client.id());
+      let _ = client.connect(inner.policy.read().clone());
+      client.wait_for_connect().await?;
+      client
+    }
+  };
+
+  Ok(DedicatedClientGuard {
+    inner: inner.clone(),
+    client: Some(client),
+    finished: false,
+  })
+}
+
+/// Close the idle dedicated connections used for blocking stream reads.
+pub fn close_dedicated_clients(inner: &Arc<RedisClientInner>) {
+  for client in inner.stream_clients.write().drain(..) {
+    _debug!(inner, "Closing dedicated stream connection {}", client.id());
+    tokio::spawn(async move {
+      let _ = client.quit().await;
+    });
+  }
+}
+
+async fn send_read_streams(
+  inner: &Arc<RedisClientInner>,
+  group: Option<(String, String)>,
+  count: Option<u64>,
+  block: Option<u64>,
+  noack: bool,
+  keys: Vec<RedisKey>,
+  ids: Vec<XID>,
+) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError> {
+  let key_slot = lua::check_key_slot(inner, &keys)?.key_slot;
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(9 + (keys.len() * 2));
+    let inner = XreadInner {
+      key_slot,
+      blocking: block.is_some(),
+    };
+
+    let kind = if let Some((group, consumer)) = group {
+      args.push(GROUP.into());
+      args.push(group.into());
+      args.push(consumer.into());
+      RedisCommandKind::Xreadgroup(inner)
+    } else {
+      RedisCommandKind::Xread(inner)
+    };
+
+    if let Some(count) = count {
+      args.push(COUNT.into());
+      args.push(count.to_string().into());
+    }
+    if let Some(block) = block {
+      args.push(BLOCK.into());
+      args.push(block.to_string().into());
+    }
+    if noack {
+      args.push(NOACK.into());
+    }
+    args.push(STREAMS.into());
+
+    for key in keys.into_iter() {
+      args.push(key.into());
+    }
+    for id in ids.into_iter() {
+      args.push(id.into_value());
+    }
+
+    Ok((kind, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_xread_result(frame)
+}
+
+async fn read_streams(
+  inner: &Arc<RedisClientInner>,
+  group: Option<(String, String)>,
+  count: Option<u64>,
+  block: Option<u64>,
+  noack: bool,
+  keys: MultipleKeys,
+  ids: MultipleIDs,
+) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError> {
+  let (keys, ids) = (keys.inner(), ids.inner());
+  let _ = check_stream_ids(&keys, &ids)?;
+
+  if use_dedicated_client(inner, block) {
+    let mut guard = checkout_dedicated_client(inner).await?;
+    let client_inner = match guard.client {
+      Some(ref client) => client.inner.clone(),
+      None => return Err(RedisError::new(RedisErrorKind::Unknown, "Missing dedicated client.")),
+    };
+
+    let result = send_read_streams(&client_inner, group, count, block, noack, keys, ids).await;
+    guard.finished = true;
+    result
+  } else {
+    send_read_streams(inner, group, count, block, noack, keys, ids).await
+  }
+}
+
+pub async fn xread(
+  inner: &Arc<RedisClientInner>,
+  count: Option<u64>,
+  block: Option<u64>,
+  keys: MultipleKeys,
+  ids: MultipleIDs,
+) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError> {
+  read_streams(inner, None, count, block, false, keys, ids).await
+}
+
+pub async fn xreadgroup<G, C>(
+  inner: &Arc<RedisClientInner>,
+  group: G,
+  consumer: C,
+  count: Option<u64>,
+  block: Option<u64>,
+  noack: bool,
+  keys: MultipleKeys,
+  ids: MultipleIDs,
+) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError>
+where
+  G: Into<String>,
+  C: Into<String>,
+{
+  let group = Some((group.into(), consumer.into()));
+  read_streams(inner, group, count, block, noack, keys, ids).await
+}
+
+pub async fn xgroup_create<K, G>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  id: XID,
+  mkstream: bool,
+) -> Result<(), RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let (key, group) = (key.into(), group.into());
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(4);
+    args.push(key.into());
+    args.push(group.into());
+    args.push(id.into_value());
+    if mkstream {
+      args.push(MKSTREAM.into());
+    }
+
+    Ok((RedisCommandKind::XgroupCreate, args))
+  })
+  .await?;
+
+  let response = protocol_utils::frame_to_single_result(frame)?;
+  protocol_utils::expect_ok(&response)
+}
+
+pub async fn xgroup_createconsumer<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let args = vec![key.into().into(), group.into().into(), consumer.into().into()];
+  args_value_cmd(inner, RedisCommandKind::XgroupCreateConsumer, args).await
+}
+
+pub async fn xgroup_delconsumer<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let args = vec![key.into().into(), group.into().into(), consumer.into().into()];
+  args_value_cmd(inner, RedisCommandKind::XgroupDelConsumer, args).await
+}
+
+pub async fn xgroup_destroy<K, G>(inner: &Arc<RedisClientInner>, key: K, group: G) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let args = vec![key.into().into(), group.into().into()];
+  args_value_cmd(inner, RedisCommandKind::XgroupDestroy, args).await
+}
+
+pub async fn xgroup_setid<K, G>(inner: &Arc<RedisClientInner>, key: K, group: G, id: XID) -> Result<(), RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let args = vec![key.into().into(), group.into().into(), id.into_value()];
+  args_ok_cmd(inner, RedisCommandKind::XgroupSetId, args).await
+}
+
+pub async fn xack<K, G>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  ids: MultipleIDs,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let (key, group) = (key.into(), group.into());
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(2 + ids.len());
+    args.push(key.into());
+    args.push(group.into());
+
+    for id in ids.inner().into_iter() {
+      args.push(id.into_value());
+    }
+
+    Ok((RedisCommandKind::Xack, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn xpending<K, G>(inner: &Arc<RedisClientInner>, key: K, group: G) -> Result<XPendingSummary, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let (key, group) = (key.into(), group.into());
+
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::Xpending, vec![key.into(), group.into()]))
+  })
+  .await?;
+
+  protocol_utils::frame_to_xpending_summary(frame)
+}
+
+pub async fn xpending_ext<K, G>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  idle: Option<u64>,
+  start: XID,
+  end: XID,
+  count: u64,
+  consumer: Option<String>,
+) -> Result<Vec<XPendingEntry>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let (key, group) = (key.into(), group.into());
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(8);
+    args.push(key.into());
+    args.push(group.into());
+
+    if let Some(idle) = idle {
+      args.push(IDLE.into());
+      args.push(idle.to_string().into());
+    }
+    args.push(start.into_value());
+    args.push(end.into_value());
+    args.push(count.to_string().into());
+
+    if let Some(consumer) = consumer {
+      args.push(consumer.into());
+    }
+
+    Ok((RedisCommandKind::Xpending, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_xpending_entries(frame)
+}
+
+async fn xclaim_frame(
+  inner: &Arc<RedisClientInner>,
+  key: RedisKey,
+  group: String,
+  consumer: String,
+  min_idle_time: u64,
+  ids: MultipleIDs,
+  options: XClaimOptions,
+  justid: bool,
+) -> Result<ProtocolFrame, RedisError> {
+  utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(12 + ids.len());
+    args.push(key.into());
+    args.push(group.into());
+    args.push(consumer.into());
+    args.push(min_idle_time.to_string().into());
+
+    for id in ids.inner().into_iter() {
+      args.push(id.into_value());
+    }
+    if let Some(idle) = options.idle {
+      args.push(IDLE.into());
+      args.push(idle.to_string().into());
+    }
+    if let Some(time) = options.time {
+      args.push(TIME.into());
+      args.push(time.to_string().into());
+    }
+    if let Some(retry_count) = options.retry_count {
+      args.push(RETRYCOUNT.into());
+      args.push(retry_count.to_string().into());
+    }
+    if options.force {
+      args.push(FORCE.into());
+    }
+    if justid {
+      args.push(JUSTID.into());
+    }
+
+    Ok((RedisCommandKind::Xclaim, args))
+  })
+  .await
+}
+
+pub async fn xclaim<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+  min_idle_time: u64,
+  ids: MultipleIDs,
+  options: XClaimOptions,
+) -> Result<Vec<StreamEntry>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let frame = xclaim_frame(
+    inner,
+    key.into(),
+    group.into(),
+    consumer.into(),
+    min_idle_time,
+    ids,
+    options,
+    false,
+  )
+  .await?;
+
+  protocol_utils::frame_to_stream_entries(frame)
+}
+
+pub async fn xclaim_justid<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+  min_idle_time: u64,
+  ids: MultipleIDs,
+  options: XClaimOptions,
+) -> Result<Vec<String>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let frame = xclaim_frame(
+    inner,
+    key.into(),
+    group.into(),
+    consumer.into(),
+    min_idle_time,
+    ids,
+    options,
+    true,
+  )
+  .await?;
+
+  protocol_utils::frame_to_stream_ids(frame)
+}
+
+async fn xautoclaim_frame(
+  inner: &Arc<RedisClientInner>,
+  key: RedisKey,
+  group: String,
+  consumer: String,
+  min_idle_time: u64,
+  start: XID,
+  count: Option<u64>,
+  justid: bool,
+) -> Result<ProtocolFrame, RedisError> {
+  utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(8);
+    args.push(key.into());
+    args.push(group.into());
+    args.push(consumer.into());
+    args.push(min_idle_time.to_string().into());
+    args.push(start.into_value());
+
+    if let Some(count) = count {
+      args.push(COUNT.into());
+      args.push(count.to_string().into());
+    }
+    if justid {
+      args.push(JUSTID.into());
+    }
+
+    Ok((RedisCommandKind::Xautoclaim, args))
+  })
+  .await
+}
+
+pub async fn xautoclaim<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+  min_idle_time: u64,
+  start: XID,
+  count: Option<u64>,
+) -> Result<XAutoClaimResult<StreamEntry>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let frame = xautoclaim_frame(
+    inner,
+    key.into(),
+    group.into(),
+    consumer.into(),
+    min_idle_time,
+    start,
+    count,
+    false,
+  )
+  .await?;
+
+  protocol_utils::frame_to_xautoclaim_result(frame, protocol_utils::frame_to_stream_entries)
+}
+
+pub async fn xautoclaim_justid<K, G, C>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+  consumer: C,
+  min_idle_time: u64,
+  start: XID,
+  count: Option<u64>,
+) -> Result<XAutoClaimResult<String>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+  C: Into<String>,
+{
+  let frame = xautoclaim_frame(
+    inner,
+    key.into(),
+    group.into(),
+    consumer.into(),
+    min_idle_time,
+    start,
+    count,
+    true,
+  )
+  .await?;
+
+  protocol_utils::frame_to_xautoclaim_result(frame, protocol_utils::frame_to_stream_ids)
+}
+
+pub async fn xinfo_stream<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<StreamInfo, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  let frame = utils::request_response(inner, move || Ok((RedisCommandKind::XinfoStream, vec![key.into()]))).await?;
+
+  protocol_utils::frame_to_stream_info(frame)
+}
+
+pub async fn xinfo_stream_full<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  count: Option<u64>,
+) -> Result<StreamInfoFull, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(4);
+    args.push(key.into());
+    args.push(FULL.into());
+
+    if let Some(count) = count {
+      args.push(COUNT.into());
+      args.push(count.to_string().into());
+    }
+
+    Ok((RedisCommandKind::XinfoStream, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_stream_info_full(frame)
+}
+
+pub async fn xinfo_groups<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<Vec<GroupInfo>, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  let frame = utils::request_response(inner, move || Ok((RedisCommandKind::XinfoGroups, vec![key.into()]))).await?;
+
+  protocol_utils::frame_to_group_info(frame)
+}
+
+pub async fn xinfo_consumers<K, G>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  group: G,
+) -> Result<Vec<ConsumerInfo>, RedisError>
+where
+  K: Into<RedisKey>,
+  G: Into<String>,
+{
+  let (key, group) = (key.into(), group.into());
+
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::XinfoConsumers, vec![key.into(), group.into()]))
+  })
+  .await?;
+
+  protocol_utils::frame_to_consumer_info(frame)
+}
+
+pub async fn xtrim<K>(inner: &Arc<RedisClientInner>, key: K, cap: XCap) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(6);
+    args.push(key.into());
+    args.append(&mut cap.into_args());
+
+    Ok((RedisCommandKind::Xtrim, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn trim_on_interval(
+  inner: &Arc<RedisClientInner>,
+  interval: Duration,
+  policies: Vec<StreamTrimPolicy>,
+) -> Result<(), RedisError> {
+  let mut interval = tokio_interval(interval);
+  loop {
+    interval.tick().await;
+
+    if utils::is_locked_some(&inner.multi_block) {
+      _debug!(inner, "Skip stream trimming while inside transaction.");
+      continue;
+    }
+    match utils::read_client_state(&inner.state) {
+      ClientState::Connected => {}
+      ClientState::Connecting | ClientState::Reconnecting { .. } => continue,
+      _ => break,
+    };
+
+    for policy in policies.iter() {
+      match xtrim(inner, policy.key.clone(), policy.cap.clone()).await {
+        Ok(RedisValue::Integer(count)) if count > 0 => {
+          _trace!(inner, "Trimmed {} entries from {}", count, policy.key.as_str_lossy());
+          utils::incr_atomic_by(&inner.trimmed_count, count as usize);
+        }
+        Ok(_) => {}
+        Err(e) => _warn!(inner, "Error trimming stream {}: {:?}", policy.key.as_str_lossy(), e),
+      };
+    }
+  }
+
+  Ok(())
+}
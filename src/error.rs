@@ -33,7 +33,7 @@ pub enum RedisErrorKind {
   UrlError,
   /// A protocol error such as an invalid or unexpected frame from the server.
   ProtocolError,
-  /// A TLS error. The `enable-tls` feature must be enabled for this to be used.
+  /// A TLS error. The `enable-tls` or `enable-rustls` feature must be enabled for this to be used.
   Tls,
   /// An error indicating the request was canceled.
   Canceled,
@@ -47,8 +47,26 @@ pub enum RedisErrorKind {
   Parse,
   /// An error communicating with redis sentinel.
   Sentinel,
-  /// An error indicating a value was not found, often used when trying to cast a `nil` response from the server to a non-nullable type.
+  /// An error indicating a value was not found.
   NotFound,
+  /// An error indicating the command is not supported by the server, such as when the server version is too old.
+  Unsupported,
+  /// An error indicating a write was not acknowledged by the required number of replicas via `WAIT`.
+  NotEnoughReplicas,
+  /// An error indicating the client stopped trying to reconnect. See [unrecoverable_errors](crate::types::RedisConfig::unrecoverable_errors).
+  Unrecoverable,
+  /// An error indicating the user does not have permission to run a command, usually due to ACL rules.
+  Permission,
+  /// An error indicating the command was rejected because too many commands were in-flight or buffered. See
+  /// [BackpressurePolicy](crate::types::BackpressurePolicy).
+  Backpressure,
+  /// An error indicating the script is not in the server's script cache, usually returned by `EVALSHA`.
+  NoScript,
+  /// An error indicating the server is busy running a script or function. See [BusyRetryConfig](crate::types::BusyRetryConfig).
+  Busy,
+  /// An error indicating a `nil` response was converted into a type that cannot represent it. Responses that may be `nil`
+  /// should be converted into an `Option<T>`.
+  UnexpectedNil,
 }
 
 impl RedisErrorKind {
@@ -69,6 +87,14 @@ impl RedisErrorKind {
       RedisErrorKind::Parse => "Parse Error",
       RedisErrorKind::Sentinel => "Sentinel Error",
       RedisErrorKind::NotFound => "Not Found",
+      RedisErrorKind::Unsupported => "Unsupported",
+      RedisErrorKind::NotEnoughReplicas => "Not Enough Replicas",
+      RedisErrorKind::Unrecoverable => "Unrecoverable Error",
+      RedisErrorKind::Permission => "Permission Error",
+      RedisErrorKind::Backpressure => "Backpressure Error",
+      RedisErrorKind::NoScript => "No Script Error",
+      RedisErrorKind::Busy => "Busy Error",
+      RedisErrorKind::UnexpectedNil => "Unexpected Nil",
     }
   }
 }
@@ -232,6 +258,14 @@ impl From<native_tls::Error> for RedisError {
   }
 }
 
+#[cfg(feature = "serde-json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-json")))]
+impl From<serde_json::Error> for RedisError {
+  fn from(e: serde_json::Error) -> Self {
+    RedisError::new(RedisErrorKind::Parse, format!("{:?}", e))
+  }
+}
+
 impl RedisError {
   /// Create a new Redis error with the provided details.
   pub fn new<T>(kind: RedisErrorKind, details: T) -> RedisError
@@ -315,6 +349,14 @@ impl RedisError {
     RedisError::new(RedisErrorKind::Parse, details)
   }
 
+  /// Create a new error for a `nil` response converted into a non-optional type.
+  pub(crate) fn new_unexpected_nil(type_name: &str) -> RedisError {
+    RedisError::new(
+      RedisErrorKind::UnexpectedNil,
+      format!("Cannot convert nil response to {}.", type_name),
+    )
+  }
+
   /// Whether or not the error is a `Canceled` error.
   pub fn is_canceled(&self) -> bool {
     match self.kind {
@@ -330,6 +372,46 @@ impl RedisError {
       _ => false,
     }
   }
+
+  /// Whether or not the error is an `UnexpectedNil` error.
+  pub fn is_unexpected_nil(&self) -> bool {
+    match self.kind {
+      RedisErrorKind::UnexpectedNil => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the error is an `Unsupported` error.
+  pub fn is_unsupported(&self) -> bool {
+    match self.kind {
+      RedisErrorKind::Unsupported => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the error is a `NoScript` error.
+  pub fn is_no_script(&self) -> bool {
+    match self.kind {
+      RedisErrorKind::NoScript => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the error is a `Busy` error.
+  pub fn is_busy(&self) -> bool {
+    match self.kind {
+      RedisErrorKind::Busy => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the error is a `NotEnoughReplicas` error.
+  pub fn is_not_enough_replicas(&self) -> bool {
+    match self.kind {
+      RedisErrorKind::NotEnoughReplicas => true,
+      _ => false,
+    }
+  }
 }
 
 impl Error for RedisError {
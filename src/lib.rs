@@ -55,6 +55,10 @@ extern crate indexmap;
 extern crate native_tls;
 #[cfg(feature = "enable-tls")]
 extern crate tokio_native_tls;
+#[cfg(feature = "enable-rustls")]
+extern crate tokio_rustls;
+#[cfg(feature = "enable-rustls")]
+extern crate webpki_roots;
 #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
 extern crate tracing;
 #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
@@ -78,12 +82,20 @@ pub mod error;
 #[cfg(feature = "monitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "monitor")))]
 pub mod monitor;
+/// An interface for consuming the replication stream from a primary node via `PSYNC`.
+#[cfg(feature = "replica")]
+#[cfg_attr(docsrs, doc(cfg(feature = "replica")))]
+pub mod replica;
 /// An interface for interacting directly with sentinel nodes.
 #[cfg(feature = "sentinel-client")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sentinel-client")))]
 pub mod sentinel;
+/// Utilities for writing hermetic tests against a shared Redis server.
+#[cfg(feature = "test-utils")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub mod testing;
 
-pub use crate::modules::{globals, pool, types};
+pub use crate::modules::{analytics, bulk, dump, globals, migrate, pool, script, types};
 
 /// Convenience module to `use` a `RedisClient`, `RedisError`, and any argument types.
 pub mod prelude {
@@ -0,0 +1,271 @@
+use crate::client::RedisClient;
+use crate::commands;
+use crate::error::RedisError;
+use crate::modules::migrate;
+use crate::types::RedisKey;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+/// Options for [keyspace_report](keyspace_report).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportOptions {
+  /// The pattern used to `SCAN` the keyspace.
+  ///
+  /// Default: `*`
+  pub pattern: String,
+  /// The delimiter used to split keys into prefixes.
+  ///
+  /// Default: `:`
+  pub delimiter: char,
+  /// The maximum number of delimited segments to include in each prefix.
+  ///
+  /// For example, with a depth of `2` the key `a:b:c:d` is grouped under `a:b`. Keys without the delimiter are grouped under the
+  /// empty prefix.
+  ///
+  /// Default: `1`
+  pub depth: usize,
+  /// Read `MEMORY USAGE` on one of every `memory_sample_interval` keys, extrapolating the memory usage of the other keys from
+  /// the average of the sampled keys in each prefix. Setting this to `0` will skip `MEMORY USAGE` entirely.
+  ///
+  /// Default: `1`
+  pub memory_sample_interval: usize,
+  /// The `COUNT` argument used when scanning the keyspace.
+  ///
+  /// Default: `100`
+  pub scan_count: u32,
+  /// The maximum number of keys inspected concurrently.
+  ///
+  /// Default: `10`
+  pub parallelism: usize,
+}
+
+impl Default for ReportOptions {
+  fn default() -> Self {
+    ReportOptions {
+      pattern: "*".into(),
+      delimiter: ':',
+      depth: 1,
+      memory_sample_interval: 1,
+      scan_count: 100,
+      parallelism: 10,
+    }
+  }
+}
+
+/// The number of keys in each TTL range.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TtlDistribution {
+  /// Keys without an expiration.
+  pub none: usize,
+  /// Keys that expire in less than one minute.
+  pub under_minute: usize,
+  /// Keys that expire in at least one minute and less than one hour.
+  pub under_hour: usize,
+  /// Keys that expire in at least one hour and less than one day.
+  pub under_day: usize,
+  /// Keys that expire in one day or more.
+  pub over_day: usize,
+}
+
+impl TtlDistribution {
+  fn add(&mut self, pttl: i64) {
+    match pttl {
+      p if p < 0 => self.none += 1,
+      p if p < 60_000 => self.under_minute += 1,
+      p if p < 3_600_000 => self.under_hour += 1,
+      p if p < 86_400_000 => self.under_day += 1,
+      _ => self.over_day += 1,
+    }
+  }
+}
+
+/// Statistics about the keys that share a prefix.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrefixStats {
+  /// The number of keys with the prefix.
+  pub keys: usize,
+  /// The number of keys on which `MEMORY USAGE` was read.
+  pub sampled_keys: usize,
+  /// The total memory used by the sampled keys, in bytes.
+  pub sampled_memory: u64,
+  /// The distribution of TTLs on the keys.
+  pub ttl: TtlDistribution,
+  /// The number of keys of each type, keyed by the output of `TYPE`.
+  pub types: HashMap<String, usize>,
+}
+
+impl PrefixStats {
+  /// Estimate the total memory used by the keys with this prefix, in bytes.
+  pub fn estimated_memory(&self) -> u64 {
+    if self.sampled_keys == 0 {
+      0
+    } else {
+      (self.sampled_memory as f64 / self.sampled_keys as f64 * self.keys as f64) as u64
+    }
+  }
+}
+
+/// A report on the keyspace grouped by prefix.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyspaceReport {
+  /// The total number of keys scanned.
+  pub keys: usize,
+  /// Statistics for each prefix.
+  pub prefixes: BTreeMap<String, PrefixStats>,
+}
+
+impl KeyspaceReport {
+  /// Estimate the total memory used by all scanned keys, in bytes.
+  pub fn estimated_memory(&self) -> u64 {
+    self.prefixes.values().map(|stats| stats.estimated_memory()).sum()
+  }
+
+  /// Read the prefixes sorted by estimated memory usage, largest first.
+  pub fn by_memory(&self) -> Vec<(&String, &PrefixStats)> {
+    let mut out: Vec<_> = self.prefixes.iter().collect();
+    out.sort_by(|(_, lhs), (_, rhs)| match rhs.estimated_memory().cmp(&lhs.estimated_memory()) {
+      Ordering::Equal => rhs.keys.cmp(&lhs.keys),
+      ordering => ordering,
+    });
+    out
+  }
+}
+
+struct KeyInfo {
+  key: RedisKey,
+  kind: String,
+  pttl: i64,
+  memory: Option<u64>,
+}
+
+fn key_prefix(key: &str, delimiter: char, depth: usize) -> String {
+  let segments: Vec<&str> = key.split(delimiter).collect();
+  let len = (segments.len() - 1).min(depth);
+
+  segments[.. len].join(&delimiter.to_string())
+}
+
+async fn inspect_key(client: &RedisClient, key: RedisKey, sample: bool) -> Result<KeyInfo, RedisError> {
+  let kind = commands::keys::r#type(&client.inner, key.clone()).await?;
+  let pttl = client.pttl::<i64, _>(key.clone()).await?;
+  let memory = if sample {
+    client.memory_usage(key.clone(), None).await?
+  } else {
+    None
+  };
+
+  Ok(KeyInfo {
+    key,
+    kind: kind.as_string().unwrap_or_default(),
+    pttl,
+    memory,
+  })
+}
+
+/// Scan the keyspace and group keys by prefix, reporting the number of keys, memory usage, TTL distribution, and types of keys
+/// under each prefix.
+///
+/// If the client is clustered the keyspace will be scanned on each primary node in the cluster. Keys that are deleted while the
+/// report is running will be counted with the `none` type.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::analytics::{self, ReportOptions};
+/// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+///   let report = analytics::keyspace_report(client, ReportOptions::default()).await?;
+///
+///   for (prefix, stats) in report.by_memory().into_iter().take(10) {
+///     println!("{}: {} keys, ~{} bytes", prefix, stats.keys, stats.estimated_memory());
+///   }
+///   Ok(())
+/// }
+/// ```
+pub async fn keyspace_report(client: &RedisClient, options: ReportOptions) -> Result<KeyspaceReport, RedisError> {
+  let (clients, should_quit) = migrate::scan_clients(client).await?;
+  let result = build_report(&clients, &options).await;
+
+  if should_quit {
+    for client in clients.iter() {
+      let _ = client.quit().await;
+    }
+  }
+  result
+}
+
+async fn build_report(clients: &[RedisClient], options: &ReportOptions) -> Result<KeyspaceReport, RedisError> {
+  let mut report = KeyspaceReport::default();
+
+  for client in clients.iter() {
+    let mut scanner = client.scan(options.pattern.clone(), Some(options.scan_count), None);
+
+    while let Some(page) = scanner.next().await {
+      let mut page = page?;
+      let keys = page.take_results().unwrap_or_default();
+      let offset = report.keys;
+      report.keys += keys.len();
+
+      let interval = options.memory_sample_interval;
+      let mut results = stream::iter(keys.into_iter().enumerate())
+        .map(|(idx, key)| inspect_key(client, key, interval > 0 && (offset + idx) % interval == 0))
+        .buffer_unordered(options.parallelism.max(1));
+
+      while let Some(info) = results.try_next().await? {
+        let prefix = key_prefix(&info.key.as_str_lossy(), options.delimiter, options.depth);
+        let stats = report.prefixes.entry(prefix).or_default();
+
+        stats.keys += 1;
+        stats.ttl.add(info.pttl);
+        *stats.types.entry(info.kind).or_insert(0) += 1;
+        if let Some(memory) = info.memory {
+          stats.sampled_keys += 1;
+          stats.sampled_memory += memory;
+        }
+      }
+
+      let _ = page.next()?;
+    }
+  }
+
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_group_keys_by_prefix() {
+    assert_eq!(key_prefix("a:b:c:d", ':', 1), "a");
+    assert_eq!(key_prefix("a:b:c:d", ':', 2), "a:b");
+    assert_eq!(key_prefix("a:b", ':', 5), "a");
+    assert_eq!(key_prefix("foo", ':', 1), "");
+    assert_eq!(key_prefix("a/b/c", '/', 2), "a/b");
+  }
+
+  #[test]
+  fn should_estimate_memory_from_samples() {
+    let stats = PrefixStats {
+      keys: 10,
+      sampled_keys: 2,
+      sampled_memory: 200,
+      ..Default::default()
+    };
+
+    assert_eq!(stats.estimated_memory(), 1000);
+  }
+
+  #[test]
+  fn should_bucket_ttls() {
+    let mut ttl = TtlDistribution::default();
+    for pttl in [-1, 1_000, 120_000, 7_200_000, 100_000_000].iter() {
+      ttl.add(*pttl);
+    }
+
+    assert_eq!(ttl.none, 1);
+    assert_eq!(ttl.under_minute, 1);
+    assert_eq!(ttl.under_hour, 1);
+    assert_eq!(ttl.under_day, 1);
+    assert_eq!(ttl.over_day, 1);
+  }
+}
@@ -2,11 +2,14 @@ use crate::error::RedisError;
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::ConnectionIDs;
 use crate::protocol::connection;
+#[cfg(unix)]
+use crate::protocol::connection::FramedUnix;
 use crate::protocol::connection::{FramedTcp, FramedTls, RedisTransport};
 use crate::protocol::types::RedisCommand;
 use crate::protocol::utils as protocol_utils;
 use crate::types::Resolve;
 use redis_protocol::resp2::types::Frame as ProtocolFrame;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 async fn create_transport(
@@ -44,11 +47,23 @@ fn map_tls_response(
     .map_err(|(e, t)| (e, RedisTransport::Tls(t)))
 }
 
+#[cfg(unix)]
+fn map_unix_response(
+  result: Result<(ProtocolFrame, FramedUnix), (RedisError, FramedUnix)>,
+) -> Result<(ProtocolFrame, RedisTransport), (RedisError, RedisTransport)> {
+  result
+    .map(|(f, t)| (f, RedisTransport::Unix(t)))
+    .map_err(|(e, t)| (e, RedisTransport::Unix(t)))
+}
+
 /// A struct that allows for a backchannel to the server(s) even when the connections are blocked.
+///
+/// Connections are created lazily and cached per server, so control commands such as `CLIENT UNBLOCK`, `CLIENT KILL`, or
+/// `SCRIPT KILL` can be sent to any server while its main connection is blocked or saturated.
 #[derive(Default)]
 pub struct Backchannel {
-  /// A connection to any of the servers, with the associated server name.
-  pub transport: Option<(RedisTransport, Arc<String>)>,
+  /// Connections to the server(s), keyed by the server name.
+  pub transports: HashMap<Arc<String>, RedisTransport>,
   /// The server (host/port) that is blocked, if any.
   pub blocked: Option<Arc<String>>,
   /// A shared mapping of server IDs to connection IDs.
@@ -87,46 +102,43 @@ impl Backchannel {
     self.blocked.is_some()
   }
 
-  /// Whether or not an open transport exists to the blocked server.
-  pub fn has_blocked_transport(&self) -> bool {
-    match self.blocked {
-      Some(ref server) => match self.transport {
-        Some((_, ref _server)) => server == _server,
-        None => false,
-      },
-      None => false,
-    }
+  /// Whether or not an open transport exists to the provided server.
+  pub fn has_transport(&self, server: &Arc<String>) -> bool {
+    self.transports.contains_key(server)
+  }
+
+  /// Close and remove the transport to the provided server, if any.
+  pub fn remove_transport(&mut self, server: &Arc<String>) {
+    self.transports.remove(server);
   }
 
-  /// Take the current transport or create a new one, returning the new transport, the server name, and whether a new connection was needed.
+  /// Take the current transport to the server or create a new one, returning the transport and whether a new connection was needed.
   pub async fn take_or_create_transport(
     &mut self,
     inner: &Arc<RedisClientInner>,
-    host: &str,
-    port: u16,
+    server: &Arc<String>,
     uses_tls: bool,
-  ) -> Result<(RedisTransport, Option<Arc<String>>, bool), RedisError> {
-    if self.has_blocked_transport() {
-      if let Some((transport, server)) = self.transport.take() {
-        Ok((transport, Some(server), false))
-      } else {
-        _debug!(inner, "Creating backchannel to {}:{}", host, port);
-        let transport = create_transport(inner, host, port, uses_tls).await?;
-        Ok((transport, None, true))
-      }
+  ) -> Result<(RedisTransport, bool), RedisError> {
+    if let Some(transport) = self.transports.remove(server) {
+      Ok((transport, false))
+    } else if let Some(path) = protocol_utils::read_unix_path(&inner.config) {
+      _debug!(inner, "Creating backchannel to {}", path.display());
+
+      let transport = connection::create_unix_transport(inner, &path).await?;
+      Ok((transport, true))
     } else {
-      let _ = self.transport.take();
+      let (host, port) = protocol_utils::server_to_parts(server)?;
       _debug!(inner, "Creating backchannel to {}:{}", host, port);
 
       let transport = create_transport(inner, host, port, uses_tls).await?;
-      Ok((transport, None, true))
+      Ok((transport, true))
     }
   }
 
   /// Send the provided command to the server at `host:port`.
   ///
   /// If an existing transport to the provided server is found this function will try to use it, but will automatically retry once if the connection is dead.
-  /// If a new transport has to be created this function will create it, use it, and set it on `self` if the command succeeds.
+  /// If a new transport has to be created this function will create it, use it, and store it on `self` if the command succeeds.
   pub async fn request_response(
     &mut self,
     inner: &Arc<RedisClientInner>,
@@ -134,10 +146,8 @@ impl Backchannel {
     command: RedisCommand,
   ) -> Result<ProtocolFrame, RedisError> {
     let uses_tls = inner.config.read().uses_tls();
-    let (host, port) = protocol_utils::server_to_parts(server)?;
 
-    let (transport, _server, try_once) = self.take_or_create_transport(inner, host, port, uses_tls).await?;
-    let server = _server.unwrap_or(server.clone());
+    let (transport, try_once) = self.take_or_create_transport(inner, server, uses_tls).await?;
     let result = match transport {
       RedisTransport::Tcp(transport) => {
         map_tcp_response(connection::request_response_safe(transport, &command).await)
@@ -145,12 +155,18 @@ impl Backchannel {
       RedisTransport::Tls(transport) => {
         map_tls_response(connection::request_response_safe(transport, &command).await)
       }
+      #[cfg(unix)]
+      RedisTransport::Unix(transport) => {
+        map_unix_response(connection::request_response_safe(transport, &command).await)
+      }
     };
 
     match result {
       Ok((frame, transport)) => {
-        _debug!(inner, "Created backchannel to {}", server);
-        self.transport = Some((transport, server));
+        if try_once {
+          _debug!(inner, "Created backchannel to {}", server);
+        }
+        self.transports.insert(server.clone(), transport);
         Ok(frame)
       }
       Err((e, _)) => {
@@ -159,7 +175,7 @@ impl Backchannel {
           Err(e)
         } else {
           // need to avoid async recursion
-          let (transport, _, _) = self.take_or_create_transport(inner, host, port, uses_tls).await?;
+          let (transport, _) = self.take_or_create_transport(inner, server, uses_tls).await?;
           let result = match transport {
             RedisTransport::Tcp(transport) => {
               map_tcp_response(connection::request_response_safe(transport, &command).await)
@@ -167,11 +183,15 @@ impl Backchannel {
             RedisTransport::Tls(transport) => {
               map_tls_response(connection::request_response_safe(transport, &command).await)
             }
+            #[cfg(unix)]
+            RedisTransport::Unix(transport) => {
+              map_unix_response(connection::request_response_safe(transport, &command).await)
+            }
           };
 
           match result {
             Ok((frame, transport)) => {
-              self.transport = Some((transport, server));
+              self.transports.insert(server.clone(), transport);
               Ok(frame)
             }
             Err((e, _)) => Err(e),
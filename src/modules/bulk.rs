@@ -0,0 +1,222 @@
+use crate::client::RedisClient;
+use crate::error::RedisError;
+use crate::modules::migrate;
+use crate::types::RedisKey;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::BTreeMap;
+
+/// Options for [scan_and_delete](scan_and_delete) and [scan_and_expire](scan_and_expire).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BulkOptions {
+  /// The `COUNT` argument used when scanning the keyspace.
+  ///
+  /// Default: `100`
+  pub scan_count: u32,
+  /// The maximum number of keys changed by each batch of commands.
+  ///
+  /// Default: `100`
+  pub batch_size: usize,
+  /// Scan the keyspace and report matching keys without changing them.
+  ///
+  /// Default: `false`
+  pub dry_run: bool,
+}
+
+impl Default for BulkOptions {
+  fn default() -> Self {
+    BulkOptions {
+      scan_count: 100,
+      batch_size: 100,
+      dry_run: false,
+    }
+  }
+}
+
+/// The progress of a [scan_and_delete](scan_and_delete) or [scan_and_expire](scan_and_expire) operation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BulkProgress {
+  /// The number of keys returned by `SCAN`.
+  ///
+  /// `SCAN` can return a key more than once, so this may be larger than the number of keys that matched the pattern.
+  pub matched: usize,
+  /// The number of keys that were deleted or had their expiration set. This is always `0` in a dry run.
+  pub changed: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+  Delete,
+  Expire(i64),
+}
+
+/// Group the keys by hash slot when using a cluster, since multi-key commands must only use keys from one slot.
+fn group_by_slot(client: &RedisClient, keys: Vec<RedisKey>) -> Vec<Vec<RedisKey>> {
+  if !client.is_clustered() {
+    return vec![keys];
+  }
+
+  let mut groups: BTreeMap<u16, Vec<RedisKey>> = BTreeMap::new();
+  for key in keys.into_iter() {
+    groups.entry(key.cluster_hash()).or_insert_with(Vec::new).push(key);
+  }
+  groups.into_iter().map(|(_, keys)| keys).collect()
+}
+
+/// Apply the action to a batch of keys, returning the number of keys that were changed.
+async fn apply_batch(client: &RedisClient, keys: Vec<RedisKey>, action: Action) -> Result<usize, RedisError> {
+  match action {
+    Action::Delete => {
+      let groups = group_by_slot(client, keys);
+      let parallelism = groups.len().max(1);
+      stream::iter(groups.into_iter())
+        .map(|keys| client.unlink::<i64, _>(keys))
+        .buffer_unordered(parallelism)
+        .try_fold(0, |changed, result| async move { Ok(changed + result as usize) })
+        .await
+    }
+    Action::Expire(seconds) => {
+      let parallelism = keys.len().max(1);
+      // the client will pipeline these if pipelining is enabled
+      stream::iter(keys.into_iter())
+        .map(|key| client.expire::<i64, _, _>(key, seconds, None))
+        .buffer_unordered(parallelism)
+        .try_fold(0, |changed, result| async move { Ok(changed + result as usize) })
+        .await
+    }
+  }
+}
+
+async fn scan_and_apply<F>(
+  client: &RedisClient,
+  pattern: String,
+  action: Action,
+  options: &BulkOptions,
+  progress: &mut BulkProgress,
+  on_progress: &mut F,
+) -> Result<(), RedisError>
+where
+  F: FnMut(&BulkProgress),
+{
+  let (clients, should_quit) = migrate::scan_clients(client).await?;
+
+  let mut result = Ok(());
+  'outer: for scan_client in clients.iter() {
+    let mut scanner = scan_client.scan(pattern.clone(), Some(options.scan_count), None);
+
+    while let Some(page) = scanner.next().await {
+      let mut page = match page {
+        Ok(page) => page,
+        Err(e) => {
+          result = Err(e);
+          break 'outer;
+        }
+      };
+
+      let keys = page.take_results().unwrap_or_default();
+      for batch in keys.chunks(options.batch_size.max(1)) {
+        progress.matched += batch.len();
+
+        if !options.dry_run {
+          // commands are sent with the caller's client so keys are routed to their current owner in a cluster
+          match apply_batch(client, batch.to_vec(), action).await {
+            Ok(changed) => progress.changed += changed,
+            Err(e) => {
+              result = Err(e);
+              break 'outer;
+            }
+          };
+        }
+        on_progress(progress);
+      }
+
+      if let Err(e) = page.next() {
+        result = Err(e);
+        break 'outer;
+      }
+    }
+  }
+
+  if should_quit {
+    for client in clients.iter() {
+      let _ = client.quit().await;
+    }
+  }
+  result
+}
+
+/// Delete the keys matching `pattern` via `SCAN` and `UNLINK`, calling `on_progress` after each batch of keys.
+///
+/// If the client is clustered the keyspace will be scanned on each primary node in the cluster. Keys created while the
+/// keyspace is scanned may not be deleted. This function will stop and return the first error encountered.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::bulk::{self, BulkOptions};
+/// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+///   let options = BulkOptions {
+///     dry_run: true,
+///     ..Default::default()
+///   };
+///   let progress = bulk::scan_and_delete(client, "session:*", options, |_| {}).await?;
+///   println!("Would delete {} keys", progress.matched);
+///
+///   let progress = bulk::scan_and_delete(client, "session:*", BulkOptions::default(), |progress| {
+///     println!("Deleted {} keys", progress.changed);
+///   })
+///   .await?;
+///   Ok(())
+/// }
+/// ```
+pub async fn scan_and_delete<P, F>(
+  client: &RedisClient,
+  pattern: P,
+  options: BulkOptions,
+  mut on_progress: F,
+) -> Result<BulkProgress, RedisError>
+where
+  P: Into<String>,
+  F: FnMut(&BulkProgress),
+{
+  let mut progress = BulkProgress::default();
+  let _ = scan_and_apply(
+    client,
+    pattern.into(),
+    Action::Delete,
+    &options,
+    &mut progress,
+    &mut on_progress,
+  )
+  .await?;
+
+  Ok(progress)
+}
+
+/// Set a timeout of `seconds` on the keys matching `pattern` via `SCAN` and `EXPIRE`, calling `on_progress` after each batch
+/// of keys.
+///
+/// If the client is clustered the keyspace will be scanned on each primary node in the cluster. This function will stop and
+/// return the first error encountered.
+pub async fn scan_and_expire<P, F>(
+  client: &RedisClient,
+  pattern: P,
+  seconds: i64,
+  options: BulkOptions,
+  mut on_progress: F,
+) -> Result<BulkProgress, RedisError>
+where
+  P: Into<String>,
+  F: FnMut(&BulkProgress),
+{
+  let mut progress = BulkProgress::default();
+  let _ = scan_and_apply(
+    client,
+    pattern.into(),
+    Action::Expire(seconds),
+    &options,
+    &mut progress,
+    &mut on_progress,
+  )
+  .await?;
+
+  Ok(progress)
+}
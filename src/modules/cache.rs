@@ -0,0 +1,274 @@
+use crate::types::{RedisKey, RedisValue};
+use parking_lot::RwLock;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Statistics describing the client-side cache.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+  /// The number of reads served from the cache.
+  pub hits: usize,
+  /// The number of reads that were sent to the server.
+  pub misses: usize,
+  /// The number of entries removed due to invalidation messages from the server.
+  pub invalidations: usize,
+  /// The number of entries removed to keep the cache under its max size.
+  pub evictions: usize,
+  /// The number of entries in the cache.
+  pub size: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+  values: HashMap<RedisKey, RedisValue>,
+  /// The keys in the order they were inserted, used to evict the oldest values when the cache is full. This may contain
+  /// keys that were already removed, which are skipped when evicting.
+  order: VecDeque<RedisKey>,
+}
+
+impl CacheEntries {
+  fn evict(&mut self, max_entries: usize) -> usize {
+    let mut evicted = 0;
+    while self.values.len() > max_entries {
+      match self.order.pop_front() {
+        Some(key) => {
+          if self.values.remove(&key).is_some() {
+            evicted += 1;
+          }
+        }
+        None => break,
+      }
+    }
+    if self.order.len() > max_entries.saturating_mul(2) {
+      let values = &self.values;
+      self.order.retain(|key| values.contains_key(key));
+    }
+
+    evicted
+  }
+
+  fn clear(&mut self) {
+    self.values.clear();
+    self.order.clear();
+  }
+}
+
+/// A local cache of values for keys tracked by the server with `CLIENT TRACKING`.
+#[derive(Debug, Default)]
+pub struct ClientCache {
+  entries: RwLock<CacheEntries>,
+  /// The max number of values in the cache, if any.
+  max_entries: Option<usize>,
+  hits: AtomicUsize,
+  misses: AtomicUsize,
+  invalidations: AtomicUsize,
+  evictions: AtomicUsize,
+  /// Incremented on each invalidation so that a value read from the server is not cached if an invalidation arrived
+  /// while the read was in flight.
+  epoch: AtomicUsize,
+  /// Senders for invalidated keys to `on_invalidation` streams.
+  listeners: RwLock<VecDeque<UnboundedSender<RedisKey>>>,
+}
+
+impl ClientCache {
+  /// Create a new cache that holds at most `max_entries` values, evicting the oldest values first.
+  pub fn new(max_entries: Option<usize>) -> Self {
+    ClientCache {
+      max_entries,
+      ..Default::default()
+    }
+  }
+
+  /// Read a value from the cache, updating the hit or miss count.
+  pub fn get(&self, key: &RedisKey) -> Option<RedisValue> {
+    let value = self.entries.read().values.get(key).cloned();
+    if value.is_some() {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+    } else {
+      self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    value
+  }
+
+  /// Read the current invalidation epoch, which should be read before sending a command whose result will be cached.
+  pub fn epoch(&self) -> usize {
+    self.epoch.load(Ordering::Acquire)
+  }
+
+  /// Cache a value read from the server, unless an invalidation was received since `epoch` was read.
+  ///
+  /// If the cache is full the oldest values are evicted.
+  pub fn insert(&self, epoch: usize, key: RedisKey, value: RedisValue) {
+    let mut guard = self.entries.write();
+    if self.epoch.load(Ordering::Acquire) != epoch {
+      return;
+    }
+
+    if let Some(max_entries) = self.max_entries {
+      if guard.values.insert(key.clone(), value).is_none() {
+        guard.order.push_back(key);
+      }
+      let evicted = guard.evict(max_entries);
+      self.evictions.fetch_add(evicted, Ordering::Relaxed);
+    } else {
+      guard.values.insert(key, value);
+    }
+  }
+
+  /// Remove the provided keys from the cache, sending each key to any `on_invalidation` listeners.
+  pub fn invalidate(&self, keys: &[RedisKey]) {
+    {
+      let mut guard = self.entries.write();
+      self.epoch.fetch_add(1, Ordering::AcqRel);
+
+      for key in keys.iter() {
+        if guard.values.remove(key).is_some() {
+          self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+      }
+    }
+
+    let mut closed = false;
+    for tx in self.listeners.read().iter() {
+      for key in keys.iter() {
+        closed |= tx.send(key.clone()).is_err();
+      }
+    }
+    if closed {
+      self.listeners.write().retain(|tx| !tx.is_closed());
+    }
+  }
+
+  /// Create a stream of keys invalidated by the server.
+  pub fn add_listener(&self) -> UnboundedReceiver<RedisKey> {
+    let (tx, rx) = unbounded_channel();
+    self.listeners.write().push_back(tx);
+    rx
+  }
+
+  /// Close all `on_invalidation` streams.
+  pub fn close_listeners(&self) {
+    for _ in self.listeners.write().drain(..) {
+      trace!("Closing invalidation tx");
+    }
+  }
+
+  fn remove_all(&self, invalidated: bool) {
+    let mut guard = self.entries.write();
+    self.epoch.fetch_add(1, Ordering::AcqRel);
+    if invalidated {
+      self.invalidations.fetch_add(guard.values.len(), Ordering::Relaxed);
+    }
+    guard.clear();
+  }
+
+  /// Remove all entries from the cache.
+  pub fn clear(&self) {
+    self.remove_all(false);
+  }
+
+  /// Invalidate keys based on the payload of an invalidation message.
+  ///
+  /// A `nil` payload means the server flushed the keyspace, so all entries are removed.
+  pub fn invalidate_value(&self, value: &RedisValue) {
+    match value {
+      RedisValue::Null => self.remove_all(true),
+      RedisValue::Array(ref values) => {
+        let keys: Vec<RedisKey> = values
+          .iter()
+          .filter_map(|v| v.as_bytes().map(|b| RedisKey::new(b.to_vec())))
+          .collect();
+        self.invalidate(&keys);
+      }
+      _ => {
+        if let Some(key) = value.as_bytes() {
+          self.invalidate(&[RedisKey::new(key.to_vec())]);
+        }
+      }
+    }
+  }
+
+  /// Read the cache statistics.
+  pub fn stats(&self) -> CacheStats {
+    CacheStats {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+      invalidations: self.invalidations.load(Ordering::Relaxed),
+      evictions: self.evictions.load(Ordering::Relaxed),
+      size: self.entries.read().values.len(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_track_hits_and_misses() {
+    let cache = ClientCache::default();
+    assert!(cache.get(&"foo".into()).is_none());
+    cache.insert(cache.epoch(), "foo".into(), "bar".into());
+    assert_eq!(cache.get(&"foo".into()), Some("bar".into()));
+
+    assert_eq!(cache.stats(), CacheStats {
+      hits: 1,
+      misses: 1,
+      invalidations: 0,
+      evictions: 0,
+      size: 1,
+    });
+  }
+
+  #[test]
+  fn should_evict_oldest_values() {
+    let cache = ClientCache::new(Some(2));
+    cache.insert(cache.epoch(), "a".into(), 1.into());
+    cache.insert(cache.epoch(), "b".into(), 2.into());
+    cache.invalidate(&["a".into()]);
+    cache.insert(cache.epoch(), "c".into(), 3.into());
+    cache.insert(cache.epoch(), "d".into(), 4.into());
+
+    assert!(cache.get(&"b".into()).is_none());
+    assert_eq!(cache.get(&"c".into()), Some(3.into()));
+    assert_eq!(cache.get(&"d".into()), Some(4.into()));
+    assert_eq!(cache.stats().evictions, 1);
+    assert_eq!(cache.stats().size, 2);
+  }
+
+  #[test]
+  fn should_invalidate_from_message_payload() {
+    let cache = ClientCache::default();
+    cache.insert(cache.epoch(), "foo".into(), "bar".into());
+    cache.insert(cache.epoch(), "baz".into(), "bar".into());
+
+    cache.invalidate_value(&RedisValue::Array(vec!["foo".into()]));
+    assert_eq!(cache.stats().size, 1);
+    cache.invalidate_value(&RedisValue::Null);
+    assert_eq!(cache.stats().size, 0);
+    assert_eq!(cache.stats().invalidations, 2);
+  }
+
+  #[test]
+  fn should_send_invalidated_keys_to_listeners() {
+    let cache = ClientCache::default();
+    let mut rx = cache.add_listener();
+    cache.invalidate_value(&RedisValue::Array(vec!["foo".into(), "bar".into()]));
+
+    assert_eq!(rx.try_recv().unwrap(), "foo".into());
+    assert_eq!(rx.try_recv().unwrap(), "bar".into());
+    assert!(rx.try_recv().is_err());
+  }
+
+  #[test]
+  fn should_not_cache_after_concurrent_invalidation() {
+    let cache = ClientCache::default();
+    let epoch = cache.epoch();
+    cache.invalidate(&["foo".into()]);
+    cache.insert(epoch, "foo".into(), "bar".into());
+
+    assert_eq!(cache.stats().size, 0);
+  }
+}
@@ -0,0 +1,712 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::types::{RedisMap, RedisValue};
+use crate::utils;
+use std::cmp;
+use std::convert::TryInto;
+use std::str;
+
+const RDB_TYPE_STRING: u8 = 0;
+const RDB_TYPE_LIST: u8 = 1;
+const RDB_TYPE_SET: u8 = 2;
+const RDB_TYPE_ZSET: u8 = 3;
+const RDB_TYPE_HASH: u8 = 4;
+const RDB_TYPE_ZSET_2: u8 = 5;
+const RDB_TYPE_LIST_ZIPLIST: u8 = 10;
+const RDB_TYPE_SET_INTSET: u8 = 11;
+const RDB_TYPE_ZSET_ZIPLIST: u8 = 12;
+const RDB_TYPE_HASH_ZIPLIST: u8 = 13;
+const RDB_TYPE_LIST_QUICKLIST: u8 = 14;
+const RDB_TYPE_HASH_LISTPACK: u8 = 16;
+const RDB_TYPE_ZSET_LISTPACK: u8 = 17;
+const RDB_TYPE_LIST_QUICKLIST_2: u8 = 18;
+const RDB_TYPE_SET_LISTPACK: u8 = 20;
+
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+/// The size of the RDB version and CRC64 footer on a `DUMP` payload.
+const FOOTER_LEN: usize = 10;
+
+/// The type of value in a `DUMP` payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DumpType {
+  String,
+  List,
+  Set,
+  SortedSet,
+  Hash,
+}
+
+/// A value decoded from the payload returned by [DUMP](https://redis.io/commands/dump).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DumpValue {
+  /// The RDB version used by the server that created the payload.
+  pub rdb_version: u16,
+  /// The type of the dumped value.
+  pub kind: DumpType,
+  /// The dumped value.
+  ///
+  /// Lists and sets are returned as arrays, hashes are returned as maps, and sorted sets are returned as an array of `member, score`
+  /// pairs in the same format as `ZRANGE ... WITHSCORES`.
+  pub value: RedisValue,
+}
+
+fn parse_error<S>(details: S) -> RedisError
+where
+  S: Into<String>,
+{
+  RedisError::new(RedisErrorKind::Parse, details.into())
+}
+
+/// A table-driven implementation of the CRC-64 variant (Jones) used by Redis.
+fn crc64(data: &[u8]) -> u64 {
+  // the reflected form of the 0xad93d23594c935a9 polynomial
+  const POLY: u64 = 0x95ac9329ac4bc9b5;
+  let mut table = [0u64; 256];
+  for (i, entry) in table.iter_mut().enumerate() {
+    let mut crc = i as u64;
+    for _ in 0 .. 8 {
+      crc = if crc & 1 == 1 { (crc >> 1) ^ POLY } else { crc >> 1 };
+    }
+    *entry = crc;
+  }
+
+  data
+    .iter()
+    .fold(0u64, |crc, b| table[((crc ^ *b as u64) & 0xff) as usize] ^ (crc >> 8))
+}
+
+/// Decompress an LZF compressed string.
+fn lzf_decompress(input: &[u8], expected_len: usize) -> Result<Vec<u8>, RedisError> {
+  // the expected length is read from the payload, so the output is not allocated up front beyond the input size
+  let mut out: Vec<u8> = Vec::with_capacity(cmp::min(expected_len, input.len()));
+  let mut idx = 0;
+
+  while idx < input.len() {
+    let ctrl = input[idx] as usize;
+    idx += 1;
+
+    if ctrl < 32 {
+      let len = ctrl + 1;
+      if idx + len > input.len() {
+        return Err(parse_error("Invalid LZF literal length."));
+      }
+      if out.len() + len > expected_len {
+        return Err(parse_error("Invalid LZF decompressed length."));
+      }
+      out.extend_from_slice(&input[idx .. idx + len]);
+      idx += len;
+    } else {
+      let mut len = ctrl >> 5;
+      if len == 7 {
+        len += *input.get(idx).ok_or_else(|| parse_error("Invalid LZF back reference."))? as usize;
+        idx += 1;
+      }
+      let offset = ((ctrl & 0x1f) << 8) + *input.get(idx).ok_or_else(|| parse_error("Invalid LZF back reference."))? as usize + 1;
+      idx += 1;
+
+      if offset > out.len() {
+        return Err(parse_error("Invalid LZF back reference offset."));
+      }
+      if out.len() + len + 2 > expected_len {
+        return Err(parse_error("Invalid LZF decompressed length."));
+      }
+      let start = out.len() - offset;
+      // the back reference may overlap with the bytes being copied, so this copies one byte at a time
+      for i in 0 .. len + 2 {
+        let b = out[start + i];
+        out.push(b);
+      }
+    }
+  }
+
+  if out.len() != expected_len {
+    return Err(parse_error("Invalid LZF decompressed length."));
+  }
+  Ok(out)
+}
+
+fn bytes_to_value(bytes: Vec<u8>) -> RedisValue {
+  match String::from_utf8(bytes) {
+    Ok(s) => RedisValue::String(s),
+    Err(e) => RedisValue::Bytes(e.into_bytes()),
+  }
+}
+
+fn value_to_map_key(value: RedisValue) -> Result<String, RedisError> {
+  match value {
+    RedisValue::String(s) => Ok(s),
+    RedisValue::Integer(i) => Ok(i.to_string()),
+    _ => Err(parse_error("Invalid hash field. Expected UTF8 string.")),
+  }
+}
+
+fn score_to_value(score: f64) -> Result<RedisValue, RedisError> {
+  if score.is_nan() {
+    Ok("nan".into())
+  } else {
+    utils::f64_to_redis_string(score)
+  }
+}
+
+fn pairs_to_map(values: Vec<RedisValue>) -> Result<RedisValue, RedisError> {
+  if values.len() % 2 != 0 {
+    return Err(parse_error("Expected an even number of hash values."));
+  }
+
+  let mut map = RedisMap::new();
+  let mut values = values.into_iter();
+  while let Some(field) = values.next() {
+    let value = values.next().unwrap();
+    map.inner.insert(value_to_map_key(field)?, value);
+  }
+  Ok(RedisValue::Map(map))
+}
+
+fn pairs_to_zset(mut values: Vec<RedisValue>) -> Result<RedisValue, RedisError> {
+  if values.len() % 2 != 0 {
+    return Err(parse_error("Expected an even number of sorted set values."));
+  }
+
+  // listpack and ziplist encodings store scores as strings or integers
+  for score in values.iter_mut().skip(1).step_by(2) {
+    if let RedisValue::Integer(i) = score {
+      *score = RedisValue::String(i.to_string());
+    }
+  }
+  Ok(RedisValue::Array(values))
+}
+
+/// A cursor over a byte slice.
+struct Reader<'a> {
+  buf: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn new(buf: &'a [u8]) -> Self {
+    Reader { buf, pos: 0 }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.pos >= self.buf.len()
+  }
+
+  fn remaining(&self) -> usize {
+    self.buf.len().saturating_sub(self.pos)
+  }
+
+  /// Check that `len` values, each using at least `size` bytes, can fit in the rest of the payload.
+  fn check_len(&self, len: usize, size: usize) -> Result<usize, RedisError> {
+    match len.checked_mul(size) {
+      Some(total) if total <= self.remaining() => Ok(len),
+      _ => Err(parse_error(format!("Invalid length: {}", len))),
+    }
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], RedisError> {
+    if len > self.remaining() {
+      return Err(parse_error("Unexpected end of payload."));
+    }
+
+    let out = &self.buf[self.pos .. self.pos + len];
+    self.pos += len;
+    Ok(out)
+  }
+
+  fn u8(&mut self) -> Result<u8, RedisError> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn peek(&self) -> Result<u8, RedisError> {
+    self
+      .buf
+      .get(self.pos)
+      .cloned()
+      .ok_or_else(|| parse_error("Unexpected end of payload."))
+  }
+
+  fn u16_le(&mut self) -> Result<u16, RedisError> {
+    Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+  }
+
+  fn u32_le(&mut self) -> Result<u32, RedisError> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn i16_le(&mut self) -> Result<i64, RedisError> {
+    Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()) as i64)
+  }
+
+  fn i24_le(&mut self) -> Result<i64, RedisError> {
+    let b = self.take(3)?;
+    // shift into the top of an i32 to sign extend
+    Ok((i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as i64)
+  }
+
+  fn i32_le(&mut self) -> Result<i64, RedisError> {
+    Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()) as i64)
+  }
+
+  fn i64_le(&mut self) -> Result<i64, RedisError> {
+    Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+  }
+
+  fn f64_le(&mut self) -> Result<f64, RedisError> {
+    Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+  }
+
+  /// Read an RDB length, returning the length and whether or not it describes a special string encoding.
+  fn rdb_length(&mut self) -> Result<(u64, bool), RedisError> {
+    let first = self.u8()?;
+
+    match first >> 6 {
+      0 => Ok(((first & 0x3f) as u64, false)),
+      1 => Ok(((((first & 0x3f) as u64) << 8) | self.u8()? as u64, false)),
+      2 => match first {
+        0x80 => Ok((u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64, false)),
+        0x81 => Ok((u64::from_be_bytes(self.take(8)?.try_into().unwrap()), false)),
+        _ => Err(parse_error(format!("Invalid RDB length encoding: {}", first))),
+      },
+      _ => Ok(((first & 0x3f) as u64, true)),
+    }
+  }
+
+  fn rdb_len(&mut self) -> Result<usize, RedisError> {
+    match self.rdb_length()? {
+      (len, false) => Ok(len as usize),
+      _ => Err(parse_error("Unexpected RDB string encoding.")),
+    }
+  }
+
+  /// Read the length of a collection whose values each use at least `size` bytes.
+  fn rdb_collection_len(&mut self, size: usize) -> Result<usize, RedisError> {
+    let len = self.rdb_len()?;
+    self.check_len(len, size)
+  }
+
+  fn rdb_string(&mut self) -> Result<Vec<u8>, RedisError> {
+    let (len, encoded) = self.rdb_length()?;
+    if !encoded {
+      return Ok(self.take(len as usize)?.to_vec());
+    }
+
+    match len {
+      0 => Ok((self.u8()? as i8).to_string().into_bytes()),
+      1 => Ok(self.i16_le()?.to_string().into_bytes()),
+      2 => Ok(self.i32_le()?.to_string().into_bytes()),
+      3 => {
+        let compressed_len = self.rdb_len()?;
+        let len = self.rdb_len()?;
+        lzf_decompress(self.take(compressed_len)?, len)
+      }
+      _ => Err(parse_error(format!("Invalid RDB string encoding: {}", len))),
+    }
+  }
+
+  fn rdb_string_value(&mut self) -> Result<RedisValue, RedisError> {
+    self.rdb_string().map(bytes_to_value)
+  }
+
+  /// Read a sorted set score in the legacy string format.
+  fn rdb_double(&mut self) -> Result<f64, RedisError> {
+    match self.u8()? {
+      253 => Ok(f64::NAN),
+      254 => Ok(f64::INFINITY),
+      255 => Ok(f64::NEG_INFINITY),
+      len => {
+        let s = str::from_utf8(self.take(len as usize)?)?;
+        utils::redis_string_to_f64(s)
+      }
+    }
+  }
+}
+
+fn decode_ziplist(buf: &[u8]) -> Result<Vec<RedisValue>, RedisError> {
+  let mut reader = Reader::new(buf);
+  let _zlbytes = reader.u32_le()?;
+  let _zltail = reader.u32_le()?;
+  let _zllen = reader.u16_le()?;
+  let mut out = Vec::new();
+
+  loop {
+    if reader.peek()? == 0xff {
+      break;
+    }
+
+    let prevlen = reader.u8()?;
+    if prevlen == 0xfe {
+      let _ = reader.take(4)?;
+    }
+
+    let encoding = reader.u8()?;
+    let value = match encoding >> 6 {
+      0 => bytes_to_value(reader.take((encoding & 0x3f) as usize)?.to_vec()),
+      1 => {
+        let len = (((encoding & 0x3f) as usize) << 8) | reader.u8()? as usize;
+        bytes_to_value(reader.take(len)?.to_vec())
+      }
+      2 => {
+        let len = u32::from_be_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+        bytes_to_value(reader.take(len)?.to_vec())
+      }
+      _ => match encoding {
+        0xc0 => RedisValue::Integer(reader.i16_le()?),
+        0xd0 => RedisValue::Integer(reader.i32_le()?),
+        0xe0 => RedisValue::Integer(reader.i64_le()?),
+        0xf0 => RedisValue::Integer(reader.i24_le()?),
+        0xfe => RedisValue::Integer(reader.u8()? as i8 as i64),
+        0xf1 ..= 0xfd => RedisValue::Integer((encoding & 0x0f) as i64 - 1),
+        _ => return Err(parse_error(format!("Invalid ziplist encoding: {}", encoding))),
+      },
+    };
+
+    out.push(value);
+  }
+
+  Ok(out)
+}
+
+/// The number of bytes used to encode the length of a listpack entry after the entry.
+fn listpack_backlen_size(len: usize) -> usize {
+  if len <= 127 {
+    1
+  } else if len < 16383 {
+    2
+  } else if len < 2097151 {
+    3
+  } else if len < 268435455 {
+    4
+  } else {
+    5
+  }
+}
+
+fn decode_listpack(buf: &[u8]) -> Result<Vec<RedisValue>, RedisError> {
+  let mut reader = Reader::new(buf);
+  let _total_bytes = reader.u32_le()?;
+  let _num_elements = reader.u16_le()?;
+  let mut out = Vec::new();
+
+  loop {
+    let start = reader.pos;
+    let encoding = reader.u8()?;
+
+    let value = if encoding == 0xff {
+      break;
+    } else if encoding & 0x80 == 0 {
+      RedisValue::Integer((encoding & 0x7f) as i64)
+    } else if encoding & 0xc0 == 0x80 {
+      bytes_to_value(reader.take((encoding & 0x3f) as usize)?.to_vec())
+    } else if encoding & 0xe0 == 0xc0 {
+      let uval = (((encoding & 0x1f) as i64) << 8) | reader.u8()? as i64;
+      // sign extend the 13 bit integer
+      RedisValue::Integer(if uval >= 1 << 12 { uval - (1 << 13) } else { uval })
+    } else if encoding & 0xf0 == 0xe0 {
+      let len = (((encoding & 0x0f) as usize) << 8) | reader.u8()? as usize;
+      bytes_to_value(reader.take(len)?.to_vec())
+    } else {
+      match encoding {
+        0xf0 => {
+          let len = reader.u32_le()? as usize;
+          bytes_to_value(reader.take(len)?.to_vec())
+        }
+        0xf1 => RedisValue::Integer(reader.i16_le()?),
+        0xf2 => RedisValue::Integer(reader.i24_le()?),
+        0xf3 => RedisValue::Integer(reader.i32_le()?),
+        0xf4 => RedisValue::Integer(reader.i64_le()?),
+        _ => return Err(parse_error(format!("Invalid listpack encoding: {}", encoding))),
+      }
+    };
+
+    let _ = reader.take(listpack_backlen_size(reader.pos - start))?;
+    out.push(value);
+  }
+
+  Ok(out)
+}
+
+fn decode_intset(buf: &[u8]) -> Result<Vec<RedisValue>, RedisError> {
+  let mut reader = Reader::new(buf);
+  let encoding = reader.u32_le()?;
+  if encoding != 2 && encoding != 4 && encoding != 8 {
+    return Err(parse_error(format!("Invalid intset encoding: {}", encoding)));
+  }
+  let len = reader.u32_le()? as usize;
+  let len = reader.check_len(len, encoding as usize)?;
+
+  let mut out = Vec::with_capacity(len);
+  for _ in 0 .. len {
+    let value = match encoding {
+      2 => reader.i16_le()?,
+      4 => reader.i32_le()?,
+      8 => reader.i64_le()?,
+      _ => return Err(parse_error(format!("Invalid intset encoding: {}", encoding))),
+    };
+    out.push(RedisValue::Integer(value));
+  }
+
+  Ok(out)
+}
+
+fn decode_value(reader: &mut Reader, kind: u8) -> Result<(DumpType, RedisValue), RedisError> {
+  let out = match kind {
+    RDB_TYPE_STRING => (DumpType::String, reader.rdb_string_value()?),
+    RDB_TYPE_LIST | RDB_TYPE_SET => {
+      // each value uses at least one byte for its length
+      let len = reader.rdb_collection_len(1)?;
+      let mut values = Vec::with_capacity(len);
+      for _ in 0 .. len {
+        values.push(reader.rdb_string_value()?);
+      }
+
+      let kind = if kind == RDB_TYPE_LIST {
+        DumpType::List
+      } else {
+        DumpType::Set
+      };
+      (kind, RedisValue::Array(values))
+    }
+    RDB_TYPE_ZSET | RDB_TYPE_ZSET_2 => {
+      // each member uses at least one byte for its length and one byte for its score
+      let len = reader.rdb_collection_len(2)?;
+      let mut values = Vec::with_capacity(len * 2);
+      for _ in 0 .. len {
+        values.push(reader.rdb_string_value()?);
+        let score = if kind == RDB_TYPE_ZSET {
+          reader.rdb_double()?
+        } else {
+          reader.f64_le()?
+        };
+        values.push(score_to_value(score)?);
+      }
+
+      (DumpType::SortedSet, RedisValue::Array(values))
+    }
+    RDB_TYPE_HASH => {
+      // each field and value uses at least one byte for its length
+      let len = reader.rdb_collection_len(2)?;
+      let mut values = Vec::with_capacity(len * 2);
+      for _ in 0 .. len * 2 {
+        values.push(reader.rdb_string_value()?);
+      }
+
+      (DumpType::Hash, pairs_to_map(values)?)
+    }
+    RDB_TYPE_LIST_ZIPLIST => (DumpType::List, RedisValue::Array(decode_ziplist(&reader.rdb_string()?)?)),
+    RDB_TYPE_SET_INTSET => (DumpType::Set, RedisValue::Array(decode_intset(&reader.rdb_string()?)?)),
+    RDB_TYPE_SET_LISTPACK => (DumpType::Set, RedisValue::Array(decode_listpack(&reader.rdb_string()?)?)),
+    RDB_TYPE_ZSET_ZIPLIST => (DumpType::SortedSet, pairs_to_zset(decode_ziplist(&reader.rdb_string()?)?)?),
+    RDB_TYPE_ZSET_LISTPACK => (DumpType::SortedSet, pairs_to_zset(decode_listpack(&reader.rdb_string()?)?)?),
+    RDB_TYPE_HASH_ZIPLIST => (DumpType::Hash, pairs_to_map(decode_ziplist(&reader.rdb_string()?)?)?),
+    RDB_TYPE_HASH_LISTPACK => (DumpType::Hash, pairs_to_map(decode_listpack(&reader.rdb_string()?)?)?),
+    RDB_TYPE_LIST_QUICKLIST | RDB_TYPE_LIST_QUICKLIST_2 => {
+      let len = reader.rdb_len()?;
+      let mut values = Vec::new();
+
+      for _ in 0 .. len {
+        if kind == RDB_TYPE_LIST_QUICKLIST {
+          values.extend(decode_ziplist(&reader.rdb_string()?)?);
+        } else if reader.rdb_len()? as u64 == QUICKLIST_NODE_PLAIN {
+          values.push(reader.rdb_string_value()?);
+        } else {
+          values.extend(decode_listpack(&reader.rdb_string()?)?);
+        }
+      }
+
+      (DumpType::List, RedisValue::Array(values))
+    }
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::Unsupported,
+        format!("Unsupported RDB value type: {}", kind),
+      ))
+    }
+  };
+
+  Ok(out)
+}
+
+/// Verify the CRC64 checksum on a `DUMP` payload, returning the RDB version used to create the payload.
+pub fn verify(payload: &[u8]) -> Result<u16, RedisError> {
+  if payload.len() < FOOTER_LEN + 1 {
+    return Err(parse_error("Invalid DUMP payload length."));
+  }
+
+  let (data, crc) = payload.split_at(payload.len() - 8);
+  let expected = u64::from_le_bytes(crc.try_into().unwrap());
+  // older servers do not compute a checksum and send zeros instead
+  if expected != 0 && crc64(data) != expected {
+    return Err(parse_error("Invalid DUMP payload checksum."));
+  }
+
+  let version = &data[data.len() - 2 ..];
+  Ok(u16::from_le_bytes(version.try_into().unwrap()))
+}
+
+/// Decode the payload returned by [DUMP](https://redis.io/commands/dump) without sending it to a server.
+///
+/// Strings, lists, sets, sorted sets, and hashes are supported with any of their internal encodings. Other types, such as streams
+/// or module types, will return an `Unsupported` error.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::dump;
+/// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+///   let payload = client.dump("foo").await?;
+///   let value = dump::decode(payload.as_bytes().unwrap_or(&[]))?;
+///   println!("{:?}: {:?}", value.kind, value.value);
+///
+///   Ok(())
+/// }
+/// ```
+pub fn decode(payload: &[u8]) -> Result<DumpValue, RedisError> {
+  let rdb_version = verify(payload)?;
+  let mut reader = Reader::new(&payload[.. payload.len() - FOOTER_LEN]);
+
+  let kind = reader.u8()?;
+  let (kind, value) = decode_value(&mut reader, kind)?;
+  if !reader.is_empty() {
+    return Err(parse_error("Unexpected trailing bytes in DUMP payload."));
+  }
+
+  Ok(DumpValue {
+    rdb_version,
+    kind,
+    value,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn with_footer(mut data: Vec<u8>, version: u16) -> Vec<u8> {
+    data.extend_from_slice(&version.to_le_bytes());
+    let crc = crc64(&data);
+    data.extend_from_slice(&crc.to_le_bytes());
+    data
+  }
+
+  #[test]
+  fn should_compute_crc64_check_value() {
+    assert_eq!(crc64(b"123456789"), 0xe9c6d914c4b8d9ca);
+  }
+
+  #[test]
+  fn should_decode_string() {
+    // DUMP on "bar" from redis 6.2
+    let payload = b"\x00\x03bar\t\x006L\x18\xac\xba\xe0\x9e\xa6";
+    let value = decode(payload).unwrap();
+
+    assert_eq!(value.rdb_version, 9);
+    assert_eq!(value.kind, DumpType::String);
+    assert_eq!(value.value, RedisValue::String("bar".into()));
+  }
+
+  #[test]
+  fn should_decode_int_encoded_string() {
+    let payload = with_footer(vec![RDB_TYPE_STRING, 0xc1, 0x39, 0x30], 9);
+    assert_eq!(decode(&payload).unwrap().value, RedisValue::String("12345".into()));
+  }
+
+  #[test]
+  fn should_decode_lzf_string() {
+    // "aaaaaaaaaa" => literal "a", then a back reference of length 9 at offset 1
+    let compressed = vec![0x00, b'a', 0xe0, 0x00, 0x00];
+    let mut data = vec![RDB_TYPE_STRING, 0xc3, compressed.len() as u8, 10];
+    data.extend(compressed);
+
+    let payload = with_footer(data, 9);
+    assert_eq!(decode(&payload).unwrap().value, RedisValue::String("a".repeat(10)));
+  }
+
+  #[test]
+  fn should_reject_invalid_checksum() {
+    let mut payload = with_footer(vec![RDB_TYPE_STRING, 0x03, b'b', b'a', b'r'], 9);
+    let len = payload.len();
+    payload[len - 1] ^= 0xff;
+
+    assert!(decode(&payload).is_err());
+  }
+
+  #[test]
+  fn should_decode_listpack_hash() {
+    let mut listpack = vec![0, 0, 0, 0, 4, 0];
+    for s in ["a", "b", "c"].iter() {
+      listpack.push(0x80 | s.len() as u8);
+      listpack.extend_from_slice(s.as_bytes());
+      listpack.push(2);
+    }
+    // 7 bit uint
+    listpack.extend_from_slice(&[0x05, 0x01]);
+    listpack.push(0xff);
+    let total = listpack.len() as u32;
+    listpack[0 .. 4].copy_from_slice(&total.to_le_bytes());
+
+    let mut data = vec![RDB_TYPE_HASH_LISTPACK, listpack.len() as u8];
+    data.extend(listpack);
+    let value = decode(&with_footer(data, 10)).unwrap();
+
+    let mut expected = RedisMap::new();
+    expected.inner.insert("a".into(), "b".into());
+    expected.inner.insert("c".into(), 5.into());
+    assert_eq!(value.kind, DumpType::Hash);
+    assert_eq!(value.value, RedisValue::Map(expected));
+  }
+
+  #[test]
+  fn should_decode_intset() {
+    let mut intset = vec![2, 0, 0, 0, 3, 0, 0, 0];
+    for i in [1i16, -2, 300].iter() {
+      intset.extend_from_slice(&i.to_le_bytes());
+    }
+
+    let mut data = vec![RDB_TYPE_SET_INTSET, intset.len() as u8];
+    data.extend(intset);
+    let value = decode(&with_footer(data, 9)).unwrap();
+
+    assert_eq!(value.kind, DumpType::Set);
+    assert_eq!(value.value, RedisValue::Array(vec![1.into(), (-2).into(), 300.into()]));
+  }
+
+  #[test]
+  fn should_reject_invalid_lengths() {
+    let mut data = vec![RDB_TYPE_HASH, 0x81];
+    data.extend_from_slice(&u64::MAX.to_be_bytes());
+    assert!(decode(&with_footer(data, 9)).is_err());
+
+    let mut data = vec![RDB_TYPE_LIST, 0x80];
+    data.extend_from_slice(&u32::MAX.to_be_bytes());
+    assert!(decode(&with_footer(data, 9)).is_err());
+
+    let intset = vec![8, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+    let mut data = vec![RDB_TYPE_SET_INTSET, intset.len() as u8];
+    data.extend(intset);
+    assert!(decode(&with_footer(data, 9)).is_err());
+  }
+
+  #[test]
+  fn should_reject_invalid_lzf_length() {
+    // the decompressed value is longer than the expected length
+    let compressed = vec![0x00, b'a', 0xe0, 0x00, 0x00];
+    let mut data = vec![RDB_TYPE_STRING, 0xc3, compressed.len() as u8, 5];
+    data.extend(compressed);
+    assert!(decode(&with_footer(data, 9)).is_err());
+
+    let compressed = vec![0x00, b'a'];
+    let mut data = vec![RDB_TYPE_STRING, 0xc3, compressed.len() as u8, 0x80];
+    data.extend_from_slice(&u32::MAX.to_be_bytes());
+    data.extend(compressed);
+    assert!(decode(&with_footer(data, 9)).is_err());
+  }
+
+  #[test]
+  fn should_decode_zset_2() {
+    let mut data = vec![RDB_TYPE_ZSET_2, 1, 3];
+    data.extend_from_slice(b"foo");
+    data.extend_from_slice(&1.5f64.to_le_bytes());
+    let value = decode(&with_footer(data, 9)).unwrap();
+
+    assert_eq!(value.kind, DumpType::SortedSet);
+    assert_eq!(value.value, RedisValue::Array(vec!["foo".into(), "1.5".into()]));
+  }
+}
@@ -54,6 +54,8 @@ impl ReconnectError {
 pub(crate) struct Globals {
   /// Max number of times a command can be written to the wire before returning an error.
   pub(crate) max_command_attempts: Arc<AtomicUsize>,
+  /// Max number of `MOVED` or `ASK` redirections the client will follow for a command before returning an error.
+  pub(crate) max_redirections: Arc<AtomicUsize>,
   /// Max number of in-flight commands per socket before the caller receives backpressure.
   pub(crate) backpressure_count: Arc<AtomicUsize>,
   /// Number of frames that can be fed into a socket before the socket must be flushed.
@@ -66,6 +68,12 @@ pub(crate) struct Globals {
   pub(crate) default_command_timeout: Arc<AtomicUsize>,
   /// The default timeout to apply to connections to sentinel nodes.
   pub(crate) sentinel_connection_timeout_ms: Arc<AtomicUsize>,
+  /// The timeout to apply to each connection attempt when a host resolves to multiple addresses.
+  pub(crate) connection_attempt_timeout_ms: Arc<AtomicUsize>,
+  /// The amount of time a connection must stay healthy before the reconnection attempt counter is reset.
+  pub(crate) reconnect_reset_delay_ms: Arc<AtomicUsize>,
+  /// The maximum amount of time `quit` waits for buffered and in-flight commands to finish.
+  pub(crate) quit_timeout_ms: Arc<AtomicUsize>,
   #[cfg(feature = "blocking-encoding")]
   /// The minimum size, in bytes, of frames that should be encoded or decoded with a blocking task.
   pub(crate) blocking_encode_threshold: Arc<AtomicUsize>,
@@ -78,12 +86,16 @@ impl Default for Globals {
   fn default() -> Self {
     Globals {
       max_command_attempts: Arc::new(AtomicUsize::new(3)),
+      max_redirections: Arc::new(AtomicUsize::new(5)),
       backpressure_count: Arc::new(AtomicUsize::new(5000)),
       feed_count: Arc::new(AtomicUsize::new(500)),
       min_backpressure_time_ms: Arc::new(AtomicUsize::new(100)),
       cluster_error_cache_delay: Arc::new(AtomicUsize::new(100)),
       default_command_timeout: Arc::new(AtomicUsize::new(0)),
       sentinel_connection_timeout_ms: Arc::new(AtomicUsize::new(200)),
+      connection_attempt_timeout_ms: Arc::new(AtomicUsize::new(500)),
+      reconnect_reset_delay_ms: Arc::new(AtomicUsize::new(0)),
+      quit_timeout_ms: Arc::new(AtomicUsize::new(10_000)),
       #[cfg(feature = "blocking-encoding")]
       blocking_encode_threshold: Arc::new(AtomicUsize::new(500_000)),
       #[cfg(feature = "custom-reconnect-errors")]
@@ -101,10 +113,26 @@ impl Globals {
     read_atomic(&self.sentinel_connection_timeout_ms)
   }
 
+  pub fn connection_attempt_timeout_ms(&self) -> usize {
+    read_atomic(&self.connection_attempt_timeout_ms)
+  }
+
+  pub fn reconnect_reset_delay_ms(&self) -> usize {
+    read_atomic(&self.reconnect_reset_delay_ms)
+  }
+
+  pub fn quit_timeout_ms(&self) -> usize {
+    read_atomic(&self.quit_timeout_ms)
+  }
+
   pub fn max_command_attempts(&self) -> usize {
     read_atomic(&self.max_command_attempts)
   }
 
+  pub fn max_redirections(&self) -> usize {
+    read_atomic(&self.max_redirections)
+  }
+
   pub fn backpressure_count(&self) -> usize {
     read_atomic(&self.backpressure_count)
   }
@@ -196,6 +224,22 @@ pub fn set_max_command_attempts(val: usize) -> usize {
   set_atomic(&globals().max_command_attempts, val)
 }
 
+/// Read the max number of `MOVED` or `ASK` redirections the client will follow for a command before returning the redirection
+/// error to the caller.
+///
+/// This also limits the number of times a command is retried after a cluster failover error, such as `CLUSTERDOWN` or
+/// `READONLY`, or a `TRYAGAIN` error.
+///
+/// Default: 5
+pub fn get_max_redirections() -> usize {
+  read_atomic(&globals().max_redirections)
+}
+
+/// See [get_max_redirections] for more information.
+pub fn set_max_redirections(val: usize) -> usize {
+  set_atomic(&globals().max_redirections, val)
+}
+
 /// Read the maximum allowed number of in-flight commands per connection before backpressure is put on callers.
 ///
 /// Backpressure is handled automatically by the client without returning errors. This setting can drastically affect performance.
@@ -272,3 +316,48 @@ pub fn get_sentinel_connection_timeout_ms() -> usize {
 pub fn set_sentinel_connection_timeout_ms(val: usize) -> usize {
   set_atomic(&globals().sentinel_connection_timeout_ms, val)
 }
+
+/// The timeout to apply to each connection attempt when a host resolves to multiple addresses. The last address is
+/// always tried without a timeout.
+///
+/// A value of 0 means no timeout.
+///
+/// Default: 500 ms
+pub fn get_connection_attempt_timeout_ms() -> usize {
+  read_atomic(&globals().connection_attempt_timeout_ms)
+}
+
+/// See [get_connection_attempt_timeout_ms] for more information.
+pub fn set_connection_attempt_timeout_ms(val: usize) -> usize {
+  set_atomic(&globals().connection_attempt_timeout_ms, val)
+}
+
+/// The amount of time, in ms, that a connection must stay healthy after reconnecting before the reconnection attempt
+/// counter on the [ReconnectPolicy](crate::types::ReconnectPolicy) is reset.
+///
+/// Connections that close again before this amount of time continue counting towards the `max_attempts` on the policy,
+/// so a connection that repeatedly closes shortly after reconnecting will eventually stop reconnecting. A value of 0
+/// resets the counter as soon as the client reconnects.
+///
+/// Default: 0
+pub fn get_reconnect_reset_delay_ms() -> usize {
+  read_atomic(&globals().reconnect_reset_delay_ms)
+}
+
+/// See [get_reconnect_reset_delay_ms] for more information.
+pub fn set_reconnect_reset_delay_ms(val: usize) -> usize {
+  set_atomic(&globals().reconnect_reset_delay_ms, val)
+}
+
+/// Read the maximum amount of time [quit](crate::client::RedisClient::quit) will wait for buffered and in-flight commands to
+/// finish before sending `QUIT` to the server. A value of 0 disables this wait.
+///
+/// Default: 10_000 ms
+pub fn get_quit_timeout_ms() -> usize {
+  read_atomic(&globals().quit_timeout_ms)
+}
+
+/// See [get_quit_timeout_ms] for more information.
+pub fn set_quit_timeout_ms(val: usize) -> usize {
+  set_atomic(&globals().quit_timeout_ms, val)
+}
@@ -1,25 +1,60 @@
 use crate::client::RedisClient;
 use crate::error::*;
 use crate::modules::backchannel::Backchannel;
+use crate::modules::cache::ClientCache;
+use crate::modules::pubsub::PubsubSender;
 use crate::multiplexer::SentCommand;
 use crate::protocol::types::DefaultResolver;
 use crate::protocol::types::RedisCommand;
 use crate::types::*;
 use crate::utils;
-use parking_lot::RwLock;
-use std::collections::VecDeque;
+use parking_lot::{Mutex, RwLock};
+use semver::Version;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::ops::Deref;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::oneshot::Sender as OneshotSender;
+use tokio::sync::watch::{channel as watch_channel, Sender as WatchSender};
+use tokio::sync::Notify;
 use tokio::sync::RwLock as AsyncRwLock;
 use tokio::task::JoinHandle;
 
 #[cfg(feature = "metrics")]
-use crate::modules::metrics::MovingStats;
+use crate::modules::metrics::{ConnectionCounters, MovingStats};
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use crate::protocol::tls::TlsConnectorCache;
 
 pub type CommandSender = UnboundedSender<RedisCommand>;
-pub type CommandReceiver = UnboundedReceiver<RedisCommand>;
+
+/// The channels and patterns to which the client is subscribed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Subscriptions {
+  pub channels: BTreeSet<String>,
+  pub patterns: BTreeSet<String>,
+  pub shard_channels: BTreeSet<String>,
+}
+
+/// The receiver half of the multiplexer command channels.
+pub struct CommandReceiver {
+  /// Commands that should run before any commands on the `commands` channel.
+  pub priority: UnboundedReceiver<RedisCommand>,
+  pub commands: UnboundedReceiver<RedisCommand>,
+}
+
+impl CommandReceiver {
+  /// Read the next command, preferring commands on the priority channel.
+  pub async fn recv(&mut self) -> Option<RedisCommand> {
+    tokio::select! {
+      biased;
+      Some(command) = self.priority.recv() => Some(command),
+      command = self.commands.recv() => command,
+    }
+  }
+}
 
 /// State sent to the task that performs reconnection logic.
 pub struct ClosedState {
@@ -31,6 +66,12 @@ pub struct ClosedState {
 
 pub type ConnectionClosedTx = UnboundedSender<ClosedState>;
 
+/// Senders for RESP3 push messages, shared between the client and each connection's codec.
+pub type PushSenders = Arc<RwLock<VecDeque<UnboundedSender<PushMessage>>>>;
+
+/// A callback invoked when the client stops trying to reconnect.
+pub type UnrecoverableCallback = Arc<dyn Fn(&RedisError) + Send + Sync>;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MultiPolicy {
   /// The hash slot against which the transaction is running.
@@ -39,6 +80,8 @@ pub struct MultiPolicy {
   pub abort_on_error: bool,
   /// Whether or not the MULTI command has been sent. In clustered mode we defer sending the MULTI command until we know the hash slot.
   pub sent_multi: bool,
+  /// Whether or not a command inside the transaction was rejected by the server while `abort_on_error` is set.
+  pub aborted: bool,
 }
 
 impl MultiPolicy {
@@ -47,7 +90,10 @@ impl MultiPolicy {
       if slot != old_slot {
         return Err(RedisError::new(
           RedisErrorKind::InvalidArgument,
-          "Invalid hash slot. All commands inside a transaction must use the same hash slot.",
+          format!(
+            "Invalid hash slot {}. All commands inside a transaction must use the same hash slot ({}).",
+            slot, old_slot
+          ),
         ));
       }
     } else {
@@ -58,13 +104,16 @@ impl MultiPolicy {
   }
 }
 
-pub struct RedisClientInner {
+/// The state shared by a client, its clones, and each of its [Pipeline](crate::client::Pipeline) handles.
+pub struct SharedClientInner {
   /// The client ID as seen by the server.
   pub id: Arc<String>,
   /// The response policy to apply when the client is in a MULTI block.
   pub multi_block: RwLock<Option<MultiPolicy>>,
   /// The state of the underlying connection.
   pub state: RwLock<ClientState>,
+  /// A watch sender for changes to `state`.
+  pub state_tx: WatchSender<ClientState>,
   /// The redis config used for initializing connections.
   pub config: RwLock<RedisConfig>,
   /// An optional reconnect policy.
@@ -73,32 +122,82 @@ pub struct RedisClientInner {
   pub error_tx: RwLock<VecDeque<UnboundedSender<RedisError>>>,
   /// An mpsc sender for commands to the multiplexer.
   pub command_tx: CommandSender,
+  /// An mpsc sender for high priority commands to the multiplexer, such as health checks.
+  pub priority_command_tx: CommandSender,
   /// Temporary storage for the receiver half of the multiplexer command channel.
   pub command_rx: RwLock<Option<CommandReceiver>>,
   /// An mpsc sender for pubsub messages to `on_message` streams.
-  pub message_tx: RwLock<VecDeque<UnboundedSender<(String, RedisValue)>>>,
+  pub message_tx: RwLock<VecDeque<PubsubSender<(String, RedisValue)>>>,
   /// An mpsc sender for pubsub messages to `on_keyspace_event` streams.
-  pub keyspace_tx: RwLock<VecDeque<UnboundedSender<KeyspaceEvent>>>,
+  pub keyspace_tx: RwLock<VecDeque<PubsubSender<KeyspaceEvent>>>,
+  /// An mpsc sender for pubsub messages, keyspace events, and gaps to `on_pubsub_event` streams.
+  pub pubsub_tx: RwLock<VecDeque<PubsubSender<PubsubEvent>>>,
+  /// The channels and patterns to which the client is subscribed.
+  pub subscriptions: RwLock<Subscriptions>,
+  /// MPSC senders for RESP3 push messages to `on_push` streams.
+  pub push_tx: PushSenders,
+  /// The client-side cache used with `CLIENT TRACKING`.
+  pub cache: Arc<ClientCache>,
+  /// The socket address used for the most recent connection to each server.
+  pub connected_addrs: RwLock<HashMap<String, SocketAddr>>,
+  /// Routing policies for custom commands, keyed by the uppercase command name.
+  pub custom_routing: RwLock<HashMap<String, Arc<dyn ClusterRouting>>>,
+  /// Callbacks that run around each command.
+  pub command_hooks: RwLock<Vec<Arc<dyn CommandHook>>>,
+  /// The time at which the connection closed, if the client is reconnecting.
+  pub disconnected_at: RwLock<Option<SystemTime>>,
   /// An mpsc sender for reconnection events to `on_reconnect` streams.
   pub reconnect_tx: RwLock<VecDeque<UnboundedSender<RedisClient>>>,
-  /// MPSC senders for `on_connect` futures.
+  /// Callbacks invoked when the client stops trying to reconnect.
+  pub unrecoverable_callbacks: RwLock<Vec<UnrecoverableCallback>>,
+  /// MPSC senders for `wait_for_connect` futures.
   pub connect_tx: RwLock<VecDeque<OneshotSender<Result<(), RedisError>>>>,
+  /// An mpsc sender for connection events to `on_connect` streams.
+  pub connection_tx: RwLock<VecDeque<UnboundedSender<Result<(), RedisError>>>>,
   /// A join handle for the task that sleeps waiting to reconnect.
   pub reconnect_sleep_jh: RwLock<Option<JoinHandle<Result<(), ()>>>>,
   /// Command queue buffer size.
   pub cmd_buffer_len: Arc<AtomicUsize>,
+  /// Number of commands written to the server that have not yet received a response, across all connections.
+  pub in_flight_len: Arc<AtomicUsize>,
+  /// Wakes callers blocked by backpressure when commands leave the buffer or responses arrive.
+  pub backpressure_notify: Arc<Notify>,
+  /// Commands waiting to be retried after a `BUSY` error, in the order the errors were received.
+  pub busy_retries: Mutex<VecDeque<RedisCommand>>,
+  /// Notifications sent when the commands waiting to be retried after a `BUSY` error are sent to the multiplexer.
+  pub busy_notify: Notify,
   /// Number of message redeliveries.
   pub redeliver_count: Arc<AtomicUsize>,
+  /// The number of stream entries removed by the stream trimming task.
+  pub trimmed_count: Arc<AtomicUsize>,
+  /// The number of pubsub messages dropped because a listener's buffer was full.
+  pub pubsub_dropped: Arc<AtomicUsize>,
   /// Channel listening to connection closed events.
   pub connection_closed_tx: RwLock<Option<ConnectionClosedTx>>,
   /// The cached view of the cluster state, if running against a clustered deployment.
   pub cluster_state: RwLock<Option<ClusterKeyCache>>,
   /// The DNS resolver to use when establishing new connections.
   pub resolver: DefaultResolver,
+  /// The TLS connector used with connections to the Redis servers.
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  pub tls_connector: TlsConnectorCache,
+  /// The TLS connector used with connections to the sentinel nodes.
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  pub sentinel_tls_connector: TlsConnectorCache,
   /// A backchannel that can be used to control the multiplexer connections even while the connections are blocked.
   pub backchannel: Arc<AsyncRwLock<Backchannel>>,
+  /// Idle clients with dedicated connections for blocking stream reads.
+  pub stream_clients: RwLock<Vec<RedisClient>>,
+  /// Clients subscribed to the invalidation messages for each server when using client-side caching with `RESP2`.
+  pub invalidation_clients: RwLock<HashMap<String, RedisClient>>,
   /// The server host/port resolved from the sentinel nodes, if known.
   pub sentinel_primary: RwLock<Option<Arc<String>>>,
+  /// The RESP version used by the codec on each connection.
+  pub resp_version: Arc<RwLock<RespVersion>>,
+  /// The oldest version of any server the client has connected to.
+  pub server_version: RwLock<Option<Version>>,
+  /// The version of each server, read via `INFO` the first time the client connects to the server.
+  pub server_versions: RwLock<HashMap<String, Version>>,
 
   /// Command latency metrics.
   #[cfg(feature = "metrics")]
@@ -112,16 +211,39 @@ pub struct RedisClientInner {
   /// Payload size metrics tracking for responses
   #[cfg(feature = "metrics")]
   pub res_size_stats: Arc<RwLock<MovingStats>>,
+  /// Network statistics for each server, shared with the codec on each connection.
+  #[cfg(feature = "metrics")]
+  pub connection_stats: RwLock<HashMap<String, Arc<ConnectionCounters>>>,
+}
+
+pub struct RedisClientInner {
+  shared: Arc<SharedClientInner>,
+  /// Commands buffered by the [Pipeline](crate::client::Pipeline) that owns this handle and have not been sent yet.
+  ///
+  /// This is only set on the handle used by a pipeline, so commands sent on the client or its clones are never buffered.
+  pub pipeline: Option<RwLock<Vec<RedisCommand>>>,
+}
+
+impl Deref for RedisClientInner {
+  type Target = SharedClientInner;
+
+  fn deref(&self) -> &Self::Target {
+    &self.shared
+  }
 }
 
 impl RedisClientInner {
   pub fn new(config: RedisConfig) -> Arc<RedisClientInner> {
     let backchannel = Backchannel::default();
     let id = Arc::new(format!("fred-{}", utils::random_string(10)));
-    let resolver = DefaultResolver::new(&id);
-    let (command_tx, command_rx) = unbounded_channel();
+    let resolver = DefaultResolver::new(&id, config.address_map.clone());
+    let max_cache_entries = config.tracking.as_ref().and_then(|tracking| tracking.max_entries);
+    let (command_tx, commands) = unbounded_channel();
+    let (priority_command_tx, priority) = unbounded_channel();
+    let command_rx = CommandReceiver { priority, commands };
+    let (state_tx, _) = watch_channel(ClientState::Disconnected);
 
-    Arc::new(RedisClientInner {
+    let shared = Arc::new(SharedClientInner {
       #[cfg(feature = "metrics")]
       latency_stats: RwLock::new(MovingStats::default()),
       #[cfg(feature = "metrics")]
@@ -130,6 +252,8 @@ impl RedisClientInner {
       req_size_stats: Arc::new(RwLock::new(MovingStats::default())),
       #[cfg(feature = "metrics")]
       res_size_stats: Arc::new(RwLock::new(MovingStats::default())),
+      #[cfg(feature = "metrics")]
+      connection_stats: RwLock::new(HashMap::new()),
 
       config: RwLock::new(config),
       policy: RwLock::new(None),
@@ -137,20 +261,57 @@ impl RedisClientInner {
       error_tx: RwLock::new(VecDeque::new()),
       message_tx: RwLock::new(VecDeque::new()),
       keyspace_tx: RwLock::new(VecDeque::new()),
+      pubsub_tx: RwLock::new(VecDeque::new()),
+      subscriptions: RwLock::new(Subscriptions::default()),
+      push_tx: Arc::new(RwLock::new(VecDeque::new())),
+      cache: Arc::new(ClientCache::new(max_cache_entries)),
+      connected_addrs: RwLock::new(HashMap::new()),
+      custom_routing: RwLock::new(HashMap::new()),
+      command_hooks: RwLock::new(Vec::new()),
+      disconnected_at: RwLock::new(None),
       reconnect_tx: RwLock::new(VecDeque::new()),
+      unrecoverable_callbacks: RwLock::new(Vec::new()),
       connect_tx: RwLock::new(VecDeque::new()),
+      connection_tx: RwLock::new(VecDeque::new()),
       reconnect_sleep_jh: RwLock::new(None),
       cmd_buffer_len: Arc::new(AtomicUsize::new(0)),
+      in_flight_len: Arc::new(AtomicUsize::new(0)),
+      backpressure_notify: Arc::new(Notify::new()),
+      busy_retries: Mutex::new(VecDeque::new()),
+      busy_notify: Notify::new(),
       redeliver_count: Arc::new(AtomicUsize::new(0)),
+      trimmed_count: Arc::new(AtomicUsize::new(0)),
+      pubsub_dropped: Arc::new(AtomicUsize::new(0)),
       connection_closed_tx: RwLock::new(None),
       multi_block: RwLock::new(None),
       cluster_state: RwLock::new(None),
       backchannel: Arc::new(AsyncRwLock::new(backchannel)),
+      stream_clients: RwLock::new(Vec::new()),
+      invalidation_clients: RwLock::new(HashMap::new()),
       sentinel_primary: RwLock::new(None),
+      server_version: RwLock::new(None),
+      server_versions: RwLock::new(HashMap::new()),
+      resp_version: Arc::new(RwLock::new(RespVersion::RESP2)),
       command_rx: RwLock::new(Some(command_rx)),
       command_tx,
+      priority_command_tx,
+      state_tx,
       resolver,
+      #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+      tls_connector: TlsConnectorCache::default(),
+      #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+      sentinel_tls_connector: TlsConnectorCache::default(),
       id,
+    });
+
+    Arc::new(RedisClientInner { shared, pipeline: None })
+  }
+
+  /// Create a handle that shares the client's connections and state, but buffers the commands sent through it.
+  pub fn with_pipeline(&self) -> Arc<RedisClientInner> {
+    Arc::new(RedisClientInner {
+      shared: self.shared.clone(),
+      pipeline: Some(RwLock::new(Vec::new())),
     })
   }
 
@@ -185,6 +346,12 @@ impl RedisClientInner {
     *guard = Some(server.clone());
   }
 
+  pub fn update_server_version(&self, server: &str, version: Version) {
+    let mut versions = self.server_versions.write();
+    versions.insert(server.to_owned(), version);
+    *self.server_version.write() = versions.values().min().cloned();
+  }
+
   #[cfg(feature = "partial-tracing")]
   pub fn should_trace(&self) -> bool {
     self.config.read().tracing
@@ -195,6 +362,21 @@ impl RedisClientInner {
     false
   }
 
+  /// Read the network statistics counters for a server, creating them if needed.
+  #[cfg(feature = "metrics")]
+  pub fn connection_counters(&self, server: &str) -> Arc<ConnectionCounters> {
+    if let Some(counters) = self.connection_stats.read().get(server) {
+      return counters.clone();
+    }
+
+    self
+      .connection_stats
+      .write()
+      .entry(server.to_owned())
+      .or_insert_with(|| Arc::new(ConnectionCounters::default()))
+      .clone()
+  }
+
   pub fn take_command_rx(&self) -> Option<CommandReceiver> {
     self.command_rx.write().take()
   }
@@ -204,3 +386,52 @@ impl RedisClientInner {
     *guard = Some(rx);
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::types::RedisCommandKind;
+
+  #[tokio::test]
+  async fn should_recv_priority_commands_first() {
+    let (tx, commands) = unbounded_channel();
+    let (priority_tx, priority) = unbounded_channel();
+    let mut rx = CommandReceiver { priority, commands };
+
+    let _ = tx.send(RedisCommand::new(RedisCommandKind::Get, vec![], None));
+    let _ = priority_tx.send(RedisCommand::new(RedisCommandKind::Ping, vec![], None));
+
+    assert_eq!(rx.recv().await.unwrap().kind, RedisCommandKind::Ping);
+    assert_eq!(rx.recv().await.unwrap().kind, RedisCommandKind::Get);
+  }
+
+  #[test]
+  fn should_notify_state_watchers() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    let rx = inner.state_tx.subscribe();
+
+    utils::set_client_state(&inner, ClientState::Reconnecting { attempt: 1 });
+    utils::set_connecting_state(&inner);
+    assert_eq!(*rx.borrow(), ClientState::Reconnecting { attempt: 1 });
+
+    utils::set_client_state(&inner, ClientState::Connected);
+    assert_eq!(*rx.borrow(), ClientState::Connected);
+    assert!(!utils::check_and_set_client_state(
+      &inner,
+      ClientState::Disconnected,
+      ClientState::Closed
+    ));
+    assert_eq!(*rx.borrow(), ClientState::Connected);
+  }
+
+  #[test]
+  fn should_use_oldest_server_version() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+
+    inner.update_server_version("foo:30001", Version::new(7, 0, 0));
+    inner.update_server_version("foo:30002", Version::new(6, 2, 6));
+    assert_eq!(*inner.server_version.read(), Some(Version::new(6, 2, 6)));
+    inner.update_server_version("foo:30002", Version::new(7, 0, 5));
+    assert_eq!(*inner.server_version.read(), Some(Version::new(7, 0, 0)));
+  }
+}
@@ -2,6 +2,7 @@
 #![allow(dead_code)]
 
 use std::cmp;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Stats describing a distribution of samples.
 pub struct Stats {
@@ -104,3 +105,97 @@ impl<'a> From<&'a MovingStats> for Stats {
     }
   }
 }
+
+/// Cumulative network statistics for the connection to one server.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConnectionStats {
+  /// The number of bytes read from the server.
+  pub bytes_read: u64,
+  /// The number of bytes written to the server.
+  pub bytes_written: u64,
+  /// The number of frames decoded from the server.
+  pub frames_decoded: u64,
+  /// The number of frames encoded and sent to the server.
+  pub frames_encoded: u64,
+  /// The number of times the client connected to the server.
+  pub connections: u64,
+  /// Whether or not the connection is currently open.
+  pub connected: bool,
+}
+
+/// Counters shared between the codec and the client for one server.
+#[derive(Debug, Default)]
+pub struct ConnectionCounters {
+  bytes_read: AtomicU64,
+  bytes_written: AtomicU64,
+  frames_decoded: AtomicU64,
+  frames_encoded: AtomicU64,
+  connections: AtomicU64,
+  open: AtomicU64,
+}
+
+impl ConnectionCounters {
+  pub fn sample(&self, decode: bool, bytes: u64) {
+    if decode {
+      self.bytes_read.fetch_add(bytes, Ordering::Relaxed);
+      self.frames_decoded.fetch_add(1, Ordering::Relaxed);
+    } else {
+      self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+      self.frames_encoded.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  /// Track a connection opening or closing.
+  ///
+  /// A count of open connections is used instead of a flag since the old connection may close after the new one opens.
+  pub fn set_connected(&self, connected: bool) {
+    if connected {
+      self.connections.fetch_add(1, Ordering::Relaxed);
+      self.open.fetch_add(1, Ordering::Relaxed);
+    } else {
+      let _ = self
+        .open
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |open| Some(open.saturating_sub(1)));
+    }
+  }
+
+  pub fn read(&self) -> ConnectionStats {
+    ConnectionStats {
+      bytes_read: self.bytes_read.load(Ordering::Relaxed),
+      bytes_written: self.bytes_written.load(Ordering::Relaxed),
+      frames_decoded: self.frames_decoded.load(Ordering::Relaxed),
+      frames_encoded: self.frames_encoded.load(Ordering::Relaxed),
+      connections: self.connections.load(Ordering::Relaxed),
+      connected: self.open.load(Ordering::Relaxed) > 0,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_track_connection_counters() {
+    let counters = ConnectionCounters::default();
+    counters.set_connected(true);
+    counters.sample(false, 10);
+    counters.sample(true, 4);
+    counters.sample(true, 6);
+
+    let stats = counters.read();
+    assert_eq!(stats.bytes_written, 10);
+    assert_eq!(stats.frames_encoded, 1);
+    assert_eq!(stats.bytes_read, 10);
+    assert_eq!(stats.frames_decoded, 2);
+    assert_eq!(stats.connections, 1);
+    assert!(stats.connected);
+
+    counters.set_connected(true);
+    counters.set_connected(false);
+    assert!(counters.read().connected);
+    counters.set_connected(false);
+    assert!(!counters.read().connected);
+    assert_eq!(counters.read().connections, 2);
+  }
+}
@@ -0,0 +1,621 @@
+use crate::client::RedisClient;
+use crate::commands;
+use crate::error::{RedisError, RedisErrorKind};
+use crate::types::{ClusterSetSlotState, Expiration, MigrateAuth, ReconnectPolicy, RedisKey, RedisValue, SetOptions};
+use crate::utils;
+use bytes::Bytes;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+
+/// The keys to copy from the source server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeySource {
+  /// A list of keys.
+  Keys(Vec<RedisKey>),
+  /// A glob-style pattern used to `SCAN` the source server.
+  ///
+  /// If the source client is clustered the pattern will be scanned on each primary node in the cluster.
+  Pattern(String),
+}
+
+impl From<Vec<RedisKey>> for KeySource {
+  fn from(keys: Vec<RedisKey>) -> Self {
+    KeySource::Keys(keys)
+  }
+}
+
+impl From<Vec<String>> for KeySource {
+  fn from(keys: Vec<String>) -> Self {
+    KeySource::Keys(keys.into_iter().map(|k| k.into()).collect())
+  }
+}
+
+impl From<Vec<&str>> for KeySource {
+  fn from(keys: Vec<&str>) -> Self {
+    KeySource::Keys(keys.into_iter().map(|k| k.into()).collect())
+  }
+}
+
+impl<'a> From<&'a str> for KeySource {
+  fn from(pattern: &'a str) -> Self {
+    KeySource::Pattern(pattern.to_owned())
+  }
+}
+
+impl From<String> for KeySource {
+  fn from(pattern: String) -> Self {
+    KeySource::Pattern(pattern)
+  }
+}
+
+/// Options for [copy_keys](copy_keys).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CopyOptions {
+  /// The maximum number of keys copied concurrently.
+  ///
+  /// Default: `10`
+  pub parallelism: usize,
+  /// Whether or not to overwrite keys that already exist on the destination server via `RESTORE ... REPLACE`.
+  ///
+  /// Default: `true`
+  pub replace: bool,
+  /// The `COUNT` argument used when scanning a [pattern](KeySource::Pattern).
+  ///
+  /// Default: `100`
+  pub scan_count: u32,
+}
+
+impl Default for CopyOptions {
+  fn default() -> Self {
+    CopyOptions {
+      parallelism: 10,
+      replace: true,
+      scan_count: 100,
+    }
+  }
+}
+
+/// Options for [rename_cross_slot](rename_cross_slot).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenameOptions {
+  /// Whether or not to overwrite the destination key if it already exists.
+  ///
+  /// Default: `false`
+  pub replace: bool,
+  /// A lock key to `SET ... NX` while the key is renamed. The rename will fail if the lock is already held.
+  ///
+  /// Callers that write to the source key elsewhere should acquire the same lock around those writes to avoid losing them.
+  ///
+  /// Default: `None`
+  pub lock_key: Option<RedisKey>,
+  /// The expiration on the lock key, in milliseconds.
+  ///
+  /// Default: `10000`
+  pub lock_ttl_ms: u64,
+}
+
+impl Default for RenameOptions {
+  fn default() -> Self {
+    RenameOptions {
+      replace: false,
+      lock_key: None,
+      lock_ttl_ms: 10_000,
+    }
+  }
+}
+
+/// Delete the lock key if it still contains the provided token.
+static RELEASE_LOCK_SCRIPT: &'static str =
+  "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('del', KEYS[1]) else return 0 end";
+
+/// The progress of a [copy_keys](copy_keys) operation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CopyProgress {
+  /// The number of keys copied to the destination server.
+  pub copied: usize,
+  /// The number of keys that no longer existed on the source server when they were copied.
+  pub skipped: usize,
+}
+
+/// Read the serialized value and the TTL argument for `RESTORE`, or `None` if the key does not exist.
+///
+/// The TTL is read first so that a key that expires in between is skipped by `DUMP` instead of restored without a TTL.
+async fn dump_with_ttl(client: &RedisClient, key: &RedisKey) -> Result<Option<(RedisValue, i64)>, RedisError> {
+  let pttl: i64 = client.pttl(key.clone()).await?;
+  if pttl == -2 {
+    return Ok(None);
+  }
+  let serialized = client.dump(key.clone()).await?;
+  if serialized.is_null() {
+    return Ok(None);
+  }
+
+  Ok(Some((serialized, if pttl < 0 { 0 } else { pttl })))
+}
+
+/// Copy one key, returning whether or not the key existed on the source server.
+async fn copy_key(
+  source: &RedisClient,
+  dest: &RedisClient,
+  key: RedisKey,
+  replace: bool,
+) -> Result<bool, RedisError> {
+  let (serialized, ttl) = match dump_with_ttl(source, &key).await? {
+    Some(result) => result,
+    None => return Ok(false),
+  };
+
+  let _ = dest.restore(key, ttl, serialized, replace, false, None, None).await?;
+  Ok(true)
+}
+
+async fn copy_page<F>(
+  source: &RedisClient,
+  dest: &RedisClient,
+  keys: Vec<RedisKey>,
+  options: &CopyOptions,
+  progress: &mut CopyProgress,
+  on_progress: &mut F,
+) -> Result<(), RedisError>
+where
+  F: FnMut(&CopyProgress),
+{
+  let mut results = stream::iter(keys.into_iter())
+    .map(|key| copy_key(source, dest, key, options.replace))
+    .buffer_unordered(options.parallelism.max(1));
+
+  while let Some(copied) = results.try_next().await? {
+    if copied {
+      progress.copied += 1;
+    } else {
+      progress.skipped += 1;
+    }
+    on_progress(progress);
+  }
+
+  Ok(())
+}
+
+/// Read the centralized clients that should be scanned on the source server, and whether or not they were created by this module.
+pub(crate) async fn scan_clients(source: &RedisClient) -> Result<(Vec<RedisClient>, bool), RedisError> {
+  if source.is_clustered() {
+    let clients = source.split_cluster().await?;
+    for client in clients.iter() {
+      let _ = client.connect(Some(ReconnectPolicy::default()));
+      let _ = client.wait_for_connect().await?;
+    }
+
+    Ok((clients, true))
+  } else {
+    Ok((vec![source.clone()], false))
+  }
+}
+
+async fn copy_pattern<F>(
+  source: &RedisClient,
+  dest: &RedisClient,
+  pattern: String,
+  options: &CopyOptions,
+  progress: &mut CopyProgress,
+  on_progress: &mut F,
+) -> Result<(), RedisError>
+where
+  F: FnMut(&CopyProgress),
+{
+  let (clients, should_quit) = scan_clients(source).await?;
+
+  let mut result = Ok(());
+  'outer: for client in clients.iter() {
+    let mut scanner = client.scan(pattern.clone(), Some(options.scan_count), None);
+
+    while let Some(page) = scanner.next().await {
+      let mut page = match page {
+        Ok(page) => page,
+        Err(e) => {
+          result = Err(e);
+          break 'outer;
+        }
+      };
+
+      let keys = page.take_results().unwrap_or_default();
+      if let Err(e) = copy_page(source, dest, keys, options, progress, on_progress).await {
+        result = Err(e);
+        break 'outer;
+      }
+      if let Err(e) = page.next() {
+        result = Err(e);
+        break 'outer;
+      }
+    }
+  }
+
+  if should_quit {
+    for client in clients.iter() {
+      let _ = client.quit().await;
+    }
+  }
+  result
+}
+
+/// Copy keys from one server to another via `DUMP`, `PTTL`, and `RESTORE`, calling `on_progress` after each key is copied.
+///
+/// The source and destination clients can use different deployment types, such as two clusters with different topologies or a
+/// centralized server and a cluster, since each key is routed independently. Keys are copied with their remaining TTL, if any.
+///
+/// This function will stop and return the first error encountered. Keys copied before the error are not rolled back.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::migrate::{self, CopyOptions};
+/// async fn example(source: &RedisClient, dest: &RedisClient) -> Result<(), RedisError> {
+///   let progress = migrate::copy_keys(source, dest, "user:*", CopyOptions::default(), |progress| {
+///     println!("Copied {} keys", progress.copied);
+///   })
+///   .await?;
+///
+///   println!("Copied {} keys, skipped {} keys", progress.copied, progress.skipped);
+///   Ok(())
+/// }
+/// ```
+pub async fn copy_keys<K, F>(
+  source: &RedisClient,
+  dest: &RedisClient,
+  keys: K,
+  options: CopyOptions,
+  mut on_progress: F,
+) -> Result<CopyProgress, RedisError>
+where
+  K: Into<KeySource>,
+  F: FnMut(&CopyProgress),
+{
+  let mut progress = CopyProgress::default();
+
+  match keys.into() {
+    KeySource::Keys(keys) => copy_page(source, dest, keys, &options, &mut progress, &mut on_progress).await?,
+    KeySource::Pattern(pattern) => {
+      copy_pattern(source, dest, pattern, &options, &mut progress, &mut on_progress).await?
+    }
+  };
+
+  Ok(progress)
+}
+
+async fn rename_inner(
+  client: &RedisClient,
+  source: RedisKey,
+  dest: RedisKey,
+  replace: bool,
+) -> Result<(), RedisError> {
+  let (serialized, ttl) = match dump_with_ttl(client, &source).await? {
+    Some(result) => result,
+    None => return Err(RedisError::new(RedisErrorKind::NotFound, "Source key does not exist.")),
+  };
+  if source == dest {
+    // restoring and then deleting the same key would remove it
+    return Ok(());
+  }
+
+  let _ = client.restore(dest.clone(), ttl, serialized, replace, false, None, None).await?;
+  if client.exists::<i64, _>(dest).await? == 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::Unknown,
+      "Failed to verify destination key after RESTORE.",
+    ));
+  }
+
+  let _ = client.del::<i64, _>(source).await?;
+  Ok(())
+}
+
+/// Rename a key via `DUMP`, `PTTL`, `RESTORE`, and `DEL`, which works when the source and destination keys are in different
+/// cluster hash slots.
+///
+/// Unlike `RENAME` this is not atomic. Writes to the source key after it is dumped will be lost when the source key is deleted,
+/// and readers may observe both keys, or neither key if the operation fails between steps. Callers can use
+/// [lock_key](RenameOptions::lock_key) to coordinate with other writers.
+///
+/// If the destination key is restored but the source key cannot be deleted the error is returned and both keys will exist.
+///
+/// Like `RENAME`, renaming a key to itself does nothing if the key exists.
+pub async fn rename_cross_slot<S, D>(
+  client: &RedisClient,
+  source: S,
+  dest: D,
+  options: RenameOptions,
+) -> Result<(), RedisError>
+where
+  S: Into<RedisKey>,
+  D: Into<RedisKey>,
+{
+  let (source, dest) = (source.into(), dest.into());
+
+  let lock_key = match options.lock_key {
+    Some(key) => key,
+    None => return rename_inner(client, source, dest, options.replace).await,
+  };
+  let token = utils::random_string(32);
+  let acquired: Option<String> = client
+    .set(
+      lock_key.clone(),
+      token.clone(),
+      Some(Expiration::PX(options.lock_ttl_ms as i64)),
+      Some(SetOptions::NX),
+      false,
+    )
+    .await?;
+  if acquired.is_none() {
+    return Err(RedisError::new(
+      RedisErrorKind::Unknown,
+      "Failed to acquire lock before renaming key.",
+    ));
+  }
+
+  let result = rename_inner(client, source, dest, options.replace).await;
+  let _ = client
+    .eval::<i64, _, _, _>(RELEASE_LOCK_SCRIPT, vec![lock_key], vec![token])
+    .await;
+
+  result
+}
+
+/// Options for [reshard_slots](reshard_slots).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReshardOptions {
+  /// The maximum number of keys read with `CLUSTER GETKEYSINSLOT` and moved with each `MIGRATE` command.
+  ///
+  /// Default: `100`
+  pub batch_size: u64,
+  /// The `timeout` argument, in milliseconds, used with `MIGRATE`.
+  ///
+  /// Default: `5000`
+  pub timeout_ms: u64,
+  /// Whether or not to overwrite keys that already exist on the destination node via `MIGRATE ... REPLACE`.
+  ///
+  /// Default: `false`
+  pub replace: bool,
+}
+
+impl Default for ReshardOptions {
+  fn default() -> Self {
+    ReshardOptions {
+      batch_size: 100,
+      timeout_ms: 5000,
+      replace: false,
+    }
+  }
+}
+
+/// The progress of a [reshard_slots](reshard_slots) operation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReshardProgress {
+  /// The hash slot currently being moved.
+  pub slot: u16,
+  /// The number of keys moved from the current hash slot.
+  pub slot_keys: usize,
+  /// The number of keys moved across all hash slots.
+  pub keys: usize,
+  /// The number of hash slots moved to the destination node. Slots that the destination node already owned are not
+  /// counted.
+  pub slots: usize,
+}
+
+fn parse_server(server: &str) -> Result<(String, u16), RedisError> {
+  let mut parts = server.rsplitn(2, ":");
+
+  match (parts.next(), parts.next()) {
+    (Some(port), Some(host)) => Ok((host.to_owned(), port.parse::<u16>()?)),
+    _ => Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      format!("Invalid host/port for {}", server),
+    )),
+  }
+}
+
+/// Read or create a client connected to the provided cluster node.
+async fn node_client(
+  client: &RedisClient,
+  nodes: &mut HashMap<String, RedisClient>,
+  server: &str,
+) -> Result<RedisClient, RedisError> {
+  if let Some(node) = nodes.get(server) {
+    return Ok(node.clone());
+  }
+
+  let node = commands::cluster::connect_node(&client.inner, server).await?;
+  nodes.insert(server.to_owned(), node.clone());
+  Ok(node)
+}
+
+/// Move the keys in the provided slot from the source to the destination node with `MIGRATE`.
+async fn migrate_slot_keys<F>(
+  client: &RedisClient,
+  source: &RedisClient,
+  dest: &str,
+  options: &ReshardOptions,
+  progress: &mut ReshardProgress,
+  on_progress: &mut F,
+) -> Result<(), RedisError>
+where
+  F: FnMut(&ReshardProgress),
+{
+  let (host, port) = parse_server(dest)?;
+  let config = client.client_config();
+  let auth = match (config.username, config.password) {
+    (Some(username), Some(password)) => Some(MigrateAuth::UsernamePassword { username, password }),
+    (None, Some(password)) => Some(MigrateAuth::Password(password)),
+    _ => None,
+  };
+
+  loop {
+    let keys: Vec<Bytes> = source
+      .cluster_get_keys_in_slot(progress.slot, options.batch_size)
+      .await?;
+    if keys.is_empty() {
+      return Ok(());
+    }
+    let count = keys.len();
+
+    let _: RedisValue = source
+      .migrate(
+        host.as_str(),
+        port,
+        keys,
+        0,
+        options.timeout_ms,
+        false,
+        options.replace,
+        auth.clone(),
+      )
+      .await?;
+    progress.slot_keys += count;
+    progress.keys += count;
+    on_progress(progress);
+  }
+}
+
+/// Move one hash slot to the destination node, returning whether or not the slot was moved.
+async fn reshard_slot<F>(
+  client: &RedisClient,
+  nodes: &mut HashMap<String, RedisClient>,
+  dest: &str,
+  dest_id: &str,
+  options: &ReshardOptions,
+  progress: &mut ReshardProgress,
+  on_progress: &mut F,
+) -> Result<bool, RedisError>
+where
+  F: FnMut(&ReshardProgress),
+{
+  let source = match client.cached_slot_owner(progress.slot) {
+    Some(source) => source,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::Cluster,
+        format!("Missing cluster node for hash slot {}", progress.slot),
+      ))
+    }
+  };
+  if source.as_str() == dest {
+    return Ok(false);
+  }
+  let (source_client, dest_client) = (
+    node_client(client, nodes, &source).await?,
+    node_client(client, nodes, dest).await?,
+  );
+  let source_id: String = source_client.cluster_myid().await?;
+
+  let _ = dest_client
+    .cluster_setslot(progress.slot, ClusterSetSlotState::Importing(source_id))
+    .await?;
+  let _ = source_client
+    .cluster_setslot(progress.slot, ClusterSetSlotState::Migrating(dest_id.to_owned()))
+    .await?;
+  migrate_slot_keys(client, &source_client, dest, options, progress, on_progress).await?;
+
+  // assign the slot on the destination first so it doesn't lose the slot if the source node fails
+  let _ = dest_client
+    .cluster_setslot(progress.slot, ClusterSetSlotState::Node(dest_id.to_owned()))
+    .await?;
+  let _ = source_client
+    .cluster_setslot(progress.slot, ClusterSetSlotState::Node(dest_id.to_owned()))
+    .await?;
+  Ok(true)
+}
+
+/// Move the inclusive range of hash slots to the primary node at `dest` (`host:port`) with the `CLUSTER SETSLOT` and
+/// `MIGRATE` commands, calling `on_progress` after each batch of keys is moved.
+///
+/// For each hash slot the destination node is set to `IMPORTING` the slot and the current owner is set to `MIGRATING` it,
+/// then the keys are moved in batches with `MIGRATE` and both nodes are told the destination now owns the slot. The other
+/// nodes learn about the new owner via the cluster bus. Slots that the destination already owns are skipped, and the
+/// destination does not need to own any slots before this is called.
+///
+/// This function will stop and return the first error encountered, which may leave a slot in the importing or migrating
+/// state. Calling this function again will continue moving the slot. The client's cached cluster state is refreshed
+/// before returning.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::migrate::{self, ReshardOptions};
+/// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+///   let progress = migrate::reshard_slots(client, 0, 100, "127.0.0.1:30004", ReshardOptions::default(), |progress| {
+///     println!("Moved {} keys from hash slot {}", progress.slot_keys, progress.slot);
+///   })
+///   .await?;
+///
+///   println!("Moved {} keys across {} slots", progress.keys, progress.slots);
+///   Ok(())
+/// }
+/// ```
+pub async fn reshard_slots<S, F>(
+  client: &RedisClient,
+  start: u16,
+  end: u16,
+  dest: S,
+  options: ReshardOptions,
+  mut on_progress: F,
+) -> Result<ReshardProgress, RedisError>
+where
+  S: AsRef<str>,
+  F: FnMut(&ReshardProgress),
+{
+  let _ = utils::check_clustered(&client.inner)?;
+  if start > end {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "Invalid hash slot range.",
+    ));
+  }
+  let dest = dest.as_ref();
+  let mut nodes = HashMap::new();
+  let mut progress = ReshardProgress::default();
+
+  let mut result = async {
+    let dest_id: String = node_client(client, &mut nodes, dest).await?.cluster_myid().await?;
+
+    for slot in start..=end {
+      progress.slot = slot;
+      progress.slot_keys = 0;
+      let moved = reshard_slot(
+        client,
+        &mut nodes,
+        dest,
+        &dest_id,
+        &options,
+        &mut progress,
+        &mut on_progress,
+      )
+      .await?;
+
+      if moved {
+        progress.slots += 1;
+        on_progress(&progress);
+      }
+    }
+
+    Ok::<_, RedisError>(())
+  }
+  .await;
+
+  for (_, node) in nodes.into_iter() {
+    let _ = node.quit().await;
+  }
+  if let Err(e) = client.sync_cluster().await {
+    result = result.and(Err(e));
+  }
+
+  result.map(|_| progress)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_reshard_destination() {
+    assert_eq!(parse_server("127.0.0.1:30004").unwrap(), ("127.0.0.1".to_owned(), 30004));
+    assert_eq!(parse_server("::1:30004").unwrap(), ("::1".to_owned(), 30004));
+    assert!(parse_server("127.0.0.1").is_err());
+    assert!(parse_server("127.0.0.1:abc").is_err());
+  }
+}
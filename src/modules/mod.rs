@@ -1,10 +1,23 @@
+/// Utility functions for inspecting the keyspace.
+pub mod analytics;
 pub mod backchannel;
+/// Utility functions for deleting or expiring the keys that match a pattern.
+pub mod bulk;
+/// Client-side caching structs.
+pub mod cache;
+/// Utility functions for decoding the payload returned by `DUMP`.
+pub mod dump;
 /// Utility functions for manipulating global values that can affect performance.
 pub mod globals;
 pub mod inner;
 pub mod metrics;
+/// Utility functions for copying keys between servers or hash slots.
+pub mod migrate;
 /// Client pooling structs.
 pub mod pool;
+pub mod pubsub;
 pub mod response;
+/// Lua script helpers that manage the server's script cache.
+pub mod script;
 /// The structs and enums used by the Redis client.
 pub mod types;
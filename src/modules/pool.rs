@@ -249,6 +249,11 @@ pub struct StaticRedisPool {
   inner: Arc<StaticRedisPoolInner>,
 }
 
+/// A pool of clients that dispatches each command to the next client, round-robin.
+///
+/// This is an alias for [StaticRedisPool](crate::pool::StaticRedisPool).
+pub type RedisPool = StaticRedisPool;
+
 impl fmt::Display for StaticRedisPool {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "[Static Redis Pool]")
@@ -313,6 +318,15 @@ impl StaticRedisPool {
     Ok(())
   }
 
+  /// Connect each client to the server and wait for all the clients to connect, returning the task driving each
+  /// connection.
+  pub async fn connect_all(&self, policy: Option<ReconnectPolicy>) -> Result<Vec<ConnectHandle>, RedisError> {
+    let connections = self.connect(policy);
+    let _ = self.wait_for_connect().await?;
+
+    Ok(connections)
+  }
+
   /// Read the size of the pool.
   pub fn size(&self) -> usize {
     self.inner.clients.len()
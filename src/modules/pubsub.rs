@@ -0,0 +1,168 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::inner::RedisClientInner;
+use crate::multiplexer::utils as multiplexer_utils;
+use crate::types::{LagPolicy, PubsubBufferConfig};
+use crate::utils;
+use futures::stream::{self, Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast::{
+  self,
+  error::RecvError as BroadcastRecvError,
+  Receiver as BroadcastReceiver,
+  Sender as BroadcastSender,
+};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, unbounded_channel, Sender, UnboundedSender};
+use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
+
+/// A boxed stream returned to callers listening on the publish-subscribe interface.
+pub type PubsubStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/// The sender half of a publish-subscribe listener, bounded according to the client's `PubsubBufferConfig`.
+pub enum PubsubSender<T> {
+  Unbounded(UnboundedSender<T>),
+  DropNewest(Sender<T>),
+  DropOldest(BroadcastSender<T>),
+  Error(Sender<T>),
+}
+
+impl<T> PubsubSender<T>
+where
+  T: Clone + Send + 'static,
+{
+  /// Send a value to the listener, returning `false` if the listener should be removed.
+  pub fn send(&self, inner: &Arc<RedisClientInner>, value: T) -> bool {
+    match self {
+      PubsubSender::Unbounded(ref tx) => tx.send(value).is_ok(),
+      // the broadcast channel drops the oldest value when full, which the receiver reports as a lagged error
+      PubsubSender::DropOldest(ref tx) => tx.send(value).is_ok(),
+      PubsubSender::DropNewest(ref tx) => match tx.try_send(value) {
+        Ok(_) => true,
+        Err(TrySendError::Full(_)) => {
+          _trace!(inner, "Dropping pubsub message for slow listener.");
+          utils::incr_atomic(&inner.pubsub_dropped);
+          true
+        }
+        Err(TrySendError::Closed(_)) => false,
+      },
+      PubsubSender::Error(ref tx) => match tx.try_send(value) {
+        Ok(_) => true,
+        Err(TrySendError::Full(_)) => {
+          _warn!(inner, "Closing pubsub listener with a full buffer.");
+          utils::incr_atomic(&inner.pubsub_dropped);
+          let error = RedisError::new(RedisErrorKind::Canceled, "Pubsub listener buffer is full.");
+          multiplexer_utils::emit_error(inner, &error);
+          false
+        }
+        Err(TrySendError::Closed(_)) => false,
+      },
+    }
+  }
+}
+
+/// Create a new publish-subscribe listener according to the client's buffer config.
+pub fn listener<T>(inner: &Arc<RedisClientInner>) -> (PubsubSender<T>, PubsubStream<T>)
+where
+  T: Clone + Send + 'static,
+{
+  let config = inner.config.read().pubsub_buffer.clone();
+
+  match config {
+    None => {
+      let (tx, rx) = unbounded_channel();
+      (PubsubSender::Unbounded(tx), Box::pin(UnboundedReceiverStream::new(rx)))
+    }
+    Some(PubsubBufferConfig { capacity, policy }) => {
+      let capacity = if capacity == 0 { 1 } else { capacity };
+
+      match policy {
+        LagPolicy::DropNewest => {
+          let (tx, rx) = channel(capacity);
+          (PubsubSender::DropNewest(tx), Box::pin(ReceiverStream::new(rx)))
+        }
+        LagPolicy::Error => {
+          let (tx, rx) = channel(capacity);
+          (PubsubSender::Error(tx), Box::pin(ReceiverStream::new(rx)))
+        }
+        LagPolicy::DropOldest => {
+          let (tx, rx) = broadcast::channel(capacity);
+          (PubsubSender::DropOldest(tx), broadcast_stream(inner, rx))
+        }
+      }
+    }
+  }
+}
+
+fn broadcast_stream<T>(inner: &Arc<RedisClientInner>, rx: BroadcastReceiver<T>) -> PubsubStream<T>
+where
+  T: Clone + Send + 'static,
+{
+  stream::unfold((inner.clone(), rx), |(inner, mut rx)| async move {
+    loop {
+      match rx.recv().await {
+        Ok(value) => return Some((value, (inner, rx))),
+        Err(BroadcastRecvError::Lagged(count)) => {
+          _trace!(inner, "Dropped {} pubsub messages for slow listener.", count);
+          utils::incr_atomic_by(&inner.pubsub_dropped, count as usize);
+        }
+        Err(BroadcastRecvError::Closed) => return None,
+      }
+    }
+  })
+  .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::RedisConfig;
+
+  fn inner_with_policy(policy: LagPolicy) -> Arc<RedisClientInner> {
+    let mut config = RedisConfig::default();
+    config.pubsub_buffer = Some(PubsubBufferConfig { capacity: 2, policy });
+    RedisClientInner::new(config)
+  }
+
+  #[tokio::test]
+  async fn should_drop_newest_messages() {
+    let inner = inner_with_policy(LagPolicy::DropNewest);
+    let (tx, rx) = listener::<usize>(&inner);
+
+    for idx in 0..4 {
+      assert!(tx.send(&inner, idx));
+    }
+    drop(tx);
+
+    assert_eq!(rx.collect::<Vec<_>>().await, vec![0, 1]);
+    assert_eq!(utils::read_atomic(&inner.pubsub_dropped), 2);
+  }
+
+  #[tokio::test]
+  async fn should_drop_oldest_messages() {
+    let inner = inner_with_policy(LagPolicy::DropOldest);
+    let (tx, rx) = listener::<usize>(&inner);
+
+    for idx in 0..4 {
+      assert!(tx.send(&inner, idx));
+    }
+    drop(tx);
+
+    assert_eq!(rx.collect::<Vec<_>>().await, vec![2, 3]);
+    assert_eq!(utils::read_atomic(&inner.pubsub_dropped), 2);
+  }
+
+  #[tokio::test]
+  async fn should_close_listener_with_full_buffer() {
+    let inner = inner_with_policy(LagPolicy::Error);
+    let (tx, rx) = listener::<usize>(&inner);
+
+    assert!(tx.send(&inner, 0));
+    assert!(tx.send(&inner, 1));
+    assert!(!tx.send(&inner, 2));
+    drop(tx);
+
+    assert_eq!(rx.collect::<Vec<_>>().await, vec![0, 1]);
+    assert_eq!(utils::read_atomic(&inner.pubsub_dropped), 1);
+  }
+}
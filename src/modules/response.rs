@@ -1,20 +1,23 @@
-use crate::error::{RedisError, RedisErrorKind};
+use crate::error::RedisError;
 use crate::types::{RedisValue, QUEUED};
+use bytes::Bytes;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::hash::{BuildHasher, Hash};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 macro_rules! to_signed_number(
   ($t:ty, $v:expr) => {
     match $v {
-      RedisValue::Integer(i) => Ok(i as $t),
-      RedisValue::String(s) => s.parse::<$t>().map_err(|e| e.into()),
-      RedisValue::Null => Err(RedisError::new(RedisErrorKind::NotFound, "Cannot convert nil to number.")),
+      RedisValue::Integer(i) => <$t>::try_from(i).map_err(|_| RedisError::new_parse("Integer out of range.")),
+      RedisValue::String(s) | RedisValue::BigNumber(s) => s.parse::<$t>().map_err(|e| e.into()),
+      RedisValue::Null => Err(RedisError::new_unexpected_nil("number")),
       RedisValue::Array(mut a) => if a.len() == 1 {
         match a.pop().unwrap() {
-          RedisValue::Integer(i) => Ok(i as $t),
-          RedisValue::String(s) => s.parse::<$t>().map_err(|e| e.into()),
-          RedisValue::Null => Err(RedisError::new(RedisErrorKind::NotFound, "Cannot convert nil to number.")),
+          RedisValue::Integer(i) => <$t>::try_from(i).map_err(|_| RedisError::new_parse("Integer out of range.")),
+          RedisValue::String(s) | RedisValue::BigNumber(s) => s.parse::<$t>().map_err(|e| e.into()),
+          RedisValue::Null => Err(RedisError::new_unexpected_nil("number")),
           _ => Err(RedisError::new_parse("Cannot convert to number."))
         }
       }else{
@@ -31,24 +34,24 @@ macro_rules! to_unsigned_number(
       RedisValue::Integer(i) => if i < 0 {
         Err(RedisError::new_parse("Cannot convert from negative number."))
       }else{
-        Ok(i as $t)
+        <$t>::try_from(i).map_err(|_| RedisError::new_parse("Integer out of range."))
       },
-      RedisValue::String(s) => s.parse::<$t>().map_err(|e| e.into()),
+      RedisValue::String(s) | RedisValue::BigNumber(s) => s.parse::<$t>().map_err(|e| e.into()),
       RedisValue::Array(mut a) => if a.len() == 1 {
         match a.pop().unwrap() {
           RedisValue::Integer(i) => if i < 0 {
             Err(RedisError::new_parse("Cannot convert from negative number."))
           }else{
-            Ok(i as $t)
+            <$t>::try_from(i).map_err(|_| RedisError::new_parse("Integer out of range."))
           },
-          RedisValue::Null => Err(RedisError::new(RedisErrorKind::NotFound, "Cannot convert nil to number.")),
-          RedisValue::String(s) => s.parse::<$t>().map_err(|e| e.into()),
+          RedisValue::Null => Err(RedisError::new_unexpected_nil("number")),
+          RedisValue::String(s) | RedisValue::BigNumber(s) => s.parse::<$t>().map_err(|e| e.into()),
           _ => Err(RedisError::new_parse("Cannot convert to number."))
         }
       }else{
         Err(RedisError::new_parse("Cannot convert array to number."))
       },
-      RedisValue::Null => Err(RedisError::new(RedisErrorKind::NotFound, "Cannot convert nil to number.")),
+      RedisValue::Null => Err(RedisError::new_unexpected_nil("number")),
       _ => Err(RedisError::new_parse("Cannot convert to number.")),
     }
   }
@@ -128,10 +131,7 @@ impl_unsigned_number!(usize);
 impl RedisResponse for String {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
     if value.is_null() {
-      Err(RedisError::new(
-        RedisErrorKind::NotFound,
-        "Cannot convert nil response to string.",
-      ))
+      Err(RedisError::new_unexpected_nil("string"))
     } else {
       value
         .into_string()
@@ -140,13 +140,23 @@ impl RedisResponse for String {
   }
 }
 
+impl RedisResponse for Bytes {
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    if value.is_null() {
+      Err(RedisError::new_unexpected_nil("bytes"))
+    } else {
+      value
+        .into_bytes()
+        .map(Bytes::from)
+        .ok_or(RedisError::new_parse("Could not convert to bytes."))
+    }
+  }
+}
+
 impl RedisResponse for f64 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
     if value.is_null() {
-      Err(RedisError::new(
-        RedisErrorKind::NotFound,
-        "Cannot convert nil response to double.",
-      ))
+      Err(RedisError::new_unexpected_nil("double"))
     } else {
       value
         .as_f64()
@@ -158,10 +168,7 @@ impl RedisResponse for f64 {
 impl RedisResponse for f32 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
     if value.is_null() {
-      Err(RedisError::new(
-        RedisErrorKind::NotFound,
-        "Cannot convert nil response to float.",
-      ))
+      Err(RedisError::new_unexpected_nil("float"))
     } else {
       value
         .as_f64()
@@ -174,10 +181,7 @@ impl RedisResponse for f32 {
 impl RedisResponse for bool {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
     if value.is_null() {
-      Err(RedisError::new(
-        RedisErrorKind::NotFound,
-        "Cannot convert nil response to bool.",
-      ))
+      Err(RedisError::new_unexpected_nil("bool"))
     } else {
       value
         .as_bool()
@@ -186,15 +190,83 @@ impl RedisResponse for bool {
   }
 }
 
+/// Values are read as a number of seconds, such as the result of `TTL` or `OBJECT IDLETIME`. Negative values, such as the
+/// `-1` returned by `TTL` when the key has no timeout, return an error.
+impl RedisResponse for Duration {
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    u64::from_value(value).map(Duration::from_secs)
+  }
+}
+
+/// Values are read as a UNIX timestamp in seconds, such as the result of `EXPIRETIME`. Negative values return an error.
+impl RedisResponse for SystemTime {
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    u64::from_value(value).map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds))
+  }
+}
+
+/// Values are read as a UNIX timestamp in seconds, such as the result of `EXPIRETIME`.
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl RedisResponse for chrono::DateTime<chrono::Utc> {
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    use chrono::TimeZone;
+
+    let seconds = i64::from_value(value)?;
+    chrono::Utc
+      .timestamp_opt(seconds, 0)
+      .single()
+      .ok_or(RedisError::new_parse("Invalid timestamp."))
+  }
+}
+
+/// Strings are not parsed as JSON, and non UTF-8 bytes cannot be converted. See [json_get](crate::client::RedisClient::json_get)
+/// to deserialize JSON strings.
+#[cfg(feature = "serde-json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-json")))]
+impl RedisResponse for serde_json::Value {
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    let value = match value {
+      RedisValue::Null => serde_json::Value::Null,
+      RedisValue::Queued => serde_json::Value::String(QUEUED.into()),
+      RedisValue::Integer(i) => serde_json::Value::Number(i.into()),
+      RedisValue::Double(f) => serde_json::Number::from_f64(f)
+        .map(serde_json::Value::Number)
+        .ok_or(RedisError::new_parse("Cannot convert NaN or infinite double to JSON."))?,
+      RedisValue::Boolean(b) => serde_json::Value::Bool(b),
+      RedisValue::String(s) | RedisValue::BigNumber(s) | RedisValue::VerbatimString { data: s, .. } => {
+        serde_json::Value::String(s)
+      }
+      RedisValue::Bytes(b) => serde_json::Value::String(String::from_utf8(b)?),
+      RedisValue::Array(values) | RedisValue::Set(values) => serde_json::Value::Array(
+        values
+          .into_iter()
+          .map(serde_json::Value::from_value)
+          .collect::<Result<_, _>>()?,
+      ),
+      RedisValue::Map(map) => {
+        let mut out = serde_json::Map::with_capacity(map.len());
+        for (key, value) in map.inner.into_iter() {
+          out.insert(key, serde_json::Value::from_value(value)?);
+        }
+        serde_json::Value::Object(out)
+      }
+    };
+
+    Ok(value)
+  }
+}
+
 impl<T> RedisResponse for Option<T>
 where
   T: RedisResponse,
 {
   fn from_value(value: RedisValue) -> Result<Option<T>, RedisError> {
-    if value.is_null() {
-      Ok(None)
-    } else {
-      Ok(Some(T::from_value(value)?))
+    match value {
+      RedisValue::Null => Ok(None),
+      // such as the response to `MGET` with one key
+      RedisValue::Array(ref values) if values.len() == 1 && values[0].is_null() => Ok(None),
+      value => Ok(Some(T::from_value(value)?)),
     }
   }
 }
@@ -214,7 +286,7 @@ where
           Ok(vec![T::from_value(RedisValue::String(string))?])
         }
       }
-      RedisValue::Array(values) => T::from_values(values),
+      RedisValue::Array(values) | RedisValue::Set(values) => T::from_values(values),
       RedisValue::Map(map) => {
         // not being able to use collect() here is unfortunate
         let out = Vec::with_capacity(map.len() * 2);
@@ -229,6 +301,10 @@ where
       RedisValue::Null => Ok(vec![]),
       RedisValue::Integer(i) => Ok(vec![T::from_value(RedisValue::Integer(i))?]),
       RedisValue::Queued => Ok(vec![T::from_value(RedisValue::String(QUEUED.into()))?]),
+      RedisValue::Double(_)
+      | RedisValue::Boolean(_)
+      | RedisValue::BigNumber(_)
+      | RedisValue::VerbatimString { .. } => Ok(vec![T::from_value(value)?]),
     }
   }
 }
@@ -240,7 +316,9 @@ where
   S: BuildHasher + Default,
 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
-    let as_map = if value.is_array() || value.is_map() {
+    let as_map = if value.is_null() {
+      return Ok(Self::default());
+    } else if value.is_array() || value.is_map() {
       value
         .into_map()
         .map_err(|_| RedisError::new_parse("Cannot convert to map."))?
@@ -268,6 +346,10 @@ where
   S: BuildHasher + Default,
 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    if value.is_null() {
+      return Ok(Self::default());
+    }
+
     value.into_array().into_iter().map(|v| V::from_value(v)).collect()
   }
 }
@@ -278,7 +360,9 @@ where
   V: RedisResponse,
 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
-    let as_map = if value.is_array() || value.is_map() {
+    let as_map = if value.is_null() {
+      return Ok(Self::default());
+    } else if value.is_array() || value.is_map() {
       value
         .into_map()
         .map_err(|_| RedisError::new_parse("Cannot convert to map."))?
@@ -305,6 +389,10 @@ where
   V: RedisResponse + Ord,
 {
   fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    if value.is_null() {
+      return Ok(Self::default());
+    }
+
     value.into_array().into_iter().map(|v| V::from_value(v)).collect()
   }
 }
@@ -317,43 +405,75 @@ macro_rules! impl_redis_response_tuple {
     impl<$($name: RedisResponse),*> RedisResponse for ($($name,)*) {
       #[allow(non_snake_case, unused_variables)]
       fn from_value(v: RedisValue) -> Result<($($name,)*), RedisError> {
-        if let RedisValue::Array(mut values) = v {
-          let mut n = 0;
-          $(let $name = (); n += 1;)*
-          if values.len() != n {
-            return Err(RedisError::new_parse("Invalid tuple dimension."));
-          }
+        let mut n = 0;
+        $(let $name = (); n += 1;)*
 
-          // since we have ownership over the values we have some freedom in how to implement this
-          values.reverse();
-          Ok(($({let $name = (); values
-            .pop()
-            .ok_or(RedisError::new_parse("Expected value, found none."))?
-            .convert()?
-          },)*))
-        }else{
-          Err(RedisError::new_parse("Could not convert to tuple."))
+        let mut values = match v {
+          RedisValue::Array(values) => values,
+          // single element arrays are flattened when parsing responses, such as the response to a transaction with one command
+          v if n == 1 => vec![v],
+          RedisValue::Null => return Err(RedisError::new_unexpected_nil("tuple")),
+          _ => return Err(RedisError::new_parse("Could not convert to tuple.")),
+        };
+        if values.len() != n {
+          return Err(RedisError::new_parse(format!(
+            "Invalid tuple dimension. Expected {} values, found {}.",
+            n,
+            values.len()
+          )));
         }
+
+        // since we have ownership over the values we have some freedom in how to implement this
+        values.reverse();
+        Ok(($({let $name = (); values
+          .pop()
+          .ok_or(RedisError::new_parse("Expected value, found none."))?
+          .convert()?
+        },)*))
       }
 
       #[allow(non_snake_case, unused_variables)]
-      fn from_values(mut values: Vec<RedisValue>) -> Result<Vec<($($name,)*)>, RedisError> {
+      fn from_values(values: Vec<RedisValue>) -> Result<Vec<($($name,)*)>, RedisError> {
+        #[allow(non_snake_case, unused_variables)]
+        fn from_flat_values<$($name: RedisResponse),*>(
+          mut values: Vec<RedisValue>,
+          n: usize,
+        ) -> Result<Vec<($($name,)*)>, RedisError> {
+          if values.len() % n != 0 {
+            return Err(RedisError::new_parse("Invalid tuple dimension."))
+          }
+
+          let mut out = Vec::with_capacity(values.len() / n);
+          // this would be cleaner if there were an owned `chunks` variant
+          for chunk in values.chunks_exact_mut(n) {
+            match chunk {
+              [$($name),*] => out.push(($($name.take().convert()?),*),),
+               _ => unreachable!(),
+            }
+          }
+
+          Ok(out)
+        }
+
         let mut n = 0;
         $(let $name = (); n += 1;)*
-        if values.len() % n != 0 {
-          return Err(RedisError::new_parse("Invalid tuple dimension."))
-        }
 
-        let mut out = Vec::with_capacity(values.len() / n);
-        // this would be cleaner if there were an owned `chunks` variant
-        for chunk in values.chunks_exact_mut(n) {
-          match chunk {
-            [$($name),*] => out.push(($($name.take().convert()?),*),),
-             _ => unreachable!(),
+        // nested arrays such as `[[member, score], ...]` convert each inner array into a tuple. a flat array whose
+        // elements all happen to be arrays of length `n` is ambiguous, so the flat reading is preferred when it succeeds.
+        let is_nested = n > 1 && !values.is_empty() && values.iter().all(|value| match value {
+          RedisValue::Array(ref inner) => inner.len() == n,
+          _ => false,
+        });
+        if !is_nested {
+          return from_flat_values::<$($name),*>(values, n);
+        }
+        if values.len() % n == 0 {
+          if let Ok(out) = from_flat_values::<$($name),*>(values.clone(), n) {
+            return Ok(out);
           }
         }
 
-        Ok(out)
+        values.into_iter().map(|value| value.convert()).collect()
       }
     }
     impl_redis_response_peel!($($name,)*);
@@ -364,13 +484,15 @@ macro_rules! impl_redis_response_peel {
   ($name:ident, $($other:ident,)*) => (impl_redis_response_tuple!($($other,)*);)
 }
 
-impl_redis_response_tuple! { T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, }
+impl_redis_response_tuple! { T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, }
 
 #[cfg(test)]
 mod tests {
   use crate::error::RedisError;
   use crate::types::RedisValue;
+  use bytes::Bytes;
   use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+  use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
   #[test]
   fn should_convert_null() {
@@ -438,39 +560,80 @@ mod tests {
   }
 
   #[test]
-  fn should_return_not_found_with_null_scalar_values() {
+  fn should_convert_big_numbers() {
+    let big = "170141183460469231731687303715884105727";
+    let _foo: i128 = RedisValue::BigNumber(big.into()).convert().unwrap();
+    assert_eq!(_foo, i128::MAX);
+    let _foo: u128 = RedisValue::BigNumber(big.into()).convert().unwrap();
+    assert_eq!(_foo, i128::MAX as u128);
+    let _foo: i64 = RedisValue::BigNumber("123".into()).convert().unwrap();
+    assert_eq!(_foo, 123);
+
+    let result: Result<i64, RedisError> = RedisValue::BigNumber(big.into()).convert();
+    assert!(result.is_err());
+    assert_eq!(RedisValue::BigNumber(big.into()).as_i128(), Some(i128::MAX));
+    assert_eq!(RedisValue::BigNumber(big.into()).as_i64(), None);
+  }
+
+  #[test]
+  fn should_not_truncate_integers() {
+    let result: Result<i8, RedisError> = RedisValue::Integer(300).convert();
+    assert!(result.is_err());
+    let result: Result<u16, RedisError> = RedisValue::Integer(i64::MAX).convert();
+    assert!(result.is_err());
+    let _foo: u64 = RedisValue::Integer(i64::MAX).convert().unwrap();
+    assert_eq!(_foo, i64::MAX as u64);
+  }
+
+  #[test]
+  fn should_convert_large_integers_to_big_numbers() {
+    use std::convert::TryFrom;
+
+    assert_eq!(RedisValue::try_from(10_i128).unwrap(), RedisValue::Integer(10));
+    assert_eq!(
+      RedisValue::try_from(i128::MIN).unwrap(),
+      RedisValue::BigNumber(i128::MIN.to_string())
+    );
+    assert_eq!(
+      RedisValue::try_from(u128::MAX).unwrap(),
+      RedisValue::BigNumber(u128::MAX.to_string())
+    );
+  }
+
+  #[test]
+  fn should_return_unexpected_nil_with_null_scalar_values() {
     let result: Result<u8, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<u16, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<u32, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<u64, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<u128, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<usize, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<i8, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<i16, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<i32, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<i64, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<i128, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<isize, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
   }
 
   #[test]
-  fn should_return_not_found_with_null_strings_and_bools() {
+  fn should_return_unexpected_nil_with_null_strings_and_bools() {
     let result: Result<String, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
     let result: Result<bool, RedisError> = RedisValue::Null.convert();
-    assert!(result.unwrap_err().is_not_found());
+    assert!(result.unwrap_err().is_unexpected_nil());
   }
 
   #[test]
@@ -503,6 +666,60 @@ mod tests {
     assert_eq!(_foo, "foo".as_bytes().to_vec());
   }
 
+  #[test]
+  fn should_convert_bytes_buffers() {
+    let _foo: Bytes = RedisValue::Bytes(vec![0, 159, 146, 150]).convert().unwrap();
+    assert_eq!(_foo, Bytes::from_static(&[0, 159, 146, 150]));
+    let _foo: Bytes = RedisValue::String("foo".into()).convert().unwrap();
+    assert_eq!(_foo, Bytes::from_static(b"foo"));
+    let result: Result<Bytes, RedisError> = RedisValue::Null.convert();
+    assert!(result.unwrap_err().is_unexpected_nil());
+
+    let value: RedisValue = Bytes::from_static(&[0, 159, 146, 150]).into();
+    assert_eq!(value, RedisValue::Bytes(vec![0, 159, 146, 150]));
+  }
+
+  #[test]
+  fn should_convert_durations_and_timestamps() {
+    let _foo: Duration = RedisValue::Integer(10).convert().unwrap();
+    assert_eq!(_foo, Duration::from_secs(10));
+    let _foo: SystemTime = RedisValue::Integer(1_000).convert().unwrap();
+    assert_eq!(_foo, UNIX_EPOCH + Duration::from_secs(1_000));
+    let result: Result<Duration, RedisError> = RedisValue::Integer(-1).convert();
+    assert!(result.is_err());
+    let result: Result<SystemTime, RedisError> = RedisValue::Null.convert();
+    assert!(result.unwrap_err().is_unexpected_nil());
+  }
+
+  #[test]
+  fn should_convert_nil_to_none_or_empty_collections() {
+    let foo: Option<String> = RedisValue::Null.convert().unwrap();
+    assert_eq!(foo, None);
+    let foo: Option<String> = RedisValue::Array(vec![RedisValue::Null]).convert().unwrap();
+    assert_eq!(foo, None);
+    let foo: Vec<Option<String>> = RedisValue::Array(vec!["a".into(), RedisValue::Null]).convert().unwrap();
+    assert_eq!(foo, vec![Some("a".to_owned()), None]);
+    let foo: Vec<Option<String>> = RedisValue::Array(vec![RedisValue::Null]).convert().unwrap();
+    assert_eq!(foo, vec![None]);
+
+    let foo: HashMap<String, i64> = RedisValue::Null.convert().unwrap();
+    assert!(foo.is_empty());
+    let foo: BTreeMap<String, i64> = RedisValue::Null.convert().unwrap();
+    assert!(foo.is_empty());
+    let foo: HashSet<String> = RedisValue::Null.convert().unwrap();
+    assert!(foo.is_empty());
+    let foo: BTreeSet<String> = RedisValue::Null.convert().unwrap();
+    assert!(foo.is_empty());
+  }
+
+  #[test]
+  fn should_return_unexpected_nil_with_null_tuples() {
+    let result: Result<(String, i64), RedisError> = RedisValue::Null.convert();
+    assert!(result.unwrap_err().is_unexpected_nil());
+    let result: Result<(String, i64), RedisError> = RedisValue::Array(vec!["a".into(), RedisValue::Null]).convert();
+    assert!(result.unwrap_err().is_unexpected_nil());
+  }
+
   #[test]
   fn should_convert_arrays() {
     let foo: Vec<String> = RedisValue::Array(vec!["a".into(), "b".into()]).convert().unwrap();
@@ -559,6 +776,34 @@ mod tests {
     assert_eq!(foo, ("a".to_owned(), 1));
   }
 
+  #[test]
+  fn should_convert_typed_tuples() {
+    let foo: (i64, String, Option<f64>) = RedisValue::Array(vec![1.into(), "a".into(), RedisValue::Null])
+      .convert()
+      .unwrap();
+    assert_eq!(foo, (1, "a".to_owned(), None));
+    let foo: (i64, String, Option<f64>) = RedisValue::Array(vec![1.into(), "a".into(), "1.5".into()])
+      .convert()
+      .unwrap();
+    assert_eq!(foo, (1, "a".to_owned(), Some(1.5)));
+  }
+
+  #[test]
+  fn should_convert_single_value_tuples() {
+    let foo: (String,) = RedisValue::String("a".into()).convert().unwrap();
+    assert_eq!(foo, ("a".to_owned(),));
+    let foo: (Option<i64>,) = RedisValue::Null.convert().unwrap();
+    assert_eq!(foo, (None,));
+  }
+
+  #[test]
+  fn should_not_convert_tuples_with_wrong_dimension() {
+    let result: Result<(String, i64), _> = RedisValue::Array(vec!["a".into()]).convert();
+    assert!(result.is_err());
+    let result: Result<(String, i64), _> = RedisValue::String("a".into()).convert();
+    assert!(result.is_err());
+  }
+
   #[test]
   fn should_convert_array_tuples() {
     let foo: Vec<(String, i64)> = RedisValue::Array(vec!["a".into(), 1.into(), "b".into(), 2.into()])
@@ -566,4 +811,77 @@ mod tests {
       .unwrap();
     assert_eq!(foo, vec![("a".to_owned(), 1), ("b".to_owned(), 2)]);
   }
+
+  #[test]
+  fn should_convert_nested_array_tuples() {
+    let foo: Vec<(String, f64)> = RedisValue::Array(vec![
+      RedisValue::Array(vec!["a".into(), "1.5".into()]),
+      RedisValue::Array(vec!["b".into(), 2.into()]),
+    ])
+    .convert()
+    .unwrap();
+    assert_eq!(foo, vec![("a".to_owned(), 1.5), ("b".to_owned(), 2.0)]);
+  }
+
+  #[test]
+  fn should_prefer_flat_tuples_when_nested_arrays_are_ambiguous() {
+    let value = RedisValue::Array(vec![
+      RedisValue::Array(vec!["a".into(), "b".into()]),
+      RedisValue::Array(vec!["c".into(), "d".into()]),
+    ]);
+
+    let flat: Vec<(Vec<String>, Vec<String>)> = value.clone().convert().unwrap();
+    assert_eq!(flat, vec![(
+      vec!["a".to_owned(), "b".to_owned()],
+      vec!["c".to_owned(), "d".to_owned()]
+    )]);
+    let nested: Vec<(String, String)> = value.convert().unwrap();
+    assert_eq!(nested, vec![
+      ("a".to_owned(), "b".to_owned()),
+      ("c".to_owned(), "d".to_owned())
+    ]);
+  }
+
+  #[test]
+  fn should_convert_nested_tuples() {
+    let foo: ((String, f64), (String, f64)) = RedisValue::Array(vec![
+      RedisValue::Array(vec!["a".into(), "1.5".into()]),
+      RedisValue::Array(vec!["b".into(), 2.into()]),
+    ])
+    .convert()
+    .unwrap();
+    assert_eq!(foo, (("a".to_owned(), 1.5), ("b".to_owned(), 2.0)));
+
+    let foo: (String, (i64, i64)) = RedisValue::Array(vec!["a".into(), RedisValue::Array(vec![1.into(), 2.into()])])
+      .convert()
+      .unwrap();
+    assert_eq!(foo, ("a".to_owned(), (1, 2)));
+  }
+
+  #[test]
+  #[cfg(feature = "serde-json")]
+  fn should_convert_values_to_json() {
+    let mut map = HashMap::new();
+    map.insert("a".to_owned(), RedisValue::Array(vec![1.into(), "b".into(), RedisValue::Null]));
+    map.insert("c".to_owned(), RedisValue::Boolean(true));
+    let value: serde_json::Value = RedisValue::from(map).convert().unwrap();
+
+    assert_eq!(value, serde_json::json!({ "a": [1, "b", null], "c": true }));
+  }
+
+  #[test]
+  #[cfg(feature = "serde-json")]
+  fn should_convert_json_to_values() {
+    let value: RedisValue = serde_json::json!({ "a": [1, 1.5, "b", null], "c": true }).into();
+    let map = match value {
+      RedisValue::Map(map) => map.inner(),
+      _ => panic!("Expected map."),
+    };
+
+    assert_eq!(
+      map.get("a"),
+      Some(&RedisValue::Array(vec![1.into(), "1.5".into(), "b".into(), RedisValue::Null]))
+    );
+    assert_eq!(map.get("c"), Some(&"true".into()));
+  }
 }
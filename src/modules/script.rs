@@ -0,0 +1,256 @@
+use crate::client::util::sha1_hash;
+use crate::client::RedisClient;
+use crate::error::RedisError;
+use crate::modules::response::RedisResponse;
+use crate::types::{ConnectHandle, ScriptArgs};
+use futures::StreamExt;
+use std::borrow::Cow;
+use std::fmt;
+
+const HEX_CHARS: &'static [u8; 16] = b"0123456789abcdef";
+const SHA1_INIT: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// Embed a Lua script file in the binary as a [Script](crate::script::Script), computing its SHA1 hash at compile time.
+///
+/// The path is resolved relative to the current file, like [include_str].
+///
+/// ```rust ignore
+/// use fred::script::Script;
+///
+/// static INCR_BY: Script = fred::include_script!("scripts/incr_by.lua");
+/// ```
+#[macro_export]
+macro_rules! include_script {
+  ($path:expr) => {{
+    const SOURCE: &'static str = include_str!($path);
+    const HASH: [u8; 40] = $crate::script::sha1_hex(SOURCE.as_bytes());
+    // the hash only contains lowercase hex characters
+    const SCRIPT: $crate::script::Script =
+      $crate::script::Script::from_static(SOURCE, unsafe { ::std::str::from_utf8_unchecked(&HASH) });
+    SCRIPT
+  }};
+}
+
+/// Read the byte at `index` in the input after applying SHA1 padding.
+const fn padded_byte(input: &[u8], index: usize, padded_len: usize) -> u8 {
+  if index < input.len() {
+    input[index]
+  } else if index == input.len() {
+    0x80
+  } else if index >= padded_len - 8 {
+    let bits = (input.len() as u64).wrapping_mul(8);
+    (bits >> ((padded_len - 1 - index) * 8)) as u8
+  } else {
+    0
+  }
+}
+
+/// Compute the SHA1 hash of the input as lowercase hex bytes in a const context.
+///
+/// This is used by [include_script](crate::include_script) and is not intended to be called directly.
+#[doc(hidden)]
+pub const fn sha1_hex(input: &[u8]) -> [u8; 40] {
+  let padded_len = (input.len() + 8) / 64 * 64 + 64;
+  let mut state = SHA1_INIT;
+
+  let mut block = 0;
+  while block < padded_len {
+    let mut w = [0u32; 80];
+    let mut i = 0;
+    while i < 16 {
+      let offset = block + i * 4;
+      w[i] = (padded_byte(input, offset, padded_len) as u32) << 24
+        | (padded_byte(input, offset + 1, padded_len) as u32) << 16
+        | (padded_byte(input, offset + 2, padded_len) as u32) << 8
+        | padded_byte(input, offset + 3, padded_len) as u32;
+      i += 1;
+    }
+    while i < 80 {
+      w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+      i += 1;
+    }
+
+    let (mut a, mut b, mut c, mut d, mut e) = (state[0], state[1], state[2], state[3], state[4]);
+    i = 0;
+    while i < 80 {
+      let (f, k) = if i < 20 {
+        ((b & c) | (!b & d), 0x5A827999)
+      } else if i < 40 {
+        (b ^ c ^ d, 0x6ED9EBA1)
+      } else if i < 60 {
+        ((b & c) | (b & d) | (c & d), 0x8F1BBCDC)
+      } else {
+        (b ^ c ^ d, 0xCA62C1D6)
+      };
+
+      let temp = a
+        .rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(k)
+        .wrapping_add(w[i]);
+      e = d;
+      d = c;
+      c = b.rotate_left(30);
+      b = a;
+      a = temp;
+      i += 1;
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+    block += 64;
+  }
+
+  let mut out = [0u8; 40];
+  let mut i = 0;
+  while i < 40 {
+    let nibble = (state[i / 8] >> (28 - (i % 8) * 4)) & 0x0f;
+    out[i] = HEX_CHARS[nibble as usize];
+    i += 1;
+  }
+  out
+}
+
+/// A Lua script that caches its SHA1 hash.
+///
+/// Scripts are sent with `EVALSHA` first, and if the server responds with a `NOSCRIPT` error the script is sent again with
+/// `EVAL`, which also adds the script to the server's script cache. This makes it safe to use scripts after a server restarts
+/// or a cluster node fails over to a replica without the script in its cache.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::script::Script;
+/// async fn example(client: &RedisClient) -> Result<(), RedisError> {
+///   let script = Script::new("return redis.call('INCRBY', KEYS[1], ARGV[1])");
+///   script.load(client).await?;
+///
+///   let foo: i64 = script.eval(client, ScriptArgs::new().key("foo").arg(10)).await?;
+///   Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Script {
+  source: Cow<'static, str>,
+  hash: Cow<'static, str>,
+}
+
+impl fmt::Display for Script {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "[Script {}]", self.hash)
+  }
+}
+
+impl Script {
+  /// Create a new script, computing the SHA1 hash of the source.
+  pub fn new<S>(source: S) -> Script
+  where
+    S: Into<String>,
+  {
+    let source = source.into();
+    let hash = sha1_hash(&source);
+
+    Script {
+      source: Cow::Owned(source),
+      hash: Cow::Owned(hash),
+    }
+  }
+
+  /// Create a script from a static source string and its precomputed SHA1 hash.
+  ///
+  /// The hash is not checked against the source. If the hash is incorrect every call to [eval](Self::eval) will fall back to `EVAL`.
+  /// See [include_script](crate::include_script) to embed a script file and compute the hash at compile time.
+  pub const fn from_static(source: &'static str, hash: &'static str) -> Script {
+    Script {
+      source: Cow::Borrowed(source),
+      hash: Cow::Borrowed(hash),
+    }
+  }
+
+  /// Read the source of the script.
+  pub fn source(&self) -> &str {
+    &self.source
+  }
+
+  /// Read the SHA1 hash of the script.
+  pub fn sha1(&self) -> &str {
+    &self.hash
+  }
+
+  /// Load the script into the script cache, or into the script cache on every primary node if the client is clustered.
+  pub async fn load(&self, client: &RedisClient) -> Result<(), RedisError> {
+    let _ = if client.is_clustered() {
+      client.script_load_cluster(self.source.as_ref()).await?
+    } else {
+      client.script_load(self.source.as_ref()).await?
+    };
+
+    Ok(())
+  }
+
+  /// Evaluate the script with `EVALSHA`, falling back to `EVAL` if the server does not have the script in its cache.
+  pub async fn eval<R>(&self, client: &RedisClient, args: ScriptArgs) -> Result<R, RedisError>
+  where
+    R: RedisResponse,
+  {
+    match client.evalsha_with_args(self.hash.as_ref(), args.clone()).await {
+      Err(error) if error.is_no_script() => {
+        debug!(
+          "{}: Script {} not found, retrying with EVAL.",
+          client.id(),
+          self.hash
+        );
+        client.eval_with_args(self.source.as_ref(), args).await
+      }
+      result => result,
+    }
+  }
+
+  /// Load the script each time the client connects or reconnects to the server, returning the task that loads the script.
+  ///
+  /// Errors loading the script are logged and do not stop the task. Callers can still rely on [eval](Self::eval) to load the
+  /// script on nodes that were not reached, such as replicas promoted after a failover.
+  pub fn load_on_reconnect(&self, client: &RedisClient) -> ConnectHandle {
+    let (script, client) = (self.clone(), client.clone());
+
+    tokio::spawn(async move {
+      let mut reconnect_stream = client.on_reconnect();
+
+      while let Some(client) = reconnect_stream.next().await {
+        if let Err(error) = script.load(&client).await {
+          warn!(
+            "{}: Failed to load script {} after reconnecting: {:?}",
+            client.id(),
+            script.hash,
+            error
+          );
+        }
+      }
+
+      Ok(())
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_hash_in_const_context() {
+    const HASH: [u8; 40] = sha1_hex(b"return 1");
+    assert_eq!(std::str::from_utf8(&HASH).unwrap(), sha1_hash("return 1"));
+  }
+
+  #[test]
+  fn should_hash_inputs_around_block_boundaries() {
+    for len in [0, 1, 55, 56, 63, 64, 65, 119, 120, 128, 1000].iter() {
+      let input = "a".repeat(*len);
+      let hash = sha1_hex(input.as_bytes());
+      assert_eq!(std::str::from_utf8(&hash).unwrap(), sha1_hash(&input), "length {}", len);
+    }
+  }
+}
@@ -23,12 +23,15 @@ pub struct Command {
   pub db: u8,
   /// The host and port of the client that ran the command, or `lua` when run from a script.
   pub client: String,
+  /// The host and port of the server that ran the command.
+  pub server: String,
 }
 
 impl PartialEq for Command {
   fn eq(&self, other: &Self) -> bool {
     client_utils::f64_eq(self.timestamp, other.timestamp)
       && self.client == other.client
+      && self.server == other.server
       && self.db == other.db
       && self.command == other.command
       && self.args == other.args
@@ -53,6 +56,81 @@ impl fmt::Display for Command {
   }
 }
 
+/// A filter applied to the `MONITOR` stream before commands are sent to the caller.
+///
+/// Each condition is optional, and a command must match every condition that was added. Adding the same kind of condition
+/// more than once matches any of the provided values.
+///
+/// ```rust
+/// # use fred::monitor::MonitorFilter;
+/// // `GET` or `SET` commands on keys starting with `session:` in database 0
+/// let filter = MonitorFilter::new().command("get").command("set").key("session:*").db(0);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MonitorFilter {
+  commands: Vec<String>,
+  keys: Vec<String>,
+  dbs: Vec<u8>,
+  clients: Vec<String>,
+}
+
+impl MonitorFilter {
+  /// Create a new filter that matches all commands.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Match commands with the provided name, ignoring case.
+  pub fn command<S>(mut self, command: S) -> Self
+  where
+    S: Into<String>,
+  {
+    self.commands.push(command.into().to_uppercase());
+    self
+  }
+
+  /// Match commands where the first argument matches the provided glob-style pattern.
+  pub fn key<S>(mut self, pattern: S) -> Self
+  where
+    S: Into<String>,
+  {
+    self.keys.push(pattern.into());
+    self
+  }
+
+  /// Match commands run against the provided database.
+  pub fn db(mut self, db: u8) -> Self {
+    self.dbs.push(db);
+    self
+  }
+
+  /// Match commands from clients where the address matches the provided glob-style pattern, such as `10.0.0.*`.
+  pub fn client<S>(mut self, pattern: S) -> Self
+  where
+    S: Into<String>,
+  {
+    self.clients.push(pattern.into());
+    self
+  }
+
+  /// Whether or not the command matches the filter.
+  pub fn matches(&self, command: &Command) -> bool {
+    let key = command.args.first().and_then(|arg| arg.as_bytes());
+
+    (self.commands.is_empty() || self.commands.iter().any(|c| c.eq_ignore_ascii_case(&command.command)))
+      && (self.dbs.is_empty() || self.dbs.contains(&command.db))
+      && (self.clients.is_empty()
+        || self
+          .clients
+          .iter()
+          .any(|p| client_utils::glob_match(p.as_bytes(), command.client.as_bytes())))
+      && (self.keys.is_empty()
+        || key
+          .map(|key| self.keys.iter().any(|p| client_utils::glob_match(p.as_bytes(), key)))
+          .unwrap_or(false))
+  }
+}
+
 /// Configuration options for the `MONITOR` command.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config {
@@ -60,34 +138,56 @@ pub struct Config {
   pub port: u16,
   pub username: Option<String>,
   pub password: Option<String>,
-  #[cfg(feature = "enable-tls")]
+  /// Whether or not the server is part of a cluster. If `true` the cluster nodes are read from the provided server and
+  /// `MONITOR` runs against every primary node concurrently.
+  ///
+  /// Default: `false`
+  pub clustered: bool,
+  /// Whether or not to also run `MONITOR` against the replica nodes when `clustered` is `true`.
+  ///
+  /// Default: `false`
+  pub replicas: bool,
+  /// A filter applied to the commands on the stream.
+  ///
+  /// Default: `MonitorFilter::new()`
+  pub filter: MonitorFilter,
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
   pub tls: Option<TlsConfig>,
 }
 
 impl Default for Config {
-  #[cfg(feature = "enable-tls")]
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
   fn default() -> Self {
     Config {
       host: "127.0.0.1".into(),
       port: 6379,
       username: None,
       password: None,
+      clustered: false,
+      replicas: false,
+      filter: MonitorFilter::new(),
       tls: None,
     }
   }
 
-  #[cfg(not(feature = "enable-tls"))]
+  #[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
   fn default() -> Self {
     Config {
       host: "127.0.0.1".into(),
       port: 6379,
       username: None,
       password: None,
+      clustered: false,
+      replicas: false,
+      filter: MonitorFilter::new(),
     }
   }
 }
 
 /// Run the [MONITOR](https://redis.io/commands/monitor) command against the provided server.
+///
+/// The `MONITOR` command runs on a dedicated connection that closes when the returned stream is dropped. When `clustered` is
+/// `true` the output from each node is merged into one stream, and the `server` field on each command can be used to tell them apart.
 pub async fn run(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
   utils::start(config).await
 }
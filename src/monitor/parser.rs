@@ -100,6 +100,7 @@ fn d_parse_frame(input: &[u8]) -> Result<Command, RedisParseError<&[u8]>> {
   let (_, args) = d_parse_args(input)?;
 
   Ok(Command {
+    server: String::new(),
     timestamp,
     db,
     client,
@@ -120,7 +121,7 @@ fn log_frame(inner: &Arc<RedisClientInner>, frame: &[u8]) {
 #[cfg(not(feature = "network-logs"))]
 fn log_frame(_: &Arc<RedisClientInner>, _: &[u8]) {}
 
-pub fn parse(inner: &Arc<RedisClientInner>, frame: ProtocolFrame) -> Option<Command> {
+pub fn parse(inner: &Arc<RedisClientInner>, server: &str, frame: ProtocolFrame) -> Option<Command> {
   let frame_bytes = match frame {
     ProtocolFrame::SimpleString(ref s) => s.as_bytes(),
     ProtocolFrame::BulkString(ref b) => b,
@@ -131,7 +132,10 @@ pub fn parse(inner: &Arc<RedisClientInner>, frame: ProtocolFrame) -> Option<Comm
   };
 
   log_frame(inner, frame_bytes);
-  d_parse_frame(frame_bytes).ok()
+  d_parse_frame(frame_bytes).ok().map(|mut command| {
+    command.server = server.to_owned();
+    command
+  })
 }
 
 #[cfg(test)]
@@ -146,6 +150,7 @@ mod tests {
       timestamp: 1631469940.785623,
       db: 0,
       client: "127.0.0.1:46998".into(),
+      server: String::new(),
       command: "SET".into(),
       args: vec!["foo".into(), "2".into()],
     };
@@ -161,6 +166,7 @@ mod tests {
       timestamp: 1631469940.785623,
       db: 0,
       client: "127.0.0.1:46998".into(),
+      server: String::new(),
       command: "SET".into(),
       args: vec!["foo bar".into(), "2".into()],
     };
@@ -176,6 +182,7 @@ mod tests {
       timestamp: 1631475365.563304,
       db: 0,
       client: "127.0.0.1:47438".into(),
+      server: String::new(),
       command: "SET".into(),
       args: vec![
         "foo".into(),
@@ -196,6 +203,7 @@ mod tests {
       timestamp: 1631469940.785623,
       db: 0,
       client: "127.0.0.1:46998".into(),
+      server: String::new(),
       command: "KEYS".into(),
       args: vec![],
     };
@@ -1,12 +1,13 @@
-use crate::error::RedisError;
+use crate::error::{RedisError, RedisErrorKind};
 use crate::modules::inner::RedisClientInner;
 use crate::monitor::parser;
-use crate::monitor::{Command, Config};
+use crate::monitor::{Command, Config, MonitorFilter};
 use crate::protocol::codec::RedisCodec;
 use crate::protocol::connection::{self, RedisTransport};
 use crate::protocol::types::{RedisCommand, RedisCommandKind};
 use crate::protocol::utils as protocol_utils;
 use crate::types::{RedisConfig, ServerConfig};
+use futures::future::try_join_all;
 use futures::stream::{Stream, StreamExt};
 use redis_protocol::resp2::types::Frame as ProtocolFrame;
 use std::sync::Arc;
@@ -21,6 +22,7 @@ use crate::globals::globals;
 #[cfg(feature = "blocking-encoding")]
 async fn handle_monitor_frame(
   inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
   frame: Result<ProtocolFrame, RedisError>,
 ) -> Option<Command> {
   let frame = match frame {
@@ -40,19 +42,20 @@ async fn handle_monitor_frame(
       frame_size
     );
 
-    let inner = inner.clone();
-    tokio::task::spawn_blocking(move || parser::parse(&inner, frame))
+    let (inner, server) = (inner.clone(), server.clone());
+    tokio::task::spawn_blocking(move || parser::parse(&inner, &server, frame))
       .await
       .ok()
       .flatten()
   } else {
-    parser::parse(inner, frame)
+    parser::parse(inner, server, frame)
   }
 }
 
 #[cfg(not(feature = "blocking-encoding"))]
 async fn handle_monitor_frame(
   inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
   frame: Result<ProtocolFrame, RedisError>,
 ) -> Option<Command> {
   let frame = match frame {
@@ -63,40 +66,80 @@ async fn handle_monitor_frame(
     }
   };
 
-  parser::parse(inner, frame)
+  parser::parse(inner, server, frame)
 }
 
-#[cfg(feature = "enable-tls")]
-fn create_client_inner(config: Config) -> Arc<RedisClientInner> {
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+fn create_client_inner(config: &Config, host: String, port: u16) -> Arc<RedisClientInner> {
   let config = RedisConfig {
-    username: config.username,
-    password: config.password,
-    server: ServerConfig::Centralized {
-      host: config.host,
-      port: config.port,
-    },
-    tls: config.tls,
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server: ServerConfig::Centralized { host, port },
+    tls: config.tls.clone(),
     ..Default::default()
   };
 
   RedisClientInner::new(config)
 }
 
-#[cfg(not(feature = "enable-tls"))]
-fn create_client_inner(config: Config) -> Arc<RedisClientInner> {
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
+fn create_client_inner(config: &Config, host: String, port: u16) -> Arc<RedisClientInner> {
   let config = RedisConfig {
-    username: config.username,
-    password: config.password,
-    server: ServerConfig::Centralized {
-      host: config.host,
-      port: config.port,
-    },
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server: ServerConfig::Centralized { host, port },
     ..Default::default()
   };
 
   RedisClientInner::new(config)
 }
 
+/// Read the host and port of each node in the `CLUSTER NODES` response that should be monitored.
+fn read_monitor_nodes(status: &str, replicas: bool) -> Result<Vec<(String, u16)>, RedisError> {
+  let mut out = Vec::new();
+
+  for node in protocol_utils::parse_cluster_node_lines(status)?.into_iter() {
+    if node.is_unavailable() || !(node.is_primary() || (replicas && node.is_replica())) {
+      continue;
+    }
+
+    let (host, port) = match node.server.rfind(':') {
+      Some(idx) => (&node.server[..idx], &node.server[idx + 1..]),
+      None => {
+        return Err(RedisError::new(
+          RedisErrorKind::ProtocolError,
+          format!("Invalid cluster node address {}.", node.server),
+        ))
+      }
+    };
+    out.push((host.to_owned(), port.parse::<u16>()?));
+  }
+
+  Ok(out)
+}
+
+async fn read_cluster_nodes(inner: &Arc<RedisClientInner>, replicas: bool) -> Result<Vec<(String, u16)>, RedisError> {
+  let connection = connection::create_centralized_connection(inner).await?;
+  let command = RedisCommand::new(RedisCommandKind::ClusterNodes, vec![], None);
+  let frame = match connection {
+    RedisTransport::Tcp(framed) => connection::request_response(framed, &command).await?.0,
+    RedisTransport::Tls(framed) => connection::request_response(framed, &command).await?.0,
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => connection::request_response(framed, &command).await?.0,
+  };
+
+  let status = match protocol_utils::frame_to_single_result(frame)?.into_string() {
+    Some(status) => status,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid CLUSTER NODES response.",
+      ))
+    }
+  };
+  read_monitor_nodes(&status, replicas)
+}
+
 async fn send_monitor_command(
   inner: &Arc<RedisClientInner>,
   connection: RedisTransport,
@@ -113,6 +156,11 @@ async fn send_monitor_command(
       let (frame, framed) = connection::request_response(framed, &command).await?;
       (frame, RedisTransport::Tls(framed))
     }
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => {
+      let (frame, framed) = connection::request_response(framed, &command).await?;
+      (frame, RedisTransport::Unix(framed))
+    }
   };
 
   _trace!(inner, "Recv MONITOR response: {:?}", frame);
@@ -123,13 +171,31 @@ async fn send_monitor_command(
 
 async fn forward_results<T>(
   inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  filter: &MonitorFilter,
   tx: UnboundedSender<Command>,
   mut framed: Framed<T, RedisCodec>,
 ) where
   T: AsyncRead + AsyncWrite + Unpin + 'static,
 {
-  while let Some(frame) = framed.next().await {
-    if let Some(command) = handle_monitor_frame(inner, frame).await {
+  loop {
+    // stop as soon as the stream is dropped, even if the server is idle, so the connection closes
+    let frame = tokio::select! {
+      frame = framed.next() => frame,
+      _ = tx.closed() => {
+        _debug!(inner, "Closing monitor connection after the stream was dropped.");
+        return;
+      }
+    };
+    let frame = match frame {
+      Some(frame) => frame.map(|frame| frame.into_resp2()),
+      None => return,
+    };
+
+    if let Some(command) = handle_monitor_frame(inner, server, frame).await {
+      if !filter.matches(&command) {
+        continue;
+      }
       if let Err(_) = tx.send(command) {
         _warn!(inner, "Stopping monitor stream.");
         return;
@@ -140,29 +206,130 @@ async fn forward_results<T>(
   }
 }
 
-async fn process_stream(inner: &Arc<RedisClientInner>, tx: UnboundedSender<Command>, connection: RedisTransport) {
-  _debug!(inner, "Starting monitor stream processing...");
+async fn process_stream(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  filter: &MonitorFilter,
+  tx: UnboundedSender<Command>,
+  connection: RedisTransport,
+) {
+  _debug!(inner, "Starting monitor stream processing for {}...", server);
 
   match connection {
-    RedisTransport::Tcp(framed) => forward_results(inner, tx, framed).await,
-    RedisTransport::Tls(framed) => forward_results(inner, tx, framed).await,
+    RedisTransport::Tcp(framed) => forward_results(inner, server, filter, tx, framed).await,
+    RedisTransport::Tls(framed) => forward_results(inner, server, filter, tx, framed).await,
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => forward_results(inner, server, filter, tx, framed).await,
   };
 
-  _warn!(inner, "Stopping monitor stream.");
+  _warn!(inner, "Stopping monitor stream for {}.", server);
 }
 
-pub async fn start(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
-  let inner = create_client_inner(config);
+async fn connect_monitor(
+  config: &Config,
+  host: String,
+  port: u16,
+) -> Result<(Arc<RedisClientInner>, RedisTransport), RedisError> {
+  let inner = create_client_inner(config, host, port);
   let connection = connection::create_centralized_connection(&inner).await?;
   let connection = send_monitor_command(&inner, connection).await?;
 
+  Ok((inner, connection))
+}
+
+pub async fn start(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
+  let nodes = if config.clustered {
+    let inner = create_client_inner(&config, config.host.clone(), config.port);
+    read_cluster_nodes(&inner, config.replicas).await?
+  } else {
+    vec![(config.host.clone(), config.port)]
+  };
+  let connections = try_join_all(
+    nodes
+      .into_iter()
+      .map(|(host, port)| connect_monitor(&config, host, port)),
+  )
+  .await?;
+
   // there isn't really a mechanism to surface backpressure to the server for the MONITOR stream, so we use a
   // background task with a channel to process the frames so that the server can keep sending data even if the
   // stream consumer slows down processing the frames.
   let (tx, rx) = unbounded_channel();
-  let _ = tokio::spawn(async move {
-    process_stream(&inner, tx, connection).await;
-  });
+  let filter = Arc::new(config.filter);
+  for (inner, connection) in connections.into_iter() {
+    let (tx, filter) = (tx.clone(), filter.clone());
+    let server = match inner.config.read().server {
+      ServerConfig::Centralized { ref host, ref port } => Arc::new(format!("{}:{}", host, port)),
+      _ => Arc::new(String::new()),
+    };
+
+    let _ = tokio::spawn(async move {
+      process_stream(&inner, &server, &filter, tx, connection).await;
+    });
+  }
 
   Ok(UnboundedReceiverStream::new(rx))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  #[tokio::test]
+  async fn should_stop_when_stream_is_dropped() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    let (socket, _server) = tokio::io::duplex(64);
+    let framed = Framed::new(socket, RedisCodec::new(&inner, "127.0.0.1:6379".into()));
+    let (tx, rx) = unbounded_channel();
+    drop(rx);
+
+    let server = Arc::new("127.0.0.1:6379".to_owned());
+    let filter = MonitorFilter::new();
+    let result = tokio::time::timeout(
+      Duration::from_secs(1),
+      forward_results(&inner, &server, &filter, tx, framed),
+    )
+    .await;
+    assert!(result.is_ok());
+  }
+
+  #[test]
+  fn should_filter_monitor_commands() {
+    let command = Command {
+      command: "SET".into(),
+      args: vec!["session:1".into(), "bar".into()],
+      timestamp: 1631469940.785623,
+      db: 0,
+      client: "127.0.0.1:46998".into(),
+      server: "127.0.0.1:6379".into(),
+    };
+
+    assert!(MonitorFilter::new().matches(&command));
+    assert!(MonitorFilter::new().command("get").command("set").matches(&command));
+    assert!(MonitorFilter::new()
+      .key("session:*")
+      .db(0)
+      .client("127.0.0.1:*")
+      .matches(&command));
+    assert!(!MonitorFilter::new().key("user:*").matches(&command));
+    assert!(!MonitorFilter::new().command("set").db(1).matches(&command));
+  }
+
+  #[test]
+  fn should_parse_monitor_cluster_nodes() {
+    let status = "07c37dfeb235213a872192d90877d0cd55635b91 127.0.0.1:30004@31004 slave e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 0 1426238317239 4 connected
+67ed2db8d677e59ec4a4cefb06858cf2a1a89fa1 127.0.0.1:30002@31002 master - 0 1426238316232 2 connected 5461-10922
+292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f 127.0.0.1:30003@31003 master,fail - 0 1426238318243 3 connected 10923-16383
+e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001@31001 myself,master - 0 0 1 connected 0-5460";
+
+    let primaries = read_monitor_nodes(status, false).unwrap();
+    assert_eq!(primaries, vec![
+      ("127.0.0.1".to_owned(), 30002),
+      ("127.0.0.1".to_owned(), 30001)
+    ]);
+    let all = read_monitor_nodes(status, true).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0], ("127.0.0.1".to_owned(), 30004));
+  }
+}
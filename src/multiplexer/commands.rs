@@ -1,30 +1,57 @@
 use crate::client::RedisClient;
 use crate::error::{RedisError, RedisErrorKind};
 use crate::globals::globals;
-use crate::modules::inner::RedisClientInner;
+use crate::modules::inner::{CommandReceiver, RedisClientInner};
 use crate::multiplexer::{utils, SentCommand};
 use crate::multiplexer::{Backpressure, Multiplexer};
-use crate::protocol::connection::read_cluster_nodes;
-use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::connection::{read_cluster_nodes, OK};
+use crate::protocol::types::{RedisCommand, RedisCommandKind, ResponseFrame};
 use crate::protocol::utils::pretty_error;
 use crate::trace;
-use crate::types::{ClientState, ReconnectPolicy, ServerConfig};
+use crate::types::{BackpressurePolicy, ClientState, ReconnectPolicy, ServerConfig};
 use crate::utils as client_utils;
-use redis_protocol::redis_keyslot;
 use redis_protocol::resp2::types::Frame as ProtocolFrame;
 use std::collections::VecDeque;
-use std::ops::DerefMut;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::oneshot::channel as oneshot_channel;
 use tokio::sync::oneshot::Receiver as OneshotReceiver;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
 
 #[cfg(feature = "partial-tracing")]
 use tracing_futures::Instrument;
 
+fn backpressure_policy(inner: &Arc<RedisClientInner>) -> BackpressurePolicy {
+  inner
+    .config
+    .read()
+    .backpressure
+    .as_ref()
+    .map(|config| config.policy.clone())
+    .unwrap_or_default()
+}
+
+/// Sleep for the provided duration, or until a response arrives if the client uses the `Block` policy.
+async fn wait_for_backpressure(inner: &Arc<RedisClientInner>, duration: Duration, command: &RedisCommand) {
+  if backpressure_policy(inner) == BackpressurePolicy::Block {
+    _trace!(inner, "Waiting for in-flight commands due to connection backpressure.");
+    let _ = timeout(duration, inner.backpressure_notify.notified()).await;
+    return;
+  }
+
+  _warn!(
+    inner,
+    "Sleeping for {} ms due to connection backpressure.",
+    duration.as_millis()
+  );
+  if inner.should_trace() {
+    trace::backpressure_event(command, duration.as_millis());
+  }
+  sleep(duration).await;
+}
+
 async fn backpressure(
   inner: &Arc<RedisClientInner>,
   multiplexer: &Multiplexer,
@@ -32,15 +59,7 @@ async fn backpressure(
   mut command: RedisCommand,
 ) -> Result<Backpressure, RedisError> {
   loop {
-    _warn!(
-      inner,
-      "Sleeping for {} ms due to connection backpressure.",
-      duration.as_millis()
-    );
-    if inner.should_trace() {
-      trace::backpressure_event(&command, duration.as_millis());
-    }
-    sleep(duration).await;
+    wait_for_backpressure(inner, duration, &command).await;
 
     match multiplexer.write(command).await? {
       Backpressure::Wait((_duration, _command)) => {
@@ -60,15 +79,7 @@ async fn cluster_backpressure(
   mut command: RedisCommand,
 ) -> Result<Backpressure, RedisError> {
   loop {
-    _warn!(
-      inner,
-      "Sleeping for {} ms due to cluster connection backpressure.",
-      duration.as_millis()
-    );
-    if inner.should_trace() {
-      trace::backpressure_event(&command, duration.as_millis());
-    }
-    sleep(duration).await;
+    wait_for_backpressure(inner, duration, &command).await;
 
     match multiplexer.write_all_cluster(command).await? {
       Backpressure::Wait((_duration, _command)) => {
@@ -149,13 +160,52 @@ fn split_connection(inner: &Arc<RedisClientInner>, _multiplexer: &Multiplexer, c
   });
 }
 
+async fn sync_cluster(inner: &Arc<RedisClientInner>, multiplexer: &Multiplexer, command: RedisCommand) {
+  _debug!(inner, "Refreshing cluster state...");
+
+  match multiplexer.sync_cluster().await {
+    Ok(_) => {
+      if let Some(tx) = command.tx {
+        let _ = tx.send(Ok(ProtocolFrame::SimpleString(OK.into()).into()));
+      }
+    }
+    Err(e) => {
+      _warn!(inner, "Error refreshing cluster state: {:?}", e);
+      respond_with_error(inner, command, e);
+    }
+  }
+}
+
+/// Whether or not the client should stop trying to reconnect after the provided error.
+fn is_unrecoverable(inner: &Arc<RedisClientInner>, error: &RedisError) -> bool {
+  inner.config.read().unrecoverable_errors.contains(error.kind())
+}
+
+fn unrecoverable_error(error: &RedisError) -> RedisError {
+  RedisError::new(RedisErrorKind::Unrecoverable, format!("{}", error))
+}
+
 fn shutdown_client(inner: &Arc<RedisClientInner>, error: &RedisError) {
   utils::emit_connect_error(inner, &error);
   utils::emit_error(&inner, &error);
   client_utils::shutdown_listeners(&inner);
   client_utils::set_locked(&inner.multi_block, None);
-  client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+  client_utils::set_client_state(&inner, ClientState::Closed);
   inner.update_cluster_state(None);
+  utils::emit_unrecoverable(inner, error);
+}
+
+/// Respond to any commands buffered in the command channel after the client stops trying to reconnect.
+fn drain_closed_command_rx(inner: &Arc<RedisClientInner>, rx: &mut CommandReceiver) {
+  if client_utils::read_client_state(&inner.state) != ClientState::Closed {
+    return;
+  }
+
+  let error = RedisError::new(RedisErrorKind::Unrecoverable, "Client stopped trying to reconnect.");
+  while let Some(command) = rx.priority.try_recv().ok().or_else(|| rx.commands.try_recv().ok()) {
+    client_utils::decr_atomic(&inner.cmd_buffer_len);
+    respond_with_error(inner, command, error.clone());
+  }
 }
 
 fn respond_with_error(inner: &Arc<RedisClientInner>, command: RedisCommand, error: RedisError) {
@@ -218,6 +268,7 @@ fn handle_connection_closed(
     attempts: 0,
     max_attempts: 0,
     delay: globals().cluster_error_cache_delay() as u32,
+    jitter: 0,
   });
 
   let reconnect_inner = inner.clone();
@@ -225,9 +276,13 @@ fn handle_connection_closed(
     let (tx, mut rx) = unbounded_channel();
     _debug!(inner, "Set inner connection closed sender.");
     client_utils::set_locked(&inner.connection_closed_tx, Some(tx));
+    let mut connected_at = Instant::now();
 
     'recv: while let Some(state) = rx.recv().await {
       let (mut commands, error) = (state.commands, state.error);
+      if connected_at.elapsed() >= Duration::from_millis(globals().reconnect_reset_delay_ms() as u64) {
+        policy.reset_attempts();
+      }
 
       let client_state = client_utils::read_client_state(&inner.state);
       _debug!(
@@ -244,19 +299,24 @@ fn handle_connection_closed(
         );
         break;
       }
-      client_utils::set_client_state(&inner.state, ClientState::Connecting);
+      if !error.is_cluster_error() {
+        utils::record_disconnect(&inner);
+      }
+      let mut attempt = 0;
 
       'reconnect: loop {
         let next_delay = match next_reconnect_delay(&inner, &mut policy, &error) {
           Some(delay) => delay,
           None => {
             _warn!(inner, "Max reconnect attempts reached. Stopping redis client.");
-            let error = RedisError::new(RedisErrorKind::Unknown, "Max reconnection attempts reached.");
+            let error = RedisError::new(RedisErrorKind::Unrecoverable, "Max reconnection attempts reached.");
             write_final_error_to_callers(&inner, commands, &error);
             shutdown_client(&inner, &error);
             break 'recv;
           }
         };
+        attempt += 1;
+        client_utils::set_client_state(&inner, ClientState::Reconnecting { attempt });
 
         _info!(inner, "Sleeping for {} ms before reconnecting", next_delay);
         sleep(Duration::from_millis(next_delay)).await;
@@ -275,9 +335,9 @@ fn handle_connection_closed(
         if let Err(error) = result {
           _warn!(inner, "Failed to reconnect with error {:?}", error);
 
-          if *error.kind() == RedisErrorKind::Auth {
-            // stop trying to connect if auth is failing
-            _warn!(inner, "Stop trying to reconnect due to auth failure.");
+          if is_unrecoverable(&inner, &error) {
+            _warn!(inner, "Stop trying to reconnect due to unrecoverable error.");
+            let error = unrecoverable_error(&error);
             write_final_error_to_callers(&inner, commands, &error);
             shutdown_client(&inner, &error);
             break 'recv;
@@ -313,12 +373,14 @@ fn handle_connection_closed(
         }
 
         // break when the connection is established
+        client_utils::set_client_state(&inner, ClientState::Connected);
         break 'reconnect;
       }
 
-      policy.reset_attempts();
+      connected_at = Instant::now();
       utils::emit_connect(&inner);
       utils::emit_reconnect(&inner);
+      utils::spawn_resubscribe(&inner);
     }
 
     _debug!(inner, "Exit reconnection task.");
@@ -353,6 +415,11 @@ fn is_exec_or_discard_without_multi_block(inner: &Arc<RedisClientInner>, command
   command.kind.ends_transaction() && !client_utils::is_locked_some(&inner.multi_block)
 }
 
+/// Whether or not the command is inside a transaction that was already aborted due to an error queueing an earlier command.
+fn is_aborted_transaction_command(inner: &Arc<RedisClientInner>, command: &RedisCommand) -> bool {
+  !command.kind.is_multi() && !command.kind.ends_transaction() && client_utils::is_multi_block_aborted(inner)
+}
+
 /// Whether or not to disable pipelining for a request. If this returns true the multiplexer will block subsequent commands until the current command receives a response.
 fn should_disable_pipeline(inner: &Arc<RedisClientInner>, command: &RedisCommand, disable_pipeline: bool) -> bool {
   let in_multi_block = command.kind != RedisCommandKind::Multi && client_utils::is_locked_some(&inner.multi_block);
@@ -371,19 +438,6 @@ fn should_disable_pipeline(inner: &Arc<RedisClientInner>, command: &RedisCommand
   force_no_pipeline || (!in_multi_block && (disable_pipeline || command.kind.is_blocking()))
 }
 
-/// Check that all commands within a transaction against a clustered deployment only modify the same hash slot.
-fn check_transaction_hash_slot(inner: &Arc<RedisClientInner>, command: &RedisCommand) -> Result<(), RedisError> {
-  if client_utils::is_clustered(&inner.config) && client_utils::is_locked_some(&inner.multi_block) {
-    if let Some(key) = command.extract_key() {
-      if let Some(policy) = inner.multi_block.write().deref_mut() {
-        let _ = policy.check_and_set_hash_slot(redis_keyslot(&key))?;
-      }
-    }
-  }
-
-  Ok(())
-}
-
 /// Handle an error writing to the socket, returning whether the error should close the command stream.
 async fn handle_write_error(
   inner: &Arc<RedisClientInner>,
@@ -411,12 +465,12 @@ async fn handle_write_error(
 /// Handle the response to the MULTI command, forwarding any errors onto the caller of the next command and returning whether the multiplexers should skip the next command.
 async fn handle_deferred_multi_response(
   inner: &Arc<RedisClientInner>,
-  rx: OneshotReceiver<Result<ProtocolFrame, RedisError>>,
+  rx: OneshotReceiver<Result<ResponseFrame, RedisError>>,
   command: &mut RedisCommand,
 ) -> bool {
   match rx.await {
     Ok(Ok(frame)) => {
-      if let ProtocolFrame::Error(s) = frame {
+      if let ProtocolFrame::Error(s) = frame.frame {
         if let Some(tx) = command.tx.take() {
           let _ = tx.send(Err(pretty_error(&s)));
         }
@@ -447,7 +501,12 @@ async fn handle_backpressure(
 ) -> Result<Option<Arc<String>>, RedisError> {
   match result {
     Backpressure::Wait((duration, command)) => {
-      if wait {
+      if wait && backpressure_policy(inner) == BackpressurePolicy::Error {
+        _debug!(inner, "Rejecting {} due to connection backpressure.", command.kind.to_str_debug());
+        let error = RedisError::new(RedisErrorKind::Backpressure, "Too many in-flight commands.");
+        respond_with_error(inner, command, error);
+        Ok(None)
+      } else if wait {
         let backpressure_result = if cluster {
           cluster_backpressure(inner, multiplexer, duration, command).await?
         } else {
@@ -567,6 +626,10 @@ async fn check_command_structure(
     split_connection(&inner, &multiplexer, command);
     return Ok(None);
   }
+  if command.kind.is_sync_cluster() {
+    sync_cluster(&inner, &multiplexer, command).await;
+    return Ok(None);
+  }
   if command.kind == RedisCommandKind::Mget {
     if let Err(error) = utils::check_mget_cluster_keys(&multiplexer, &command.args) {
       respond_with_error(&inner, command, error);
@@ -583,8 +646,8 @@ async fn check_command_structure(
     respond_with_canceled_error(&inner, command, "Cannot use EXEC or DISCARD outside MULTI block.");
     return Ok(None);
   }
-  if let Err(error) = check_transaction_hash_slot(&inner, &command) {
-    respond_with_error(&inner, command, error);
+  if is_aborted_transaction_command(&inner, &command) {
+    respond_with_canceled_error(&inner, command, "Transaction aborted due to a previous error.");
     return Ok(None);
   }
   if check_deferred_multi_command(&inner, &multiplexer, &mut command, has_policy).await? {
@@ -657,6 +720,7 @@ async fn handle_command(
   disable_pipeline: bool,
 ) -> Result<(), RedisError> {
   let cmd_buffer_len = client_utils::decr_atomic(&inner.cmd_buffer_len);
+  inner.backpressure_notify.notify_waiters();
   _trace!(
     inner,
     "Recv command on multiplexer {}. Buffer len: {}",
@@ -664,6 +728,10 @@ async fn handle_command(
     cmd_buffer_len
   );
 
+  if command.is_canceled() || command.has_dropped_receiver() {
+    _debug!(inner, "Skip canceled command {}", command.kind.to_str_debug());
+    return Ok(());
+  }
   let command = match check_command_structure_t(&inner, &multiplexer, has_policy, command).await? {
     Some(cmd) => cmd,
     None => return Ok(()),
@@ -794,6 +862,12 @@ async fn connect_with_policy(
     loop {
       if let Err(err) = multiplexer.connect_and_flush().await {
         _warn!(inner, "Failed to connect with error {:?}", err);
+        if is_unrecoverable(inner, &err) {
+          _warn!(inner, "Stop trying to connect due to unrecoverable error.");
+          let error = unrecoverable_error(&err);
+          shutdown_client(inner, &error);
+          return Err(error);
+        }
 
         let delay = match policy.next_delay() {
           Some(delay) => delay,
@@ -802,9 +876,8 @@ async fn connect_with_policy(
               inner,
               "Max reconnect attempts reached. Stopping initial connection logic."
             );
-            let error = RedisError::new(RedisErrorKind::Unknown, "Max reconnection attempts reached.");
-            utils::emit_connect_error(inner, &error);
-            utils::emit_error(inner, &error);
+            let error = RedisError::new(RedisErrorKind::Unrecoverable, "Max reconnection attempts reached.");
+            shutdown_client(inner, &error);
             return Err(error);
           }
         };
@@ -825,12 +898,38 @@ async fn connect_with_policy(
   Ok(())
 }
 
+/// Wait for any commands retried after a `BUSY` error to be sent on the priority channel, then write them before the
+/// next command so that the retried commands keep their original order.
+async fn write_busy_retries(
+  inner: &Arc<RedisClientInner>,
+  multiplexer: &Multiplexer,
+  rx: &mut CommandReceiver,
+  has_policy: bool,
+  disable_pipeline: bool,
+) -> Result<(), RedisError> {
+  loop {
+    let notified = inner.busy_notify.notified();
+    if inner.busy_retries.lock().is_empty() {
+      break;
+    }
+    _debug!(inner, "Waiting to retry commands after BUSY error.");
+    notified.await;
+  }
+
+  while let Ok(command) = rx.priority.try_recv() {
+    handle_command_t(inner, multiplexer, command, has_policy, disable_pipeline).await?;
+  }
+  Ok(())
+}
+
 /// Initialize the multiplexer and network interface to accept commands.
 ///
 /// This function runs until the connection closes or all retry attempts have failed.
 /// If a retry policy with infinite attempts is provided then this runs forever.
 pub async fn init(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPolicy>) -> Result<(), RedisError> {
-  if !client_utils::check_and_set_client_state(&inner.state, ClientState::Disconnected, ClientState::Connecting) {
+  if !client_utils::check_and_set_client_state(&inner, ClientState::Disconnected, ClientState::Connecting)
+    && !client_utils::check_and_set_client_state(&inner, ClientState::Closed, ClientState::Connecting)
+  {
     return Err(RedisError::new(
       RedisErrorKind::Unknown,
       "Connections are already initialized or connecting.",
@@ -855,11 +954,13 @@ pub async fn init(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPol
       utils::emit_error(inner, &err);
       return Err(err);
     }
-  } else {
-    connect_with_policy(inner, &multiplexer, &mut policy).await?;
+  } else if let Err(err) = connect_with_policy(inner, &multiplexer, &mut policy).await {
+    drain_closed_command_rx(inner, &mut rx);
+    inner.store_command_rx(rx);
+    return Err(err);
   }
 
-  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  client_utils::set_client_state(&inner, ClientState::Connected);
   utils::emit_connect(inner);
   utils::emit_reconnect(inner);
 
@@ -869,10 +970,21 @@ pub async fn init(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPol
 
   _debug!(inner, "Starting command stream...");
   while let Some(command) = rx.recv().await {
-    if let Err(e) = handle_command_t(inner, &multiplexer, command, has_policy, disable_pipeline).await {
+    let result = if command.busy_retries == 0 {
+      write_busy_retries(inner, &multiplexer, &mut rx, has_policy, disable_pipeline).await
+    } else {
+      Ok(())
+    };
+    let result = match result {
+      Ok(_) => handle_command_t(inner, &multiplexer, command, has_policy, disable_pipeline).await,
+      Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
       if e.is_canceled() {
         break;
       } else {
+        drain_closed_command_rx(inner, &mut rx);
         inner.store_command_rx(rx);
         return Err(e);
       }
@@ -4,15 +4,17 @@ use crate::modules::inner::RedisClientInner;
 use crate::protocol::connection::RedisSink;
 use crate::protocol::types::ClusterKeyCache;
 use crate::protocol::types::RedisCommand;
+use redis_protocol::types::Redirection;
 use crate::types::ClientState;
 use crate::utils as client_utils;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{BTreeMap, VecDeque};
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::broadcast::Sender as BroadcastSender;
 use tokio::sync::oneshot::{channel as oneshot_channel, Sender as OneshotSender};
+use tokio::sync::Notify;
 use tokio::sync::RwLock as AsyncRwLock;
 
 pub mod commands;
@@ -38,6 +40,8 @@ pub struct SentCommand {
   pub command: RedisCommand,
   pub network_start: Option<Instant>,
   pub multi_queued: bool,
+  /// The server to which the command was written.
+  pub server: Option<Arc<String>>,
 }
 
 impl From<RedisCommand> for SentCommand {
@@ -46,6 +50,7 @@ impl From<RedisCommand> for SentCommand {
       command: cmd,
       network_start: None,
       multi_queued: false,
+      server: None,
     }
   }
 }
@@ -55,14 +60,18 @@ pub struct Counters {
   pub cmd_buffer_len: Arc<AtomicUsize>,
   pub in_flight: Arc<AtomicUsize>,
   pub feed_count: Arc<AtomicUsize>,
+  pub total_in_flight: Arc<AtomicUsize>,
+  pub notify: Arc<Notify>,
 }
 
 impl Counters {
-  pub fn new(cmd_buffer_len: &Arc<AtomicUsize>) -> Self {
+  pub fn new(inner: &Arc<RedisClientInner>) -> Self {
     Counters {
-      cmd_buffer_len: cmd_buffer_len.clone(),
+      cmd_buffer_len: inner.cmd_buffer_len.clone(),
       in_flight: Arc::new(AtomicUsize::new(0)),
       feed_count: Arc::new(AtomicUsize::new(0)),
+      total_in_flight: inner.in_flight_len.clone(),
+      notify: inner.backpressure_notify.clone(),
     }
   }
 
@@ -76,11 +85,23 @@ impl Counters {
   }
 
   pub fn incr_in_flight(&self) -> usize {
+    client_utils::incr_atomic(&self.total_in_flight);
     client_utils::incr_atomic(&self.in_flight)
   }
 
   pub fn decr_in_flight(&self) -> usize {
-    client_utils::decr_atomic(&self.in_flight)
+    let in_flight = match self
+      .in_flight
+      .fetch_update(Ordering::AcqRel, Ordering::Acquire, |val| val.checked_sub(1))
+    {
+      Ok(prev) => {
+        client_utils::decr_atomic(&self.total_in_flight);
+        prev - 1
+      }
+      Err(_) => 0,
+    };
+    self.notify.notify_waiters();
+    in_flight
   }
 
   pub fn reset_feed_count(&self) {
@@ -88,7 +109,9 @@ impl Counters {
   }
 
   pub fn reset_in_flight(&self) {
-    client_utils::set_atomic(&self.in_flight, 0);
+    let in_flight = client_utils::set_atomic(&self.in_flight, 0);
+    client_utils::decr_atomic_by(&self.total_in_flight, in_flight);
+    self.notify.notify_waiters();
   }
 }
 
@@ -118,12 +141,12 @@ pub enum Connections {
 }
 
 impl Connections {
-  pub fn new_centralized(inner: &Arc<RedisClientInner>, cmd_buffer_len: &Arc<AtomicUsize>) -> Self {
+  pub fn new_centralized(inner: &Arc<RedisClientInner>) -> Self {
     let server = utils::centralized_server_name(inner);
 
     Connections::Centralized {
       server: Arc::new(AsyncRwLock::new(Arc::new(server))),
-      counters: Counters::new(cmd_buffer_len),
+      counters: Counters::new(inner),
       writer: Arc::new(AsyncRwLock::new(None)),
       commands: Arc::new(Mutex::new(VecDeque::new())),
       connection_id: Arc::new(RwLock::new(None)),
@@ -172,7 +195,7 @@ impl Multiplexer {
     let connections = if clustered {
       Connections::new_clustered()
     } else {
-      Connections::new_centralized(inner, &inner.cmd_buffer_len)
+      Connections::new_centralized(inner)
     };
 
     Multiplexer {
@@ -200,6 +223,7 @@ impl Multiplexer {
     hash_slot: u16,
   ) -> Result<Backpressure, RedisError> {
     let _ = self.wait_for_sync().await;
+    utils::run_before_send_hooks(&self.inner, &mut command);
 
     if utils::max_attempts_reached(&self.inner, &mut command) {
       return Ok(Backpressure::Skipped);
@@ -218,6 +242,7 @@ impl Multiplexer {
   /// Write a command to the server(s), signaling back to the caller whether they should implement backpressure.
   pub async fn write(&self, mut command: RedisCommand) -> Result<Backpressure, RedisError> {
     let _ = self.wait_for_sync().await;
+    utils::run_before_send_hooks(&self.inner, &mut command);
 
     if command.kind.is_all_cluster_nodes() {
       return self.write_all_cluster(command).await;
@@ -230,6 +255,10 @@ impl Multiplexer {
     }
 
     if self.clustered {
+      if let Some(redirection) = command.redirection.take() {
+        return self.write_redirected(command, redirection).await;
+      }
+
       let custom_key_slot = command.key_slot();
       utils::write_clustered_command(&self.inner, &self.connections, command, custom_key_slot, false).await
     } else {
@@ -237,9 +266,45 @@ impl Multiplexer {
     }
   }
 
+  /// Write a command that received a `MOVED` or `ASK` error, following the redirection.
+  ///
+  /// A `MOVED` error updates the cached owner of the hash slot, and an `ASK` error sends `ASKING` followed by the command to
+  /// the importing node without changing the cache. If the client is not connected to the new node it will sync the cluster
+  /// state first.
+  async fn write_redirected(&self, command: RedisCommand, redirection: Redirection) -> Result<Backpressure, RedisError> {
+    let inner = &self.inner;
+    let (slot, server, is_moved) = match redirection {
+      Redirection::Moved { slot, server } => (slot, Arc::new(server), true),
+      Redirection::Ask { slot, server } => (slot, Arc::new(server), false),
+    };
+    _debug!(
+      inner,
+      "Following {} redirection for {} to {} (slot {})",
+      if is_moved { "MOVED" } else { "ASK" },
+      command.kind.to_str_debug(),
+      server,
+      slot
+    );
+
+    if !utils::has_cluster_writer(&self.connections, &server).await {
+      _debug!(inner, "Syncing cluster state after redirection to unknown server {}", server);
+      self.sync_cluster().await?;
+    }
+
+    if is_moved {
+      if let Connections::Clustered { ref cache, .. } = self.connections {
+        cache.write().update_slot(slot, server);
+      }
+      utils::write_clustered_command(inner, &self.connections, command, Some(slot), true).await
+    } else {
+      utils::write_asking_command(inner, &self.connections, command, &server).await
+    }
+  }
+
   /// Write a command to all nodes in the cluster.
   pub async fn write_all_cluster(&self, mut command: RedisCommand) -> Result<Backpressure, RedisError> {
     let _ = self.wait_for_sync().await;
+    utils::run_before_send_hooks(&self.inner, &mut command);
 
     if utils::max_attempts_reached(&self.inner, &mut command) {
       return Ok(Backpressure::Skipped);
@@ -338,7 +403,7 @@ impl Multiplexer {
     utils::sync_cluster(&self.inner, &self.connections, &self.close_tx).await?;
 
     self.set_synchronizing(false);
-    client_utils::set_client_state(&self.inner.state, ClientState::Connected);
+    client_utils::set_client_state(&self.inner, ClientState::Connected);
     utils::finish_synchronizing(&self.inner, &self.synchronizing_tx);
     Ok(())
   }
@@ -1,21 +1,27 @@
+use crate::commands;
 use crate::error::{RedisError, RedisErrorKind};
+use crate::globals::globals;
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::utils;
 use crate::multiplexer::{Counters, SentCommand, SentCommands};
 use crate::protocol::types::RedisCommandKind;
-use crate::protocol::types::{ResponseKind, ValueScanInner, ValueScanResult};
+use crate::protocol::types::{ResponseFrame, ResponseKind, ValueScanInner, ValueScanResult};
 use crate::protocol::utils as protocol_utils;
 use crate::protocol::utils::{frame_to_error, frame_to_single_result};
 use crate::trace;
-use crate::types::{HScanResult, KeyspaceEvent, RedisKey, RedisValue, SScanResult, ScanResult, ZScanResult};
+use crate::types::{
+  HScanResult, KeyspaceEvent, PubsubEvent, RedisKey, RedisValue, SScanResult, ScanResult, ZScanResult,
+};
 use crate::utils as client_utils;
 use parking_lot::{Mutex, RwLock};
 use redis_protocol::resp2::types::Frame as ProtocolFrame;
+use redis_protocol::types::Redirection;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
 
-#[cfg(feature = "custom-reconnect-errors")]
-use crate::globals::globals;
 #[cfg(feature = "metrics")]
 use crate::modules::metrics::MovingStats;
 #[cfg(feature = "metrics")]
@@ -340,12 +346,19 @@ fn send_value_scan_error(
 
 /// Emit `(channel, message)` tuples on the pubsub interface, closing any senders for whom the receiver has been dropped.
 fn emit_pubsub_message(inner: &Arc<RedisClientInner>, channel: String, message: RedisValue) {
+  utils::emit_pubsub_event(
+    inner,
+    PubsubEvent::Message {
+      channel: channel.clone(),
+      message: message.clone(),
+    },
+  );
   let mut to_remove = BTreeSet::new();
 
   // check for closed senders as we emit messages, and drop them at the end
   {
     for (idx, tx) in inner.message_tx.read().iter().enumerate() {
-      if let Err(_) = tx.send((channel.clone(), message.clone())) {
+      if !tx.send(inner, (channel.clone(), message.clone())) {
         to_remove.insert(idx);
       }
     }
@@ -369,12 +382,13 @@ fn emit_pubsub_message(inner: &Arc<RedisClientInner>, channel: String, message:
 
 /// Emit keyspace events on the keyspace interface, closing any senders for whom the receiver has been dropped.
 fn emit_keyspace_event(inner: &Arc<RedisClientInner>, event: KeyspaceEvent) {
+  utils::emit_pubsub_event(inner, PubsubEvent::Keyspace(event.clone()));
   let mut to_remove = BTreeSet::new();
 
   // check for closed senders as we emit messages, and drop them at the end
   {
     for (idx, tx) in inner.keyspace_tx.read().iter().enumerate() {
-      if let Err(_) = tx.send(event.clone()) {
+      if !tx.send(inner, event.clone()) {
         to_remove.insert(idx);
       }
     }
@@ -382,7 +396,7 @@ fn emit_keyspace_event(inner: &Arc<RedisClientInner>, event: KeyspaceEvent) {
 
   if !to_remove.is_empty() {
     _trace!(inner, "Removing {} closed keyspace listeners", to_remove.len());
-    let mut message_tx_guard = inner.message_tx.write();
+    let mut message_tx_guard = inner.keyspace_tx.write();
     let message_tx_ref = &mut *message_tx_guard;
 
     let mut new_listeners = VecDeque::with_capacity(message_tx_ref.len() - to_remove.len());
@@ -397,16 +411,17 @@ fn emit_keyspace_event(inner: &Arc<RedisClientInner>, event: KeyspaceEvent) {
 }
 
 /// Respond to the caller with the output of the command.
-fn respond_to_caller(inner: &Arc<RedisClientInner>, last_command: SentCommand, frame: ProtocolFrame) {
+fn respond_to_caller(inner: &Arc<RedisClientInner>, last_command: SentCommand, frame: ResponseFrame) {
   _trace!(
     inner,
     "Responding to caller for {}",
     last_command.command.kind.to_str_debug()
   );
+  utils::run_after_response_hooks(inner, &last_command, Ok(&frame.frame));
 
   if let Some(tx) = last_command.command.tx {
     if let Err(_) = tx.send(Ok(frame)) {
-      _warn!(inner, "Failed to respond to caller.");
+      _debug!(inner, "Discarding response for dropped command.");
     }
   } else {
     _debug!(inner, "Skip writing response to caller for command without response.");
@@ -420,10 +435,11 @@ fn respond_to_caller_error(inner: &Arc<RedisClientInner>, last_command: SentComm
     "Responding to caller with error for {}",
     last_command.command.kind.to_str_debug()
   );
+  utils::run_after_response_hooks(inner, &last_command, Err(&error));
 
   if let Some(tx) = last_command.command.tx {
     if let Err(_) = tx.send(Err(error)) {
-      _warn!(inner, "Failed to respond to caller.");
+      _debug!(inner, "Discarding error for dropped command.");
     }
   } else {
     _debug!(
@@ -488,14 +504,14 @@ async fn process_response(
   server: &Arc<String>,
   counters: &Counters,
   mut last_command: SentCommand,
-  frame: ProtocolFrame,
+  response: ResponseFrame,
 ) -> Result<Option<SentCommand>, RedisError> {
   _trace!(
     inner,
     "Processing response from {} to {} with frame kind {:?}",
     server,
     last_command.command.kind.to_str_debug(),
-    frame.kind()
+    response.frame.kind()
   );
 
   if last_command.command.kind.has_multiple_response_kind() {
@@ -506,7 +522,7 @@ async fn process_response(
           ref mut buffer,
         } = kind
         {
-          buffer.push_back(frame);
+          buffer.push_back(response.into_resp2());
 
           if buffer.len() < *count {
             _trace!(
@@ -534,16 +550,17 @@ async fn process_response(
     };
 
     if let Some(frames) = frames {
+      counters.decr_in_flight();
       check_command_resp_tx(inner, &last_command).await;
-      respond_to_caller(inner, last_command, frames);
+      respond_to_caller(inner, last_command, frames.into());
     } else {
       // more responses are expected so return the last command to be put back in the queue
       return Ok(Some(last_command));
     }
   } else if last_command.command.kind.is_scan() {
-    client_utils::decr_atomic(&counters.in_flight);
+    counters.decr_in_flight();
 
-    let (next_cursor, keys) = match handle_key_scan_result(frame) {
+    let (next_cursor, keys) = match handle_key_scan_result(response.into_resp2()) {
       Ok(result) => result,
       Err(e) => {
         let _ = send_key_scan_error(inner, &last_command, e);
@@ -560,9 +577,9 @@ async fn process_response(
       _warn!(inner, "Failed to send key scan result");
     }
   } else if last_command.command.kind.is_value_scan() {
-    client_utils::decr_atomic(&counters.in_flight);
+    counters.decr_in_flight();
 
-    let (next_cursor, values) = match handle_value_scan_result(frame) {
+    let (next_cursor, values) = match handle_value_scan_result(response.into_resp2()) {
       Ok(result) => result,
       Err(e) => {
         let _ = send_value_scan_error(inner, &last_command, e);
@@ -579,13 +596,14 @@ async fn process_response(
       _warn!(inner, "Failed to send value scan result");
     }
   } else if last_command.command.kind.is_all_cluster_nodes() {
-    return Ok(handle_all_nodes_response(inner, last_command, frame).await);
+    counters.decr_in_flight();
+    return Ok(handle_all_nodes_response(inner, last_command, response.into_resp2()).await);
   } else {
-    client_utils::decr_atomic(&counters.in_flight);
+    counters.decr_in_flight();
     sample_command_latencies(inner, &mut last_command);
 
     check_command_resp_tx(inner, &last_command).await;
-    respond_to_caller(inner, last_command, frame);
+    respond_to_caller(inner, last_command, response);
   }
 
   Ok(None)
@@ -645,7 +663,15 @@ fn parse_keyspace_notification(channel: String, message: RedisValue) -> Result<K
 ///
 /// If not then return it to the caller for further processing.
 fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: ProtocolFrame) -> Option<ProtocolFrame> {
-  if frame.is_pubsub_message() {
+  if let Some(channel) = protocol_utils::parse_sunsubscribe_channel(&frame) {
+    // the server unsubscribes clients from sharded channels when the channel's hash slot moves to another node
+    if inner.subscriptions.read().shard_channels.contains(&channel) {
+      resubscribe_shard_channel(inner, channel);
+      return None;
+    }
+  }
+
+  if protocol_utils::is_pubsub_message(&frame) {
     let span = if inner.should_trace() {
       let span = trace::create_pubsub_span(inner, &frame);
       Some(span)
@@ -671,7 +697,6 @@ fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: ProtocolFrame) ->
     if let Some(ref span) = span {
       span.record("channel", &channel.as_str());
     }
-
     match parse_keyspace_notification(channel, message) {
       Ok(event) => emit_keyspace_event(inner, event),
       Err((channel, message)) => emit_pubsub_message(inner, channel, message),
@@ -683,6 +708,20 @@ fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: ProtocolFrame) ->
   }
 }
 
+/// Subscribe to a sharded channel again after the server unsubscribed the client, usually due to a slot migration.
+///
+/// If the cluster state is stale the command will be redirected to the new owner of the channel's hash slot.
+fn resubscribe_shard_channel(inner: &Arc<RedisClientInner>, channel: String) {
+  _debug!(inner, "Resubscribing to sharded channel {} after unsubscribe from server.", channel);
+  let inner = inner.clone();
+
+  let _ = tokio::spawn(async move {
+    if let Err(e) = commands::pubsub::ssubscribe(&inner, channel).await {
+      _warn!(inner, "Failed to resubscribe to sharded channel: {:?}", e);
+    }
+  });
+}
+
 #[cfg(feature = "reconnect-on-auth-error")]
 /// Parse the response frame to see if it's an auth error.
 fn parse_redis_auth_error(frame: &ProtocolFrame) -> Option<RedisError> {
@@ -749,6 +788,7 @@ fn add_back_last_cluster_command(
 /// Check if the command has a response sender to unblock the multiplexer loop, and if send a message on that channel.
 async fn check_command_resp_tx(inner: &Arc<RedisClientInner>, command: &SentCommand) {
   if command.command.kind.is_blocking() {
+    command.command.in_flight.store(false, Ordering::SeqCst);
     inner.backchannel.write().await.set_unblocked();
   }
 
@@ -799,6 +839,17 @@ fn response_is_queued(frame: &ProtocolFrame) -> bool {
   }
 }
 
+/// Whether or not the error frame is a response to a command queued inside a transaction.
+///
+/// Errors in response to `MULTI`, `EXEC`, or `DISCARD` are not considered queued command errors.
+fn response_is_queued_error(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame, command: Option<&SentCommand>) -> bool {
+  frame.is_error()
+    && client_utils::is_locked_some(&inner.multi_block)
+    && command
+      .map(|c| !c.command.kind.is_multi() && !c.command.kind.ends_transaction())
+      .unwrap_or(false)
+}
+
 /// Read the most recent (newest) command from a centralized command response queue.
 fn take_most_recent_centralized_command(commands: &Arc<Mutex<SentCommands>>) -> Option<SentCommand> {
   commands.lock().pop_back()
@@ -845,7 +896,7 @@ async fn end_centralized_multi_block(
   inner: &Arc<RedisClientInner>,
   counters: &Counters,
   commands: &Arc<Mutex<SentCommands>>,
-  frame: ProtocolFrame,
+  response: ResponseFrame,
   ending_cmd: TransactionEnded,
 ) -> Result<(), RedisError> {
   if !client_utils::is_locked_some(&inner.multi_block) {
@@ -856,7 +907,7 @@ async fn end_centralized_multi_block(
   }
   counters.decr_in_flight();
 
-  if ending_cmd == TransactionEnded::Discard || (ending_cmd == TransactionEnded::Exec && frame.is_null()) {
+  if ending_cmd == TransactionEnded::Discard || (ending_cmd == TransactionEnded::Exec && response.frame.is_null()) {
     // the transaction was discarded or aborted due to a WATCH condition failing
     _trace!(inner, "Ending transaction with discard or null response");
     let recent_cmd = take_most_recent_centralized_command(commands);
@@ -866,7 +917,7 @@ async fn end_centralized_multi_block(
     if let Some(mut recent_cmd) = recent_cmd {
       sample_command_latencies(inner, &mut recent_cmd);
       check_command_resp_tx(inner, &recent_cmd).await;
-      respond_to_caller(inner, recent_cmd, frame);
+      respond_to_caller(inner, recent_cmd, response);
       return Ok(());
     } else {
       return Err(RedisError::new(
@@ -898,7 +949,7 @@ async fn end_centralized_multi_block(
 
   sample_command_latencies(inner, &mut last_command);
   check_command_resp_tx(inner, &last_command).await;
-  respond_to_caller(inner, last_command, frame);
+  respond_to_caller(inner, last_command, response);
 
   let _ = client_utils::take_locked(&inner.multi_block);
   Ok(())
@@ -910,7 +961,7 @@ async fn end_clustered_multi_block(
   server: &Arc<String>,
   counters: &Arc<RwLock<BTreeMap<Arc<String>, Counters>>>,
   commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
-  frame: ProtocolFrame,
+  response: ResponseFrame,
   ending_cmd: TransactionEnded,
 ) -> Result<(), RedisError> {
   if !client_utils::is_locked_some(&inner.multi_block) {
@@ -923,7 +974,7 @@ async fn end_clustered_multi_block(
     counters.decr_in_flight();
   }
 
-  if ending_cmd == TransactionEnded::Discard || (ending_cmd == TransactionEnded::Exec && frame.is_null()) {
+  if ending_cmd == TransactionEnded::Discard || (ending_cmd == TransactionEnded::Exec && response.frame.is_null()) {
     // the transaction was discarded or aborted due to a WATCH condition failing
     _trace!(inner, "Ending transaction with discard or null response.");
     let recent_cmd = take_most_recent_cluster_command(commands, server);
@@ -933,7 +984,7 @@ async fn end_clustered_multi_block(
     if let Some(mut recent_cmd) = recent_cmd {
       sample_command_latencies(inner, &mut recent_cmd);
       check_command_resp_tx(inner, &recent_cmd).await;
-      respond_to_caller(inner, recent_cmd, frame);
+      respond_to_caller(inner, recent_cmd, response);
       return Ok(());
     } else {
       return Err(RedisError::new(
@@ -963,13 +1014,13 @@ async fn end_clustered_multi_block(
 
   sample_command_latencies(inner, &mut last_command);
   check_command_resp_tx(inner, &last_command).await;
-  respond_to_caller(inner, last_command, frame);
+  respond_to_caller(inner, last_command, response);
 
   let _ = client_utils::take_locked(&inner.multi_block);
   Ok(())
 }
 
-/// Handle a QUEUED response to a command on a clustered client instance.
+/// Handle a QUEUED or error response to a command inside a transaction on a clustered client instance.
 async fn handle_clustered_queued_response(
   inner: &Arc<RedisClientInner>,
   server: &Arc<String>,
@@ -1003,17 +1054,18 @@ async fn handle_clustered_queued_response(
   };
 
   if frame.is_error() && multi_block.abort_on_error {
-    let _ = client_utils::take_locked(&inner.multi_block);
+    _debug!(inner, "Aborting transaction after error queueing {}.", last_command.command.kind.to_str_debug());
+    client_utils::abort_multi_block(inner);
   }
 
   sample_command_latencies(inner, &mut last_command);
   check_command_resp_tx(inner, &last_command).await;
-  respond_to_caller(inner, last_command, frame);
+  respond_to_caller(inner, last_command, frame.into());
 
   Ok(())
 }
 
-/// Handle a QUEUED response to a command from a centralized client.
+/// Handle a QUEUED or error response to a command inside a transaction from a centralized client.
 async fn handle_centralized_queued_response(
   inner: &Arc<RedisClientInner>,
   counters: &Counters,
@@ -1046,12 +1098,13 @@ async fn handle_centralized_queued_response(
   };
 
   if frame.is_error() && multi_block.abort_on_error {
-    let _ = client_utils::take_locked(&inner.multi_block);
+    _debug!(inner, "Aborting transaction after error queueing {}.", last_command.command.kind.to_str_debug());
+    client_utils::abort_multi_block(inner);
   }
 
   sample_command_latencies(inner, &mut last_command);
   check_command_resp_tx(inner, &last_command).await;
-  respond_to_caller(inner, last_command, frame);
+  respond_to_caller(inner, last_command, frame.into());
 
   Ok(())
 }
@@ -1068,6 +1121,22 @@ fn check_redirection_error(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame)
   }
 }
 
+/// The delay before a command that received a `TRYAGAIN` error is retried, multiplied by the number of attempts.
+const TRY_AGAIN_DELAY_MS: u64 = 25;
+
+/// Check if the frame represents an error returned while the cluster fails over.
+fn check_failover_error(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame) -> Option<RedisError> {
+  if protocol_utils::is_cluster_failover_error(frame) {
+    let details = frame.as_str().unwrap_or("Cluster failover error.").to_owned();
+    let error = RedisError::new(RedisErrorKind::Cluster, details);
+    utils::emit_error(&inner, &error);
+    _debug!(inner, "Recv cluster failover error: {:?}", error);
+    Some(error)
+  } else {
+    None
+  }
+}
+
 #[cfg(feature = "custom-reconnect-errors")]
 fn check_global_reconnect_errors(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame) -> Option<RedisError> {
   if let ProtocolFrame::Error(ref message) = frame {
@@ -1091,31 +1160,229 @@ fn check_global_reconnect_errors(_: &Arc<RedisClientInner>, _: &ProtocolFrame) -
   None
 }
 
+/// Check for `READONLY` errors from a primary node that was demoted by the sentinel nodes.
+fn check_sentinel_failover_error(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame) -> Option<RedisError> {
+  if protocol_utils::is_readonly_error(frame) && inner.config.read().server.is_sentinel() {
+    let details = frame.as_str().unwrap_or("Primary node is read-only.").to_owned();
+    let error = RedisError::new(RedisErrorKind::Sentinel, details);
+    _warn!(inner, "Primary node was demoted, reconnecting via sentinel: {:?}", error);
+    utils::emit_error(inner, &error);
+    Some(error)
+  } else {
+    None
+  }
+}
+
 /// Check for special errors configured by the caller to initiate a reconnection process.
 fn check_special_errors(inner: &Arc<RedisClientInner>, frame: &ProtocolFrame) -> Option<RedisError> {
   if let Some(auth_error) = parse_redis_auth_error(frame) {
     // this closes the stream and initiates a reconnect, if applicable
     return Some(auth_error);
   }
+  if let Some(failover_error) = check_sentinel_failover_error(inner, frame) {
+    // this closes the stream so the primary node is discovered again via the sentinel nodes
+    return Some(failover_error);
+  }
 
   check_global_reconnect_errors(inner, frame)
 }
 
-/// Refresh the cluster state and retry the last command.
+/// Retry the last command, following the `MOVED` or `ASK` redirection.
+///
+/// Commands inside a transaction are retried after refreshing the cluster state, and commands that exceed the max number
+/// of redirections receive the redirection error.
 fn handle_redirection_error(
   inner: &Arc<RedisClientInner>,
   server: &Arc<String>,
   commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
+  redirection: Option<Redirection>,
   error: RedisError,
 ) -> Result<(), RedisError> {
-  let last_command = last_cluster_command(inner, commands, server)?;
+  let mut last_command = match last_cluster_command(inner, commands, server)? {
+    Some(command) => command,
+    None => return Ok(()),
+  };
 
-  if let Some(command) = last_command {
-    utils::refresh_cluster_state(inner, command, error);
+  let redirection = match redirection {
+    Some(redirection) if inner.multi_block.read().is_none() => redirection,
+    _ => {
+      utils::refresh_cluster_state(inner, last_command, error);
+      return Ok(());
+    }
+  };
+  last_command.command.redirections += 1;
+  if last_command.command.redirections > globals().max_redirections() {
+    _debug!(
+      inner,
+      "Max redirections reached for {}",
+      last_command.command.kind.to_str_debug()
+    );
+    respond_to_caller_error(inner, last_command, error);
+    return Ok(());
+  }
+
+  let mut command = last_command.command;
+  command.redirection = Some(redirection);
+  utils::unblock_multiplexer(inner, &command);
+  client_utils::send_command(inner, command)
+}
+
+/// Retry the last command after refreshing the cluster state, since the node that received the command is failing over or
+/// no longer a primary node.
+///
+/// Commands inside a transaction and commands that exceed the max number of redirections receive the error instead.
+fn handle_failover_error(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
+  error: RedisError,
+) -> Result<(), RedisError> {
+  let mut last_command = match last_cluster_command(inner, commands, server)? {
+    Some(command) => command,
+    None => return Ok(()),
+  };
+
+  last_command.command.redirections += 1;
+  if inner.multi_block.read().is_some() || last_command.command.redirections > globals().max_redirections() {
+    _debug!(
+      inner,
+      "Skip retrying {} after cluster failover error.",
+      last_command.command.kind.to_str_debug()
+    );
+    respond_to_caller_error(inner, last_command, error);
+    return Ok(());
   }
+
+  _warn!(inner, "Refreshing cluster state after failover error from {}", server);
+  utils::refresh_cluster_state(inner, last_command, error);
   Ok(())
 }
 
+/// Retry the last command after a short delay, since the keys it uses are in a hash slot that is being migrated.
+///
+/// Unlike `MOVED` or `ASK` errors this does not indicate that the cluster state changed, so the cluster state is not
+/// refreshed. Commands inside a transaction and commands that exceed the max number of redirections receive the error
+/// instead.
+fn handle_try_again_error(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
+  error: RedisError,
+) -> Result<(), RedisError> {
+  let mut last_command = match last_cluster_command(inner, commands, server)? {
+    Some(command) => command,
+    None => return Ok(()),
+  };
+
+  last_command.command.redirections += 1;
+  if inner.multi_block.read().is_some() || last_command.command.redirections > globals().max_redirections() {
+    _debug!(
+      inner,
+      "Skip retrying {} after TRYAGAIN error.",
+      last_command.command.kind.to_str_debug()
+    );
+    respond_to_caller_error(inner, last_command, error);
+    return Ok(());
+  }
+
+  let command = last_command.command;
+  let delay = Duration::from_millis(TRY_AGAIN_DELAY_MS * command.redirections as u64);
+  _debug!(
+    inner,
+    "Retrying {} after TRYAGAIN error in {:?}",
+    command.kind.to_str_debug(),
+    delay
+  );
+  utils::unblock_multiplexer(inner, &command);
+
+  let inner = inner.clone();
+  let _ = tokio::spawn(async move {
+    sleep(delay).await;
+    if let Err(e) = client_utils::send_command(&inner, command) {
+      _warn!(inner, "Failed to retry command after TRYAGAIN error: {:?}", e);
+    }
+  });
+  Ok(())
+}
+
+/// Check if the frame is a `BUSY` error that should be retried, returning the delay before the command is sent again.
+///
+/// Scripting commands, commands sent to all cluster nodes, and commands inside a transaction are never retried.
+fn check_busy_error(
+  inner: &Arc<RedisClientInner>,
+  frame: &ProtocolFrame,
+  last_command: Option<&SentCommand>,
+) -> Option<Duration> {
+  let is_busy_error = match frame {
+    ProtocolFrame::Error(ref message) => message.split_whitespace().next() == Some("BUSY"),
+    _ => false,
+  };
+  if !is_busy_error || client_utils::is_locked_some(&inner.multi_block) {
+    return None;
+  }
+  let config = match inner.config.read().busy_retry {
+    Some(ref config) => config.clone(),
+    None => return None,
+  };
+  let command = match last_command {
+    Some(command) => &command.command,
+    None => return None,
+  };
+
+  let is_scripting = command.kind.is_eval()
+    || command.kind.is_fcall()
+    || command.kind.is_script_command()
+    || command.kind.is_function_command();
+  if is_scripting || command.kind.is_all_cluster_nodes() || command.busy_retries >= config.max_attempts {
+    None
+  } else {
+    Some(config.delay)
+  }
+}
+
+/// Send the last command again after waiting for the provided delay.
+///
+/// Commands that fail while a retry is pending are retried with the same batch, and the multiplexer waits for the
+/// batch to be sent before writing other commands so that the retried commands keep their original order.
+fn retry_busy_command(inner: &Arc<RedisClientInner>, last_command: SentCommand, delay: Duration) {
+  let mut command = last_command.command;
+  command.busy_retries += 1;
+  _debug!(
+    inner,
+    "Retrying {} after BUSY error in {:?} (attempt {})",
+    command.kind.to_str_debug(),
+    delay,
+    command.busy_retries
+  );
+  utils::unblock_multiplexer(inner, &command);
+
+  let is_first = {
+    let mut guard = inner.busy_retries.lock();
+    guard.push_back(command);
+    guard.len() == 1
+  };
+  if !is_first {
+    return;
+  }
+
+  let inner = inner.clone();
+  let _ = tokio::spawn(async move {
+    sleep(delay).await;
+
+    // the lock is held while sending so the multiplexer cannot see an empty queue before the commands are sent
+    let mut guard = inner.busy_retries.lock();
+    client_utils::send_priority_commands(&inner, guard.drain(..).collect());
+    drop(guard);
+    inner.busy_notify.notify_waiters();
+  });
+}
+
+fn decr_cluster_in_flight(counters: &Arc<RwLock<BTreeMap<Arc<String>, Counters>>>, server: &Arc<String>) {
+  if let Some(counters) = counters.read().get(server) {
+    counters.decr_in_flight();
+  }
+}
+
 /// Process a frame on a clustered client instance from the provided server.
 ///
 /// Errors in this context are considered fatal and will close the stream.
@@ -1124,25 +1391,58 @@ pub async fn process_clustered_frame(
   server: &Arc<String>,
   counters: &Arc<RwLock<BTreeMap<Arc<String>, Counters>>>,
   commands: &Arc<Mutex<BTreeMap<Arc<String>, VecDeque<SentCommand>>>>,
-  frame: ProtocolFrame,
+  response: ResponseFrame,
 ) -> Result<(), RedisError> {
+  let ResponseFrame { frame, resp3 } = response;
   if let Some(error) = check_redirection_error(inner, &frame) {
-    handle_redirection_error(inner, server, commands, error)?;
+    decr_cluster_in_flight(counters, server);
+    handle_redirection_error(inner, server, commands, frame.to_redirection(), error)?;
+    return Ok(());
+  }
+  if let Some(error) = check_failover_error(inner, &frame) {
+    decr_cluster_in_flight(counters, server);
+    handle_failover_error(inner, server, commands, error)?;
+    return Ok(());
+  }
+  if protocol_utils::is_try_again_error(&frame) {
+    let details = frame.as_str().unwrap_or("TRYAGAIN").to_owned();
+    let error = RedisError::new(RedisErrorKind::Cluster, details);
+    decr_cluster_in_flight(counters, server);
+    handle_try_again_error(inner, server, commands, error)?;
     return Ok(());
   }
   if let Some(error) = check_special_errors(inner, &frame) {
     // this closes the stream and initiates a reconnect, if configured
     return Err(error);
   }
+  let busy_delay = {
+    let commands_guard = commands.lock();
+    let last_command = commands_guard.get(server).and_then(|commands| commands.front());
+    check_busy_error(inner, &frame, last_command)
+  };
+  if let Some(delay) = busy_delay {
+    decr_cluster_in_flight(counters, server);
+    if let Some(last_command) = last_cluster_command(inner, commands, server)? {
+      retry_busy_command(inner, last_command, delay);
+    }
+    return Ok(());
+  }
 
   if let Some(frame) = check_pubsub_message(inner, frame) {
-    if response_is_queued(&frame) {
+    let is_queued_error = {
+      let commands_guard = commands.lock();
+      let last_command = commands_guard.get(server).and_then(|commands| commands.front());
+      response_is_queued_error(inner, &frame, last_command)
+    };
+
+    if response_is_queued(&frame) || is_queued_error {
       let _ = handle_clustered_queued_response(inner, server, counters, commands, frame).await?;
       return Ok(());
     }
 
     if let Some(trx_ended) = last_clustered_command_ends_transaction(commands, server) {
-      end_clustered_multi_block(inner, server, counters, commands, frame, trx_ended).await
+      let response = ResponseFrame { frame, resp3 };
+      end_clustered_multi_block(inner, server, counters, commands, response, trx_ended).await
     } else {
       let counters = match counters.read().get(server) {
         Some(counters) => counters.clone(),
@@ -1164,7 +1464,8 @@ pub async fn process_clustered_frame(
         }
       };
 
-      if let Some(last_command) = process_response(inner, server, &counters, last_command, frame).await? {
+      let response = ResponseFrame { frame, resp3 };
+      if let Some(last_command) = process_response(inner, server, &counters, last_command, response).await? {
         add_back_last_cluster_command(inner, commands, server, last_command)?;
       }
       Ok(())
@@ -1182,22 +1483,35 @@ pub async fn process_centralized_frame(
   server: &Arc<String>,
   counters: &Counters,
   commands: &Arc<Mutex<SentCommands>>,
-  frame: ProtocolFrame,
+  response: ResponseFrame,
 ) -> Result<(), RedisError> {
+  let ResponseFrame { frame, resp3 } = response;
   if let Some(error) = check_special_errors(inner, &frame) {
     // this closes the stream and initiates a reconnect, if configured
     return Err(error);
   }
+  let busy_delay = check_busy_error(inner, &frame, commands.lock().front());
+  if let Some(delay) = busy_delay {
+    counters.decr_in_flight();
+    let last_command = commands.lock().pop_front();
+    if let Some(last_command) = last_command {
+      retry_busy_command(inner, last_command, delay);
+    }
+    return Ok(());
+  }
 
   if let Some(frame) = check_pubsub_message(inner, frame) {
-    if response_is_queued(&frame) {
+    let is_queued_error = response_is_queued_error(inner, &frame, commands.lock().front());
+
+    if response_is_queued(&frame) || is_queued_error {
       let _ = handle_centralized_queued_response(inner, counters, commands, frame).await?;
       return Ok(());
     }
 
     // TODO change this so we can check the last command without contending for a lock
     if let Some(trx_ended) = last_centralized_command_ends_transaction(commands).await {
-      end_centralized_multi_block(inner, counters, commands, frame, trx_ended).await
+      let response = ResponseFrame { frame, resp3 };
+      end_centralized_multi_block(inner, counters, commands, response, trx_ended).await
     } else {
       let last_command = {
         match commands.lock().pop_front() {
@@ -1214,7 +1528,8 @@ pub async fn process_centralized_frame(
         }
       };
 
-      if let Some(last_command) = process_response(inner, server, counters, last_command, frame).await? {
+      let response = ResponseFrame { frame, resp3 };
+      if let Some(last_command) = process_response(inner, server, counters, last_command, response).await? {
         commands.lock().push_front(last_command);
       }
 
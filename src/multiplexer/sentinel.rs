@@ -5,7 +5,7 @@ use crate::modules::types::ClientState;
 use crate::multiplexer::{utils, CloseTx, Connections, Counters, SentCommand};
 use crate::protocol::codec::RedisCodec;
 use crate::protocol::connection::{self, authenticate, FramedTcp, FramedTls, RedisTransport};
-use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::types::{RedisCommand, RedisCommandKind, ResolvedAddrs};
 use crate::protocol::utils as protocol_utils;
 use crate::types::Resolve;
 use crate::types::{RedisValue, ServerConfig};
@@ -13,14 +13,14 @@ use crate::utils as client_utils;
 use parking_lot::RwLock;
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
-use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpStream;
 use tokio::sync::RwLock as AsyncRwLock;
 use tokio_util::codec::Framed;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::protocol::tls;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use crate::types::TlsConfig;
 
 /// The amount of time to wait when trying to connect to the redis server.
 ///
@@ -72,21 +72,43 @@ fn read_sentinel_auth(inner: &Arc<RedisClientInner>) -> Result<(Option<String>,
   Ok((guard.username.clone(), guard.password.clone()))
 }
 
+#[cfg(all(feature = "sentinel-auth", any(feature = "enable-tls", feature = "enable-rustls")))]
+fn read_sentinel_tls(inner: &Arc<RedisClientInner>) -> Option<TlsConfig> {
+  let guard = inner.config.read();
+  match guard.server {
+    ServerConfig::Sentinel { tls: Some(ref tls), .. } => Some(tls.clone()),
+    _ => guard.tls.clone(),
+  }
+}
+
+#[cfg(all(not(feature = "sentinel-auth"), any(feature = "enable-tls", feature = "enable-rustls")))]
+fn read_sentinel_tls(inner: &Arc<RedisClientInner>) -> Option<TlsConfig> {
+  inner.config.read().tls.clone()
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+fn sentinel_uses_tls(inner: &Arc<RedisClientInner>) -> bool {
+  read_sentinel_tls(inner).is_some()
+}
+
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
+fn sentinel_uses_tls(_: &Arc<RedisClientInner>) -> bool {
+  false
+}
+
 fn read_redis_auth(inner: &Arc<RedisClientInner>) -> (Option<String>, Option<String>) {
   let guard = inner.config.read();
   (guard.username.clone(), guard.password.clone())
 }
 
 // TODO clean this up in the next major release by breaking up the connection functions
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub async fn create_authenticated_connection_tls(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   domain: &str,
   inner: &Arc<RedisClientInner>,
   is_sentinel: bool,
 ) -> Result<FramedTls, RedisError> {
-  let server = format!("{}:{}", addr.ip().to_string(), addr.port());
-  let codec = RedisCodec::new(inner, server);
   let client_name = inner.client_name();
   let (username, password) = if is_sentinel {
     read_sentinel_auth(inner)?
@@ -94,31 +116,41 @@ pub async fn create_authenticated_connection_tls(
     read_redis_auth(inner)
   };
 
-  let socket = TcpStream::connect(addr).await?;
-  let tls_stream = tls::create_tls_connector(&inner.config)?;
-  let socket = tls_stream.connect(domain, socket).await?;
+  let (socket, server) = connection::tcp_connect(inner, addrs).await?;
+  let codec = RedisCodec::new(inner, server);
+  let (tls_connector, tls_config) = if is_sentinel {
+    (&inner.sentinel_tls_connector, read_sentinel_tls(inner))
+  } else {
+    (&inner.tls_connector, inner.config.read().tls.clone())
+  };
+  let socket = tls::connect(tls_connector, tls_config.as_ref(), domain, socket).await?;
   let framed = authenticate(Framed::new(socket, codec), &client_name, username, password).await?;
+  let framed = if is_sentinel {
+    framed
+  } else {
+    let framed = connection::negotiate_version(inner, framed).await?;
+    let framed = connection::enable_tracking(inner, framed).await?;
+    connection::select_database(inner, framed).await?
+  };
 
   Ok(framed)
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub(crate) async fn create_authenticated_connection_tls(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   _domain: &str,
   inner: &Arc<RedisClientInner>,
   is_sentinel: bool,
 ) -> Result<FramedTls, RedisError> {
-  create_authenticated_connection(addr, inner, is_sentinel).await
+  create_authenticated_connection(addrs, inner, is_sentinel).await
 }
 
 pub async fn create_authenticated_connection(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   inner: &Arc<RedisClientInner>,
   is_sentinel: bool,
 ) -> Result<FramedTcp, RedisError> {
-  let server = format!("{}:{}", addr.ip().to_string(), addr.port());
-  let codec = RedisCodec::new(inner, server);
   let client_name = inner.client_name();
   let (username, password) = if is_sentinel {
     read_sentinel_auth(inner)?
@@ -126,8 +158,16 @@ pub async fn create_authenticated_connection(
     read_redis_auth(inner)
   };
 
-  let socket = TcpStream::connect(addr).await?;
+  let (socket, server) = connection::tcp_connect(inner, addrs).await?;
+  let codec = RedisCodec::new(inner, server);
   let framed = authenticate(Framed::new(socket, codec), &client_name, username, password).await?;
+  let framed = if is_sentinel {
+    framed
+  } else {
+    let framed = connection::negotiate_version(inner, framed).await?;
+    let framed = connection::enable_tracking(inner, framed).await?;
+    connection::select_database(inner, framed).await?
+  };
 
   Ok(framed)
 }
@@ -135,19 +175,23 @@ pub async fn create_authenticated_connection(
 async fn connect_to_server(
   inner: &Arc<RedisClientInner>,
   host: &str,
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   timeout: u64,
   is_sentinel: bool,
 ) -> Result<RedisTransport, RedisError> {
-  let uses_tls = inner.config.read().uses_tls();
+  let uses_tls = if is_sentinel {
+    sentinel_uses_tls(inner)
+  } else {
+    inner.config.read().uses_tls()
+  };
 
   let transport = if uses_tls {
-    let transport_ft = create_authenticated_connection_tls(addr, host, inner, is_sentinel);
+    let transport_ft = create_authenticated_connection_tls(addrs, host, inner, is_sentinel);
     let transport = stry!(client_utils::apply_timeout(transport_ft, timeout).await);
 
     RedisTransport::Tls(transport)
   } else {
-    let transport_ft = create_authenticated_connection(addr, inner, is_sentinel);
+    let transport_ft = create_authenticated_connection(addrs, inner, is_sentinel);
     let transport = stry!(client_utils::apply_timeout(transport_ft, timeout).await);
 
     RedisTransport::Tcp(transport)
@@ -160,7 +204,7 @@ async fn read_primary_node_address(
   inner: &Arc<RedisClientInner>,
   transport: RedisTransport,
   server_name: &str,
-) -> Result<(RedisTransport, String, SocketAddr), RedisError> {
+) -> Result<(RedisTransport, String, ResolvedAddrs), RedisError> {
   let request = RedisCommand::new(
     RedisCommandKind::Sentinel,
     vec!["get-master-addr-by-name".into(), server_name.into()],
@@ -189,47 +233,49 @@ pub async fn connect_to_sentinel(
   timeout: u64,
 ) -> Result<RedisTransport, RedisError> {
   let sentinel_addr = inner.resolver.resolve(host.to_owned(), port).await?;
-  _debug!(inner, "Connecting to sentinel {}", sentinel_addr);
+  _debug!(inner, "Connecting to sentinel {}", sentinel_addr.server);
   connect_to_server(inner, host, &sentinel_addr, timeout, true).await
 }
 
 async fn discover_primary_node(
   inner: &Arc<RedisClientInner>,
-) -> Result<(String, RedisTransport, String, SocketAddr), RedisError> {
+) -> Result<(String, RedisTransport, String, ResolvedAddrs), RedisError> {
   let (hosts, name) = client_utils::read_sentinel_host(inner)?;
   let timeout = globals().sentinel_connection_timeout_ms() as u64;
 
   for (idx, (sentinel_host, port)) in hosts.into_iter().enumerate() {
     let sentinel_transport = try_continue!(inner, connect_to_sentinel(inner, &sentinel_host, port, timeout).await);
+    // sentinel nodes may not know the primary node while a failover is in progress, so try the next sentinel instead
+    let (sentinel_transport, host, addrs) = try_continue!(
+      inner,
+      read_primary_node_address(inner, sentinel_transport, &name).await
+    );
     swap_first_sentinel_server(inner, idx);
-
-    let (sentinel_transport, host, addr) = read_primary_node_address(inner, sentinel_transport, &name).await?;
     _debug!(
       inner,
-      "Found primary node address {}:{} from sentinel {}:{}",
-      addr.ip(),
-      addr.port(),
+      "Found primary node address {} from sentinel {}:{}",
+      addrs.server,
       host,
       port
     );
-    return Ok((name, sentinel_transport, host, addr));
+    return Ok((name, sentinel_transport, host, addrs));
   }
 
   Err(RedisError::new(
     RedisErrorKind::Sentinel,
-    "Failed to connect to any sentinel node.",
+    "Failed to discover primary node from any sentinel node.",
   ))
 }
 
 async fn connect_and_check_primary_role(
   inner: &Arc<RedisClientInner>,
   host: &str,
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
 ) -> Result<RedisTransport, RedisError> {
   let request = RedisCommand::new(RedisCommandKind::Role, vec![], None);
-  let transport = stry!(connect_to_server(inner, host, addr, DEFAULT_CONNECTION_TIMEOUT_MS, false).await);
+  let transport = stry!(connect_to_server(inner, host, addrs, DEFAULT_CONNECTION_TIMEOUT_MS, false).await);
 
-  _debug!(inner, "Checking role for redis server at {}:{}", host, addr.port());
+  _debug!(inner, "Checking role for redis server at {}", addrs.server);
   let (frame, transport) = stry!(connection::transport_request_response(transport, &request).await);
   let result = stry!(protocol_utils::frame_to_results(frame));
 
@@ -288,6 +334,9 @@ async fn update_connection_id(
       };
       RedisTransport::Tls(framed)
     }
+    // sentinel deployments do not use unix sockets
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => RedisTransport::Unix(framed),
   };
 
   Ok(transport)
@@ -311,7 +360,7 @@ async fn connect_centralized(
   inner: &Arc<RedisClientInner>,
   connections: &Connections,
   host: &str,
-  addr: SocketAddr,
+  addrs: ResolvedAddrs,
   close_tx: &Arc<RwLock<Option<CloseTx>>>,
 ) -> Result<(), RedisError> {
   if let Connections::Centralized {
@@ -323,8 +372,8 @@ async fn connect_centralized(
     ..
   } = connections
   {
-    let new_server = Arc::new(format!("{}:{}", host, addr.port()));
-    let transport = stry!(connect_and_check_primary_role(inner, host, &addr).await);
+    let new_server = Arc::new(addrs.server.clone());
+    let transport = stry!(connect_and_check_primary_role(inner, host, &addrs).await);
     _debug!(inner, "Connected via sentinel to server: {}", new_server);
 
     let tx = update_sentinel_client_state(inner, server, counters, &new_server, close_tx).await;
@@ -443,11 +492,11 @@ pub async fn connect_centralized_from_sentinel(
 ) -> Result<VecDeque<SentCommand>, RedisError> {
   let pending_commands = utils::take_sent_commands(connections);
   connections.disconnect_centralized().await;
-  client_utils::set_client_state(&inner.state, ClientState::Connecting);
+  client_utils::set_connecting_state(&inner);
 
   let (service_name, sentinel_transport, host, primary_addr) = discover_primary_node(inner).await?;
   let _ = connect_centralized(inner, connections, &host, primary_addr, close_tx).await?;
-  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  client_utils::set_client_state(&inner, ClientState::Connected);
 
   if let Err(e) = update_sentinel_nodes(inner, sentinel_transport, &service_name).await {
     _warn!(inner, "Failed to update sentinel nodes with error: {:?}", e);
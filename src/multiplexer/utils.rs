@@ -1,17 +1,19 @@
 use crate::client::RedisClient;
+use crate::commands;
 use crate::error::{RedisError, RedisErrorKind};
 use crate::globals::globals;
-use crate::modules::inner::{ClosedState, RedisClientInner};
+use crate::modules::inner::{ClosedState, RedisClientInner, Subscriptions};
+use crate::modules::pubsub::PubsubSender;
 use crate::multiplexer::types::ClusterChange;
 use crate::multiplexer::{responses, Multiplexer};
 use crate::multiplexer::{Backpressure, CloseTx, Connections, Counters, SentCommand, SentCommands};
-use crate::protocol::connection::{self, RedisSink, RedisStream};
+use crate::protocol::connection::{self, RedisSink, RedisStream, RedisTransport};
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
 use crate::trace;
 use crate::types::*;
 use crate::utils as client_utils;
-use futures::future::Either;
+use futures::future;
 use futures::pin_mut;
 use futures::select;
 use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
@@ -21,8 +23,9 @@ use std::cmp;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::mem;
 use std::ops::DerefMut;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio;
 use tokio::sync::broadcast::{channel as broadcast_channel, Receiver as BroadcastReceiver};
 use tokio::sync::mpsc::UnboundedSender;
@@ -43,13 +46,31 @@ pub fn close_reconnect_tx(reconnect_tx: &RwLock<VecDeque<UnboundedSender<RedisCl
   }
 }
 
-pub fn close_messages_tx(messages_tx: &RwLock<VecDeque<UnboundedSender<(String, RedisValue)>>>) {
+pub fn close_connection_tx(connection_tx: &RwLock<VecDeque<UnboundedSender<Result<(), RedisError>>>>) {
+  for _ in connection_tx.write().drain(..) {
+    trace!("Closing connection tx.");
+  }
+}
+
+pub fn close_messages_tx(messages_tx: &RwLock<VecDeque<PubsubSender<(String, RedisValue)>>>) {
   for _ in messages_tx.write().drain(..) {
     trace!("Closing messages tx.");
   }
 }
 
-pub fn close_keyspace_events_tx(keyspace_tx: &RwLock<VecDeque<UnboundedSender<KeyspaceEvent>>>) {
+pub fn close_pubsub_tx(pubsub_tx: &RwLock<VecDeque<PubsubSender<PubsubEvent>>>) {
+  for _ in pubsub_tx.write().drain(..) {
+    trace!("Closing pubsub tx");
+  }
+}
+
+pub fn close_push_tx(push_tx: &RwLock<VecDeque<UnboundedSender<PushMessage>>>) {
+  for _ in push_tx.write().drain(..) {
+    trace!("Closing push tx");
+  }
+}
+
+pub fn close_keyspace_events_tx(keyspace_tx: &RwLock<VecDeque<PubsubSender<KeyspaceEvent>>>) {
   for _ in keyspace_tx.write().drain(..) {
     trace!("Closing keyspace tx");
   }
@@ -67,6 +88,7 @@ pub fn emit_connect(inner: &Arc<RedisClientInner>) {
   for tx in inner.connect_tx.write().drain(..) {
     let _ = tx.send(Ok(()));
   }
+  emit_connection_event(inner, Ok(()));
 }
 
 pub fn emit_connect_error(inner: &Arc<RedisClientInner>, error: &RedisError) {
@@ -74,6 +96,23 @@ pub fn emit_connect_error(inner: &Arc<RedisClientInner>, error: &RedisError) {
   for tx in inner.connect_tx.write().drain(..) {
     let _ = tx.send(Err(error.clone()));
   }
+  emit_connection_event(inner, Err(error.clone()));
+}
+
+/// Emit an event on the `on_connect` streams, closing any senders for whom the receiver has been dropped.
+fn emit_connection_event(inner: &Arc<RedisClientInner>, event: Result<(), RedisError>) {
+  let mut new_tx = VecDeque::new();
+  let mut tx_guard = inner.connection_tx.write();
+
+  for tx in tx_guard.drain(..) {
+    if let Err(_e) = tx.send(event.clone()) {
+      _debug!(inner, "Error emitting connection event.");
+    } else {
+      new_tx.push_back(tx);
+    }
+  }
+
+  *tx_guard = new_tx;
 }
 
 pub fn emit_error(inner: &Arc<RedisClientInner>, error: &RedisError) {
@@ -91,6 +130,14 @@ pub fn emit_error(inner: &Arc<RedisClientInner>, error: &RedisError) {
   *tx_guard = new_tx;
 }
 
+pub fn emit_unrecoverable(inner: &Arc<RedisClientInner>, error: &RedisError) {
+  _debug!(inner, "Emitting unrecoverable error: {:?}", error);
+  let callbacks = inner.unrecoverable_callbacks.read().clone();
+  for callback in callbacks.iter() {
+    callback(error);
+  }
+}
+
 pub fn emit_reconnect(inner: &Arc<RedisClientInner>) {
   let mut new_tx = VecDeque::new();
   let mut tx_guard = inner.reconnect_tx.write();
@@ -106,6 +153,69 @@ pub fn emit_reconnect(inner: &Arc<RedisClientInner>) {
   *tx_guard = new_tx;
 }
 
+/// Emit an event on the `on_pubsub_event` streams, closing any senders for whom the receiver has been dropped.
+pub fn emit_pubsub_event(inner: &Arc<RedisClientInner>, event: PubsubEvent) {
+  if inner.pubsub_tx.read().is_empty() {
+    return;
+  }
+
+  let mut new_tx = VecDeque::new();
+  let mut tx_guard = inner.pubsub_tx.write();
+
+  for tx in tx_guard.drain(..) {
+    if tx.send(inner, event.clone()) {
+      new_tx.push_back(tx);
+    } else {
+      _debug!(inner, "Error emitting pubsub event.");
+    }
+  }
+
+  *tx_guard = new_tx;
+}
+
+/// Record the time at which the connection closed, if not already set by an earlier failure.
+pub fn record_disconnect(inner: &Arc<RedisClientInner>) {
+  let mut guard = inner.disconnected_at.write();
+  if guard.is_none() {
+    *guard = Some(SystemTime::now());
+  }
+}
+
+/// Emit a `GapDetected` event covering the time from `from` until now.
+pub fn emit_pubsub_gap(inner: &Arc<RedisClientInner>, from: SystemTime) {
+  let to = SystemTime::now();
+  _debug!(inner, "Emitting pubsub gap after reconnecting.");
+  emit_pubsub_event(inner, PubsubEvent::GapDetected { from, to });
+}
+
+/// Subscribe again to the tracked channels, patterns, and sharded channels after reconnecting.
+///
+/// The `GapDetected` event is emitted once the subscriptions are restored, since messages can be missed until then,
+/// or immediately if there are no subscriptions.
+pub fn spawn_resubscribe(inner: &Arc<RedisClientInner>) {
+  let disconnected_at = inner.disconnected_at.write().take();
+  if *inner.subscriptions.read() == Subscriptions::default() {
+    if let Some(from) = disconnected_at {
+      emit_pubsub_gap(inner, from);
+    }
+    return;
+  }
+
+  let inner = inner.clone();
+  let _ = tokio::spawn(async move {
+    if let Err(e) = commands::pubsub::resubscribe(&inner, disconnected_at).await {
+      _warn!(inner, "Failed to restore subscriptions after reconnecting: {:?}", e);
+      emit_error(&inner, &e);
+
+      // the gap is still open, so the next reconnection should report it from the original disconnect time
+      if let Some(from) = disconnected_at {
+        let mut guard = inner.disconnected_at.write();
+        *guard = Some(guard.map_or(from, |at| cmp::min(at, from)));
+      }
+    }
+  });
+}
+
 fn take_commands(
   commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
   server: &Arc<String>,
@@ -215,7 +325,11 @@ pub fn max_attempts_reached(inner: &Arc<RedisClientInner>, command: &mut RedisCo
   }
 }
 
-pub fn should_apply_backpressure(connections: &Connections, server: Option<&Arc<String>>) -> Option<u64> {
+pub fn should_apply_backpressure(
+  inner: &Arc<RedisClientInner>,
+  connections: &Connections,
+  server: Option<&Arc<String>>,
+) -> Option<u64> {
   let in_flight = match connections {
     Connections::Centralized { ref counters, .. } => client_utils::read_atomic(&counters.in_flight),
     Connections::Clustered { ref counters, .. } => server
@@ -228,7 +342,10 @@ pub fn should_apply_backpressure(connections: &Connections, server: Option<&Arc<
       .unwrap_or(0),
   };
   let min_backpressure_time_ms = globals().min_backpressure_time_ms();
-  let backpressure_command_count = globals().backpressure_count();
+  let backpressure_command_count = match inner.config.read().backpressure {
+    Some(ref config) => config.max_in_flight_commands,
+    None => globals().backpressure_count(),
+  };
 
   if in_flight > backpressure_command_count {
     Some(cmp::max(in_flight - backpressure_command_count, min_backpressure_time_ms) as u64)
@@ -240,6 +357,7 @@ pub fn should_apply_backpressure(connections: &Connections, server: Option<&Arc<
 pub fn centralized_server_name(inner: &Arc<RedisClientInner>) -> String {
   match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port, .. } => format!("{}:{}", host, port),
+    ServerConfig::Unix { ref path } => path.to_string_lossy().into_owned(),
     // for sentinel configs this will be replaced later after reading the primary node from the sentinel(s)
     _ => "unknown".to_owned(),
   }
@@ -324,6 +442,9 @@ pub fn prepare_command(
   let mut sent_command: SentCommand = command.into();
   sent_command.command.incr_attempted();
   sent_command.network_start = Some(Instant::now());
+  if sent_command.command.kind.is_blocking() {
+    sent_command.command.in_flight.store(true, Ordering::SeqCst);
+  }
   if inner.should_trace() {
     trace::set_network_span(&mut sent_command.command, true);
   }
@@ -341,6 +462,63 @@ pub fn prepare_command(
   Ok((sent_command, frame, should_flush))
 }
 
+/// Run the `before_send` function on each command hook if the hooks have not already run on the command.
+///
+/// This must run before the command is routed since the hooks can change the keys in the command.
+pub fn run_before_send_hooks(inner: &Arc<RedisClientInner>, command: &mut RedisCommand) {
+  if command.ran_hooks {
+    return;
+  }
+  command.ran_hooks = true;
+  if inner.command_hooks.read().is_empty() {
+    return;
+  }
+
+  let hooks = inner.command_hooks.read().clone();
+  for hook in hooks.iter() {
+    hook.before_send(command.kind.to_str_debug(), &mut command.args);
+  }
+}
+
+/// Run the `after_response` function on each command hook.
+pub fn run_after_response_hooks(
+  inner: &Arc<RedisClientInner>,
+  command: &SentCommand,
+  result: Result<&Frame, &RedisError>,
+) {
+  if inner.command_hooks.read().is_empty() {
+    return;
+  }
+
+  let hooks = inner.command_hooks.read().clone();
+  let server = command.server.as_ref().map(|s| s.as_str()).unwrap_or("");
+  let latency = Instant::now().saturating_duration_since(command.command.sent);
+  let result = match result {
+    Ok(frame) => protocol_utils::frame_to_results(frame.clone()),
+    Err(error) => Err(error.clone()),
+  };
+
+  for hook in hooks.iter() {
+    hook.after_response(
+      command.command.kind.to_str_debug(),
+      &command.command.args,
+      server,
+      latency,
+      result.as_ref(),
+    );
+  }
+}
+
+/// Create the `CLIENT CACHING` command that should be sent immediately before the provided command, if any.
+fn caching_command(command: &RedisCommand) -> Option<RedisCommand> {
+  command.options.as_ref().and_then(|options| options.caching).map(|caching| {
+    let arg = if caching { "yes" } else { "no" };
+    let mut caching_command = RedisCommand::new(RedisCommandKind::ClientCaching, vec![arg.into()], None);
+    caching_command.options = None;
+    caching_command
+  })
+}
+
 pub async fn send_centralized_command(
   inner: &Arc<RedisClientInner>,
   server: &Arc<String>,
@@ -349,7 +527,14 @@ pub async fn send_centralized_command(
   commands: &Arc<Mutex<SentCommands>>,
   command: RedisCommand,
 ) -> Result<(), RedisError> {
-  let (command, frame, should_flush) = prepare_command(inner, counters, command)?;
+  if let Some(caching_command) = caching_command(&command) {
+    let (mut caching_command, frame, _) = prepare_command(inner, counters, caching_command)?;
+    caching_command.server = Some(server.clone());
+    commands.lock().push_back(caching_command);
+    connection::write_command(inner, writer, counters, frame, false).await?;
+  }
+  let (mut command, frame, should_flush) = prepare_command(inner, counters, command)?;
+  command.server = Some(server.clone());
   _debug!(
     inner,
     "Writing command {} to {}",
@@ -372,7 +557,22 @@ pub async fn send_clustered_command(
   commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
   command: RedisCommand,
 ) -> Result<(), RedisError> {
-  let (command, frame, should_flush) = prepare_command(inner, counters, command)?;
+  if let Some(caching_command) = caching_command(&command) {
+    let (mut caching_command, frame, _) = prepare_command(inner, counters, caching_command)?;
+    caching_command.server = Some(server.clone());
+    match commands.lock().get_mut(server) {
+      Some(commands) => commands.push_back(caching_command),
+      None => {
+        return Err(RedisError::new(
+          RedisErrorKind::IO,
+          format!("Missing command queue for {}", server),
+        ))
+      }
+    };
+    connection::write_command(inner, writer, counters, frame, false).await?;
+  }
+  let (mut command, frame, should_flush) = prepare_command(inner, counters, command)?;
+  command.server = Some(server.clone());
   _debug!(
     inner,
     "Writing command {} to {}",
@@ -396,6 +596,62 @@ pub async fn send_clustered_command(
   connection::write_command(inner, writer, counters, frame, should_flush).await
 }
 
+/// Whether or not the client has a connection to the provided cluster node.
+pub async fn has_cluster_writer(connections: &Connections, server: &Arc<String>) -> bool {
+  if let Connections::Clustered { ref writers, .. } = connections {
+    writers.read().await.contains_key(server)
+  } else {
+    false
+  }
+}
+
+/// Write `ASKING` followed by the command to the provided cluster node, following an `ASK` redirection.
+pub async fn write_asking_command(
+  inner: &Arc<RedisClientInner>,
+  connections: &Connections,
+  command: RedisCommand,
+  server: &Arc<String>,
+) -> Result<Backpressure, RedisError> {
+  if let Connections::Clustered {
+    ref writers,
+    ref commands,
+    ref counters,
+    ..
+  } = connections
+  {
+    let counters = match counters.read().get(server).cloned() {
+      Some(counters) => counters,
+      None => {
+        return Err(RedisError::new_context(
+          RedisErrorKind::Unknown,
+          format!("Unable to find server counters for {}", server),
+          command,
+        ))
+      }
+    };
+    let mut writers_guard = writers.write().await;
+    let writer = match writers_guard.get_mut(server) {
+      Some(writer) => writer,
+      None => {
+        return Err(RedisError::new_context(
+          RedisErrorKind::Cluster,
+          format!("Unable to find server connection for {}", server),
+          command,
+        ))
+      }
+    };
+
+    // the server only accepts the command for an importing slot if `ASKING` is sent first on the same connection
+    let asking = RedisCommand::new(RedisCommandKind::Asking, vec![], None);
+    send_clustered_command(inner, server, &counters, writer, commands, asking).await?;
+    send_clustered_command(inner, server, &counters, writer, commands, command)
+      .await
+      .map(|_| Backpressure::Ok(server.clone()))
+  } else {
+    Err(RedisError::new(RedisErrorKind::Config, "Invalid connection type."))
+  }
+}
+
 pub async fn write_centralized_command(
   inner: &Arc<RedisClientInner>,
   connections: &Connections,
@@ -403,7 +659,7 @@ pub async fn write_centralized_command(
   no_backpressure: bool,
 ) -> Result<Backpressure, RedisError> {
   if !no_backpressure {
-    if let Some(backpressure) = should_apply_backpressure(connections, None) {
+    if let Some(backpressure) = should_apply_backpressure(inner, connections, None) {
       _warn!(inner, "Applying backpressure for {} ms", backpressure);
       return Ok(Backpressure::Wait((Duration::from_millis(backpressure), command)));
     }
@@ -482,7 +738,7 @@ pub async fn write_clustered_command(
     };
 
     if !no_backpressure {
-      if let Some(backpressure) = should_apply_backpressure(connections, Some(&server)) {
+      if let Some(backpressure) = should_apply_backpressure(inner, connections, Some(&server)) {
         _warn!(inner, "Applying backpressure for {} ms", backpressure);
         return Ok(Backpressure::Wait((Duration::from_millis(backpressure), command)));
       }
@@ -494,7 +750,7 @@ pub async fn write_clustered_command(
           "Using server {} with hash slot {:?} from key {}",
           server,
           hash_slot,
-          key
+          String::from_utf8_lossy(key)
         );
       }
     }
@@ -611,6 +867,43 @@ async fn remove_cluster_writer(connections: &Connections, server: &Arc<String>)
   }
 }
 
+/// Read the time at which the oldest non-blocking command was written to a connection, if any.
+fn oldest_command_start(commands: &VecDeque<SentCommand>) -> Option<Instant> {
+  commands
+    .front()
+    .filter(|command| !command.command.kind.is_blocking())
+    .and_then(|command| command.network_start)
+}
+
+/// Return an error if the oldest command on a connection waits longer than the [UnresponsiveConfig] allows.
+///
+/// This never resolves if the client does not use an `UnresponsiveConfig`.
+async fn detect_unresponsive<F>(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  oldest: F,
+) -> Result<(), RedisError>
+where
+  F: Fn() -> Option<Instant>,
+{
+  let config = inner.config.read().unresponsive.clone();
+  let config = match config {
+    Some(config) => config,
+    None => return future::pending().await,
+  };
+
+  loop {
+    tokio::time::sleep(config.interval).await;
+
+    if let Some(start) = oldest() {
+      if start.elapsed() > config.max_timeout {
+        _warn!(inner, "Closing unresponsive connection to {}", server);
+        return Err(RedisError::new(RedisErrorKind::IO, "Unresponsive connection."));
+      }
+    }
+  }
+}
+
 pub fn spawn_clustered_listener(
   inner: &Arc<RedisClientInner>,
   connections: &Connections,
@@ -627,25 +920,32 @@ pub fn spawn_clustered_listener(
   let server = server.clone();
 
   let _ = tokio::spawn(async move {
+    let unresponsive_commands = commands.clone();
     let memo = (inner.clone(), server.clone(), counters, commands);
 
     let stream_ft = match stream {
-      RedisStream::Tls(stream) => Either::Left(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-      RedisStream::Tcp(stream) => Either::Right(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
+      RedisStream::Tls(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
+      RedisStream::Tcp(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
+      #[cfg(unix)]
+      RedisStream::Unix(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
     }
     .fuse();
     pin_mut!(stream_ft);
@@ -661,9 +961,19 @@ pub fn spawn_clustered_listener(
       .fuse();
     pin_mut!(close_ft);
 
+    let unresponsive_ft = detect_unresponsive(&inner, &server, || {
+      unresponsive_commands
+        .lock()
+        .get(&server)
+        .and_then(|commands| oldest_command_start(commands))
+    })
+    .fuse();
+    pin_mut!(unresponsive_ft);
+
     let result = select! {
       close_res = close_ft => close_res,
-      stream_res = stream_ft => stream_res
+      stream_res = stream_ft => stream_res,
+      unresponsive_res = unresponsive_ft => unresponsive_res
     };
     let error = result
       .and_then(|_| Err::<(), _>(RedisError::new_canceled()))
@@ -671,13 +981,13 @@ pub fn spawn_clustered_listener(
 
     if client_utils::read_client_state(&inner.state) == ClientState::Disconnecting {
       // client was closed intentionally via Quit
-      client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+      client_utils::set_client_state(&inner, ClientState::Disconnected);
       return Ok(());
     }
 
     _debug!(inner, "Redis clustered frame stream closed with error {:?}", error);
     remove_cluster_writer(&connections, &server).await;
-    client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+    client_utils::set_client_state(&inner, ClientState::Disconnected);
     emit_connection_closed(&inner, &connections, &server, error);
 
     Ok::<(), RedisError>(())
@@ -706,6 +1016,7 @@ async fn create_cluster_connection(
       }
       Err((_, socket)) => socket,
     };
+    let socket = connection::read_and_set_server_version(inner, socket).await;
 
     let (sink, stream) = socket.split();
     Ok((RedisSink::Tls(sink), RedisStream::Tls(stream)))
@@ -720,6 +1031,7 @@ async fn create_cluster_connection(
       }
       Err((_, socket)) => socket,
     };
+    let socket = connection::read_and_set_server_version(inner, socket).await;
 
     let (sink, stream) = socket.split();
     Ok((RedisSink::Tcp(sink), RedisStream::Tcp(stream)))
@@ -754,7 +1066,7 @@ pub async fn connect_clustered(
     ref connection_ids,
   } = connections
   {
-    client_utils::set_client_state(&inner.state, ClientState::Connecting);
+    client_utils::set_connecting_state(&inner);
     let uses_tls = protocol_utils::uses_tls(inner);
     let cluster_state = connection::read_cluster_nodes(inner).await?;
     let main_nodes = cluster_state.unique_main_nodes();
@@ -767,12 +1079,14 @@ pub async fn connect_clustered(
 
       insert_locked_map_mutex(commands, server.clone(), VecDeque::new());
       insert_locked_map_async(writers, server.clone(), sink).await;
-      insert_locked_map(counters, server.clone(), Counters::new(&inner.cmd_buffer_len));
+      if let Some(counters) = insert_locked_map(counters, server.clone(), Counters::new(inner)) {
+        counters.reset_in_flight();
+      }
       spawn_clustered_listener(inner, connections, commands, counters, tx.subscribe(), &server, stream);
     }
 
     _debug!(inner, "Set clustered connection closed sender.");
-    client_utils::set_client_state(&inner.state, ClientState::Connected);
+    client_utils::set_client_state(&inner, ClientState::Connected);
     Ok(pending_commands)
   } else {
     Err(RedisError::new(
@@ -798,25 +1112,32 @@ pub fn spawn_centralized_listener(
   let server = server.clone();
 
   let _ = tokio::spawn(async move {
+    let unresponsive_commands = commands.clone();
     let memo = (inner.clone(), server.clone(), counters, commands);
 
     let stream_ft = match stream {
-      RedisStream::Tls(stream) => Either::Left(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-      RedisStream::Tcp(stream) => Either::Right(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
+      RedisStream::Tls(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
+      RedisStream::Tcp(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
+      #[cfg(unix)]
+      RedisStream::Unix(stream) => stream
+        .try_fold(memo, |(inner, server, counters, commands), frame| async {
+          responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
+          Ok((inner, server, counters, commands))
+        })
+        .and_then(|_| async { Ok(()) })
+        .boxed(),
     }
     .fuse();
     pin_mut!(stream_ft);
@@ -832,9 +1153,14 @@ pub fn spawn_centralized_listener(
       .fuse();
     pin_mut!(close_ft);
 
+    let unresponsive_ft =
+      detect_unresponsive(&inner, &server, || oldest_command_start(&unresponsive_commands.lock())).fuse();
+    pin_mut!(unresponsive_ft);
+
     let result = select! {
       close_res = close_ft => close_res,
-      stream_res = stream_ft => stream_res
+      stream_res = stream_ft => stream_res,
+      unresponsive_res = unresponsive_ft => unresponsive_res
     };
     let error = result
       .and_then(|_| Err::<(), _>(RedisError::new_canceled()))
@@ -842,12 +1168,12 @@ pub fn spawn_centralized_listener(
 
     if client_utils::read_client_state(&inner.state) == ClientState::Disconnecting {
       // client was closed intentionally via Quit
-      client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+      client_utils::set_client_state(&inner, ClientState::Disconnected);
       return Ok(());
     }
 
     _debug!(inner, "Redis frame stream closed with error {:?}", error);
-    client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+    client_utils::set_client_state(&inner, ClientState::Disconnected);
     emit_connection_closed(&inner, &connections, &server, error);
 
     Ok::<(), RedisError>(())
@@ -869,13 +1195,35 @@ pub async fn connect_centralized(
     ref connection_id,
   } = connections
   {
-    let addr = protocol_utils::read_centralized_addr(&inner).await?;
+    let unix_path = protocol_utils::read_unix_path(&inner.config);
     let uses_tls = protocol_utils::uses_tls(inner);
-    client_utils::set_client_state(&inner.state, ClientState::Connecting);
+    client_utils::set_connecting_state(&inner);
+
+    let (sink, stream) = if let Some(path) = unix_path {
+      _trace!(inner, "Connecting to {}", path.display());
+      let transport = connection::create_unix_transport(inner, &path).await?;
+      let transport = match transport {
+        #[cfg(unix)]
+        RedisTransport::Unix(socket) => {
+          let socket = match connection::read_client_id(inner, socket).await {
+            Ok((id, socket)) => {
+              if let Some(id) = id {
+                connection_id.write().replace(id);
+              }
+              socket
+            }
+            Err((_, socket)) => socket,
+          };
+          RedisTransport::Unix(connection::read_and_set_server_version(inner, socket).await)
+        }
+        transport => transport,
+      };
 
-    let (sink, stream) = if uses_tls {
+      connection::split_transport(transport)
+    } else if uses_tls {
+      let addr = protocol_utils::read_centralized_addr(&inner).await?;
       let domain = protocol_utils::read_centralized_domain(&inner.config)?;
-      _trace!(inner, "Connecting to {} with domain {}", addr, domain);
+      _trace!(inner, "Connecting to {} with domain {}", addr.server, domain);
       let socket = connection::create_authenticated_connection_tls(&addr, &domain, inner).await?;
       let socket = match connection::read_client_id(inner, socket).await {
         Ok((id, socket)) => {
@@ -886,11 +1234,13 @@ pub async fn connect_centralized(
         }
         Err((_, socket)) => socket,
       };
+      let socket = connection::read_and_set_server_version(inner, socket).await;
 
       let (sink, stream) = socket.split();
       (RedisSink::Tls(sink), RedisStream::Tls(stream))
     } else {
-      _trace!(inner, "Connecting to {}", addr);
+      let addr = protocol_utils::read_centralized_addr(&inner).await?;
+      _trace!(inner, "Connecting to {}", addr.server);
       let socket = connection::create_authenticated_connection(&addr, inner).await?;
       let socket = match connection::read_client_id(inner, socket).await {
         Ok((id, socket)) => {
@@ -901,6 +1251,7 @@ pub async fn connect_centralized(
         }
         Err((_, socket)) => socket,
       };
+      let socket = connection::read_and_set_server_version(inner, socket).await;
 
       let (sink, stream) = socket.split();
       (RedisSink::Tcp(sink), RedisStream::Tcp(stream))
@@ -914,7 +1265,7 @@ pub async fn connect_centralized(
     let server = server.read().await.clone();
 
     spawn_centralized_listener(inner, &server, connections, tx.subscribe(), commands, counters, stream);
-    client_utils::set_client_state(&inner.state, ClientState::Connected);
+    client_utils::set_client_state(&inner, ClientState::Connected);
 
     Ok(pending_commands)
   } else {
@@ -931,17 +1282,17 @@ pub fn check_mget_cluster_keys(multiplexer: &Multiplexer, keys: &Vec<RedisValue>
     let mut nodes = BTreeSet::new();
 
     for key in keys.iter() {
-      let key_str = match key.as_str() {
-        Some(s) => s,
+      let key_bytes = match key.as_bytes() {
+        Some(b) => b,
         None => return Err(RedisError::new(RedisErrorKind::InvalidArgument, "Expected key string.")),
       };
-      let hash_slot = redis_protocol::redis_keyslot(&key_str);
+      let hash_slot = redis_keyslot(key_bytes);
       let server = match cache.read().get_server(hash_slot) {
         Some(s) => s.id.clone(),
         None => {
           return Err(RedisError::new(
             RedisErrorKind::InvalidArgument,
-            format!("Failed to find cluster node for {}", key_str),
+            format!("Failed to find cluster node for {}", String::from_utf8_lossy(key_bytes)),
           ))
         }
       };
@@ -974,17 +1325,17 @@ pub fn check_mset_cluster_keys(multiplexer: &Multiplexer, args: &Vec<RedisValue>
     let mut nodes = BTreeSet::new();
 
     for chunk in args.chunks(2) {
-      let key = match chunk[0].as_str() {
-        Some(s) => s,
+      let key = match chunk[0].as_bytes() {
+        Some(b) => b,
         None => return Err(RedisError::new(RedisErrorKind::InvalidArgument, "Expected key string.")),
       };
-      let hash_slot = redis_protocol::redis_keyslot(&key);
+      let hash_slot = redis_keyslot(key);
       let server = match cache.read().get_server(hash_slot) {
         Some(s) => s.id.clone(),
         None => {
           return Err(RedisError::new(
             RedisErrorKind::InvalidArgument,
-            format!("Failed to find cluster node for {}", key),
+            format!("Failed to find cluster node for {}", String::from_utf8_lossy(key)),
           ))
         }
       };
@@ -1045,8 +1396,11 @@ async fn remove_server(
   _debug!(inner, "Removing clustered connection to server {}", server);
   let commands = {
     let _ = { writers.write().await.remove(server) };
-    let _ = { counters.write().remove(server) };
+    if let Some(counters) = { counters.write().remove(server) } {
+      counters.reset_in_flight();
+    }
     let _ = { connection_ids.write().remove(server) };
+    let _ = { inner.backchannel.write().await.remove_transport(server) };
     commands.lock().remove(server)
   };
 
@@ -1083,30 +1437,25 @@ async fn add_server(
 
   insert_locked_map_mutex(commands, server.clone(), VecDeque::new());
   insert_locked_map_async(writers, server.clone(), sink).await;
-  insert_locked_map(counters, server.clone(), Counters::new(&inner.cmd_buffer_len));
+  if let Some(counters) = insert_locked_map(counters, server.clone(), Counters::new(inner)) {
+    counters.reset_in_flight();
+  }
   spawn_clustered_listener(inner, connections, commands, counters, tx.subscribe(), &server, stream);
   Ok(())
 }
 
 /// Read the offset of the existing backchannel server in `servers`, if found.
 async fn existing_backchannel_connection(inner: &Arc<RedisClientInner>, servers: &Vec<Arc<String>>) -> Option<usize> {
-  if let Some((_, ref backchannel_server)) = inner.backchannel.read().await.transport {
-    let mut swap = None;
-
-    for (idx, server) in servers.iter().enumerate() {
-      if idx != 0 && server == backchannel_server {
-        swap = Some(idx);
-        break;
-      }
-    }
+  let backchannel = inner.backchannel.read().await;
 
-    swap
-  } else {
-    None
-  }
+  servers
+    .iter()
+    .enumerate()
+    .find(|(idx, server)| *idx != 0 && backchannel.has_transport(server))
+    .map(|(idx, _)| idx)
 }
 
-async fn cluster_nodes_backchannel(inner: &Arc<RedisClientInner>) -> Result<ClusterKeyCache, RedisError> {
+async fn cluster_state_backchannel(inner: &Arc<RedisClientInner>) -> Result<ClusterKeyCache, RedisError> {
   let mut servers: Vec<Arc<String>> = inner
     .config
     .read()
@@ -1121,9 +1470,27 @@ async fn cluster_nodes_backchannel(inner: &Arc<RedisClientInner>) -> Result<Clus
   }
 
   for server in servers.into_iter() {
-    let cmd = RedisCommand::new(RedisCommandKind::ClusterNodes, vec![], None);
+    let cmd = RedisCommand::new(RedisCommandKind::ClusterSlots, vec![], None);
     let mut backchannel = inner.backchannel.write().await;
 
+    _debug!(inner, "Reading cluster slots on backchannel: {}", server);
+    match backchannel.request_response(inner, &server, cmd).await {
+      Ok(frame) => {
+        let host = server.rsplitn(2, ":").last().unwrap_or("");
+
+        match ClusterKeyCache::from_cluster_slots(frame, host) {
+          Ok(state) => return Ok(state),
+          Err(e) => _debug!(inner, "Failed to read cluster slots, falling back to cluster nodes: {:?}", e),
+        }
+      }
+      Err(e) => {
+        _warn!(inner, "Error creating or using backchannel for cluster slots: {:?}", e);
+        continue;
+      }
+    };
+
+    let cmd = RedisCommand::new(RedisCommandKind::ClusterNodes, vec![], None);
+
     _debug!(inner, "Reading cluster nodes on backchannel: {}", server);
     let frame = match backchannel.request_response(inner, &server, cmd).await {
       Ok(frame) => frame,
@@ -1175,7 +1542,7 @@ pub async fn sync_cluster(
   } = connections
   {
     let cluster_state = {
-      let state = cluster_nodes_backchannel(inner).await?;
+      let state = cluster_state_backchannel(inner).await?;
       let mut old_cache = cache.write();
       *old_cache = state.clone();
       state
@@ -1213,6 +1580,7 @@ pub async fn sync_cluster(
 #[cfg(test)]
 mod tests {
   use super::*;
+  use std::sync::atomic::AtomicBool;
   use std::time::Instant;
   use tokio;
 
@@ -1227,6 +1595,12 @@ mod tests {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      options: None,
+      redirection: None,
+      redirections: 0,
+      busy_retries: 0,
+      ran_hooks: false,
+      in_flight: Arc::new(AtomicBool::new(false)),
       #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
       traces: CommandTraces::default(),
     };
@@ -1,24 +1,48 @@
 use crate::error::RedisError;
-use crate::modules::inner::RedisClientInner;
+use crate::modules::cache::ClientCache;
+use crate::modules::inner::{PushSenders, RedisClientInner};
+use crate::protocol::types::ResponseFrame;
 use crate::protocol::utils as protocol_utils;
 use bytes::BytesMut;
 use redis_protocol::resp2::decode::decode as resp2_decode;
 use redis_protocol::resp2::encode::encode_bytes as resp2_encode;
 use redis_protocol::resp2::types::Frame as Resp2Frame;
+use redis_protocol::resp3::decode::streaming::decode as resp3_decode;
+use redis_protocol::resp3::types::{Frame as Resp3Frame, RespVersion, StreamedFrame};
+use redis_protocol::types::{RedisProtocolError, RedisProtocolErrorKind};
+use std::collections::HashMap;
+use parking_lot::RwLock;
+use std::str;
 use std::sync::Arc;
 use tokio_util::codec::{Decoder, Encoder};
 
+#[cfg(feature = "network-logs")]
+use redis_protocol::resp3::types::{FrameMap, VerbatimStringFormat};
+
 #[cfg(feature = "blocking-encoding")]
 use crate::globals::globals;
 #[cfg(feature = "metrics")]
-use crate::modules::metrics::MovingStats;
-#[cfg(feature = "metrics")]
-use parking_lot::RwLock;
-#[cfg(feature = "network-logs")]
-use std::str;
+use crate::modules::metrics::{ConnectionCounters, MovingStats};
+
+const RESP2_NULL_BULK: &[u8] = b"$-1\r\n";
+const RESP2_NULL_ARRAY: &[u8] = b"*-1\r\n";
+const INVALIDATE_PUSH_KIND: &str = "invalidate";
+const PUBSUB_PUSH_KINDS: [&str; 9] = [
+  "subscribe",
+  "unsubscribe",
+  "psubscribe",
+  "punsubscribe",
+  "ssubscribe",
+  "sunsubscribe",
+  "message",
+  "pmessage",
+  "smessage",
+];
 
 #[cfg(not(feature = "network-logs"))]
 fn log_resp2_frame(_: &str, _: &Resp2Frame, _: bool) {}
+#[cfg(not(feature = "network-logs"))]
+fn log_resp3_frame(_: &str, _: &Resp3Frame, _: bool) {}
 
 #[cfg(feature = "network-logs")]
 #[derive(Debug)]
@@ -26,7 +50,29 @@ enum DebugFrame {
   String(String),
   Bytes(Vec<u8>),
   Integer(i64),
+  Double(f64),
+  Boolean(bool),
   Array(Vec<DebugFrame>),
+  Set(Vec<DebugFrame>),
+  Push(Vec<DebugFrame>),
+  Map(Vec<(DebugFrame, DebugFrame)>),
+  Attributed {
+    frame: Box<DebugFrame>,
+    attributes: Vec<(DebugFrame, DebugFrame)>,
+  },
+}
+
+#[cfg(feature = "network-logs")]
+fn bytes_to_debug_frame(b: &[u8]) -> DebugFrame {
+  match str::from_utf8(b) {
+    Ok(s) => DebugFrame::String(s.to_owned()),
+    Err(_) => DebugFrame::Bytes(b.to_vec()),
+  }
+}
+
+#[cfg(feature = "network-logs")]
+fn map_to_debug_frame(map: &FrameMap) -> Vec<(DebugFrame, DebugFrame)> {
+  map.iter().map(|(k, v)| (k.into(), v.into())).collect()
 }
 
 #[cfg(feature = "network-logs")]
@@ -35,16 +81,61 @@ impl<'a> From<&'a Resp2Frame> for DebugFrame {
     match f {
       Resp2Frame::Error(s) | Resp2Frame::SimpleString(s) => DebugFrame::String(s.to_owned()),
       Resp2Frame::Integer(i) => DebugFrame::Integer(*i),
-      Resp2Frame::BulkString(b) => match str::from_utf8(b) {
-        Ok(s) => DebugFrame::String(s.to_owned()),
-        Err(_) => DebugFrame::Bytes(b.to_vec()),
-      },
+      Resp2Frame::BulkString(b) => bytes_to_debug_frame(b),
       Resp2Frame::Null => DebugFrame::String("nil".into()),
       Resp2Frame::Array(frames) => DebugFrame::Array(frames.iter().map(|f| f.into()).collect()),
     }
   }
 }
 
+#[cfg(feature = "network-logs")]
+impl<'a> From<&'a Resp3Frame> for DebugFrame {
+  fn from(f: &'a Resp3Frame) -> Self {
+    let frame = match f {
+      Resp3Frame::BlobString { data, .. }
+      | Resp3Frame::BlobError { data, .. }
+      | Resp3Frame::BigNumber { data, .. }
+      | Resp3Frame::ChunkedString(data) => bytes_to_debug_frame(data),
+      Resp3Frame::VerbatimString { data, format, .. } => match str::from_utf8(data) {
+        Ok(s) => {
+          let prefix = match format {
+            VerbatimStringFormat::Text => "txt",
+            VerbatimStringFormat::Markdown => "mkd",
+          };
+          DebugFrame::String(format!("{}:{}", prefix, s))
+        }
+        Err(_) => DebugFrame::Bytes(data.to_vec()),
+      },
+      Resp3Frame::SimpleString { data, .. } | Resp3Frame::SimpleError { data, .. } => {
+        DebugFrame::String(data.to_owned())
+      }
+      Resp3Frame::Number { data, .. } => DebugFrame::Integer(*data),
+      Resp3Frame::Double { data, .. } => DebugFrame::Double(*data),
+      Resp3Frame::Boolean { data, .. } => DebugFrame::Boolean(*data),
+      Resp3Frame::Null => DebugFrame::String("nil".into()),
+      Resp3Frame::Array { data, .. } => DebugFrame::Array(data.iter().map(|f| f.into()).collect()),
+      Resp3Frame::Push { data, .. } => DebugFrame::Push(data.iter().map(|f| f.into()).collect()),
+      Resp3Frame::Set { data, .. } => DebugFrame::Set(data.iter().map(|f| f.into()).collect()),
+      Resp3Frame::Map { data, .. } => DebugFrame::Map(map_to_debug_frame(data)),
+      Resp3Frame::Hello { version, .. } => DebugFrame::String(format!("HELLO {}", version.to_byte() as char)),
+    };
+
+    match f.attributes() {
+      Some(attributes) => DebugFrame::Attributed {
+        frame: Box::new(frame),
+        attributes: map_to_debug_frame(attributes),
+      },
+      None => frame,
+    }
+  }
+}
+
+#[cfg(feature = "network-logs")]
+fn log_resp3_frame(name: &str, frame: &Resp3Frame, encode: bool) {
+  let prefix = if encode { "Encoded" } else { "Decoded" };
+  trace!("{}: {} {:?}", name, prefix, DebugFrame::from(frame))
+}
+
 #[cfg(feature = "network-logs")]
 fn log_resp2_frame(name: &str, frame: &Resp2Frame, encode: bool) {
   let prefix = if encode { "Encoded" } else { "Decoded" };
@@ -58,13 +149,32 @@ fn sample_stats(codec: &RedisCodec, decode: bool, value: i64) {
   } else {
     codec.req_size_stats.write().sample(value);
   }
+  codec.connection_stats.sample(decode, value as u64);
 }
 
 #[cfg(not(feature = "metrics"))]
 fn sample_stats(_: &RedisCodec, _: bool, _: i64) {}
 
-fn resp2_encode_frame(codec: &RedisCodec, item: Resp2Frame, dst: &mut BytesMut) -> Result<(), RedisError> {
+/// Rewrite the command name in an outgoing frame if the command was renamed on the server.
+fn rename_command(codec: &RedisCodec, item: &mut Resp2Frame) {
+  if let Some(ref renamed) = codec.renamed_commands {
+    if let Resp2Frame::Array(ref mut frames) = item {
+      if let Some(Resp2Frame::BulkString(ref mut cmd)) = frames.first_mut() {
+        let new_name = str::from_utf8(cmd)
+          .ok()
+          .and_then(|name| renamed.get(&name.to_uppercase()));
+
+        if let Some(new_name) = new_name {
+          *cmd = new_name.as_bytes().to_vec();
+        }
+      }
+    }
+  }
+}
+
+fn resp2_encode_frame(codec: &RedisCodec, mut item: Resp2Frame, dst: &mut BytesMut) -> Result<(), RedisError> {
   let offset = dst.len();
+  rename_command(codec, &mut item);
 
   let res = resp2_encode(dst, &item)?;
   let len = res.saturating_sub(offset);
@@ -100,20 +210,168 @@ fn resp2_decode_frame(codec: &RedisCodec, src: &mut BytesMut) -> Result<Option<R
   }
 }
 
+/// Decode a RESP3 frame, buffering streamed aggregate types and chunked strings until the stream finishes.
+fn resp3_decode_complete_frame(codec: &mut RedisCodec, src: &mut BytesMut) -> Result<Option<Resp3Frame>, RedisError> {
+  // keep reading while inside a stream since the framed reader waits for more bytes from the socket when this returns `None`
+  let frame = loop {
+    if src.is_empty() {
+      return Ok(None);
+    }
+    if codec.decoder_stream.is_none() && (src.starts_with(RESP2_NULL_BULK) || src.starts_with(RESP2_NULL_ARRAY)) {
+      // RESP2 null values use a length of -1, which the RESP3 decoder would read as the start of a stream
+      let _ = src.split_to(RESP2_NULL_BULK.len());
+      sample_stats(codec, true, RESP2_NULL_BULK.len() as i64);
+      break Resp3Frame::Null;
+    }
+    let (frame, amt) = match resp3_decode(src)? {
+      Some(result) => result,
+      None => return Ok(None),
+    };
+    trace!("{}: Parsed {} bytes from {}", codec.name, amt, codec.server);
+    sample_stats(codec, true, amt as i64);
+    let _ = src.split_to(amt);
+
+    if let Some(ref mut streamed_frame) = codec.decoder_stream {
+      if frame.is_streaming() {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          "Cannot start a stream while already inside a stream.",
+        )
+        .into());
+      }
+
+      streamed_frame.add_frame(frame.into_complete_frame()?);
+      if streamed_frame.is_finished() {
+        let frame = streamed_frame.into_frame()?;
+        codec.decoder_stream = None;
+        break frame;
+      }
+    } else if frame.is_streaming() {
+      codec.decoder_stream = Some(frame.into_streaming_frame()?);
+    } else {
+      break frame.into_complete_frame()?;
+    }
+  };
+
+  Ok(Some(frame))
+}
+
+/// Send a push frame to the `on_push` listeners, returning whether the frame should also be returned by the decoder.
+///
+/// Pubsub push frames are still returned so that subscription responses and messages use the pubsub interface.
+fn route_push_frame(codec: &RedisCodec, frame: &Resp3Frame) -> bool {
+  let data = match frame {
+    Resp3Frame::Push { ref data, .. } => data,
+    _ => return true,
+  };
+  let message = match protocol_utils::resp3_push_to_message(&codec.server, data.clone()) {
+    Ok(message) => message,
+    Err(e) => {
+      warn!("{}: Failed to parse push frame from {}: {:?}", codec.name, codec.server, e);
+      return false;
+    }
+  };
+  let is_pubsub = PUBSUB_PUSH_KINDS.contains(&message.kind.as_str());
+  if message.kind == INVALIDATE_PUSH_KIND {
+    if let Some(keys) = message.data.first() {
+      codec.cache.invalidate_value(keys);
+    }
+  }
+
+  let mut closed = false;
+  for tx in codec.push_tx.read().iter() {
+    closed |= tx.send(message.clone()).is_err();
+  }
+  if closed {
+    trace!("{}: Removing closed push listeners.", codec.name);
+    codec.push_tx.write().retain(|tx| !tx.is_closed());
+  }
+
+  is_pubsub
+}
+
+/// Decode a RESP3 frame, routing out-of-band push frames to any `on_push` listeners.
+///
+/// Frames are converted to the equivalent RESP2 frame before being returned, keeping the original RESP3 frame if the
+/// conversion loses any type information.
+fn resp3_decode_frame(codec: &mut RedisCodec, src: &mut BytesMut) -> Result<Option<ResponseFrame>, RedisError> {
+  trace!("{}: Recv {} bytes from {}.", codec.name, src.len(), codec.server);
+
+  // push frames are not responses to a command, so keep reading after routing them elsewhere
+  loop {
+    let frame = match resp3_decode_complete_frame(codec, src)? {
+      Some(frame) => frame,
+      None => return Ok(None),
+    };
+    log_resp3_frame(&codec.name, &frame, false);
+    if !route_push_frame(codec, &frame) {
+      continue;
+    }
+
+    let resp3 = if protocol_utils::resp3_has_typed_data(&frame) {
+      Some(frame.clone())
+    } else {
+      None
+    };
+    let frame = protocol_utils::check_auth_error(protocol_utils::resp3_to_resp2(frame));
+    return Ok(Some(ResponseFrame { frame, resp3 }));
+  }
+}
+
 pub struct RedisCodec {
   pub name: Arc<String>,
   pub server: String,
+  pub renamed_commands: Option<HashMap<String, String>>,
+  /// The RESP version used to decode frames, shared with the client.
+  pub version: Arc<RwLock<RespVersion>>,
+  /// The buffered contents of a streamed RESP3 frame.
+  pub decoder_stream: Option<StreamedFrame>,
+  /// Senders for RESP3 push messages, shared with the client.
+  pub push_tx: PushSenders,
+  /// The client-side cache, invalidated by `invalidate` push messages.
+  pub cache: Arc<ClientCache>,
   #[cfg(feature = "metrics")]
   pub req_size_stats: Arc<RwLock<MovingStats>>,
   #[cfg(feature = "metrics")]
   pub res_size_stats: Arc<RwLock<MovingStats>>,
+  #[cfg(feature = "metrics")]
+  pub connection_stats: Arc<ConnectionCounters>,
 }
 
 impl RedisCodec {
   pub fn new(inner: &Arc<RedisClientInner>, server: String) -> Self {
+    let renamed_commands = {
+      let config_guard = inner.config.read();
+
+      if config_guard.renamed_commands.is_empty() {
+        None
+      } else {
+        let renamed = config_guard
+          .renamed_commands
+          .iter()
+          .map(|(k, v)| (k.to_uppercase(), v.to_owned()))
+          .collect();
+        Some(renamed)
+      }
+    };
+
+    #[cfg(feature = "metrics")]
+    let connection_stats = {
+      let counters = inner.connection_counters(&server);
+      counters.set_connected(true);
+      counters
+    };
+
     RedisCodec {
+      #[cfg(feature = "metrics")]
+      connection_stats,
       server,
+      renamed_commands,
       name: inner.id.clone(),
+      version: inner.resp_version.clone(),
+      decoder_stream: None,
+      push_tx: inner.push_tx.clone(),
+      cache: inner.cache.clone(),
       #[cfg(feature = "metrics")]
       req_size_stats: inner.req_size_stats.clone(),
       #[cfg(feature = "metrics")]
@@ -122,6 +380,13 @@ impl RedisCodec {
   }
 }
 
+#[cfg(feature = "metrics")]
+impl Drop for RedisCodec {
+  fn drop(&mut self) {
+    self.connection_stats.set_connected(false);
+  }
+}
+
 impl Encoder<Resp2Frame> for RedisCodec {
   type Error = RedisError;
 
@@ -143,22 +408,207 @@ impl Encoder<Resp2Frame> for RedisCodec {
   }
 }
 
+impl RedisCodec {
+  fn decode_frame(&mut self, src: &mut BytesMut) -> Result<Option<ResponseFrame>, RedisError> {
+    if *self.version.read() == RespVersion::RESP3 || self.decoder_stream.is_some() {
+      resp3_decode_frame(self, src)
+    } else {
+      resp2_decode_frame(self, src).map(|frame| frame.map(ResponseFrame::from))
+    }
+  }
+}
+
 impl Decoder for RedisCodec {
-  type Item = Resp2Frame;
+  type Item = ResponseFrame;
   type Error = RedisError;
 
   #[cfg(not(feature = "blocking-encoding"))]
   fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-    resp2_decode_frame(&self, src)
+    self.decode_frame(src)
   }
 
   #[cfg(feature = "blocking-encoding")]
   fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
     if src.len() >= globals().blocking_encode_threshold() {
       trace!("{}: Decoding in blocking task with size {}", self.name, src.len());
-      tokio::task::block_in_place(|| resp2_decode_frame(&self, src))
+      tokio::task::block_in_place(|| self.decode_frame(src))
     } else {
-      resp2_decode_frame(&self, src)
+      self.decode_frame(src)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::modules::inner::RedisClientInner;
+  use crate::types::{PushMessage, RedisConfig, RedisValue};
+
+  fn resp3_codec() -> RedisCodec {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    *inner.resp_version.write() = RespVersion::RESP3;
+    RedisCodec::new(&inner, "127.0.0.1:6379".into())
+  }
+
+  fn decode_all(codec: &mut RedisCodec, bytes: &[u8]) -> Vec<Resp2Frame> {
+    decode_all_responses(codec, bytes)
+      .into_iter()
+      .map(|frame| frame.into_resp2())
+      .collect()
+  }
+
+  fn decode_all_responses(codec: &mut RedisCodec, bytes: &[u8]) -> Vec<ResponseFrame> {
+    let mut src = BytesMut::from(bytes);
+    let mut out = Vec::new();
+    while let Some(frame) = codec.decode(&mut src).unwrap() {
+      out.push(frame);
     }
+    out
+  }
+
+  #[test]
+  fn should_decode_resp3_map() {
+    let mut codec = resp3_codec();
+    let frames = decode_all(&mut codec, b"%1\r\n+foo\r\n#t\r\n");
+
+    assert_eq!(frames, vec![Resp2Frame::Array(vec![
+      Resp2Frame::SimpleString("foo".into()),
+      Resp2Frame::Integer(1)
+    ])]);
+  }
+
+  #[test]
+  fn should_keep_typed_resp3_frames() {
+    let mut codec = resp3_codec();
+    let frames = decode_all_responses(&mut codec, b"*2\r\n,1.5\r\n#f\r\n*1\r\n:1\r\n");
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(
+      frames[0].frame,
+      Resp2Frame::Array(vec![Resp2Frame::BulkString("1.5".into()), Resp2Frame::Integer(0)])
+    );
+    assert_eq!(
+      protocol_utils::response_frame_to_results(frames[0].clone()).unwrap(),
+      RedisValue::Array(vec![RedisValue::Double(1.5), RedisValue::Boolean(false)])
+    );
+    assert!(frames[1].resp3.is_none());
+  }
+
+  #[test]
+  fn should_decode_resp3_streamed_array() {
+    let mut codec = resp3_codec();
+    assert!(decode_all(&mut codec, b"*?\r\n:1\r\n:2\r\n").is_empty());
+    assert!(codec.decoder_stream.is_some());
+    let frames = decode_all(&mut codec, b":3\r\n.\r\n");
+
+    assert_eq!(frames, vec![Resp2Frame::Array(vec![
+      Resp2Frame::Integer(1),
+      Resp2Frame::Integer(2),
+      Resp2Frame::Integer(3)
+    ])]);
+    assert!(codec.decoder_stream.is_none());
+  }
+
+  #[test]
+  fn should_decode_resp2_nulls_in_resp3_mode() {
+    let mut codec = resp3_codec();
+    let frames = decode_all(&mut codec, b"$-1\r\n*-1\r\n_\r\n");
+
+    assert_eq!(frames, vec![Resp2Frame::Null, Resp2Frame::Null, Resp2Frame::Null]);
+  }
+
+  #[test]
+  fn should_decode_resp3_chunked_string() {
+    let mut codec = resp3_codec();
+    let frames = decode_all(&mut codec, b"$?\r\n;3\r\nfoo\r\n;3\r\nbar\r\n;0\r\n");
+
+    assert_eq!(frames, vec![Resp2Frame::BulkString("foobar".into())]);
+  }
+
+  #[test]
+  fn should_route_push_frames_to_listeners() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    *inner.resp_version.write() = RespVersion::RESP3;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    inner.push_tx.write().push_back(tx);
+    let mut codec = RedisCodec::new(&inner, "127.0.0.1:6379".into());
+
+    let frames = decode_all(&mut codec, b">2\r\n+invalidate\r\n*1\r\n$3\r\nfoo\r\n:1\r\n");
+    assert_eq!(frames, vec![Resp2Frame::Integer(1)]);
+    assert_eq!(rx.try_recv().unwrap(), PushMessage {
+      server: "127.0.0.1:6379".into(),
+      kind: "invalidate".into(),
+      data: vec![RedisValue::Array(vec!["foo".into()])],
+    });
+
+    let frames = decode_all(&mut codec, b">3\r\n$7\r\nmessage\r\n$3\r\nbar\r\n$3\r\nbaz\r\n");
+    assert_eq!(frames, vec![Resp2Frame::Array(vec![
+      Resp2Frame::BulkString("message".into()),
+      Resp2Frame::BulkString("bar".into()),
+      Resp2Frame::BulkString("baz".into())
+    ])]);
+    assert_eq!(rx.try_recv().unwrap().kind, "message");
+  }
+
+  #[test]
+  #[cfg(feature = "network-logs")]
+  fn should_convert_resp3_frames_to_debug_frames() {
+    let mut attributes = FrameMap::new();
+    attributes.insert(
+      Resp3Frame::SimpleString {
+        data: "ttl".into(),
+        attributes: None,
+      },
+      Resp3Frame::Number {
+        data: 10,
+        attributes: None,
+      },
+    );
+    let frame = Resp3Frame::Push {
+      data: vec![
+        Resp3Frame::BlobString {
+          data: "invalidate".into(),
+          attributes: None,
+        },
+        Resp3Frame::Double {
+          data: 1.5,
+          attributes: None,
+        },
+      ],
+      attributes: Some(attributes),
+    };
+
+    assert_eq!(
+      format!("{:?}", DebugFrame::from(&frame)),
+      "Attributed { frame: Push([String(\"invalidate\"), Double(1.5)]), attributes: [(String(\"ttl\"), Integer(10))] }"
+    );
+  }
+
+  #[test]
+  fn should_encode_renamed_commands() {
+    let mut config = RedisConfig::default();
+    config.renamed_commands.insert("config".into(), "CONFIG-a1b2".into());
+    let inner = RedisClientInner::new(config);
+    let mut codec = RedisCodec::new(&inner, "127.0.0.1:6379".into());
+
+    let mut dst = BytesMut::new();
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString("CONFIG".into()),
+      Resp2Frame::BulkString("GET".into()),
+      Resp2Frame::BulkString("maxmemory".into()),
+    ]);
+    codec.encode(frame, &mut dst).unwrap();
+    assert_eq!(
+      &dst[..],
+      &b"*3\r\n$11\r\nCONFIG-a1b2\r\n$3\r\nGET\r\n$9\r\nmaxmemory\r\n"[..]
+    );
+
+    let mut dst = BytesMut::new();
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString("GET".into()),
+      Resp2Frame::BulkString("CONFIG".into()),
+    ]);
+    codec.encode(frame, &mut dst).unwrap();
+    assert_eq!(&dst[..], &b"*2\r\n$3\r\nGET\r\n$6\r\nCONFIG\r\n"[..]);
   }
 }
@@ -1,36 +1,40 @@
+use crate::commands;
 use crate::error::{RedisError, RedisErrorKind};
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::Counters;
 use crate::protocol::codec::RedisCodec;
-use crate::protocol::types::{ClusterKeyCache, RedisCommand, RedisCommandKind};
+use crate::globals::globals;
+use crate::protocol::types::{ClusterKeyCache, RedisCommand, RedisCommandKind, ResolvedAddrs};
 use crate::protocol::utils as protocol_utils;
 use crate::protocol::utils::pretty_error;
-use crate::types::{ClientState, InfoKind, Resolve};
+use crate::types::{ClientState, InfoKind, Resolve, RespVersion, TcpConfig};
 use crate::utils as client_utils;
 use futures::sink::SinkExt;
 use futures::stream::{SplitSink, SplitStream, StreamExt};
 use redis_protocol::resp2::types::Frame as ProtocolFrame;
 use semver::Version;
-use std::net::SocketAddr;
+use socket2::{SockRef, TcpKeepalive};
+use std::path::Path;
+use std::str;
 use std::sync::Arc;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 use tokio_util::codec::Framed;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::protocol::tls;
-#[cfg(feature = "monitor")]
+#[cfg(any(feature = "monitor", feature = "replica"))]
 use crate::types::ServerConfig;
-#[cfg(feature = "enable-tls")]
-use tokio_native_tls::TlsStream;
 
 /// The contents of a simplestring OK response.
 pub const OK: &'static str = "OK";
 
 pub type FramedTcp = Framed<TcpStream, RedisCodec>;
-#[cfg(feature = "enable-tls")]
-pub type FramedTls = Framed<TlsStream<TcpStream>, RedisCodec>;
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+pub type FramedTls = Framed<tls::TlsStream, RedisCodec>;
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub type FramedTls = FramedTcp;
 
 pub type TcpRedisReader = SplitStream<FramedTcp>;
@@ -39,19 +43,32 @@ pub type TcpRedisWriter = SplitSink<FramedTcp, ProtocolFrame>;
 pub type TlsRedisReader = SplitStream<FramedTls>;
 pub type TlsRedisWriter = SplitSink<FramedTls, ProtocolFrame>;
 
+#[cfg(unix)]
+pub type FramedUnix = Framed<UnixStream, RedisCodec>;
+#[cfg(unix)]
+pub type UnixRedisReader = SplitStream<FramedUnix>;
+#[cfg(unix)]
+pub type UnixRedisWriter = SplitSink<FramedUnix, ProtocolFrame>;
+
 pub enum RedisStream {
   Tls(TlsRedisReader),
   Tcp(TcpRedisReader),
+  #[cfg(unix)]
+  Unix(UnixRedisReader),
 }
 
 pub enum RedisSink {
   Tls(TlsRedisWriter),
   Tcp(TcpRedisWriter),
+  #[cfg(unix)]
+  Unix(UnixRedisWriter),
 }
 
 pub enum RedisTransport {
   Tls(FramedTls),
   Tcp(FramedTcp),
+  #[cfg(unix)]
+  Unix(FramedUnix),
 }
 
 pub fn split_transport(transport: RedisTransport) -> (RedisSink, RedisStream) {
@@ -64,6 +81,11 @@ pub fn split_transport(transport: RedisTransport) -> (RedisSink, RedisStream) {
       let (sink, stream) = framed.split();
       (RedisSink::Tls(sink), RedisStream::Tls(stream))
     }
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => {
+      let (sink, stream) = framed.split();
+      (RedisSink::Unix(sink), RedisStream::Unix(stream))
+    }
   }
 }
 
@@ -79,7 +101,7 @@ where
   let (response, transport) = transport.into_future().await;
 
   let response = match response {
-    Some(result) => result?,
+    Some(result) => result?.into_resp2(),
     None => ProtocolFrame::Null,
   };
   Ok((response, transport))
@@ -103,7 +125,7 @@ where
 
   let response = match response {
     Some(result) => match result {
-      Ok(frame) => frame,
+      Ok(frame) => frame.into_resp2(),
       Err(e) => return Err((e, transport)),
     },
     None => ProtocolFrame::Null,
@@ -131,6 +153,14 @@ pub async fn transport_request_response(
       };
       Ok((frame, RedisTransport::Tls(transport)))
     }
+    #[cfg(unix)]
+    RedisTransport::Unix(transport) => {
+      let (frame, transport) = match request_response_safe(transport, request).await {
+        Ok(result) => result,
+        Err((e, _)) => return Err(e),
+      };
+      Ok((frame, RedisTransport::Unix(transport)))
+    }
   }
 }
 
@@ -189,6 +219,97 @@ where
   }
 }
 
+/// Send `HELLO 3` if the client is configured to use RESP3, falling back to RESP2 if the server rejects the command.
+///
+/// The codec on each connection reads the negotiated version from the client, so the version is set to RESP3 before the
+/// `HELLO` response is read.
+pub async fn negotiate_version<T>(
+  inner: &Arc<RedisClientInner>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  if inner.config.read().version != RespVersion::RESP3 {
+    return Ok(transport);
+  }
+
+  _debug!(inner, "Switching to RESP3 with HELLO.");
+  *inner.resp_version.write() = RespVersion::RESP3;
+  let command = RedisCommand::new(RedisCommandKind::Hello, vec![3.into()], None);
+  let (response, transport) = request_response(transport, &command).await?;
+
+  if let ProtocolFrame::Error(error) = response {
+    _warn!(inner, "Falling back to RESP2 after HELLO error: {}", error);
+    *inner.resp_version.write() = RespVersion::RESP2;
+  }
+  Ok(transport)
+}
+
+/// Send `CLIENT TRACKING ON` if the client is configured to use client-side caching, clearing the cache since
+/// invalidation messages may have been missed while the connection was closed.
+///
+/// With `RESP2` the invalidation messages are redirected to a separate connection to the same server.
+pub async fn enable_tracking<T>(
+  inner: &Arc<RedisClientInner>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let tracking = match inner.config.read().tracking {
+    Some(ref tracking) => tracking.clone(),
+    None => return Ok(transport),
+  };
+  let redirect = if *inner.resp_version.read() == RespVersion::RESP2 {
+    let server = transport.codec().server.clone();
+    Some(commands::client::invalidation_client_id(inner, &server).await?)
+  } else {
+    None
+  };
+  let args = protocol_utils::tracking_args(&tracking, true, redirect);
+
+  _debug!(inner, "Enabling client tracking.");
+  let command = RedisCommand::new(RedisCommandKind::ClientTracking, args, None);
+  let (response, transport) = request_response(transport, &command).await?;
+  if let ProtocolFrame::Error(error) = response {
+    return Err(pretty_error(&error));
+  }
+
+  inner.cache.clear();
+  Ok(transport)
+}
+
+/// Select the database from the client's config, if any, on a new connection to a centralized server, clearing the
+/// client-side cache afterwards.
+pub async fn select_database<T>(
+  inner: &Arc<RedisClientInner>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let db = if client_utils::is_clustered(&inner.config) {
+    None
+  } else {
+    inner.config.read().database
+  };
+  let db = match db {
+    Some(db) => db,
+    None => return Ok(transport),
+  };
+
+  _debug!(inner, "Selecting database {} after connecting.", db);
+  let command = RedisCommand::new(RedisCommandKind::Select, vec![db.into()], None);
+  let (response, transport) = request_response(transport, &command).await?;
+  if let ProtocolFrame::Error(error) = response {
+    return Err(pretty_error(&error));
+  }
+
+  inner.cache.clear();
+  Ok(transport)
+}
+
 pub async fn read_client_id<T>(
   inner: &Arc<RedisClientInner>,
   transport: Framed<T, RedisCodec>,
@@ -207,54 +328,160 @@ where
   Ok((id, transport))
 }
 
-#[cfg(feature = "enable-tls")]
+/// Apply the `nodelay` and `keepalive` options to a new socket.
+fn configure_tcp_socket(socket: &TcpStream, config: &TcpConfig) -> Result<(), RedisError> {
+  if let Some(nodelay) = config.nodelay {
+    socket.set_nodelay(nodelay)?;
+  }
+  if let Some(keepalive) = config.keepalive {
+    SockRef::from(socket).set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive))?;
+  }
+
+  Ok(())
+}
+
+/// Read the connection timeout, in milliseconds, for the attempt at `idx` out of `attempts`.
+///
+/// A `connect_timeout` on the [TcpConfig] applies to every attempt, otherwise each attempt except the last is limited by the
+/// [connection attempt timeout](crate::globals::get_connection_attempt_timeout_ms).
+fn connect_timeout_ms(config: &TcpConfig, idx: usize, attempts: usize) -> u64 {
+  match config.connect_timeout {
+    Some(timeout) => timeout.as_millis() as u64,
+    None if idx + 1 == attempts => 0,
+    None => globals().connection_attempt_timeout_ms() as u64,
+  }
+}
+
+/// Connect to the first reachable address for a server, returning the socket and the address that was used.
+///
+/// Each attempt is limited by the timeout described in [connect_timeout_ms], and the socket options from the client's
+/// [TcpConfig] are applied to the new socket.
+pub async fn tcp_connect(
+  inner: &Arc<RedisClientInner>,
+  addrs: &ResolvedAddrs,
+) -> Result<(TcpStream, String), RedisError> {
+  let config = inner.config.read().tcp.clone();
+  let mut last_error = None;
+
+  for (idx, addr) in addrs.addrs.iter().enumerate() {
+    let timeout = connect_timeout_ms(&config, idx, addrs.addrs.len());
+    let result = client_utils::apply_timeout(TcpStream::connect(addr), timeout).await;
+
+    match result {
+      Ok(socket) => {
+        _debug!(inner, "Connected to {} at {}", addrs.server, addr);
+        configure_tcp_socket(&socket, &config)?;
+        inner.connected_addrs.write().insert(addrs.server.clone(), *addr);
+        return Ok((socket, format!("{}:{}", addr.ip(), addr.port())));
+      }
+      Err(e) => {
+        _debug!(inner, "Failed to connect to {} at {}: {:?}", addrs.server, addr, e);
+        last_error = Some(e);
+      }
+    }
+  }
+
+  Err(last_error.unwrap_or(RedisError::new(
+    RedisErrorKind::IO,
+    format!("Failed to resolve {}", addrs.server),
+  )))
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub async fn create_authenticated_connection_tls(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   domain: &str,
   inner: &Arc<RedisClientInner>,
 ) -> Result<FramedTls, RedisError> {
-  let server = format!("{}:{}", addr.ip().to_string(), addr.port());
-  let codec = RedisCodec::new(inner, server);
   let client_name = inner.client_name();
   let password = inner.config.read().password.clone();
   let username = inner.config.read().username.clone();
 
-  let socket = TcpStream::connect(addr).await?;
-  let tls_stream = tls::create_tls_connector(&inner.config)?;
-  let socket = tls_stream.connect(domain, socket).await?;
+  let (socket, server) = tcp_connect(inner, addrs).await?;
+  let codec = RedisCodec::new(inner, server);
+  let tls_config = inner.config.read().tls.clone();
+  let socket = tls::connect(&inner.tls_connector, tls_config.as_ref(), domain, socket).await?;
   let framed = authenticate(Framed::new(socket, codec), &client_name, username, password).await?;
+  let framed = negotiate_version(inner, framed).await?;
+  let framed = enable_tracking(inner, framed).await?;
+  let framed = select_database(inner, framed).await?;
 
-  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  client_utils::set_client_state(&inner, ClientState::Connected);
   Ok(framed)
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub(crate) async fn create_authenticated_connection_tls(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   _domain: &str,
   inner: &Arc<RedisClientInner>,
 ) -> Result<FramedTls, RedisError> {
-  create_authenticated_connection(addr, inner).await
+  create_authenticated_connection(addrs, inner).await
 }
 
 pub async fn create_authenticated_connection(
-  addr: &SocketAddr,
+  addrs: &ResolvedAddrs,
   inner: &Arc<RedisClientInner>,
 ) -> Result<FramedTcp, RedisError> {
-  let server = format!("{}:{}", addr.ip().to_string(), addr.port());
+  let client_name = inner.client_name();
+  let password = inner.config.read().password.clone();
+  let username = inner.config.read().username.clone();
+
+  let (socket, server) = tcp_connect(inner, addrs).await?;
   let codec = RedisCodec::new(inner, server);
+  let framed = authenticate(Framed::new(socket, codec), &client_name, username, password).await?;
+  let framed = negotiate_version(inner, framed).await?;
+  let framed = enable_tracking(inner, framed).await?;
+  let framed = select_database(inner, framed).await?;
+
+  client_utils::set_client_state(&inner, ClientState::Connected);
+  Ok(framed)
+}
+
+#[cfg(unix)]
+pub async fn create_authenticated_connection_unix(
+  inner: &Arc<RedisClientInner>,
+  path: &Path,
+) -> Result<FramedUnix, RedisError> {
   let client_name = inner.client_name();
   let password = inner.config.read().password.clone();
   let username = inner.config.read().username.clone();
 
-  let socket = TcpStream::connect(addr).await?;
+  let timeout = match inner.config.read().tcp.connect_timeout {
+    Some(timeout) => timeout.as_millis() as u64,
+    None => globals().connection_attempt_timeout_ms() as u64,
+  };
+  let socket = client_utils::apply_timeout(UnixStream::connect(path), timeout).await?;
+  let server = path.to_string_lossy().into_owned();
+  _debug!(inner, "Connected to {}", server);
+
+  let codec = RedisCodec::new(inner, server);
   let framed = authenticate(Framed::new(socket, codec), &client_name, username, password).await?;
+  let framed = negotiate_version(inner, framed).await?;
+  let framed = enable_tracking(inner, framed).await?;
+  let framed = select_database(inner, framed).await?;
 
-  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  client_utils::set_client_state(&inner, ClientState::Connected);
   Ok(framed)
 }
 
-#[cfg(feature = "monitor")]
+/// Create a transport to the server listening on the provided Unix domain socket.
+#[cfg(unix)]
+pub async fn create_unix_transport(inner: &Arc<RedisClientInner>, path: &Path) -> Result<RedisTransport, RedisError> {
+  create_authenticated_connection_unix(inner, path)
+    .await
+    .map(|framed| RedisTransport::Unix(framed))
+}
+
+#[cfg(not(unix))]
+pub async fn create_unix_transport(_: &Arc<RedisClientInner>, _: &Path) -> Result<RedisTransport, RedisError> {
+  Err(RedisError::new(
+    RedisErrorKind::Config,
+    "Unix domain sockets are not supported on this platform.",
+  ))
+}
+
+#[cfg(any(feature = "monitor", feature = "replica"))]
 pub async fn create_centralized_connection(inner: &Arc<RedisClientInner>) -> Result<RedisTransport, RedisError> {
   let (host, port) = match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port } => (host.clone(), *port),
@@ -277,14 +504,48 @@ pub async fn create_centralized_connection(inner: &Arc<RedisClientInner>) -> Res
   Ok(transport)
 }
 
+/// Read the cluster state with CLUSTER SLOTS, falling back to CLUSTER NODES if the server does not support it.
+async fn read_cluster_state_on_transport<T>(
+  inner: &Arc<RedisClientInner>,
+  host: &str,
+  transport: Framed<T, RedisCodec>,
+) -> Result<ClusterKeyCache, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let command = RedisCommand::new(RedisCommandKind::ClusterSlots, vec![], None);
+  let (frame, transport) = request_response(transport, &command).await?;
+
+  match ClusterKeyCache::from_cluster_slots(frame, host) {
+    Ok(cache) => return Ok(cache),
+    Err(e) => _debug!(inner, "Failed to read CLUSTER SLOTS, falling back to CLUSTER NODES: {:?}", e),
+  };
+
+  let command = RedisCommand::new(RedisCommandKind::ClusterNodes, vec![], None);
+  let (frame, _) = request_response(transport, &command).await?;
+  if let Some(error) = protocol_utils::frame_to_error(&frame) {
+    return Err(error);
+  }
+  let cluster_state = match frame.to_string() {
+    Some(response) => response,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid CLUSTER NODES response.",
+      ))
+    }
+  };
+
+  _trace!(inner, "Cluster state:\n {}", cluster_state);
+  ClusterKeyCache::new(Some(cluster_state))
+}
+
 async fn read_cluster_state(
   inner: &Arc<RedisClientInner>,
   host: String,
   port: u16,
   uses_tls: bool,
 ) -> Option<ClusterKeyCache> {
-  let command = RedisCommand::new(RedisCommandKind::ClusterNodes, vec![], None);
-
   let addr = match inner.resolver.resolve(host.clone(), port).await {
     Ok(addr) => addr,
     Err(e) => {
@@ -293,7 +554,7 @@ async fn read_cluster_state(
     }
   };
 
-  let response = if uses_tls {
+  let result = if uses_tls {
     let connection = match create_authenticated_connection_tls(&addr, &host, &inner).await {
       Ok(connection) => connection,
       Err(e) => {
@@ -302,13 +563,7 @@ async fn read_cluster_state(
       }
     };
 
-    match request_response(connection, &command).await {
-      Ok((frame, _)) => frame,
-      Err(e) => {
-        _trace!(inner, "Failed to read cluster state from {}:{} => {:?}", host, port, e);
-        return None;
-      }
-    }
+    read_cluster_state_on_transport(inner, &host, connection).await
   } else {
     let connection = match create_authenticated_connection(&addr, &inner).await {
       Ok(connection) => connection,
@@ -318,36 +573,65 @@ async fn read_cluster_state(
       }
     };
 
-    match request_response(connection, &command).await {
-      Ok((frame, _)) => frame,
-      Err(e) => {
-        _trace!(inner, "Failed to read cluster state from {}:{} => {:?}", host, port, e);
-        return None;
-      }
-    }
+    read_cluster_state_on_transport(inner, &host, connection).await
   };
 
-  if response.is_error() {
-    _trace!(
-      inner,
-      "Protocol error reading cluster state from {}:{} => {:?}",
-      host,
-      port,
-      response
-    );
-    return None;
+  match result {
+    Ok(cache) => Some(cache),
+    Err(e) => {
+      _trace!(inner, "Failed to read cluster state from {}:{} => {:?}", host, port, e);
+      None
+    }
   }
-  let cluster_state = match response.to_string() {
-    Some(response) => response,
-    None => return None,
+}
+
+/// Parse the `redis_version` field from an `INFO SERVER` response.
+pub fn parse_server_version(info: &str) -> Option<Version> {
+  info.lines().find_map(|line| {
+    let parts: Vec<&str> = line.trim().split(":").collect();
+    if parts.len() < 2 {
+      return None;
+    }
+
+    if parts[0] == "redis_version" {
+      Version::parse(&parts[1]).ok()
+    } else {
+      None
+    }
+  })
+}
+
+/// Read the server version on the provided connection, caching it on the client if found.
+///
+/// The version is read on every new connection, replacing any cached version for the server since it may have been
+/// upgraded while the client was disconnected. Errors are logged but otherwise ignored, since not all servers allow
+/// callers to run `INFO`.
+pub async fn read_and_set_server_version<T>(inner: &Arc<RedisClientInner>, transport: Framed<T, RedisCodec>) -> Framed<T, RedisCodec>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let server = transport.codec().server.clone();
+  let command = RedisCommand::new(RedisCommandKind::Info, vec![InfoKind::Server.to_str().into()], None);
+  let (result, transport) = match request_response_safe(transport, &command).await {
+    Ok(result) => result,
+    Err((e, transport)) => {
+      _warn!(inner, "Error reading server version: {:?}", e);
+      return transport;
+    }
   };
 
-  _trace!(inner, "Cluster state:\n {}", cluster_state);
-  if let Ok(cache) = ClusterKeyCache::new(Some(cluster_state)) {
-    return Some(cache);
+  let version = match result {
+    ProtocolFrame::BulkString(ref bytes) => str::from_utf8(bytes).ok().and_then(parse_server_version),
+    _ => None,
+  };
+  if let Some(version) = version {
+    _debug!(inner, "Server version for {}: {}", server, version);
+    inner.update_server_version(&server, version);
+  } else {
+    _warn!(inner, "Failed to read server version from {:?}", result);
   }
 
-  None
+  transport
 }
 
 #[allow(dead_code)]
@@ -371,20 +655,7 @@ where
     }
   };
 
-  let version = result.lines().find_map(|line| {
-    let parts: Vec<&str> = line.split(":").collect();
-    if parts.len() < 2 {
-      return None;
-    }
-
-    if parts[0] == "redis_version" {
-      Version::parse(&parts[1]).ok()
-    } else {
-      None
-    }
-  });
-
-  if let Some(version) = version {
+  if let Some(version) = parse_server_version(&result) {
     _debug!(inner, "Server version: {}", version);
     Ok((version, transport))
   } else {
@@ -500,6 +771,8 @@ pub async fn write_command(
     match sink {
       RedisSink::Tcp(ref mut inner) => inner.send(frame).await?,
       RedisSink::Tls(ref mut inner) => inner.send(frame).await?,
+      #[cfg(unix)]
+      RedisSink::Unix(ref mut inner) => inner.send(frame).await?,
     };
     counters.reset_feed_count();
   } else {
@@ -508,6 +781,8 @@ pub async fn write_command(
     match sink {
       RedisSink::Tcp(ref mut inner) => inner.feed(frame).await?,
       RedisSink::Tls(ref mut inner) => inner.feed(frame).await?,
+      #[cfg(unix)]
+      RedisSink::Unix(ref mut inner) => inner.feed(frame).await?,
     };
     counters.incr_feed_count();
   };
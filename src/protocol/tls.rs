@@ -1,19 +1,56 @@
 use std::fmt;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::error::{RedisError, RedisErrorKind};
 #[cfg(feature = "enable-tls")]
-use crate::types::RedisConfig;
-#[cfg(feature = "enable-tls")]
-use native_tls::{Certificate, Protocol, TlsConnector as NativeTlsConnector};
+use std::str;
 #[cfg(feature = "enable-tls")]
+use native_tls::{Certificate, Identity, Protocol};
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
+use native_tls::TlsConnector as NativeTlsConnector;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use parking_lot::RwLock;
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use std::env;
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use tokio::net::TcpStream;
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
 use tokio_native_tls::TlsConnector;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(feature = "enable-rustls")]
+use std::convert::TryFrom;
+#[cfg(feature = "enable-rustls")]
+use std::sync::Arc;
+#[cfg(feature = "enable-rustls")]
+use std::time::SystemTime;
+#[cfg(feature = "enable-rustls")]
+use tokio_rustls::rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+#[cfg(feature = "enable-rustls")]
+use tokio_rustls::rustls::{
+  Certificate as RustlsCertificate,
+  ClientConfig,
+  Error as RustlsError,
+  OwnedTrustAnchor,
+  RootCertStore,
+  ServerName,
+};
+#[cfg(feature = "enable-rustls")]
+use tokio_rustls::TlsConnector as RustlsConnector;
+
+/// The TLS stream type used by the client.
+///
+/// rustls is used when both the `enable-tls` and `enable-rustls` features are enabled.
+#[cfg(feature = "enable-rustls")]
+pub type TlsStream = tokio_rustls::client::TlsStream<TcpStream>;
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
+pub type TlsStream = tokio_native_tls::TlsStream<TcpStream>;
+
+#[cfg(feature = "enable-rustls")]
+type Connector = RustlsConnector;
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
+type Connector = TlsConnector;
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn should_disable_cert_verification() -> bool {
   match env::var_os("FRED_DISABLE_CERT_VERIFICATION") {
     Some(s) => match s.into_string() {
@@ -27,7 +64,7 @@ pub fn should_disable_cert_verification() -> bool {
   }
 }
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn should_disable_host_verification() -> bool {
   match env::var_os("FRED_DISABLE_HOST_VERIFICATION") {
     Some(s) => match s.into_string() {
@@ -43,38 +80,131 @@ pub fn should_disable_host_verification() -> bool {
 
 /// Configuration for Tls Connections
 ///
-/// See <https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html> for more information.
-#[cfg(feature = "enable-tls")]
+/// See <https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html> or
+/// <https://docs.rs/rustls/0.20/rustls/struct.ClientConfig.html> for more information.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 #[derive(Clone)]
 pub struct TlsConfig {
+  /// Additional root certificates used with native-tls. Ignored when the `enable-rustls` feature is enabled.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub root_certs: Option<Vec<Certificate>>,
+  /// The minimum TLS protocol version used with native-tls. Ignored when the `enable-rustls` feature is enabled.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub min_protocol_version: Option<Protocol>,
+  /// The maximum TLS protocol version used with native-tls. Ignored when the `enable-rustls` feature is enabled.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub max_protocol_version: Option<Protocol>,
+  /// The client certificate and private key used for mutual TLS with native-tls. Ignored when the `enable-rustls`
+  /// feature is enabled.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
+  pub identity: Option<Identity>,
+  /// Whether or not to skip hostname verification with native-tls. This should only be used in test environments.
+  ///
+  /// Hostname verification can also be disabled with the `FRED_DISABLE_HOST_VERIFICATION` environment variable.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
+  pub disable_host_verification: bool,
+  /// Additional root certificates used with rustls.
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub rustls_root_certs: Option<Vec<tokio_rustls::rustls::Certificate>>,
+  /// The ALPN protocols to advertise with rustls, in order of preference.
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub alpn_protocols: Vec<Vec<u8>>,
+  /// Whether or not to ignore the built in root certificates. With rustls the built in roots are provided by
+  /// `webpki-roots`.
   pub disable_built_in_roots: bool,
+  /// Whether or not to send the server name via SNI.
   pub use_sni: bool,
+  /// The hostname used for SNI and certificate verification, instead of the hostname used to connect to the server.
+  pub hostname: Option<String>,
 }
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 impl Default for TlsConfig {
   fn default() -> Self {
     TlsConfig {
+      #[cfg(feature = "enable-tls")]
       root_certs: None,
+      #[cfg(feature = "enable-tls")]
       min_protocol_version: None,
+      #[cfg(feature = "enable-tls")]
       max_protocol_version: None,
+      #[cfg(feature = "enable-tls")]
+      identity: None,
+      #[cfg(feature = "enable-tls")]
+      disable_host_verification: false,
+      #[cfg(feature = "enable-rustls")]
+      rustls_root_certs: None,
+      #[cfg(feature = "enable-rustls")]
+      alpn_protocols: Vec::new(),
       disable_built_in_roots: false,
       use_sni: true,
+      hostname: None,
     }
   }
 }
 
+#[cfg(feature = "enable-tls")]
+impl TlsConfig {
+  /// Add each certificate in a PEM encoded CA bundle to the native-tls root certificates.
+  pub fn add_root_certs_pem(&mut self, bundle: &[u8]) -> Result<(), RedisError> {
+    let bundle = str::from_utf8(bundle)?;
+    let certs = split_pem_certs(bundle)?
+      .into_iter()
+      .map(|cert| Certificate::from_pem(cert.as_bytes()))
+      .collect::<Result<Vec<Certificate>, _>>()?;
+
+    self.root_certs.get_or_insert_with(Vec::new).extend(certs);
+    Ok(())
+  }
+
+  /// Set the client certificate and private key used for mutual TLS with native-tls from a PEM encoded certificate chain
+  /// and PKCS #8 private key.
+  pub fn set_identity_pem(&mut self, cert: &[u8], key: &[u8]) -> Result<(), RedisError> {
+    self.identity = Some(Identity::from_pkcs8(cert, key)?);
+    Ok(())
+  }
+}
+
+/// Split a PEM bundle into the individual certificate blocks.
+#[cfg(feature = "enable-tls")]
+fn split_pem_certs(bundle: &str) -> Result<Vec<&str>, RedisError> {
+  const BEGIN: &'static str = "-----BEGIN CERTIFICATE-----";
+  const END: &'static str = "-----END CERTIFICATE-----";
+
+  let mut out = Vec::new();
+  let mut rest = bundle;
+  while let Some(start) = rest.find(BEGIN) {
+    let end = match rest[start ..].find(END) {
+      Some(end) => start + end + END.len(),
+      None => return Err(RedisError::new(RedisErrorKind::Tls, "Invalid PEM certificate bundle.")),
+    };
+
+    out.push(&rest[start .. end]);
+    rest = &rest[end ..];
+  }
+
+  if out.is_empty() {
+    Err(RedisError::new(RedisErrorKind::Tls, "Missing PEM certificates."))
+  } else {
+    Ok(out)
+  }
+}
+
 /// Configuration for Tls Connections
 ///
 /// See https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html for more information.
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 #[derive(Clone)]
 pub struct TlsConfig;
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 impl Default for TlsConfig {
   fn default() -> Self {
     TlsConfig
@@ -95,8 +225,8 @@ impl fmt::Debug for TlsConfig {
   }
 }
 
-#[cfg(feature = "enable-tls")]
-pub fn create_tls_connector(config: &RwLock<RedisConfig>) -> Result<TlsConnector, RedisError> {
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
+pub fn create_tls_connector(config: Option<&TlsConfig>) -> Result<TlsConnector, RedisError> {
   let mut builder = NativeTlsConnector::builder();
 
   if should_disable_cert_verification() {
@@ -106,7 +236,7 @@ pub fn create_tls_connector(config: &RwLock<RedisConfig>) -> Result<TlsConnector
     builder.danger_accept_invalid_hostnames(true);
   }
 
-  if let Some(ref config) = config.read().tls {
+  if let Some(config) = config {
     if let Some(ref root_certs) = config.root_certs {
       for cert in root_certs.iter() {
         builder.add_root_certificate(cert.clone());
@@ -118,6 +248,12 @@ pub fn create_tls_connector(config: &RwLock<RedisConfig>) -> Result<TlsConnector
     if config.disable_built_in_roots {
       builder.disable_built_in_roots(true);
     }
+    if config.disable_host_verification {
+      builder.danger_accept_invalid_hostnames(true);
+    }
+    if let Some(ref identity) = config.identity {
+      builder.identity(identity.clone());
+    }
     if let Some(ref protocol) = config.min_protocol_version {
       builder.min_protocol_version(Some(protocol.clone()));
     }
@@ -131,3 +267,150 @@ pub fn create_tls_connector(config: &RwLock<RedisConfig>) -> Result<TlsConnector
     .map(|t| TlsConnector::from(t))
     .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("{:?}", e)))
 }
+
+/// A certificate verifier used with rustls when certificate or hostname verification is disabled via the
+/// `FRED_DISABLE_CERT_VERIFICATION` or `FRED_DISABLE_HOST_VERIFICATION` environment variables.
+#[cfg(feature = "enable-rustls")]
+struct RelaxedVerifier {
+  inner: WebPkiVerifier,
+  verify_certs: bool,
+}
+
+#[cfg(feature = "enable-rustls")]
+impl ServerCertVerifier for RelaxedVerifier {
+  fn verify_server_cert(
+    &self,
+    end_entity: &RustlsCertificate,
+    intermediates: &[RustlsCertificate],
+    server_name: &ServerName,
+    scts: &mut dyn Iterator<Item = &[u8]>,
+    ocsp_response: &[u8],
+    now: SystemTime,
+  ) -> Result<ServerCertVerified, RustlsError> {
+    if !self.verify_certs {
+      return Ok(ServerCertVerified::assertion());
+    }
+
+    match self
+      .inner
+      .verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)
+    {
+      // the hostname is checked after the certificate chain, so this error means the chain is valid
+      Err(RustlsError::InvalidCertificateData(ref message)) if message.contains("CertNotValidForName") => {
+        Ok(ServerCertVerified::assertion())
+      }
+      result => result,
+    }
+  }
+}
+
+#[cfg(feature = "enable-rustls")]
+pub fn create_tls_connector(config: Option<&TlsConfig>) -> Result<RustlsConnector, RedisError> {
+  let mut roots = RootCertStore::empty();
+
+  if !config.map(|config| config.disable_built_in_roots).unwrap_or(false) {
+    roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+      OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+    }));
+  }
+  if let Some(root_certs) = config.and_then(|config| config.rustls_root_certs.as_ref()) {
+    for cert in root_certs.iter() {
+      roots
+        .add(cert)
+        .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("{:?}", e)))?;
+    }
+  }
+
+  let (disable_certs, disable_hosts) = (should_disable_cert_verification(), should_disable_host_verification());
+  let mut client_config = ClientConfig::builder()
+    .with_safe_defaults()
+    .with_root_certificates(roots.clone())
+    .with_no_client_auth();
+  if disable_certs || disable_hosts {
+    client_config.dangerous().set_certificate_verifier(Arc::new(RelaxedVerifier {
+      inner: WebPkiVerifier::new(roots, None),
+      verify_certs: !disable_certs,
+    }));
+  }
+  if let Some(config) = config {
+    client_config.enable_sni = config.use_sni;
+    client_config.alpn_protocols = config.alpn_protocols.clone();
+  }
+
+  Ok(RustlsConnector::from(Arc::new(client_config)))
+}
+
+/// A TLS connector created from a [TlsConfig], cached so that the connector is built once per client instead of once
+/// per connection.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+#[derive(Default)]
+pub struct TlsConnectorCache {
+  connector: RwLock<Option<Connector>>,
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+impl TlsConnectorCache {
+  fn get_or_create(&self, config: Option<&TlsConfig>) -> Result<Connector, RedisError> {
+    if let Some(connector) = self.connector.read().clone() {
+      return Ok(connector);
+    }
+
+    let connector = create_tls_connector(config)?;
+    *self.connector.write() = Some(connector.clone());
+    Ok(connector)
+  }
+}
+
+/// Wrap the socket in a TLS stream, using the configured hostname override or the provided domain to verify the server.
+#[cfg(all(feature = "enable-tls", not(feature = "enable-rustls")))]
+pub async fn connect(
+  cache: &TlsConnectorCache,
+  config: Option<&TlsConfig>,
+  domain: &str,
+  socket: TcpStream,
+) -> Result<TlsStream, RedisError> {
+  let domain = config.and_then(|config| config.hostname.as_deref()).unwrap_or(domain);
+  let connector = cache.get_or_create(config)?;
+
+  Ok(connector.connect(domain, socket).await?)
+}
+
+/// Wrap the socket in a TLS stream, using the configured hostname override or the provided domain to verify the server.
+#[cfg(feature = "enable-rustls")]
+pub async fn connect(
+  cache: &TlsConnectorCache,
+  config: Option<&TlsConfig>,
+  domain: &str,
+  socket: TcpStream,
+) -> Result<TlsStream, RedisError> {
+  let domain = config.and_then(|config| config.hostname.as_deref()).unwrap_or(domain);
+  let server_name = ServerName::try_from(domain)
+    .map_err(|_| RedisError::new(RedisErrorKind::Tls, format!("Invalid TLS server name: {}", domain)))?;
+  let connector = cache.get_or_create(config)?;
+
+  Ok(connector.connect(server_name, socket).await?)
+}
+
+#[cfg(test)]
+#[cfg(feature = "enable-tls")]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_split_pem_certs() {
+    let bundle = "# first\n-----BEGIN CERTIFICATE-----\nabc\n-----END CERTIFICATE-----\n\
+                  # second\n-----BEGIN CERTIFICATE-----\ndef\n-----END CERTIFICATE-----\n";
+    let expected = vec![
+      "-----BEGIN CERTIFICATE-----\nabc\n-----END CERTIFICATE-----",
+      "-----BEGIN CERTIFICATE-----\ndef\n-----END CERTIFICATE-----",
+    ];
+
+    assert_eq!(split_pem_certs(bundle).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_not_split_invalid_pem_certs() {
+    assert!(split_pem_certs("").is_err());
+    assert!(split_pem_certs("-----BEGIN CERTIFICATE-----\nabc\n").is_err());
+  }
+}
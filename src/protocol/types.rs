@@ -6,10 +6,12 @@ use crate::types::*;
 use crate::utils;
 use crate::utils::{set_locked, take_locked};
 use parking_lot::RwLock;
-pub use redis_protocol::{redis_keyslot, resp2::types::NULL, types::CRLF};
-use std::collections::{BTreeSet, VecDeque};
+pub use crate::utils::redis_keyslot;
+use redis_protocol::types::Redirection;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fmt;
 use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc::UnboundedSender;
@@ -22,7 +24,6 @@ use crate::trace::CommandTraces;
 #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
 use crate::trace::Span;
 use rand::Rng;
-use std::borrow::Cow;
 
 pub const REDIS_CLUSTER_SLOTS: u16 = 16384;
 
@@ -80,6 +81,13 @@ pub struct CustomKeySlot {
   pub key_slot: Option<u16>,
 }
 
+/// Arguments for the `XREAD` family of commands, which do not start with a key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct XreadInner {
+  pub key_slot: Option<u16>,
+  pub blocking: bool,
+}
+
 #[derive(Clone)]
 pub struct SplitCommand {
   pub tx: Arc<RwLock<Option<OneshotSender<Result<Vec<RedisClient>, RedisError>>>>>,
@@ -131,6 +139,8 @@ impl Eq for ResponseKind {}
 
 pub struct KeyScanInner {
   pub key_slot: Option<u16>,
+  /// The primary node being scanned, if the scan is part of a cluster-wide scan.
+  pub server: Option<Arc<String>>,
   pub cursor: String,
   pub tx: UnboundedSender<Result<ScanResult, RedisError>>,
 }
@@ -241,11 +251,13 @@ pub enum RedisCommandKind {
   AclLog,
   AclHelp,
   Append,
+  Asking,
   Auth,
   BgreWriteAof,
   BgSave,
   BitCount,
   BitField,
+  BitFieldRo,
   BitOp,
   BitPos,
   BlPop,
@@ -260,12 +272,16 @@ pub enum RedisCommandKind {
   ClientList,
   ClientGetName,
   ClientGetRedir,
+  ClientTracking,
+  ClientCaching,
   ClientPause,
   ClientUnpause,
   ClientUnblock,
   ClientReply,
   ClientSetname,
   ClusterAddSlots,
+  ClusterAddSlotsRange,
+  ClusterDelSlotsRange,
   ClusterCountFailureReports,
   ClusterCountKeysInSlot,
   ClusterDelSlots,
@@ -286,6 +302,7 @@ pub enum RedisCommandKind {
   ClusterSetSlot,
   ClusterReplicas,
   ClusterSlots,
+  ClusterShards,
   ConfigGet,
   ConfigRewrite,
   ConfigSet,
@@ -304,6 +321,7 @@ pub enum RedisCommandKind {
   Exists,
   Expire,
   ExpireAt,
+  ExpireTime,
   Failover,
   FlushAll,
   FlushDB,
@@ -338,9 +356,11 @@ pub enum RedisCommandKind {
   Incr,
   IncrBy,
   IncrByFloat,
+  Hello,
   Info,
   Keys,
   LastSave,
+  Lcs,
   LIndex,
   LInsert,
   LLen,
@@ -366,10 +386,14 @@ pub enum RedisCommandKind {
   Mset,
   Msetnx,
   Multi,
-  Object,
+  ObjectEncoding,
+  ObjectFreq,
+  ObjectIdleTime,
+  ObjectRefCount,
   Persist,
   Pexpire,
   Pexpireat,
+  PexpireTime,
   Pfadd,
   Pfcount,
   Pfmerge,
@@ -414,15 +438,21 @@ pub enum RedisCommandKind {
   Smismember,
   Smove,
   Sort,
+  SortRo,
   Spop,
   Srandmember,
   Srem,
   Strlen,
   Subscribe,
+  Spublish,
+  Ssubscribe,
+  Sunsubscribe,
   Sunion,
   Sunionstore,
   Swapdb,
   Sync,
+  Psync,
+  Replconf,
   Time,
   Touch,
   Ttl,
@@ -459,6 +489,25 @@ pub enum RedisCommandKind {
   Zmscore,
   Zunion,
   Zunionstore,
+  Xadd,
+  Xlen,
+  Xrange,
+  Xrevrange,
+  Xread(XreadInner),
+  Xreadgroup(XreadInner),
+  XgroupCreate,
+  XgroupCreateConsumer,
+  XgroupDelConsumer,
+  XgroupDestroy,
+  XgroupSetId,
+  Xack,
+  Xpending,
+  Xclaim,
+  Xautoclaim,
+  XinfoStream,
+  XinfoGroups,
+  XinfoConsumers,
+  Xtrim,
   Zpopmax,
   Zpopmin,
   ScriptLoad,
@@ -466,17 +515,31 @@ pub enum RedisCommandKind {
   ScriptExists,
   ScriptFlush,
   ScriptKill,
+  Fcall(CustomKeySlot),
+  FcallRO(CustomKeySlot),
+  FunctionDelete,
+  FunctionDump,
+  FunctionKill,
+  FunctionFlush,
+  FunctionList,
+  FunctionLoad,
+  FunctionRestore,
+  FunctionStats,
   Scan(KeyScanInner),
   Sscan(ValueScanInner),
   Hscan(ValueScanInner),
   Zscan(ValueScanInner),
   _Close,
   _Split(SplitCommand),
+  _SyncCluster,
   _AuthAllCluster(AllNodesResponse),
   _FlushAllCluster(AllNodesResponse),
   _ScriptFlushCluster(AllNodesResponse),
   _ScriptLoadCluster(AllNodesResponse),
-  _ScriptKillCluster(AllNodesResponse),
+  _FunctionDeleteCluster(AllNodesResponse),
+  _FunctionFlushCluster(AllNodesResponse),
+  _FunctionLoadCluster(AllNodesResponse),
+  _FunctionRestoreCluster(AllNodesResponse),
   _Custom(CustomCommand),
 }
 
@@ -599,6 +662,13 @@ impl RedisCommandKind {
     }
   }
 
+  pub fn is_sync_cluster(&self) -> bool {
+    match *self {
+      RedisCommandKind::_SyncCluster => true,
+      _ => false,
+    }
+  }
+
   pub fn is_close(&self) -> bool {
     match *self {
       RedisCommandKind::_Close => true,
@@ -636,11 +706,13 @@ impl RedisCommandKind {
       RedisCommandKind::AclLog => "ACL LOG",
       RedisCommandKind::AclHelp => "ACL HELP",
       RedisCommandKind::Append => "APPEND",
+      RedisCommandKind::Asking => "ASKING",
       RedisCommandKind::Auth => "AUTH",
       RedisCommandKind::BgreWriteAof => "BGREWRITEAOF",
       RedisCommandKind::BgSave => "BGSAVE",
       RedisCommandKind::BitCount => "BITCOUNT",
       RedisCommandKind::BitField => "BITFIELD",
+      RedisCommandKind::BitFieldRo => "BITFIELD_RO",
       RedisCommandKind::BitOp => "BITOP",
       RedisCommandKind::BitPos => "BITPOS",
       RedisCommandKind::BlPop => "BLPOP",
@@ -654,6 +726,8 @@ impl RedisCommandKind {
       RedisCommandKind::ClientKill => "CLIENT KILL",
       RedisCommandKind::ClientList => "CLIENT LIST",
       RedisCommandKind::ClientGetRedir => "CLIENT GETREDIR",
+      RedisCommandKind::ClientTracking => "CLIENT TRACKING",
+      RedisCommandKind::ClientCaching => "CLIENT CACHING",
       RedisCommandKind::ClientGetName => "CLIENT GETNAME",
       RedisCommandKind::ClientPause => "CLIENT PAUSE",
       RedisCommandKind::ClientUnpause => "CLIENT UNPAUSE",
@@ -661,6 +735,8 @@ impl RedisCommandKind {
       RedisCommandKind::ClientReply => "CLIENT REPLY",
       RedisCommandKind::ClientSetname => "CLIENT SETNAME",
       RedisCommandKind::ClusterAddSlots => "CLUSTER ADDSLOTS",
+      RedisCommandKind::ClusterAddSlotsRange => "CLUSTER ADDSLOTSRANGE",
+      RedisCommandKind::ClusterDelSlotsRange => "CLUSTER DELSLOTSRANGE",
       RedisCommandKind::ClusterCountFailureReports => "CLUSTER COUNT-FAILURE-REPORTS",
       RedisCommandKind::ClusterCountKeysInSlot => "CLUSTER COUNTKEYSINSLOT",
       RedisCommandKind::ClusterDelSlots => "CLUSTER DEL SLOTS",
@@ -678,6 +754,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClusterSetSlot => "CLUSTER SETSLOT",
       RedisCommandKind::ClusterReplicas => "CLUSTER REPLICAS",
       RedisCommandKind::ClusterSlots => "CLUSTER SLOTS",
+      RedisCommandKind::ClusterShards => "CLUSTER SHARDS",
       RedisCommandKind::ClusterBumpEpoch => "CLUSTER BUMPEPOCH",
       RedisCommandKind::ClusterFlushSlots => "CLUSTER FLUSHSLOTS",
       RedisCommandKind::ClusterMyID => "CLUSTER MYID",
@@ -699,6 +776,7 @@ impl RedisCommandKind {
       RedisCommandKind::Exists => "EXISTS",
       RedisCommandKind::Expire => "EXPIRE",
       RedisCommandKind::ExpireAt => "EXPIREAT",
+      RedisCommandKind::ExpireTime => "EXPIRETIME",
       RedisCommandKind::Failover => "FAILOVER",
       RedisCommandKind::FlushAll => "FLUSHALL",
       RedisCommandKind::FlushDB => "FLUSHDB",
@@ -733,9 +811,11 @@ impl RedisCommandKind {
       RedisCommandKind::Incr => "INCR",
       RedisCommandKind::IncrBy => "INCRBY",
       RedisCommandKind::IncrByFloat => "INCRBYFLOAT",
+      RedisCommandKind::Hello => "HELLO",
       RedisCommandKind::Info => "INFO",
       RedisCommandKind::Keys => "KEYS",
       RedisCommandKind::LastSave => "LASTSAVE",
+      RedisCommandKind::Lcs => "LCS",
       RedisCommandKind::LIndex => "LINDEX",
       RedisCommandKind::LInsert => "LINSERT",
       RedisCommandKind::LLen => "LLEN",
@@ -761,10 +841,14 @@ impl RedisCommandKind {
       RedisCommandKind::Mset => "MSET",
       RedisCommandKind::Msetnx => "MSETNX",
       RedisCommandKind::Multi => "MULTI",
-      RedisCommandKind::Object => "OBJECT",
+      RedisCommandKind::ObjectEncoding => "OBJECT ENCODING",
+      RedisCommandKind::ObjectFreq => "OBJECT FREQ",
+      RedisCommandKind::ObjectIdleTime => "OBJECT IDLETIME",
+      RedisCommandKind::ObjectRefCount => "OBJECT REFCOUNT",
       RedisCommandKind::Persist => "PERSIST",
       RedisCommandKind::Pexpire => "PEXPIRE",
       RedisCommandKind::Pexpireat => "PEXPIREAT",
+      RedisCommandKind::PexpireTime => "PEXPIRETIME",
       RedisCommandKind::Pfadd => "PFADD",
       RedisCommandKind::Pfcount => "PFCOUNT",
       RedisCommandKind::Pfmerge => "PFMERGE",
@@ -809,15 +893,21 @@ impl RedisCommandKind {
       RedisCommandKind::Smismember => "SMISMEMBER",
       RedisCommandKind::Smove => "SMOVE",
       RedisCommandKind::Sort => "SORT",
+      RedisCommandKind::SortRo => "SORT_RO",
       RedisCommandKind::Spop => "SPOP",
       RedisCommandKind::Srandmember => "SRANDMEMBER",
       RedisCommandKind::Srem => "SREM",
       RedisCommandKind::Strlen => "STRLEN",
       RedisCommandKind::Subscribe => "SUBSCRIBE",
+      RedisCommandKind::Spublish => "SPUBLISH",
+      RedisCommandKind::Ssubscribe => "SSUBSCRIBE",
+      RedisCommandKind::Sunsubscribe => "SUNSUBSCRIBE",
       RedisCommandKind::Sunion => "SUNION",
       RedisCommandKind::Sunionstore => "SUNIONSTORE",
       RedisCommandKind::Swapdb => "SWAPDB",
       RedisCommandKind::Sync => "SYNC",
+      RedisCommandKind::Psync => "PSYNC",
+      RedisCommandKind::Replconf => "REPLCONF",
       RedisCommandKind::Time => "TIME",
       RedisCommandKind::Touch => "TOUCH",
       RedisCommandKind::Ttl => "TTL",
@@ -854,6 +944,25 @@ impl RedisCommandKind {
       RedisCommandKind::Zmscore => "ZMSCORE",
       RedisCommandKind::Zunion => "ZUNION",
       RedisCommandKind::Zunionstore => "ZUNIONSTORE",
+      RedisCommandKind::Xadd => "XADD",
+      RedisCommandKind::Xlen => "XLEN",
+      RedisCommandKind::Xrange => "XRANGE",
+      RedisCommandKind::Xrevrange => "XREVRANGE",
+      RedisCommandKind::Xread(_) => "XREAD",
+      RedisCommandKind::Xreadgroup(_) => "XREADGROUP",
+      RedisCommandKind::XgroupCreate => "XGROUP CREATE",
+      RedisCommandKind::XgroupCreateConsumer => "XGROUP CREATECONSUMER",
+      RedisCommandKind::XgroupDelConsumer => "XGROUP DELCONSUMER",
+      RedisCommandKind::XgroupDestroy => "XGROUP DESTROY",
+      RedisCommandKind::XgroupSetId => "XGROUP SETID",
+      RedisCommandKind::Xack => "XACK",
+      RedisCommandKind::Xpending => "XPENDING",
+      RedisCommandKind::Xclaim => "XCLAIM",
+      RedisCommandKind::Xautoclaim => "XAUTOCLAIM",
+      RedisCommandKind::XinfoStream => "XINFO STREAM",
+      RedisCommandKind::XinfoGroups => "XINFO GROUPS",
+      RedisCommandKind::XinfoConsumers => "XINFO CONSUMERS",
+      RedisCommandKind::Xtrim => "XTRIM",
       RedisCommandKind::Zpopmax => "ZPOPMAX",
       RedisCommandKind::Zpopmin => "ZPOPMIN",
       RedisCommandKind::Scan(_) => "SCAN",
@@ -865,13 +974,27 @@ impl RedisCommandKind {
       RedisCommandKind::ScriptFlush => "SCRIPT FLUSH",
       RedisCommandKind::ScriptKill => "SCRIPT KILL",
       RedisCommandKind::ScriptLoad => "SCRIPT LOAD",
+      RedisCommandKind::Fcall(_) => "FCALL",
+      RedisCommandKind::FcallRO(_) => "FCALL_RO",
+      RedisCommandKind::FunctionDelete => "FUNCTION DELETE",
+      RedisCommandKind::FunctionDump => "FUNCTION DUMP",
+      RedisCommandKind::FunctionKill => "FUNCTION KILL",
+      RedisCommandKind::FunctionFlush => "FUNCTION FLUSH",
+      RedisCommandKind::FunctionList => "FUNCTION LIST",
+      RedisCommandKind::FunctionLoad => "FUNCTION LOAD",
+      RedisCommandKind::FunctionRestore => "FUNCTION RESTORE",
+      RedisCommandKind::FunctionStats => "FUNCTION STATS",
       RedisCommandKind::_Close => "CLOSE",
       RedisCommandKind::_Split(_) => "SPLIT",
+      RedisCommandKind::_SyncCluster => "SYNC CLUSTER",
       RedisCommandKind::_AuthAllCluster(_) => "AUTH ALL CLUSTER",
       RedisCommandKind::_FlushAllCluster(_) => "FLUSHALL CLUSTER",
       RedisCommandKind::_ScriptFlushCluster(_) => "SCRIPT FLUSH CLUSTER",
       RedisCommandKind::_ScriptLoadCluster(_) => "SCRIPT LOAD CLUSTER",
-      RedisCommandKind::_ScriptKillCluster(_) => "SCRIPT Kill CLUSTER",
+      RedisCommandKind::_FunctionDeleteCluster(_) => "FUNCTION DELETE CLUSTER",
+      RedisCommandKind::_FunctionFlushCluster(_) => "FUNCTION FLUSH CLUSTER",
+      RedisCommandKind::_FunctionLoadCluster(_) => "FUNCTION LOAD CLUSTER",
+      RedisCommandKind::_FunctionRestoreCluster(_) => "FUNCTION RESTORE CLUSTER",
       RedisCommandKind::_Custom(ref kind) => kind.cmd,
     }
   }
@@ -892,11 +1015,13 @@ impl RedisCommandKind {
       RedisCommandKind::AclLog => "ACL",
       RedisCommandKind::AclHelp => "ACL",
       RedisCommandKind::Append => "APPEND",
+      RedisCommandKind::Asking => "ASKING",
       RedisCommandKind::Auth => "AUTH",
       RedisCommandKind::BgreWriteAof => "BGREWRITEAOF",
       RedisCommandKind::BgSave => "BGSAVE",
       RedisCommandKind::BitCount => "BITCOUNT",
       RedisCommandKind::BitField => "BITFIELD",
+      RedisCommandKind::BitFieldRo => "BITFIELD_RO",
       RedisCommandKind::BitOp => "BITOP",
       RedisCommandKind::BitPos => "BITPOS",
       RedisCommandKind::BlPop => "BLPOP",
@@ -911,12 +1036,16 @@ impl RedisCommandKind {
       RedisCommandKind::ClientList => "CLIENT",
       RedisCommandKind::ClientGetName => "CLIENT",
       RedisCommandKind::ClientGetRedir => "CLIENT",
+      RedisCommandKind::ClientTracking => "CLIENT",
+      RedisCommandKind::ClientCaching => "CLIENT",
       RedisCommandKind::ClientPause => "CLIENT",
       RedisCommandKind::ClientUnpause => "CLIENT",
       RedisCommandKind::ClientUnblock => "CLIENT",
       RedisCommandKind::ClientReply => "CLIENT",
       RedisCommandKind::ClientSetname => "CLIENT",
       RedisCommandKind::ClusterAddSlots => "CLUSTER",
+      RedisCommandKind::ClusterAddSlotsRange => "CLUSTER",
+      RedisCommandKind::ClusterDelSlotsRange => "CLUSTER",
       RedisCommandKind::ClusterCountFailureReports => "CLUSTER",
       RedisCommandKind::ClusterCountKeysInSlot => "CLUSTER",
       RedisCommandKind::ClusterDelSlots => "CLUSTER",
@@ -934,6 +1063,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClusterSetSlot => "CLUSTER",
       RedisCommandKind::ClusterReplicas => "CLUSTER",
       RedisCommandKind::ClusterSlots => "CLUSTER",
+      RedisCommandKind::ClusterShards => "CLUSTER",
       RedisCommandKind::ClusterBumpEpoch => "CLUSTER",
       RedisCommandKind::ClusterFlushSlots => "CLUSTER",
       RedisCommandKind::ClusterMyID => "CLUSTER",
@@ -955,6 +1085,7 @@ impl RedisCommandKind {
       RedisCommandKind::Exists => "EXISTS",
       RedisCommandKind::Expire => "EXPIRE",
       RedisCommandKind::ExpireAt => "EXPIREAT",
+      RedisCommandKind::ExpireTime => "EXPIRETIME",
       RedisCommandKind::Failover => "FAILOVER",
       RedisCommandKind::FlushAll => "FLUSHALL",
       RedisCommandKind::_FlushAllCluster(_) => "FLUSHALL",
@@ -990,9 +1121,11 @@ impl RedisCommandKind {
       RedisCommandKind::Incr => "INCR",
       RedisCommandKind::IncrBy => "INCRBY",
       RedisCommandKind::IncrByFloat => "INCRBYFLOAT",
+      RedisCommandKind::Hello => "HELLO",
       RedisCommandKind::Info => "INFO",
       RedisCommandKind::Keys => "KEYS",
       RedisCommandKind::LastSave => "LASTSAVE",
+      RedisCommandKind::Lcs => "LCS",
       RedisCommandKind::LIndex => "LINDEX",
       RedisCommandKind::LInsert => "LINSERT",
       RedisCommandKind::LLen => "LLEN",
@@ -1018,10 +1151,14 @@ impl RedisCommandKind {
       RedisCommandKind::Mset => "MSET",
       RedisCommandKind::Msetnx => "MSETNX",
       RedisCommandKind::Multi => "MULTI",
-      RedisCommandKind::Object => "OBJECT",
+      RedisCommandKind::ObjectEncoding
+      | RedisCommandKind::ObjectFreq
+      | RedisCommandKind::ObjectIdleTime
+      | RedisCommandKind::ObjectRefCount => "OBJECT",
       RedisCommandKind::Persist => "PERSIST",
       RedisCommandKind::Pexpire => "PEXPIRE",
       RedisCommandKind::Pexpireat => "PEXPIREAT",
+      RedisCommandKind::PexpireTime => "PEXPIRETIME",
       RedisCommandKind::Pfadd => "PFADD",
       RedisCommandKind::Pfcount => "PFCOUNT",
       RedisCommandKind::Pfmerge => "PFMERGE",
@@ -1066,15 +1203,21 @@ impl RedisCommandKind {
       RedisCommandKind::Smismember => "SMISMEMBER",
       RedisCommandKind::Smove => "SMOVE",
       RedisCommandKind::Sort => "SORT",
+      RedisCommandKind::SortRo => "SORT_RO",
       RedisCommandKind::Spop => "SPOP",
       RedisCommandKind::Srandmember => "SRANDMEMBER",
       RedisCommandKind::Srem => "SREM",
       RedisCommandKind::Strlen => "STRLEN",
       RedisCommandKind::Subscribe => "SUBSCRIBE",
+      RedisCommandKind::Spublish => "SPUBLISH",
+      RedisCommandKind::Ssubscribe => "SSUBSCRIBE",
+      RedisCommandKind::Sunsubscribe => "SUNSUBSCRIBE",
       RedisCommandKind::Sunion => "SUNION",
       RedisCommandKind::Sunionstore => "SUNIONSTORE",
       RedisCommandKind::Swapdb => "SWAPDB",
       RedisCommandKind::Sync => "SYNC",
+      RedisCommandKind::Psync => "PSYNC",
+      RedisCommandKind::Replconf => "REPLCONF",
       RedisCommandKind::Time => "TIME",
       RedisCommandKind::Touch => "TOUCH",
       RedisCommandKind::Ttl => "TTL",
@@ -1111,6 +1254,25 @@ impl RedisCommandKind {
       RedisCommandKind::Zmscore => "ZMSCORE",
       RedisCommandKind::Zunion => "ZUNION",
       RedisCommandKind::Zunionstore => "ZUNIONSTORE",
+      RedisCommandKind::Xadd => "XADD",
+      RedisCommandKind::Xlen => "XLEN",
+      RedisCommandKind::Xrange => "XRANGE",
+      RedisCommandKind::Xrevrange => "XREVRANGE",
+      RedisCommandKind::Xread(_) => "XREAD",
+      RedisCommandKind::Xreadgroup(_) => "XREADGROUP",
+      RedisCommandKind::XgroupCreate => "XGROUP",
+      RedisCommandKind::XgroupCreateConsumer => "XGROUP",
+      RedisCommandKind::XgroupDelConsumer => "XGROUP",
+      RedisCommandKind::XgroupDestroy => "XGROUP",
+      RedisCommandKind::XgroupSetId => "XGROUP",
+      RedisCommandKind::Xack => "XACK",
+      RedisCommandKind::Xpending => "XPENDING",
+      RedisCommandKind::Xclaim => "XCLAIM",
+      RedisCommandKind::Xautoclaim => "XAUTOCLAIM",
+      RedisCommandKind::XinfoStream => "XINFO",
+      RedisCommandKind::XinfoGroups => "XINFO",
+      RedisCommandKind::XinfoConsumers => "XINFO",
+      RedisCommandKind::Xtrim => "XTRIM",
       RedisCommandKind::Zpopmax => "ZPOPMAX",
       RedisCommandKind::Zpopmin => "ZPOPMIN",
       RedisCommandKind::ScriptDebug => "SCRIPT",
@@ -1120,14 +1282,27 @@ impl RedisCommandKind {
       RedisCommandKind::ScriptLoad => "SCRIPT",
       RedisCommandKind::_ScriptFlushCluster(_) => "SCRIPT",
       RedisCommandKind::_ScriptLoadCluster(_) => "SCRIPT",
-      RedisCommandKind::_ScriptKillCluster(_) => "SCRIPT",
+      RedisCommandKind::Fcall(_) => "FCALL",
+      RedisCommandKind::FcallRO(_) => "FCALL_RO",
+      RedisCommandKind::FunctionDelete
+      | RedisCommandKind::FunctionDump
+      | RedisCommandKind::FunctionKill
+      | RedisCommandKind::FunctionFlush
+      | RedisCommandKind::FunctionList
+      | RedisCommandKind::FunctionLoad
+      | RedisCommandKind::FunctionRestore
+      | RedisCommandKind::FunctionStats
+      | RedisCommandKind::_FunctionDeleteCluster(_)
+      | RedisCommandKind::_FunctionFlushCluster(_)
+      | RedisCommandKind::_FunctionLoadCluster(_)
+      | RedisCommandKind::_FunctionRestoreCluster(_) => "FUNCTION",
       RedisCommandKind::Scan(_) => "SCAN",
       RedisCommandKind::Sscan(_) => "SSCAN",
       RedisCommandKind::Hscan(_) => "HSCAN",
       RedisCommandKind::Zscan(_) => "ZSCAN",
       RedisCommandKind::_AuthAllCluster(_) => "AUTH",
       RedisCommandKind::_Custom(ref kind) => kind.cmd,
-      RedisCommandKind::_Close | RedisCommandKind::_Split(_) => {
+      RedisCommandKind::_Close | RedisCommandKind::_Split(_) | RedisCommandKind::_SyncCluster => {
         panic!("unreachable (redis command)")
       }
     }
@@ -1143,7 +1318,14 @@ impl RedisCommandKind {
       RedisCommandKind::ScriptExists => "EXISTS",
       RedisCommandKind::_ScriptFlushCluster(_) => "FLUSH",
       RedisCommandKind::_ScriptLoadCluster(_) => "LOAD",
-      RedisCommandKind::_ScriptKillCluster(_) => "KILL",
+      RedisCommandKind::FunctionDelete | RedisCommandKind::_FunctionDeleteCluster(_) => "DELETE",
+      RedisCommandKind::FunctionDump => "DUMP",
+      RedisCommandKind::FunctionKill => "KILL",
+      RedisCommandKind::FunctionFlush | RedisCommandKind::_FunctionFlushCluster(_) => "FLUSH",
+      RedisCommandKind::FunctionList => "LIST",
+      RedisCommandKind::FunctionLoad | RedisCommandKind::_FunctionLoadCluster(_) => "LOAD",
+      RedisCommandKind::FunctionRestore | RedisCommandKind::_FunctionRestoreCluster(_) => "RESTORE",
+      RedisCommandKind::FunctionStats => "STATS",
       RedisCommandKind::AclLoad => "LOAD",
       RedisCommandKind::AclSave => "SAVE",
       RedisCommandKind::AclList => "LIST",
@@ -1157,6 +1339,8 @@ impl RedisCommandKind {
       RedisCommandKind::AclLog => "LOG",
       RedisCommandKind::AclHelp => "HELP",
       RedisCommandKind::ClusterAddSlots => "ADDSLOTS",
+      RedisCommandKind::ClusterAddSlotsRange => "ADDSLOTSRANGE",
+      RedisCommandKind::ClusterDelSlotsRange => "DELSLOTSRANGE",
       RedisCommandKind::ClusterCountFailureReports => "COUNT-FAILURE-REPORTS",
       RedisCommandKind::ClusterCountKeysInSlot => "COUNTKEYSINSLOT",
       RedisCommandKind::ClusterDelSlots => "DELSLOTS",
@@ -1174,6 +1358,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClusterSetSlot => "SETSLOT",
       RedisCommandKind::ClusterReplicas => "REPLICAS",
       RedisCommandKind::ClusterSlots => "SLOTS",
+      RedisCommandKind::ClusterShards => "SHARDS",
       RedisCommandKind::ClusterBumpEpoch => "BUMPEPOCH",
       RedisCommandKind::ClusterFlushSlots => "FLUSHSLOTS",
       RedisCommandKind::ClusterMyID => "MYID",
@@ -1182,6 +1367,8 @@ impl RedisCommandKind {
       RedisCommandKind::ClientKill => "KILL",
       RedisCommandKind::ClientList => "LIST",
       RedisCommandKind::ClientGetRedir => "GETREDIR",
+      RedisCommandKind::ClientTracking => "TRACKING",
+      RedisCommandKind::ClientCaching => "CACHING",
       RedisCommandKind::ClientGetName => "GETNAME",
       RedisCommandKind::ClientPause => "PAUSE",
       RedisCommandKind::ClientUnpause => "UNPAUSE",
@@ -1192,12 +1379,24 @@ impl RedisCommandKind {
       RedisCommandKind::ConfigRewrite => "REWRITE",
       RedisCommandKind::ConfigSet => "SET",
       RedisCommandKind::ConfigResetStat => "RESETSTAT",
+      RedisCommandKind::ObjectEncoding => "ENCODING",
+      RedisCommandKind::ObjectFreq => "FREQ",
+      RedisCommandKind::ObjectIdleTime => "IDLETIME",
+      RedisCommandKind::ObjectRefCount => "REFCOUNT",
       RedisCommandKind::MemoryDoctor => "DOCTOR",
       RedisCommandKind::MemoryHelp => "HELP",
       RedisCommandKind::MemoryUsage => "USAGE",
       RedisCommandKind::MemoryMallocStats => "MALLOC-STATS",
       RedisCommandKind::MemoryStats => "STATS",
       RedisCommandKind::MemoryPurge => "PURGE",
+      RedisCommandKind::XgroupCreate => "CREATE",
+      RedisCommandKind::XgroupCreateConsumer => "CREATECONSUMER",
+      RedisCommandKind::XgroupDelConsumer => "DELCONSUMER",
+      RedisCommandKind::XgroupDestroy => "DESTROY",
+      RedisCommandKind::XgroupSetId => "SETID",
+      RedisCommandKind::XinfoStream => "STREAM",
+      RedisCommandKind::XinfoGroups => "GROUPS",
+      RedisCommandKind::XinfoConsumers => "CONSUMERS",
       _ => return None,
     };
 
@@ -1212,12 +1411,29 @@ impl RedisCommandKind {
       | RedisCommandKind::ScriptKill
       | RedisCommandKind::_ScriptFlushCluster(_)
       | RedisCommandKind::_ScriptLoadCluster(_)
-      | RedisCommandKind::_ScriptKillCluster(_)
       | RedisCommandKind::ScriptLoad => true,
       _ => false,
     }
   }
 
+  pub fn is_function_command(&self) -> bool {
+    match *self {
+      RedisCommandKind::FunctionDelete
+      | RedisCommandKind::FunctionDump
+      | RedisCommandKind::FunctionKill
+      | RedisCommandKind::FunctionFlush
+      | RedisCommandKind::FunctionList
+      | RedisCommandKind::FunctionLoad
+      | RedisCommandKind::FunctionRestore
+      | RedisCommandKind::FunctionStats
+      | RedisCommandKind::_FunctionDeleteCluster(_)
+      | RedisCommandKind::_FunctionFlushCluster(_)
+      | RedisCommandKind::_FunctionLoadCluster(_)
+      | RedisCommandKind::_FunctionRestoreCluster(_) => true,
+      _ => false,
+    }
+  }
+
   pub fn is_acl_command(&self) -> bool {
     match *self {
       RedisCommandKind::AclLoad
@@ -1239,6 +1455,8 @@ impl RedisCommandKind {
   pub fn is_cluster_command(&self) -> bool {
     match *self {
       RedisCommandKind::ClusterAddSlots
+      | RedisCommandKind::ClusterAddSlotsRange
+      | RedisCommandKind::ClusterDelSlotsRange
       | RedisCommandKind::ClusterCountFailureReports
       | RedisCommandKind::ClusterCountKeysInSlot
       | RedisCommandKind::ClusterDelSlots
@@ -1258,7 +1476,8 @@ impl RedisCommandKind {
       | RedisCommandKind::ClusterBumpEpoch
       | RedisCommandKind::ClusterFlushSlots
       | RedisCommandKind::ClusterMyID
-      | RedisCommandKind::ClusterSlots => true,
+      | RedisCommandKind::ClusterSlots
+      | RedisCommandKind::ClusterShards => true,
       _ => false,
     }
   }
@@ -1267,6 +1486,8 @@ impl RedisCommandKind {
     match *self {
       RedisCommandKind::ClientGetName
       | RedisCommandKind::ClientGetRedir
+      | RedisCommandKind::ClientTracking
+      | RedisCommandKind::ClientCaching
       | RedisCommandKind::ClientInfo
       | RedisCommandKind::ClientID
       | RedisCommandKind::ClientKill
@@ -1311,17 +1532,118 @@ impl RedisCommandKind {
       | RedisCommandKind::BzPopMin
       | RedisCommandKind::BzPopMax
       | RedisCommandKind::Wait => true,
+      RedisCommandKind::Xread(ref inner) | RedisCommandKind::Xreadgroup(ref inner) => inner.blocking,
       RedisCommandKind::_Custom(ref kind) => kind.is_blocking,
       _ => false,
     }
   }
 
+  /// The minimum server version required to run the command, if known.
+  pub fn min_server_version(&self) -> Option<Version> {
+    match *self {
+      RedisCommandKind::Spublish
+      | RedisCommandKind::Ssubscribe
+      | RedisCommandKind::Sunsubscribe
+      | RedisCommandKind::ClusterShards
+      | RedisCommandKind::ClusterAddSlotsRange
+      | RedisCommandKind::ClusterDelSlotsRange
+      | RedisCommandKind::Fcall(_)
+      | RedisCommandKind::FcallRO(_)
+      | RedisCommandKind::FunctionDelete
+      | RedisCommandKind::FunctionDump
+      | RedisCommandKind::FunctionKill
+      | RedisCommandKind::FunctionFlush
+      | RedisCommandKind::FunctionList
+      | RedisCommandKind::FunctionLoad
+      | RedisCommandKind::FunctionRestore
+      | RedisCommandKind::FunctionStats
+      | RedisCommandKind::_FunctionDeleteCluster(_)
+      | RedisCommandKind::_FunctionFlushCluster(_)
+      | RedisCommandKind::_FunctionLoadCluster(_)
+      | RedisCommandKind::_FunctionRestoreCluster(_)
+      | RedisCommandKind::Lcs
+      | RedisCommandKind::ExpireTime
+      | RedisCommandKind::PexpireTime
+      | RedisCommandKind::SortRo => Some(Version::new(7, 0, 0)),
+      RedisCommandKind::BlMove
+      | RedisCommandKind::LMove
+      | RedisCommandKind::Copy
+      | RedisCommandKind::GetDel
+      | RedisCommandKind::GeoSearch
+      | RedisCommandKind::GeoSearchStore
+      | RedisCommandKind::HRandField
+      | RedisCommandKind::Smismember
+      | RedisCommandKind::Zdiff
+      | RedisCommandKind::Zdiffstore
+      | RedisCommandKind::Zinter
+      | RedisCommandKind::Zunion
+      | RedisCommandKind::Zrandmember
+      | RedisCommandKind::Zrangestore
+      | RedisCommandKind::Zmscore
+      | RedisCommandKind::ClientInfo
+      | RedisCommandKind::ClientUnpause
+      | RedisCommandKind::XgroupCreateConsumer
+      | RedisCommandKind::Xautoclaim
+      | RedisCommandKind::Failover => Some(Version::new(6, 2, 0)),
+      RedisCommandKind::LPos => Some(Version::new(6, 0, 6)),
+      RedisCommandKind::AclLoad
+      | RedisCommandKind::AclSave
+      | RedisCommandKind::AclList
+      | RedisCommandKind::AclUsers
+      | RedisCommandKind::AclGetUser
+      | RedisCommandKind::AclSetUser
+      | RedisCommandKind::AclDelUser
+      | RedisCommandKind::AclCat
+      | RedisCommandKind::AclGenPass
+      | RedisCommandKind::AclWhoAmI
+      | RedisCommandKind::AclLog
+      | RedisCommandKind::AclHelp
+      | RedisCommandKind::BitFieldRo => Some(Version::new(6, 0, 0)),
+      RedisCommandKind::BzPopMin
+      | RedisCommandKind::BzPopMax
+      | RedisCommandKind::Zpopmin
+      | RedisCommandKind::Zpopmax
+      | RedisCommandKind::Replicaof
+      | RedisCommandKind::ClientUnblock
+      | RedisCommandKind::ClusterReplicas
+      | RedisCommandKind::Xadd
+      | RedisCommandKind::Xlen
+      | RedisCommandKind::Xrange
+      | RedisCommandKind::Xrevrange
+      | RedisCommandKind::Xread(_)
+      | RedisCommandKind::Xreadgroup(_)
+      | RedisCommandKind::XgroupCreate
+      | RedisCommandKind::XgroupDelConsumer
+      | RedisCommandKind::XgroupDestroy
+      | RedisCommandKind::XgroupSetId
+      | RedisCommandKind::Xack
+      | RedisCommandKind::Xpending
+      | RedisCommandKind::Xclaim
+      | RedisCommandKind::XinfoStream
+      | RedisCommandKind::XinfoGroups
+      | RedisCommandKind::XinfoConsumers
+      | RedisCommandKind::Xtrim => Some(Version::new(5, 0, 0)),
+      RedisCommandKind::MemoryDoctor
+      | RedisCommandKind::MemoryHelp
+      | RedisCommandKind::MemoryMallocStats
+      | RedisCommandKind::MemoryPurge
+      | RedisCommandKind::MemoryStats
+      | RedisCommandKind::MemoryUsage
+      | RedisCommandKind::Unlink
+      | RedisCommandKind::Swapdb
+      | RedisCommandKind::ObjectFreq => Some(Version::new(4, 0, 0)),
+      _ => None,
+    }
+  }
+
   pub fn custom_key_slot(&self) -> Option<u16> {
     match *self {
       RedisCommandKind::Scan(ref inner) => inner.key_slot.clone(),
       RedisCommandKind::_Custom(ref kind) => kind.hash_slot.clone(),
       RedisCommandKind::EvalSha(ref slot) => slot.key_slot.clone(),
       RedisCommandKind::Eval(ref slot) => slot.key_slot.clone(),
+      RedisCommandKind::Fcall(ref slot) | RedisCommandKind::FcallRO(ref slot) => slot.key_slot.clone(),
+      RedisCommandKind::Xread(ref inner) | RedisCommandKind::Xreadgroup(ref inner) => inner.key_slot.clone(),
       _ => None,
     }
   }
@@ -1331,8 +1653,11 @@ impl RedisCommandKind {
       RedisCommandKind::_FlushAllCluster(_)
       | RedisCommandKind::_AuthAllCluster(_)
       | RedisCommandKind::_ScriptFlushCluster(_)
-      | RedisCommandKind::_ScriptKillCluster(_)
-      | RedisCommandKind::_ScriptLoadCluster(_) => true,
+      | RedisCommandKind::_ScriptLoadCluster(_)
+      | RedisCommandKind::_FunctionDeleteCluster(_)
+      | RedisCommandKind::_FunctionFlushCluster(_)
+      | RedisCommandKind::_FunctionLoadCluster(_)
+      | RedisCommandKind::_FunctionRestoreCluster(_) => true,
       _ => false,
     }
   }
@@ -1344,13 +1669,23 @@ impl RedisCommandKind {
     }
   }
 
+  pub fn is_fcall(&self) -> bool {
+    match *self {
+      RedisCommandKind::Fcall(_) | RedisCommandKind::FcallRO(_) => true,
+      _ => false,
+    }
+  }
+
   pub fn all_nodes_response(&self) -> Option<&AllNodesResponse> {
     match *self {
       RedisCommandKind::_AuthAllCluster(ref inner) => Some(inner),
       RedisCommandKind::_FlushAllCluster(ref inner) => Some(inner),
       RedisCommandKind::_ScriptFlushCluster(ref inner) => Some(inner),
       RedisCommandKind::_ScriptLoadCluster(ref inner) => Some(inner),
-      RedisCommandKind::_ScriptKillCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionDeleteCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionFlushCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionLoadCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionRestoreCluster(ref inner) => Some(inner),
       _ => None,
     }
   }
@@ -1361,7 +1696,14 @@ impl RedisCommandKind {
       RedisCommandKind::_FlushAllCluster(ref inner) => Some(RedisCommandKind::_FlushAllCluster(inner.clone())),
       RedisCommandKind::_ScriptFlushCluster(ref inner) => Some(RedisCommandKind::_ScriptFlushCluster(inner.clone())),
       RedisCommandKind::_ScriptLoadCluster(ref inner) => Some(RedisCommandKind::_ScriptLoadCluster(inner.clone())),
-      RedisCommandKind::_ScriptKillCluster(ref inner) => Some(RedisCommandKind::_ScriptKillCluster(inner.clone())),
+      RedisCommandKind::_FunctionDeleteCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionDeleteCluster(inner.clone()))
+      }
+      RedisCommandKind::_FunctionFlushCluster(ref inner) => Some(RedisCommandKind::_FunctionFlushCluster(inner.clone())),
+      RedisCommandKind::_FunctionLoadCluster(ref inner) => Some(RedisCommandKind::_FunctionLoadCluster(inner.clone())),
+      RedisCommandKind::_FunctionRestoreCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionRestoreCluster(inner.clone()))
+      }
       _ => None,
     }
   }
@@ -1369,13 +1711,37 @@ impl RedisCommandKind {
   pub fn is_read(&self) -> bool {
     // TODO finish this and use for sending reads to replicas
     match *self {
+      RedisCommandKind::FcallRO(_) => true,
       _ => false,
     }
   }
 }
 
+/// A response frame read from the server.
+///
+/// Responses are always converted to the equivalent RESP2 frame. When that conversion loses type information, such as
+/// with RESP3 maps, sets, doubles, or booleans, the original RESP3 frame is also kept so it can be parsed into the
+/// typed `RedisValue` variants.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseFrame {
+  pub frame: Frame,
+  pub resp3: Option<Resp3Frame>,
+}
+
+impl ResponseFrame {
+  pub fn into_resp2(self) -> Frame {
+    self.frame
+  }
+}
+
+impl From<Frame> for ResponseFrame {
+  fn from(frame: Frame) -> Self {
+    ResponseFrame { frame, resp3: None }
+  }
+}
+
 /// Alias for a sender to notify the caller that a response was received.
-pub type ResponseSender = Option<OneshotSender<Result<Frame, RedisError>>>;
+pub type ResponseSender = Option<OneshotSender<Result<ResponseFrame, RedisError>>>;
 
 /// An arbitrary Redis command.
 pub struct RedisCommand {
@@ -1389,6 +1755,20 @@ pub struct RedisCommand {
   pub sent: Instant,
   /// Sender for notifying the command processing loop that the command received a response.
   pub resp_tx: Arc<RwLock<Option<OneshotSender<()>>>>,
+  /// Options provided by the caller via `with_options`, if any.
+  pub options: Option<Options>,
+  /// The `MOVED` or `ASK` redirection to follow the next time the command is written.
+  pub redirection: Option<Redirection>,
+  /// Number of `MOVED` or `ASK` redirections followed by the command.
+  pub redirections: usize,
+  /// Number of times the command was retried after a `BUSY` error.
+  pub busy_retries: u32,
+  /// Whether or not the command hooks already ran on the command.
+  pub ran_hooks: bool,
+  /// Whether or not the command was written to the server and is still waiting on a response.
+  ///
+  /// This is only set for blocking commands, and is used to decide whether a dropped command is blocking the connection.
+  pub in_flight: Arc<AtomicBool>,
   #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
   pub traces: CommandTraces,
 }
@@ -1416,6 +1796,12 @@ impl RedisCommand {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      options: utils::read_command_options(),
+      redirection: None,
+      redirections: 0,
+      busy_retries: 0,
+      ran_hooks: false,
+      in_flight: Arc::new(AtomicBool::new(false)),
     }
   }
 
@@ -1428,6 +1814,12 @@ impl RedisCommand {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      options: utils::read_command_options(),
+      redirection: None,
+      redirections: 0,
+      busy_retries: 0,
+      ran_hooks: false,
+      in_flight: Arc::new(AtomicBool::new(false)),
     }
   }
 
@@ -1440,6 +1832,12 @@ impl RedisCommand {
       args: self.args.clone(),
       sent: self.sent.clone(),
       resp_tx: self.resp_tx.clone(),
+      options: self.options.clone(),
+      redirection: None,
+      redirections: 0,
+      busy_retries: 0,
+      ran_hooks: false,
+      in_flight: Arc::new(AtomicBool::new(false)),
       traces: CommandTraces::default(),
     }
   }
@@ -1453,9 +1851,25 @@ impl RedisCommand {
       args: self.args.clone(),
       sent: self.sent.clone(),
       resp_tx: self.resp_tx.clone(),
+      options: self.options.clone(),
+      redirection: None,
+      redirections: 0,
+      busy_retries: 0,
+      ran_hooks: false,
+      in_flight: Arc::new(AtomicBool::new(false)),
     }
   }
 
+  /// Whether or not the caller dropped the receiver for the response.
+  pub fn has_dropped_receiver(&self) -> bool {
+    self.tx.as_ref().map(|tx| tx.is_closed()).unwrap_or(false)
+  }
+
+  /// Whether or not the deadline or cancellation token provided via `with_options` has been triggered.
+  pub fn is_canceled(&self) -> bool {
+    self.options.as_ref().map(|options| options.is_canceled()).unwrap_or(false)
+  }
+
   #[cfg(feature = "full-tracing")]
   pub fn take_queued_span(&mut self) -> Option<Span> {
     self.traces.queued.take()
@@ -1518,25 +1932,140 @@ impl RedisCommand {
     }
   }
 
+  /// Whether or not the caller asked for the command to run before other queued commands.
+  pub fn is_high_priority(&self) -> bool {
+    self.options.as_ref().map(|options| options.priority).unwrap_or(false)
+  }
+
   /// Read the first key in the command, if any.
-  pub fn extract_key(&self) -> Option<Cow<str>> {
+  pub fn extract_key(&self) -> Option<&[u8]> {
     if self.no_cluster() {
       return None;
     }
+    if self.kind.is_eval() || self.kind.is_fcall() {
+      return self.script_keys().first().and_then(key_arg);
+    }
+    if self.kind == RedisCommandKind::Migrate {
+      return self.migrate_keys().first().and_then(key_arg);
+    }
+    if self.kind == RedisCommandKind::BitOp {
+      // the first argument is the operation
+      return self.args.get(1).and_then(key_arg);
+    }
 
     match self.args.first() {
-      Some(RedisValue::String(ref s)) => Some(Cow::Borrowed(s)),
-      Some(RedisValue::Bytes(ref b)) => Some(String::from_utf8_lossy(b)),
-      Some(_) => match self.args.get(1) {
-        // some commands take a `num_keys` argument first, followed by keys
-        Some(RedisValue::String(ref s)) => Some(Cow::Borrowed(s)),
-        Some(RedisValue::Bytes(ref b)) => Some(String::from_utf8_lossy(b)),
-        _ => None,
-      },
+      Some(RedisValue::String(_)) | Some(RedisValue::Bytes(_)) => self.args.first().and_then(key_arg),
+      // some commands take a `num_keys` argument first, followed by keys
+      Some(_) => self.args.get(1).and_then(key_arg),
       None => None,
     }
   }
 
+  /// Read all the keys in the command, if any.
+  ///
+  /// Commands that operate on several keys return each key, and all other commands fall back to [extract_key](Self::extract_key).
+  pub fn extract_keys(&self) -> Vec<&[u8]> {
+    if self.no_cluster() {
+      return Vec::new();
+    }
+
+    match self.kind {
+      RedisCommandKind::Mget
+      | RedisCommandKind::Del
+      | RedisCommandKind::Exists
+      | RedisCommandKind::Unlink
+      | RedisCommandKind::Touch
+      | RedisCommandKind::Watch
+      | RedisCommandKind::Pfcount
+      | RedisCommandKind::Pfmerge
+      | RedisCommandKind::Sdiff
+      | RedisCommandKind::Sdiffstore
+      | RedisCommandKind::Sinter
+      | RedisCommandKind::Sinterstore
+      | RedisCommandKind::Sunion
+      | RedisCommandKind::Sunionstore => self.args.iter().filter_map(key_arg).collect(),
+      RedisCommandKind::Eval(_)
+      | RedisCommandKind::EvalSha(_)
+      | RedisCommandKind::Fcall(_)
+      | RedisCommandKind::FcallRO(_) => self.script_keys().iter().filter_map(key_arg).collect(),
+      RedisCommandKind::Migrate => self.migrate_keys().iter().filter_map(key_arg).collect(),
+      RedisCommandKind::BitOp => self.args.iter().skip(1).filter_map(key_arg).collect(),
+      RedisCommandKind::Sort => self
+        .args
+        .first()
+        .into_iter()
+        .chain(self.sort_store_key())
+        .filter_map(key_arg)
+        .collect(),
+      RedisCommandKind::Mset | RedisCommandKind::Msetnx => self.args.iter().step_by(2).filter_map(key_arg).collect(),
+      RedisCommandKind::BlPop | RedisCommandKind::BrPop | RedisCommandKind::BzPopMin | RedisCommandKind::BzPopMax => {
+        // the last argument is the timeout
+        let len = self.args.len().saturating_sub(1);
+        self.args[..len].iter().filter_map(key_arg).collect()
+      }
+      RedisCommandKind::Rename
+      | RedisCommandKind::Renamenx
+      | RedisCommandKind::Copy
+      | RedisCommandKind::Smove
+      | RedisCommandKind::Rpoplpush
+      | RedisCommandKind::BrPopLPush
+      | RedisCommandKind::LMove
+      | RedisCommandKind::BlMove
+      | RedisCommandKind::Zrangestore
+      | RedisCommandKind::Lcs
+      | RedisCommandKind::GeoSearchStore => self.args.iter().take(2).filter_map(key_arg).collect(),
+      _ => self.extract_key().into_iter().collect(),
+    }
+  }
+
+  /// Read the keys in an `EVAL`, `EVALSHA`, or `FCALL` command, which follow the script and the number of keys.
+  fn script_keys(&self) -> &[RedisValue] {
+    let num_keys = match self.args.get(1) {
+      Some(RedisValue::Integer(i)) if *i > 0 => *i as usize,
+      _ => return &[],
+    };
+
+    self.args.get(2..2 + num_keys).unwrap_or(&[])
+  }
+
+  /// Read the `STORE` destination in a `SORT` command, if any.
+  fn sort_store_key(&self) -> Option<&RedisValue> {
+    // skip the options that take patterns as arguments, which could be confused with the `STORE` token
+    let mut idx = 1;
+    while let Some(arg) = self.args.get(idx) {
+      match arg.as_str().as_deref() {
+        Some("BY") | Some("GET") => idx += 2,
+        Some("LIMIT") => idx += 3,
+        Some("STORE") => return self.args.get(idx + 1),
+        _ => idx += 1,
+      };
+    }
+    None
+  }
+
+  /// Read the keys in a `MIGRATE` command, which follow the host and port or the `KEYS` token in the multi-key form.
+  fn migrate_keys(&self) -> &[RedisValue] {
+    match self.args.get(2) {
+      Some(RedisValue::String(ref s)) if s.is_empty() => {},
+      Some(RedisValue::Bytes(ref b)) if b.is_empty() => {},
+      Some(_) => return &self.args[2..3],
+      None => return &[],
+    };
+
+    // skip the db, timeout, and options, some of which take arguments that could be confused with the `KEYS` token
+    let mut idx = 5;
+    while let Some(arg) = self.args.get(idx) {
+      match arg.as_str().as_deref() {
+        Some("COPY") | Some("REPLACE") => idx += 1,
+        Some("AUTH") => idx += 2,
+        Some("AUTH2") => idx += 3,
+        Some("KEYS") => return &self.args[idx + 1..],
+        _ => return &[],
+      };
+    }
+    &[]
+  }
+
   pub fn key_slot(&self) -> Option<u16> {
     self.kind.custom_key_slot()
   }
@@ -1549,6 +2078,14 @@ impl RedisCommand {
   }
 }
 
+fn key_arg(value: &RedisValue) -> Option<&[u8]> {
+  match *value {
+    RedisValue::String(ref s) => Some(s.as_bytes()),
+    RedisValue::Bytes(ref b) => Some(b),
+    _ => None,
+  }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReplicaNodes {
   servers: Vec<Arc<String>>,
@@ -1619,6 +2156,34 @@ impl ClusterKeyCache {
     Ok(cache)
   }
 
+  /// Create a new cache from the output of CLUSTER SLOTS, using `default_host` for nodes without a known host.
+  pub fn from_cluster_slots(frame: Frame, default_host: &str) -> Result<ClusterKeyCache, RedisError> {
+    let mut data: Vec<Arc<SlotRange>> = protocol_utils::parse_cluster_slots(frame, default_host)?
+      .into_iter()
+      .map(|range| {
+        let server = Arc::new(range.primary.server());
+        let id = range.primary.id.map(|id| Arc::new(id)).unwrap_or_else(|| server.clone());
+
+        Arc::new(SlotRange {
+          start: range.start,
+          end: range.end,
+          server,
+          id,
+        })
+      })
+      .collect();
+
+    if data.is_empty() {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid empty CLUSTER SLOTS response.",
+      ));
+    }
+    data.sort_by(|lhs, rhs| lhs.start.cmp(&rhs.start));
+
+    Ok(ClusterKeyCache { data })
+  }
+
   /// Read the set of unique primary/main nodes in the cluster.
   pub fn unique_main_nodes(&self) -> Vec<Arc<String>> {
     let mut out = BTreeSet::new();
@@ -1660,8 +2225,60 @@ impl ClusterKeyCache {
   }
 
   /// Calculate the cluster hash slot for the provided key.
-  pub fn hash_key(key: &str) -> u16 {
-    redis_protocol::redis_keyslot(key)
+  pub fn hash_key<K>(key: K) -> u16
+  where
+    K: AsRef<[u8]>,
+  {
+    redis_keyslot(key)
+  }
+
+  /// Move the provided hash slot to a different server, returning `false` if the server already owns the slot.
+  ///
+  /// This is used to update the cache after a `MOVED` error without reading the entire cluster state.
+  pub fn update_slot(&mut self, slot: u16, server: Arc<String>) -> bool {
+    let idx = match self.data.iter().position(|range| range.start <= slot && range.end >= slot) {
+      Some(idx) => idx,
+      None => return false,
+    };
+    if self.data[idx].server == server {
+      return false;
+    }
+    let id = self
+      .data
+      .iter()
+      .find(|range| range.server == server)
+      .map(|range| range.id.clone())
+      .unwrap_or_else(|| server.clone());
+
+    let old = self.data.remove(idx);
+    let mut ranges = Vec::with_capacity(3);
+    if old.start < slot {
+      ranges.push(Arc::new(SlotRange {
+        start: old.start,
+        end: slot - 1,
+        server: old.server.clone(),
+        id: old.id.clone(),
+      }));
+    }
+    ranges.push(Arc::new(SlotRange {
+      start: slot,
+      end: slot,
+      server,
+      id,
+    }));
+    if old.end > slot {
+      ranges.push(Arc::new(SlotRange {
+        start: slot + 1,
+        end: old.end,
+        server: old.server.clone(),
+        id: old.id.clone(),
+      }));
+    }
+    for (offset, range) in ranges.into_iter().enumerate() {
+      self.data.insert(idx + offset, range);
+    }
+
+    true
   }
 
   /// Find the server that owns the provided hash slot.
@@ -1694,24 +2311,74 @@ impl ClusterKeyCache {
   }
 }
 
+/// The socket addresses for a server, in the order in which they should be tried.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResolvedAddrs {
+  /// The `host:port` used to find the addresses.
+  pub server: String,
+  /// The socket addresses for the server. This is never empty.
+  pub addrs: Vec<SocketAddr>,
+}
+
+impl ResolvedAddrs {
+  /// Create a new set of addresses, interleaving IPv4 and IPv6 addresses according to RFC 8305.
+  pub fn new(server: String, addrs: Vec<SocketAddr>) -> Self {
+    let prefer_ipv6 = addrs.first().map(|addr| addr.is_ipv6()).unwrap_or(false);
+    let (mut preferred, mut other): (VecDeque<_>, VecDeque<_>) =
+      addrs.into_iter().partition(|addr| addr.is_ipv6() == prefer_ipv6);
+
+    let mut addrs = Vec::with_capacity(preferred.len() + other.len());
+    while !preferred.is_empty() || !other.is_empty() {
+      addrs.extend(preferred.pop_front());
+      addrs.extend(other.pop_front());
+    }
+
+    ResolvedAddrs { server, addrs }
+  }
+}
+
 // TODO support custom DNS resolution logic by exposing this in the client.
 /// Default DNS resolver that just uses `to_socket_addrs` under the hood.
 #[derive(Clone, Debug)]
 pub struct DefaultResolver {
   id: Arc<String>,
+  address_map: HashMap<(String, u16), (String, u16)>,
 }
 
 impl DefaultResolver {
-  /// Create a new resolver using the system's default DNS resolution.
-  pub fn new(id: &Arc<String>) -> Self {
-    DefaultResolver { id: id.clone() }
+  /// Create a new resolver using the system's default DNS resolution and the provided address mapping.
+  pub fn new(id: &Arc<String>, address_map: HashMap<(String, u16), (String, u16)>) -> Self {
+    DefaultResolver {
+      id: id.clone(),
+      address_map,
+    }
+  }
+
+  /// Map the advertised address to the address that should be used to connect to the server.
+  pub fn map_address(&self, host: String, port: u16) -> (String, u16) {
+    match self.address_map.get(&(host.clone(), port)) {
+      Some((mapped_host, mapped_port)) => {
+        trace!(
+          "{}: Mapping {}:{} to {}:{}",
+          self.id,
+          host,
+          port,
+          mapped_host,
+          mapped_port
+        );
+        (mapped_host.clone(), *mapped_port)
+      }
+      None => (host, port),
+    }
   }
 }
 
 #[async_trait]
 impl Resolve for DefaultResolver {
-  async fn resolve(&self, host: String, port: u16) -> Result<SocketAddr, RedisError> {
+  async fn resolve(&self, host: String, port: u16) -> Result<ResolvedAddrs, RedisError> {
     let client_id = self.id.clone();
+    let server = format!("{}:{}", host, port);
+    let (host, port) = self.map_address(host, port);
 
     tokio::task::spawn_blocking(move || {
       let ips: Vec<SocketAddr> = format!("{}:{}", host, port).to_socket_addrs()?.into_iter().collect();
@@ -1722,20 +2389,186 @@ impl Resolve for DefaultResolver {
           format!("Failed to resolve {}:{}", host, port),
         ))
       } else {
-        let possible_addrs = ips.len();
-        let addr = ips[0];
+        let addrs = ResolvedAddrs::new(server, ips);
 
         trace!(
-          "{}: Using {} among {} possible socket addresses for {}:{}",
+          "{}: Found {} possible socket addresses for {}:{}: {:?}",
           client_id,
-          addr.ip(),
-          possible_addrs,
+          addrs.addrs.len(),
           host,
-          port
+          port,
+          addrs.addrs
         );
-        Ok(addr)
+        Ok(addrs)
       }
     })
     .await?
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn should_resolve_mapped_address() {
+    let mut address_map = HashMap::new();
+    address_map.insert(("172.17.0.2".to_owned(), 6379), ("127.0.0.1".to_owned(), 30001));
+    let resolver = DefaultResolver::new(&Arc::new("test".to_owned()), address_map);
+
+    let addrs = resolver.resolve("172.17.0.2".into(), 6379).await.unwrap();
+    assert_eq!(addrs.server, "172.17.0.2:6379");
+    assert_eq!(addrs.addrs[0], "127.0.0.1:30001".parse().unwrap());
+    let addrs = resolver.resolve("127.0.0.1".into(), 6379).await.unwrap();
+    assert_eq!(addrs.addrs[0], "127.0.0.1:6379".parse().unwrap());
+  }
+
+  #[test]
+  fn should_interleave_address_families() {
+    let addrs: Vec<SocketAddr> = vec!["[::1]:6379", "[::2]:6379", "[::3]:6379", "10.0.0.1:6379", "10.0.0.2:6379"]
+      .into_iter()
+      .map(|s| s.parse().unwrap())
+      .collect();
+    let expected: Vec<SocketAddr> = vec!["[::1]:6379", "10.0.0.1:6379", "[::2]:6379", "10.0.0.2:6379", "[::3]:6379"]
+      .into_iter()
+      .map(|s| s.parse().unwrap())
+      .collect();
+
+    assert_eq!(ResolvedAddrs::new("foo:6379".into(), addrs).addrs, expected);
+  }
+
+  #[test]
+  fn should_move_slot_to_new_server() {
+    let range = |start, end, server: &str| {
+      Arc::new(SlotRange {
+        start,
+        end,
+        server: Arc::new(server.to_owned()),
+        id: Arc::new(server.to_owned()),
+      })
+    };
+    let mut cache = ClusterKeyCache::from(vec![range(0, 8191, "foo:6379"), range(8192, 16383, "bar:6379")]);
+
+    assert!(!cache.update_slot(100, Arc::new("foo:6379".into())));
+    assert!(cache.update_slot(100, Arc::new("bar:6379".into())));
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.get_server(99).unwrap().server.as_str(), "foo:6379");
+    assert_eq!(cache.get_server(100).unwrap().server.as_str(), "bar:6379");
+    assert_eq!(cache.get_server(101).unwrap().server.as_str(), "foo:6379");
+    assert_eq!(cache.get_server(8192).unwrap().server.as_str(), "bar:6379");
+
+    assert!(cache.update_slot(0, Arc::new("baz:6379".into())));
+    assert_eq!(cache.get_server(0).unwrap().server.as_str(), "baz:6379");
+    assert_eq!(cache.get_server(1).unwrap().server.as_str(), "foo:6379");
+  }
+
+  #[test]
+  fn should_extract_all_keys() {
+    let keys = |kind, args: Vec<RedisValue>| -> Vec<Vec<u8>> {
+      RedisCommand::new(kind, args, None)
+        .extract_keys()
+        .into_iter()
+        .map(|k| k.to_vec())
+        .collect()
+    };
+
+    assert_eq!(keys(RedisCommandKind::Get, vec!["a".into()]), vec![b"a".to_vec()]);
+    assert_eq!(keys(RedisCommandKind::Del, vec!["a".into(), "b".into()]), vec![b"a".to_vec(), b"b".to_vec()]);
+    assert_eq!(
+      keys(RedisCommandKind::Mset, vec!["a".into(), 1.into(), "b".into(), 2.into()]),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(
+      keys(RedisCommandKind::BlPop, vec!["a".into(), "b".into(), 1.into()]),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(
+      keys(RedisCommandKind::Smove, vec!["a".into(), "b".into(), "c".into()]),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(
+      keys(
+        RedisCommandKind::Sort,
+        vec!["a".into(), "GET".into(), "STORE".into(), "STORE".into(), "b".into()]
+      ),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(
+      keys(RedisCommandKind::Lcs, vec!["a".into(), "b".into(), "LEN".into()]),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(
+      keys(RedisCommandKind::BitOp, vec!["AND".into(), "a".into(), "b".into()]),
+      vec![b"a".to_vec(), b"b".to_vec()]
+    );
+    assert!(keys(RedisCommandKind::Publish, vec!["a".into(), "b".into()]).is_empty());
+  }
+
+  #[test]
+  fn should_extract_script_keys() {
+    let slot = || CustomKeySlot { key_slot: None };
+    let command = RedisCommand::new(
+      RedisCommandKind::Eval(slot()),
+      vec!["return 1".into(), 2.into(), "a".into(), "b".into(), "c".into()],
+      None,
+    );
+    assert_eq!(command.extract_key().unwrap(), b"a");
+    assert_eq!(command.extract_keys(), vec![b"a", b"b"]);
+
+    let command = RedisCommand::new(
+      RedisCommandKind::EvalSha(slot()),
+      vec!["abc".into(), 0.into(), "a".into()],
+      None,
+    );
+    assert!(command.extract_key().is_none());
+    assert!(command.extract_keys().is_empty());
+  }
+
+  #[test]
+  fn should_extract_migrate_keys() {
+    let command = RedisCommand::new(
+      RedisCommandKind::Migrate,
+      vec!["host".into(), 6379.into(), "a".into(), 0.into(), 1000.into(), "COPY".into()],
+      None,
+    );
+    assert_eq!(command.extract_key().unwrap(), b"a");
+    assert_eq!(command.extract_keys(), vec![b"a"]);
+
+    let command = RedisCommand::new(
+      RedisCommandKind::Migrate,
+      vec![
+        "host".into(),
+        6379.into(),
+        "".into(),
+        0.into(),
+        1000.into(),
+        "REPLACE".into(),
+        "AUTH2".into(),
+        "KEYS".into(),
+        "KEYS".into(),
+        "KEYS".into(),
+        "b".into(),
+        "c".into(),
+      ],
+      None,
+    );
+    assert_eq!(command.extract_key().unwrap(), b"b");
+    assert_eq!(command.extract_keys(), vec![b"b", b"c"]);
+  }
+
+  #[tokio::test]
+  async fn should_only_prioritize_commands_with_priority_option() {
+    assert!(!RedisCommand::new(RedisCommandKind::Ping, vec![], None).is_high_priority());
+    assert!(!RedisCommand::new(RedisCommandKind::Auth, vec!["foo".into()], None).is_high_priority());
+
+    let options = Options {
+      priority: true,
+      ..Default::default()
+    };
+    let command = utils::with_command_options(options, async {
+      RedisCommand::new(RedisCommandKind::Ping, vec![], None)
+    })
+    .await;
+    assert!(command.is_high_priority());
+  }
+}
@@ -8,9 +8,11 @@ use crate::types::{RedisConfig, ServerConfig, QUEUED};
 use crate::utils;
 use parking_lot::RwLock;
 use redis_protocol::resp2::types::{Frame as ProtocolFrame, FrameKind as ProtocolFrameKind};
+use redis_protocol::resp3::types::Frame as Resp3Frame;
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::str;
 use std::sync::Arc;
 
@@ -20,12 +22,12 @@ macro_rules! parse_or_zero(
   }
 );
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn uses_tls(inner: &Arc<RedisClientInner>) -> bool {
   inner.config.read().tls.is_some()
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub fn uses_tls(_: &Arc<RedisClientInner>) -> bool {
   false
 }
@@ -42,7 +44,7 @@ pub fn server_to_parts(server: &Arc<String>) -> Result<(&str, u16), RedisError>
 pub async fn parse_cluster_server(
   inner: &Arc<RedisClientInner>,
   server: &str,
-) -> Result<(String, SocketAddr), RedisError> {
+) -> Result<(String, ResolvedAddrs), RedisError> {
   let parts: Vec<&str> = server.trim().split(":").collect();
   if parts.len() != 2 {
     return Err(RedisError::new(
@@ -54,7 +56,27 @@ pub async fn parse_cluster_server(
   let port = parts[1].parse::<u16>()?;
   let addr = inner.resolver.resolve(parts[0].to_owned(), port).await?;
 
-  Ok((parts[0].to_owned(), addr))
+  Ok((read_cluster_tls_domain(inner, parts[0], port), addr))
+}
+
+/// Read the TLS domain for a cluster node.
+///
+/// The `tls_server_names` mapping is used first if it contains the advertised address. Otherwise some managed services
+/// report cluster nodes by IP address, in which case the configuration endpoint is used as the domain. Hosts are only
+/// rewritten when the client uses TLS.
+fn read_cluster_tls_domain(inner: &Arc<RedisClientInner>, host: &str, port: u16) -> String {
+  let config_guard = inner.config.read();
+
+  if let Some(server_name) = config_guard.tls_server_names.get(&(host.to_owned(), port)) {
+    return server_name.to_owned();
+  }
+  if config_guard.uses_tls() && config_guard.service.is_some() && host.parse::<IpAddr>().is_ok() {
+    if let Some((ref endpoint, _)) = config_guard.server.hosts().first() {
+      return (*endpoint).to_owned();
+    }
+  }
+
+  host.to_owned()
 }
 
 pub fn read_clustered_hosts(config: &RwLock<RedisConfig>) -> Result<Vec<(String, u16)>, RedisError> {
@@ -78,7 +100,15 @@ pub fn read_centralized_domain(config: &RwLock<RedisConfig>) -> Result<String, R
   }
 }
 
-pub async fn read_centralized_addr(inner: &Arc<RedisClientInner>) -> Result<SocketAddr, RedisError> {
+/// Read the path to the Unix domain socket, if the client connects via a Unix domain socket.
+pub fn read_unix_path(config: &RwLock<RedisConfig>) -> Option<PathBuf> {
+  match config.read().server {
+    ServerConfig::Unix { ref path } => Some(path.clone()),
+    _ => None,
+  }
+}
+
+pub async fn read_centralized_addr(inner: &Arc<RedisClientInner>) -> Result<ResolvedAddrs, RedisError> {
   let (host, port) = match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port, .. } => (host.clone(), *port),
     _ => {
@@ -93,7 +123,7 @@ pub async fn read_centralized_addr(inner: &Arc<RedisClientInner>) -> Result<Sock
 }
 
 /// Server hostnames/IP addresses can have a cport suffix of the form `@1122` that needs to be removed.
-fn remove_cport_suffix(server: String) -> String {
+pub(crate) fn remove_cport_suffix(server: String) -> String {
   if let Some(first) = server.split("@").next() {
     return first.to_owned();
   }
@@ -131,27 +161,71 @@ pub fn binary_search(slots: &Vec<Arc<SlotRange>>, slot: u16) -> Option<Arc<SlotR
   None
 }
 
+/// A line from the `CLUSTER NODES` response.
+pub(crate) struct ClusterNodeLine<'a> {
+  pub id: &'a str,
+  /// The node address, without the cport suffix.
+  pub server: String,
+  pub flags: Vec<&'a str>,
+  /// The slot ranges served by the node, including any migrating or importing slots.
+  pub slots: Vec<&'a str>,
+}
+
+impl<'a> ClusterNodeLine<'a> {
+  pub fn is_primary(&self) -> bool {
+    self.flags.contains(&"master")
+  }
+
+  #[cfg(feature = "monitor")]
+  pub fn is_replica(&self) -> bool {
+    self.flags.iter().any(|flag| *flag == "slave" || *flag == "replica")
+  }
+
+  /// Whether or not the node is failing, missing an address, or still joining the cluster.
+  #[cfg(feature = "monitor")]
+  pub fn is_unavailable(&self) -> bool {
+    self
+      .flags
+      .iter()
+      .any(|flag| *flag == "fail" || *flag == "noaddr" || *flag == "handshake")
+  }
+}
+
+/// Parse each line of the `CLUSTER NODES` response.
+pub(crate) fn parse_cluster_node_lines(status: &str) -> Result<Vec<ClusterNodeLine<'_>>, RedisError> {
+  status
+    .lines()
+    .map(|line| {
+      let parts: Vec<&str> = line.split(" ").collect();
+      if parts.len() < 8 {
+        return Err(RedisError::new(
+          RedisErrorKind::ProtocolError,
+          format!("Invalid cluster node status line {}.", line),
+        ));
+      }
+
+      Ok(ClusterNodeLine {
+        id: parts[0],
+        server: remove_cport_suffix(parts[1].to_owned()),
+        flags: parts[2].split(",").collect(),
+        slots: parts[8..].to_vec(),
+      })
+    })
+    .collect()
+}
+
 pub fn parse_cluster_nodes(status: String) -> Result<HashMap<Arc<String>, Vec<SlotRange>>, RedisError> {
   let mut out: HashMap<Arc<String>, Vec<SlotRange>> = HashMap::new();
 
   // build out the slot ranges for the primary nodes
-  for line in status.lines() {
-    let parts: Vec<&str> = line.split(" ").collect();
+  for node in parse_cluster_node_lines(&status)?.into_iter() {
+    let id = Arc::new(node.id.to_owned());
 
-    if parts.len() < 8 {
-      return Err(RedisError::new(
-        RedisErrorKind::ProtocolError,
-        format!("Invalid cluster node status line {}.", line),
-      ));
-    }
-
-    let id = Arc::new(parts[0].to_owned());
-
-    if parts[2].contains("master") {
+    if node.is_primary() {
       let mut slots: Vec<SlotRange> = Vec::new();
 
-      let server = Arc::new(remove_cport_suffix(parts[1].to_owned()));
-      for slot in parts[8..].iter() {
+      let server = Arc::new(node.server);
+      for slot in node.slots.iter() {
         let inner_parts: Vec<&str> = slot.split("-").collect();
 
         if inner_parts.len() == 1 {
@@ -192,6 +266,232 @@ pub fn parse_cluster_nodes(status: String) -> Result<HashMap<Arc<String>, Vec<Sl
   Ok(out)
 }
 
+fn cluster_field_string(frame: &ProtocolFrame) -> Option<String> {
+  match frame {
+    ProtocolFrame::BulkString(ref bytes) => str::from_utf8(bytes).ok().map(|s| s.to_owned()),
+    ProtocolFrame::SimpleString(ref s) => Some(s.to_owned()),
+    ProtocolFrame::Integer(ref i) => Some(i.to_string()),
+    _ => None,
+  }
+}
+
+fn cluster_field_integer(frame: &ProtocolFrame) -> Option<i64> {
+  match frame {
+    ProtocolFrame::Integer(ref i) => Some(*i),
+    ProtocolFrame::BulkString(ref bytes) => str::from_utf8(bytes).ok().and_then(|s| s.parse::<i64>().ok()),
+    ProtocolFrame::SimpleString(ref s) => s.parse::<i64>().ok(),
+    _ => None,
+  }
+}
+
+fn cluster_slots_error(details: &str) -> RedisError {
+  RedisError::new(
+    RedisErrorKind::ProtocolError,
+    format!("Invalid CLUSTER SLOTS response: {}", details),
+  )
+}
+
+fn cluster_shards_error(details: &str) -> RedisError {
+  RedisError::new(
+    RedisErrorKind::ProtocolError,
+    format!("Invalid CLUSTER SHARDS response: {}", details),
+  )
+}
+
+fn parse_cluster_slot_node(frame: ProtocolFrame, default_host: &str) -> Result<ClusterSlotNode, RedisError> {
+  let parts = match frame {
+    ProtocolFrame::Array(parts) => parts,
+    _ => return Err(cluster_slots_error("expected an array of node fields")),
+  };
+
+  let host = match parts.get(0).and_then(|frame| cluster_field_string(frame)) {
+    Some(host) if !host.is_empty() && host != "?" => host,
+    _ => default_host.to_owned(),
+  };
+  let port = match parts.get(1).and_then(|frame| cluster_field_integer(frame)) {
+    Some(port) => port as u16,
+    None => return Err(cluster_slots_error("missing node port")),
+  };
+  let id = parts.get(2).and_then(|frame| cluster_field_string(frame));
+
+  Ok(ClusterSlotNode { host, port, id })
+}
+
+/// Parse the slot ranges from a CLUSTER SLOTS response.
+///
+/// Nodes that report an empty or unknown (`?`) host are assumed to use the same host as the node that answered the
+/// command, per the CLUSTER SLOTS docs.
+pub fn parse_cluster_slots(frame: ProtocolFrame, default_host: &str) -> Result<Vec<ClusterSlotRange>, RedisError> {
+  let ranges = match frame {
+    ProtocolFrame::Array(ranges) => ranges,
+    ProtocolFrame::Error(ref s) => return Err(pretty_error(s)),
+    _ => return Err(cluster_slots_error("expected an array")),
+  };
+  let mut out = Vec::with_capacity(ranges.len());
+
+  for range in ranges.into_iter() {
+    let mut parts = match range {
+      ProtocolFrame::Array(parts) => parts.into_iter(),
+      _ => return Err(cluster_slots_error("expected an array of slot ranges")),
+    };
+
+    let start = match parts.next().as_ref().and_then(cluster_field_integer) {
+      Some(i) => i as u16,
+      None => return Err(cluster_slots_error("missing slot range start")),
+    };
+    let end = match parts.next().as_ref().and_then(cluster_field_integer) {
+      Some(i) => i as u16,
+      None => return Err(cluster_slots_error("missing slot range end")),
+    };
+    // the first node is the primary, and any remaining nodes are replicas
+    let primary = match parts.next() {
+      Some(frame) => parse_cluster_slot_node(frame, default_host)?,
+      None => return Err(cluster_slots_error("missing primary node")),
+    };
+    let replicas = parts
+      .map(|frame| parse_cluster_slot_node(frame, default_host))
+      .collect::<Result<Vec<ClusterSlotNode>, RedisError>>()?;
+
+    out.push(ClusterSlotRange {
+      start,
+      end,
+      primary,
+      replicas,
+    });
+  }
+
+  Ok(out)
+}
+
+fn parse_cluster_shard_node(frame: ProtocolFrame) -> Result<ClusterShardNode, RedisError> {
+  let fields = match frame {
+    ProtocolFrame::Array(fields) => fields,
+    _ => return Err(cluster_shards_error("expected an array of node fields")),
+  };
+  let mut fields = fields.into_iter();
+
+  let (mut id, mut endpoint, mut ip, mut hostname) = (None, None, None, None);
+  let (mut port, mut tls_port, mut role, mut replication_offset, mut health) = (None, None, None, 0, None);
+  while let (Some(key), Some(value)) = (fields.next(), fields.next()) {
+    let key = match cluster_field_string(&key) {
+      Some(key) => key,
+      None => return Err(cluster_shards_error("invalid node field name")),
+    };
+
+    match key.as_str() {
+      "id" => id = cluster_field_string(&value),
+      "endpoint" => endpoint = cluster_field_string(&value),
+      "ip" => ip = cluster_field_string(&value),
+      "hostname" => hostname = cluster_field_string(&value).filter(|s| !s.is_empty()),
+      "port" => port = cluster_field_integer(&value).map(|i| i as u16),
+      "tls-port" => tls_port = cluster_field_integer(&value).map(|i| i as u16),
+      "replication-offset" => replication_offset = cluster_field_integer(&value).unwrap_or(0) as u64,
+      "role" => {
+        role = match cluster_field_string(&value).as_ref().map(|s| s.as_str()) {
+          Some("master") | Some("primary") => Some(ClusterNodeRole::Primary),
+          Some("replica") | Some("slave") => Some(ClusterNodeRole::Replica),
+          _ => return Err(cluster_shards_error("invalid node role")),
+        }
+      }
+      "health" => {
+        health = match cluster_field_string(&value).as_ref().map(|s| s.as_str()) {
+          Some("online") => Some(ClusterNodeHealth::Online),
+          Some("failed") => Some(ClusterNodeHealth::Failed),
+          Some("loading") => Some(ClusterNodeHealth::Loading),
+          _ => return Err(cluster_shards_error("invalid node health")),
+        }
+      }
+      _ => {}
+    };
+  }
+
+  Ok(ClusterShardNode {
+    id: id.ok_or_else(|| cluster_shards_error("missing node id"))?,
+    endpoint: endpoint.unwrap_or_default(),
+    ip: ip.unwrap_or_default(),
+    hostname,
+    port,
+    tls_port,
+    role: role.ok_or_else(|| cluster_shards_error("missing node role"))?,
+    replication_offset,
+    health: health.ok_or_else(|| cluster_shards_error("missing node health"))?,
+  })
+}
+
+/// Parse the shards from a CLUSTER SHARDS response.
+pub fn parse_cluster_shards(frame: ProtocolFrame) -> Result<Vec<ClusterShard>, RedisError> {
+  let shards = match frame {
+    ProtocolFrame::Array(shards) => shards,
+    ProtocolFrame::Error(ref s) => return Err(pretty_error(s)),
+    _ => return Err(cluster_shards_error("expected an array")),
+  };
+  let mut out = Vec::with_capacity(shards.len());
+
+  for shard in shards.into_iter() {
+    let mut fields = match shard {
+      ProtocolFrame::Array(fields) => fields.into_iter(),
+      _ => return Err(cluster_shards_error("expected an array of shards")),
+    };
+    let (mut slots, mut nodes) = (Vec::new(), Vec::new());
+
+    while let (Some(key), Some(value)) = (fields.next(), fields.next()) {
+      match (cluster_field_string(&key).as_ref().map(|s| s.as_str()), value) {
+        (Some("slots"), ProtocolFrame::Array(values)) => {
+          let values = values
+            .iter()
+            .map(|frame| cluster_field_integer(frame).map(|i| i as u16))
+            .collect::<Option<Vec<u16>>>()
+            .ok_or_else(|| cluster_shards_error("invalid slot range"))?;
+          if values.len() % 2 != 0 {
+            return Err(cluster_shards_error("invalid slot range"));
+          }
+
+          slots = values.chunks(2).map(|range| (range[0], range[1])).collect();
+        }
+        (Some("nodes"), ProtocolFrame::Array(values)) => {
+          nodes = values
+            .into_iter()
+            .map(|frame| parse_cluster_shard_node(frame))
+            .collect::<Result<Vec<ClusterShardNode>, RedisError>>()?;
+        }
+        _ => {}
+      }
+    }
+
+    out.push(ClusterShard { slots, nodes });
+  }
+
+  Ok(out)
+}
+
+/// Whether or not the error frame is a `READONLY` error, returned when a write is sent to a replica.
+pub fn is_readonly_error(frame: &ProtocolFrame) -> bool {
+  match frame {
+    ProtocolFrame::Error(ref s) => s.starts_with("READONLY"),
+    _ => false,
+  }
+}
+
+/// Whether or not the error frame indicates that the cluster is failing over, such that the command may succeed if
+/// retried after refreshing the cluster state.
+///
+/// `READONLY` errors are returned when a write is sent to a primary node that was demoted to a replica.
+pub fn is_cluster_failover_error(frame: &ProtocolFrame) -> bool {
+  match frame {
+    ProtocolFrame::Error(ref s) => s.starts_with("CLUSTERDOWN") || s.starts_with("READONLY"),
+    _ => false,
+  }
+}
+
+/// Whether or not the error frame is a `TRYAGAIN` error, returned when a multi-key command is sent while its hash slot
+/// is being migrated. The command may succeed if retried later without changing the cluster state.
+pub fn is_try_again_error(frame: &ProtocolFrame) -> bool {
+  match frame {
+    ProtocolFrame::Error(ref s) => s.starts_with("TRYAGAIN"),
+    _ => false,
+  }
+}
+
 pub fn pretty_error(resp: &str) -> RedisError {
   let kind = {
     let mut parts = resp.split_whitespace();
@@ -200,7 +500,11 @@ pub fn pretty_error(resp: &str) -> RedisError {
       "" => RedisErrorKind::Unknown,
       "ERR" => RedisErrorKind::Unknown,
       "WRONGTYPE" => RedisErrorKind::InvalidArgument,
-      "NOAUTH" => RedisErrorKind::Auth,
+      "NOAUTH" | "WRONGPASS" => RedisErrorKind::Auth,
+      "NOPERM" => RedisErrorKind::Permission,
+      "EXECABORT" => RedisErrorKind::InvalidCommand,
+      "NOSCRIPT" => RedisErrorKind::NoScript,
+      "BUSY" => RedisErrorKind::Busy,
       "MOVED" | "ASK" => RedisErrorKind::Cluster,
       "Invalid" => match parts.next().unwrap_or("").as_ref() {
         "argument(s)" | "Argument" => RedisErrorKind::InvalidArgument,
@@ -219,7 +523,64 @@ pub fn pretty_error(resp: &str) -> RedisError {
   RedisError::new(kind, details)
 }
 
+/// Whether or not the frame is a message on a sharded pubsub channel.
+pub fn is_sharded_pubsub_message(frame: &ProtocolFrame) -> bool {
+  match frame {
+    ProtocolFrame::Array(ref frames) => frames.len() == 3 && frames[0].as_str() == Some("smessage"),
+    _ => false,
+  }
+}
+
+/// Whether or not the frame is a message on a normal, pattern, or sharded pubsub channel.
+pub fn is_pubsub_message(frame: &ProtocolFrame) -> bool {
+  frame.is_pubsub_message() || is_sharded_pubsub_message(frame)
+}
+
+/// Read the channel from a `sunsubscribe` frame.
+pub fn parse_sunsubscribe_channel(frame: &ProtocolFrame) -> Option<String> {
+  match frame {
+    ProtocolFrame::Array(ref frames) if frames.len() == 3 && frames[0].as_str() == Some("sunsubscribe") => {
+      frames[1].as_str().map(|s| s.to_owned())
+    }
+    _ => None,
+  }
+}
+
 pub fn frame_to_pubsub(frame: ProtocolFrame) -> Result<(String, RedisValue), RedisError> {
+  if is_sharded_pubsub_message(&frame) {
+    if let ProtocolFrame::Array(mut frames) = frame {
+      let message = frames.pop().and_then(|f| f.to_string());
+      let channel = frames.pop().and_then(|f| f.to_string());
+
+      return match (channel, message) {
+        (Some(channel), Some(message)) => Ok((channel, RedisValue::String(message))),
+        _ => Err(RedisError::new(
+          RedisErrorKind::ProtocolError,
+          "Invalid sharded pubsub message frame.",
+        )),
+      };
+    }
+  }
+
+  let has_non_string_payload = match frame {
+    ProtocolFrame::Array(ref frames) => {
+      frame.is_pubsub_message() && frames.last().map(|f| f.is_array() || f.is_null()).unwrap_or(false)
+    }
+    _ => false,
+  };
+  if has_non_string_payload {
+    // client tracking invalidation messages contain an array of keys or `nil`, which `parse_as_pubsub` can't read
+    if let ProtocolFrame::Array(mut frames) = frame {
+      let message = frames.pop().map(frame_to_results).transpose()?.unwrap_or(RedisValue::Null);
+      let channel = frames
+        .pop()
+        .and_then(|f| f.to_string())
+        .ok_or_else(|| RedisError::new(RedisErrorKind::ProtocolError, "Invalid pubsub channel."))?;
+
+      return Ok((channel, message));
+    }
+  }
+
   if let Ok((channel, message)) = frame.parse_as_pubsub() {
     Ok((channel, RedisValue::String(message)))
   } else {
@@ -235,6 +596,192 @@ pub fn check_auth_error(frame: ProtocolFrame) -> ProtocolFrame {
   frame
 }
 
+/// Convert a RESP3 frame to the equivalent RESP2 frame, discarding any attributes.
+///
+/// Maps are flattened into arrays of alternating keys and values, sets become arrays, booleans become `1` or `0`, and doubles,
+/// big numbers, and verbatim strings become bulk strings.
+pub fn resp3_to_resp2(frame: Resp3Frame) -> ProtocolFrame {
+  match frame {
+    Resp3Frame::BlobString { data, .. } | Resp3Frame::BigNumber { data, .. } | Resp3Frame::ChunkedString(data) => {
+      ProtocolFrame::BulkString(data)
+    }
+    Resp3Frame::VerbatimString { data, .. } => ProtocolFrame::BulkString(data),
+    Resp3Frame::SimpleString { data, .. } => ProtocolFrame::SimpleString(data),
+    Resp3Frame::BlobError { data, .. } => ProtocolFrame::Error(String::from_utf8_lossy(&data).into_owned()),
+    Resp3Frame::SimpleError { data, .. } => ProtocolFrame::Error(data),
+    Resp3Frame::Boolean { data, .. } => ProtocolFrame::Integer(if data { 1 } else { 0 }),
+    Resp3Frame::Number { data, .. } => ProtocolFrame::Integer(data),
+    Resp3Frame::Double { data, .. } => ProtocolFrame::BulkString(data.to_string().into_bytes()),
+    Resp3Frame::Null => ProtocolFrame::Null,
+    Resp3Frame::Array { data, .. } | Resp3Frame::Push { data, .. } => {
+      ProtocolFrame::Array(data.into_iter().map(resp3_to_resp2).collect())
+    }
+    Resp3Frame::Set { data, .. } => ProtocolFrame::Array(data.into_iter().map(resp3_to_resp2).collect()),
+    Resp3Frame::Map { data, .. } => {
+      let mut out = Vec::with_capacity(data.len() * 2);
+      for (key, value) in data.into_iter() {
+        out.push(resp3_to_resp2(key));
+        out.push(resp3_to_resp2(value));
+      }
+      ProtocolFrame::Array(out)
+    }
+    Resp3Frame::Hello { .. } => ProtocolFrame::Null,
+  }
+}
+
+/// Whether the RESP3 frame contains any data that would lose type information when converted to a RESP2 frame.
+///
+/// Errors and push frames are never considered typed since they're always handled as RESP2 frames.
+pub fn resp3_has_typed_data(frame: &Resp3Frame) -> bool {
+  match frame {
+    Resp3Frame::Double { .. }
+    | Resp3Frame::Boolean { .. }
+    | Resp3Frame::BigNumber { .. }
+    | Resp3Frame::VerbatimString { .. }
+    | Resp3Frame::Map { .. }
+    | Resp3Frame::Set { .. } => true,
+    Resp3Frame::Array { data, .. } => data.iter().any(resp3_has_typed_data),
+    _ => false,
+  }
+}
+
+/// Convert a bulk string into a `String` value if it's valid UTF8, otherwise a `Bytes` value.
+///
+/// In both cases the frame's buffer is moved into the value without copying it.
+pub fn bytes_to_value(data: Vec<u8>) -> RedisValue {
+  match String::from_utf8(data) {
+    Ok(s) => RedisValue::String(s),
+    Err(e) => RedisValue::Bytes(e.into_bytes()),
+  }
+}
+
+fn resp3_key_to_string(frame: Resp3Frame) -> Result<String, RedisError> {
+  resp3_frame_to_value(frame)?
+    .into_string()
+    .ok_or_else(|| RedisError::new(RedisErrorKind::ProtocolError, "Expected string map key."))
+}
+
+/// Read the arguments to `CLIENT TRACKING` from the tracking config, optionally redirecting invalidation messages to the
+/// connection with ID `redirect`.
+pub fn tracking_args(config: &TrackingConfig, enabled: bool, redirect: Option<i64>) -> Vec<RedisValue> {
+  let mut args = Vec::with_capacity(4);
+  args.push(if enabled { "ON" } else { "OFF" }.into());
+
+  if let Some(redirect) = redirect {
+    args.push("REDIRECT".into());
+    args.push(redirect.into());
+  }
+  match config.mode {
+    TrackingMode::Default => {}
+    TrackingMode::Bcast { ref prefixes } => {
+      args.reserve(1 + prefixes.len() * 2);
+      args.push("BCAST".into());
+      for prefix in prefixes.iter() {
+        args.push("PREFIX".into());
+        args.push(prefix.as_str().into());
+      }
+    }
+    TrackingMode::OptIn => args.push("OPTIN".into()),
+    TrackingMode::OptOut => args.push("OPTOUT".into()),
+  }
+  args
+}
+
+/// Whether or not the server sends invalidation messages for `key` with the provided tracking config.
+///
+/// In broadcast mode only keys matching one of the prefixes are tracked, or all keys if there are no prefixes.
+pub fn is_tracked_key(config: &TrackingConfig, key: &RedisKey) -> bool {
+  match config.mode {
+    TrackingMode::Bcast { ref prefixes } => {
+      prefixes.is_empty()
+        || prefixes
+          .iter()
+          .any(|prefix| key.as_bytes().starts_with(prefix.as_bytes()))
+    }
+    _ => true,
+  }
+}
+
+/// Parse the contents of a RESP3 push frame into a push message.
+///
+/// The first value in the frame is used as the message kind.
+pub fn resp3_push_to_message(server: &str, data: Vec<Resp3Frame>) -> Result<PushMessage, RedisError> {
+  let mut data = data
+    .into_iter()
+    .map(resp3_frame_to_value)
+    .collect::<Result<VecDeque<RedisValue>, RedisError>>()?;
+  let kind = match data.pop_front().and_then(|v| v.into_string()) {
+    Some(kind) => kind,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Expected push frame kind.",
+      ))
+    }
+  };
+
+  Ok(PushMessage {
+    server: server.to_owned(),
+    kind,
+    data: data.into_iter().collect(),
+  })
+}
+
+/// Parse a RESP3 frame into a redis value, preserving the RESP3 type information and discarding any attributes.
+///
+/// Unlike [frame_to_results] single-element arrays are not unwrapped. Use [RedisValue::into_resp2] to convert the
+/// result to the value that the equivalent RESP2 frame would produce.
+pub fn resp3_frame_to_value(frame: Resp3Frame) -> Result<RedisValue, RedisError> {
+  let value = match frame {
+    Resp3Frame::SimpleString { data, .. } => {
+      if data.as_str() == QUEUED {
+        RedisValue::Queued
+      } else {
+        RedisValue::String(data)
+      }
+    }
+    Resp3Frame::BlobString { data, .. } | Resp3Frame::ChunkedString(data) => bytes_to_value(data),
+    Resp3Frame::SimpleError { data, .. } => return Err(pretty_error(&data)),
+    Resp3Frame::BlobError { data, .. } => return Err(pretty_error(&String::from_utf8_lossy(&data))),
+    Resp3Frame::Number { data, .. } => RedisValue::Integer(data),
+    Resp3Frame::Double { data, .. } => RedisValue::Double(data),
+    Resp3Frame::Boolean { data, .. } => RedisValue::Boolean(data),
+    Resp3Frame::BigNumber { data, .. } => RedisValue::BigNumber(String::from_utf8(data)?),
+    Resp3Frame::VerbatimString { data, format, .. } => RedisValue::VerbatimString {
+      format,
+      data: String::from_utf8(data)?,
+    },
+    Resp3Frame::Null => RedisValue::Null,
+    Resp3Frame::Array { data, .. } | Resp3Frame::Push { data, .. } => RedisValue::Array(
+      data
+        .into_iter()
+        .map(resp3_frame_to_value)
+        .collect::<Result<Vec<RedisValue>, RedisError>>()?,
+    ),
+    Resp3Frame::Set { data, .. } => RedisValue::Set(
+      data
+        .into_iter()
+        .map(resp3_frame_to_value)
+        .collect::<Result<Vec<RedisValue>, RedisError>>()?,
+    ),
+    Resp3Frame::Map { data, .. } => {
+      let mut inner = utils::new_map(data.len());
+      for (key, value) in data.into_iter() {
+        inner.insert(resp3_key_to_string(key)?, resp3_frame_to_value(value)?);
+      }
+      RedisValue::Map(RedisMap { inner })
+    }
+    Resp3Frame::Hello { .. } => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Cannot convert HELLO frame to a value.",
+      ))
+    }
+  };
+
+  Ok(value)
+}
+
 #[cfg(feature = "ignore-auth-error")]
 pub fn check_auth_error(frame: ProtocolFrame) -> ProtocolFrame {
   let is_auth_error = match frame {
@@ -261,13 +808,7 @@ pub fn frame_to_results(frame: ProtocolFrame) -> Result<RedisValue, RedisError>
         s.into()
       }
     }
-    ProtocolFrame::BulkString(b) => {
-      if let Some(s) = str::from_utf8(&b).ok() {
-        RedisValue::String(s.to_owned())
-      } else {
-        RedisValue::Bytes(b)
-      }
-    }
+    ProtocolFrame::BulkString(b) => bytes_to_value(b),
     ProtocolFrame::Integer(i) => i.into(),
     ProtocolFrame::Null => RedisValue::Null,
     ProtocolFrame::Array(frames) => {
@@ -289,6 +830,62 @@ pub fn frame_to_results(frame: ProtocolFrame) -> Result<RedisValue, RedisError>
   Ok(value)
 }
 
+/// Parse the response frame into a redis value, preserving the RESP3 type information if the frame has any.
+///
+/// Like [frame_to_results] arrays that contain one element are replaced by that element.
+pub fn response_frame_to_results(frame: ResponseFrame) -> Result<RedisValue, RedisError> {
+  match frame.resp3 {
+    Some(resp3) => resp3_frame_to_value(resp3).map(unwrap_single_value_arrays),
+    None => frame_to_results(frame.frame),
+  }
+}
+
+fn unwrap_single_value_arrays(value: RedisValue) -> RedisValue {
+  match value {
+    RedisValue::Array(mut values) => {
+      if values.len() == 1 {
+        unwrap_single_value_arrays(values.pop().unwrap())
+      } else {
+        RedisValue::Array(values.into_iter().map(unwrap_single_value_arrays).collect())
+      }
+    }
+    _ => value,
+  }
+}
+
+/// Parse the protocol frame into an array of values, without flattening arrays that contain one value.
+///
+/// This is used by commands such as `MGET` that return one value per argument, so the response always converts into a
+/// `Vec` with the same length as the arguments.
+pub fn frame_to_array(frame: ProtocolFrame) -> Result<RedisValue, RedisError> {
+  match frame {
+    ProtocolFrame::Array(frames) => frames
+      .into_iter()
+      .map(frame_to_results)
+      .collect::<Result<Vec<RedisValue>, RedisError>>()
+      .map(RedisValue::Array),
+    frame => frame_to_results(frame),
+  }
+}
+
+/// Parse the response to `EXEC` into a result for each queued command, in the order the commands were queued.
+///
+/// A `Null` response, indicating the transaction was aborted due to a `WATCH`ed key, returns a `Canceled` error.
+pub fn frame_to_transaction_results(frame: ProtocolFrame) -> Result<Vec<Result<RedisValue, RedisError>>, RedisError> {
+  match frame {
+    ProtocolFrame::Array(frames) => Ok(frames.into_iter().map(frame_to_results).collect()),
+    ProtocolFrame::Null => Err(RedisError::new(
+      RedisErrorKind::Canceled,
+      "Transaction aborted due to a WATCHed key.",
+    )),
+    ProtocolFrame::Error(s) => Err(pretty_error(&s)),
+    _ => Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected array or null response to EXEC.",
+    )),
+  }
+}
+
 /// Parse the protocol frame into a single redis value, returning an error if the result contains nested arrays or an array with more than one value.
 ///
 /// If the array only contains one value then that value will be returned.
@@ -304,13 +901,7 @@ pub fn frame_to_single_result(frame: ProtocolFrame) -> Result<RedisValue, RedisE
       }
     }
     ProtocolFrame::Integer(i) => Ok(i.into()),
-    ProtocolFrame::BulkString(b) => {
-      if let Some(s) = str::from_utf8(&b).ok() {
-        Ok(RedisValue::String(s.to_owned()))
-      } else {
-        Ok(RedisValue::Bytes(b))
-      }
-    }
+    ProtocolFrame::BulkString(b) => Ok(bytes_to_value(b)),
     ProtocolFrame::Array(mut frames) => {
       if frames.len() > 1 {
         return Err(RedisError::new(
@@ -412,7 +1003,11 @@ pub fn frame_to_error(frame: &ProtocolFrame) -> Option<RedisError> {
 pub fn value_to_outgoing_frame(value: &RedisValue) -> Result<ProtocolFrame, RedisError> {
   let frame = match value {
     RedisValue::Integer(ref i) => ProtocolFrame::BulkString(i.to_string().into_bytes()),
-    RedisValue::String(ref s) => ProtocolFrame::BulkString(s.as_bytes().to_vec()),
+    RedisValue::String(ref s) | RedisValue::BigNumber(ref s) | RedisValue::VerbatimString { data: ref s, .. } => {
+      ProtocolFrame::BulkString(s.as_bytes().to_vec())
+    }
+    RedisValue::Double(ref f) => ProtocolFrame::BulkString(f.to_string().into_bytes()),
+    RedisValue::Boolean(ref b) => ProtocolFrame::BulkString(b.to_string().into_bytes()),
     RedisValue::Bytes(ref b) => ProtocolFrame::BulkString(b.to_vec()),
     RedisValue::Queued => ProtocolFrame::BulkString(QUEUED.as_bytes().to_vec()),
     RedisValue::Null => ProtocolFrame::Null,
@@ -569,6 +1164,58 @@ pub fn parse_memory_stats(data: &Vec<ProtocolFrame>) -> Result<MemoryStats, Redi
   Ok(out)
 }
 
+fn parse_lcs_range(frame: &ProtocolFrame) -> Result<(u64, u64), RedisError> {
+  match frame {
+    ProtocolFrame::Array(ref range) if range.len() == 2 => Ok((parse_u64(&range[0]), parse_u64(&range[1]))),
+    _ => Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid LCS match range.",
+    )),
+  }
+}
+
+fn parse_lcs_match(frame: &ProtocolFrame) -> Result<LcsMatch, RedisError> {
+  let frames = match frame {
+    ProtocolFrame::Array(ref frames) if frames.len() == 2 || frames.len() == 3 => frames,
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid LCS match. Expected 2 or 3 frames.",
+      ))
+    }
+  };
+
+  Ok(LcsMatch {
+    first: parse_lcs_range(&frames[0])?,
+    second: parse_lcs_range(&frames[1])?,
+    len: frames.get(2).map(parse_u64),
+  })
+}
+
+pub fn parse_lcs_matches(data: &Vec<ProtocolFrame>) -> Result<LcsMatches, RedisError> {
+  if data.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid LCS response. Result must have an even number of frames.",
+    ));
+  }
+
+  let mut out = LcsMatches::default();
+  for chunk in data.chunks(2) {
+    match chunk[0].as_str() {
+      Some("matches") => {
+        if let ProtocolFrame::Array(ref matches) = chunk[1] {
+          out.matches = matches.iter().map(parse_lcs_match).collect::<Result<Vec<_>, _>>()?;
+        }
+      }
+      Some("len") => out.len = parse_u64(&chunk[1]),
+      _ => {}
+    };
+  }
+
+  Ok(out)
+}
+
 fn parse_acl_getuser_flag(value: &ProtocolFrame) -> Result<Vec<AclUserFlag>, RedisError> {
   if let ProtocolFrame::Array(ref frames) = value {
     let mut out = Vec::with_capacity(frames.len());
@@ -906,67 +1553,522 @@ pub fn parse_georadius_info(
       // 3 elements: member, dist, hash
       let _ = assert_frame_len(frames, 3)?;
 
-      out.member = parse_geo_member(&frames[0])?;
-      out.distance = Some(parse_geo_dist(&frames[1])?);
-      out.hash = Some(parse_geo_hash(&frames[2])?);
-    } else if withcoord {
-      // 2 elements: member, position
-      let _ = assert_frame_len(frames, 2)?;
+      out.member = parse_geo_member(&frames[0])?;
+      out.distance = Some(parse_geo_dist(&frames[1])?);
+      out.hash = Some(parse_geo_hash(&frames[2])?);
+    } else if withcoord {
+      // 2 elements: member, position
+      let _ = assert_frame_len(frames, 2)?;
+
+      out.member = parse_geo_member(&frames[0])?;
+      out.position = Some(parse_geo_position(&frames[1])?);
+    } else if withdist {
+      // 2 elements: member, dist
+      let _ = assert_frame_len(frames, 2)?;
+
+      out.member = parse_geo_member(&frames[0])?;
+      out.distance = Some(parse_geo_dist(&frames[1])?);
+    } else if withhash {
+      // 2 elements: member, hash
+      let _ = assert_frame_len(frames, 2)?;
+
+      out.member = parse_geo_member(&frames[0])?;
+      out.hash = Some(parse_geo_hash(&frames[1])?);
+    }
+
+    Ok(out)
+  } else {
+    let member: RedisValue = match frame.as_str() {
+      Some(s) => s.into(),
+      None => {
+        return Err(RedisError::new(
+          RedisErrorKind::ProtocolError,
+          "Expected string or array of frames.",
+        ))
+      }
+    };
+
+    Ok(GeoRadiusInfo {
+      member,
+      ..Default::default()
+    })
+  }
+}
+
+pub fn parse_georadius_result(
+  frame: ProtocolFrame,
+  withcoord: bool,
+  withdist: bool,
+  withhash: bool,
+) -> Result<Vec<GeoRadiusInfo>, RedisError> {
+  if let ProtocolFrame::Array(frames) = frame {
+    let mut out = Vec::with_capacity(frames.len());
+
+    for frame in frames.into_iter() {
+      out.push(parse_georadius_info(&frame, withcoord, withdist, withhash)?);
+    }
+
+    Ok(out)
+  } else {
+    Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array."))
+  }
+}
+
+/// Parse a stream entry in the form `[id, [field, value, ...]]`.
+///
+/// Entries deleted from the stream while still pending may have a `nil` field list.
+pub fn parse_stream_entry(frame: ProtocolFrame) -> Result<StreamEntry, RedisError> {
+  if let ProtocolFrame::Array(mut frames) = frame {
+    let _ = assert_frame_len(&frames, 2)?;
+    let fields = frames.pop().unwrap();
+    let id = match frames.pop().unwrap().as_str() {
+      Some(id) => id.to_owned(),
+      None => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected string ID.")),
+    };
+    let fields = match fields {
+      ProtocolFrame::Null => RedisMap::new(),
+      fields => frame_to_map(fields)?,
+    };
+
+    Ok(StreamEntry { id, fields })
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected array stream entry.",
+    ))
+  }
+}
+
+/// Parse an array of stream entries, such as the response to `XRANGE`.
+///
+/// `nil` entries, returned by `XCLAIM` for entries deleted from the stream, are skipped.
+pub fn frame_to_stream_entries(frame: ProtocolFrame) -> Result<Vec<StreamEntry>, RedisError> {
+  match frame {
+    ProtocolFrame::Array(frames) => frames
+      .into_iter()
+      .filter(|frame| frame.kind() != ProtocolFrameKind::Null)
+      .map(parse_stream_entry)
+      .collect(),
+    ProtocolFrame::Null => Ok(Vec::new()),
+    ProtocolFrame::Error(s) => Err(pretty_error(&s)),
+    _ => Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  }
+}
+
+/// Parse the response to `XREAD` or `XREADGROUP` into the entries read from each stream.
+///
+/// RESP2 servers respond with an array of `[key, entries]` pairs while RESP3 servers respond with a map, which arrives
+/// here as a flattened array of alternating keys and entries.
+pub fn frame_to_xread_result(frame: ProtocolFrame) -> Result<HashMap<String, Vec<StreamEntry>>, RedisError> {
+  let frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    ProtocolFrame::Null => return Ok(HashMap::new()),
+    ProtocolFrame::Error(s) => return Err(pretty_error(&s)),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  };
+
+  let is_flattened = frames.first().map(|f| f.kind() != ProtocolFrameKind::Array).unwrap_or(false);
+  let pairs: Vec<(ProtocolFrame, ProtocolFrame)> = if is_flattened {
+    if frames.len() % 2 != 0 {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Expected an even number of frames.",
+      ));
+    }
+
+    let mut pairs = Vec::with_capacity(frames.len() / 2);
+    let mut frames = frames.into_iter();
+    while let (Some(key), Some(entries)) = (frames.next(), frames.next()) {
+      pairs.push((key, entries));
+    }
+    pairs
+  } else {
+    let mut pairs = Vec::with_capacity(frames.len());
+    for frame in frames.into_iter() {
+      if let ProtocolFrame::Array(mut inner) = frame {
+        let _ = assert_frame_len(&inner, 2)?;
+        let entries = inner.pop().unwrap();
+        pairs.push((inner.pop().unwrap(), entries));
+      } else {
+        return Err(RedisError::new(
+          RedisErrorKind::ProtocolError,
+          "Expected array of stream entries.",
+        ));
+      }
+    }
+    pairs
+  };
+
+  let mut out = HashMap::with_capacity(pairs.len());
+  for (key, entries) in pairs.into_iter() {
+    let key = match key.as_str() {
+      Some(key) => key.to_owned(),
+      None => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected string key.")),
+    };
+    out.insert(key, frame_to_stream_entries(entries)?);
+  }
+
+  Ok(out)
+}
+
+fn parse_stream_string(frame: &ProtocolFrame) -> Result<String, RedisError> {
+  frame
+    .as_str()
+    .map(|s| s.to_owned())
+    .ok_or(RedisError::new(RedisErrorKind::ProtocolError, "Expected string."))
+}
+
+/// Parse an integer that may be sent as an integer or a string, such as the per-consumer counts from `XPENDING`.
+fn parse_stream_count(frame: &ProtocolFrame) -> Result<u64, RedisError> {
+  let count = match frame {
+    ProtocolFrame::Integer(i) => Some(*i),
+    _ => frame.as_str().and_then(|s| s.parse::<i64>().ok()),
+  };
+
+  match count {
+    Some(count) if count >= 0 => Ok(count as u64),
+    _ => Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected non-negative integer.",
+    )),
+  }
+}
+
+fn parse_optional_stream_string(frame: &ProtocolFrame) -> Result<Option<String>, RedisError> {
+  if let ProtocolFrame::Null = frame {
+    Ok(None)
+  } else {
+    parse_stream_string(frame).map(Some)
+  }
+}
+
+/// Parse the summary form of the `XPENDING` response.
+pub fn frame_to_xpending_summary(frame: ProtocolFrame) -> Result<XPendingSummary, RedisError> {
+  let frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    ProtocolFrame::Error(s) => return Err(pretty_error(&s)),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  };
+  let _ = assert_frame_len(&frames, 4)?;
+
+  let consumers = match frames[3] {
+    ProtocolFrame::Array(ref consumers) => {
+      let mut out = Vec::with_capacity(consumers.len());
+      for consumer in consumers.iter() {
+        if let ProtocolFrame::Array(ref inner) = consumer {
+          let _ = assert_frame_len(inner, 2)?;
+          out.push((parse_stream_string(&inner[0])?, parse_stream_count(&inner[1])?));
+        } else {
+          return Err(RedisError::new(
+            RedisErrorKind::ProtocolError,
+            "Expected array of consumer counts.",
+          ));
+        }
+      }
+      out
+    }
+    _ => Vec::new(),
+  };
+
+  Ok(XPendingSummary {
+    count: parse_stream_count(&frames[0])?,
+    min_id: parse_optional_stream_string(&frames[1])?,
+    max_id: parse_optional_stream_string(&frames[2])?,
+    consumers,
+  })
+}
+
+/// Parse the extended form of the `XPENDING` response.
+pub fn frame_to_xpending_entries(frame: ProtocolFrame) -> Result<Vec<XPendingEntry>, RedisError> {
+  let frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    ProtocolFrame::Null => return Ok(Vec::new()),
+    ProtocolFrame::Error(s) => return Err(pretty_error(&s)),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  };
+
+  let mut out = Vec::with_capacity(frames.len());
+  for frame in frames.iter() {
+    if let ProtocolFrame::Array(ref inner) = frame {
+      let _ = assert_frame_len(inner, 4)?;
+      out.push(XPendingEntry {
+        id: parse_stream_string(&inner[0])?,
+        consumer: parse_stream_string(&inner[1])?,
+        idle: parse_stream_count(&inner[2])?,
+        delivered: parse_stream_count(&inner[3])?,
+      });
+    } else {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Expected array of pending entries.",
+      ));
+    }
+  }
+
+  Ok(out)
+}
+
+/// Parse an array of stream IDs, such as the response to `XCLAIM` with `JUSTID`.
+pub fn frame_to_stream_ids(frame: ProtocolFrame) -> Result<Vec<String>, RedisError> {
+  match frame {
+    ProtocolFrame::Array(frames) => frames.iter().map(parse_stream_string).collect(),
+    ProtocolFrame::Null => Ok(Vec::new()),
+    ProtocolFrame::Error(s) => Err(pretty_error(&s)),
+    _ => Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  }
+}
+
+/// Parse the response to `XAUTOCLAIM`, using `parse_claimed` to parse the claimed entries or IDs.
+pub fn frame_to_xautoclaim_result<T, F>(frame: ProtocolFrame, parse_claimed: F) -> Result<XAutoClaimResult<T>, RedisError>
+where
+  F: FnOnce(ProtocolFrame) -> Result<Vec<T>, RedisError>,
+{
+  let mut frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    ProtocolFrame::Error(s) => return Err(pretty_error(&s)),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  };
+  if frames.len() != 2 && frames.len() != 3 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected 2 or 3 frames.",
+    ));
+  }
+
+  let deleted = if frames.len() == 3 {
+    frame_to_stream_ids(frames.pop().unwrap())?
+  } else {
+    Vec::new()
+  };
+  let claimed = parse_claimed(frames.pop().unwrap())?;
+  let next_id = parse_stream_string(&frames[0])?;
+
+  Ok(XAutoClaimResult {
+    next_id,
+    claimed,
+    deleted,
+  })
+}
+
+fn parse_stream_i64(frame: &ProtocolFrame) -> Result<i64, RedisError> {
+  let value = match frame {
+    ProtocolFrame::Integer(i) => Some(*i),
+    _ => frame.as_str().and_then(|s| s.parse::<i64>().ok()),
+  };
+
+  value.ok_or(RedisError::new(RedisErrorKind::ProtocolError, "Expected integer."))
+}
+
+/// Convert a reply in the form `[field, value, ...]` to a map of frames keyed by the field name.
+fn frame_to_frame_map(frame: ProtocolFrame) -> Result<HashMap<String, ProtocolFrame>, RedisError> {
+  let frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    ProtocolFrame::Error(s) => return Err(pretty_error(&s)),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  };
+  if frames.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected an even number of frames.",
+    ));
+  }
+
+  let mut out = HashMap::with_capacity(frames.len() / 2);
+  let mut frames = frames.into_iter();
+  while let (Some(key), Some(value)) = (frames.next(), frames.next()) {
+    out.insert(parse_stream_string(&key)?, value);
+  }
+
+  Ok(out)
+}
+
+fn take_frame(map: &mut HashMap<String, ProtocolFrame>, field: &str) -> Option<ProtocolFrame> {
+  match map.remove(field) {
+    Some(ProtocolFrame::Null) | None => None,
+    Some(frame) => Some(frame),
+  }
+}
+
+fn missing_field(field: &str) -> RedisError {
+  RedisError::new(RedisErrorKind::ProtocolError, format!("Missing `{}` field.", field))
+}
+
+fn take_u64(map: &mut HashMap<String, ProtocolFrame>, field: &str) -> Result<Option<u64>, RedisError> {
+  take_frame(map, field).map(|frame| parse_stream_count(&frame)).transpose()
+}
+
+fn take_i64(map: &mut HashMap<String, ProtocolFrame>, field: &str) -> Result<Option<i64>, RedisError> {
+  take_frame(map, field).map(|frame| parse_stream_i64(&frame)).transpose()
+}
+
+fn take_string(map: &mut HashMap<String, ProtocolFrame>, field: &str) -> Result<Option<String>, RedisError> {
+  take_frame(map, field).map(|frame| parse_stream_string(&frame)).transpose()
+}
+
+fn take_array(map: &mut HashMap<String, ProtocolFrame>, field: &str) -> Result<Vec<ProtocolFrame>, RedisError> {
+  match take_frame(map, field) {
+    Some(ProtocolFrame::Array(frames)) => Ok(frames),
+    None => Ok(Vec::new()),
+    Some(_) => Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      format!("Expected array `{}` field.", field),
+    )),
+  }
+}
+
+fn frame_to_map_array<T, F>(frame: ProtocolFrame, parse: F) -> Result<Vec<T>, RedisError>
+where
+  F: Fn(ProtocolFrame) -> Result<T, RedisError>,
+{
+  match frame {
+    ProtocolFrame::Array(frames) => frames.into_iter().map(parse).collect(),
+    ProtocolFrame::Null => Ok(Vec::new()),
+    ProtocolFrame::Error(s) => Err(pretty_error(&s)),
+    _ => Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array.")),
+  }
+}
+
+/// Parse the response to `XINFO STREAM`.
+pub fn frame_to_stream_info(frame: ProtocolFrame) -> Result<StreamInfo, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
+
+  Ok(StreamInfo {
+    length: take_u64(&mut map, "length")?.ok_or(missing_field("length"))?,
+    radix_tree_keys: take_u64(&mut map, "radix-tree-keys")?.unwrap_or(0),
+    radix_tree_nodes: take_u64(&mut map, "radix-tree-nodes")?.unwrap_or(0),
+    last_generated_id: take_string(&mut map, "last-generated-id")?.ok_or(missing_field("last-generated-id"))?,
+    max_deleted_entry_id: take_string(&mut map, "max-deleted-entry-id")?,
+    entries_added: take_u64(&mut map, "entries-added")?,
+    recorded_first_entry_id: take_string(&mut map, "recorded-first-entry-id")?,
+    groups: take_u64(&mut map, "groups")?.unwrap_or(0),
+    first_entry: take_frame(&mut map, "first-entry").map(parse_stream_entry).transpose()?,
+    last_entry: take_frame(&mut map, "last-entry").map(parse_stream_entry).transpose()?,
+  })
+}
+
+fn parse_group_info(frame: ProtocolFrame) -> Result<GroupInfo, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
 
-      out.member = parse_geo_member(&frames[0])?;
-      out.position = Some(parse_geo_position(&frames[1])?);
-    } else if withdist {
-      // 2 elements: member, dist
-      let _ = assert_frame_len(frames, 2)?;
+  Ok(GroupInfo {
+    name: take_string(&mut map, "name")?.ok_or(missing_field("name"))?,
+    consumers: take_u64(&mut map, "consumers")?.unwrap_or(0),
+    pending: take_u64(&mut map, "pending")?.unwrap_or(0),
+    last_delivered_id: take_string(&mut map, "last-delivered-id")?.ok_or(missing_field("last-delivered-id"))?,
+    entries_read: take_u64(&mut map, "entries-read")?,
+    lag: take_u64(&mut map, "lag")?,
+  })
+}
 
-      out.member = parse_geo_member(&frames[0])?;
-      out.distance = Some(parse_geo_dist(&frames[1])?);
-    } else if withhash {
-      // 2 elements: member, hash
-      let _ = assert_frame_len(frames, 2)?;
+/// Parse the response to `XINFO GROUPS`.
+pub fn frame_to_group_info(frame: ProtocolFrame) -> Result<Vec<GroupInfo>, RedisError> {
+  frame_to_map_array(frame, parse_group_info)
+}
 
-      out.member = parse_geo_member(&frames[0])?;
-      out.hash = Some(parse_geo_hash(&frames[1])?);
-    }
+fn parse_consumer_info(frame: ProtocolFrame) -> Result<ConsumerInfo, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
 
-    Ok(out)
-  } else {
-    let member: RedisValue = match frame.as_str() {
-      Some(s) => s.into(),
-      None => {
-        return Err(RedisError::new(
-          RedisErrorKind::ProtocolError,
-          "Expected string or array of frames.",
-        ))
-      }
-    };
+  Ok(ConsumerInfo {
+    name: take_string(&mut map, "name")?.ok_or(missing_field("name"))?,
+    pending: take_u64(&mut map, "pending")?.unwrap_or(0),
+    idle: take_u64(&mut map, "idle")?.unwrap_or(0),
+    inactive: take_i64(&mut map, "inactive")?,
+  })
+}
 
-    Ok(GeoRadiusInfo {
-      member,
-      ..Default::default()
+/// Parse the response to `XINFO CONSUMERS`.
+pub fn frame_to_consumer_info(frame: ProtocolFrame) -> Result<Vec<ConsumerInfo>, RedisError> {
+  frame_to_map_array(frame, parse_consumer_info)
+}
+
+fn parse_group_pending_entry(frame: ProtocolFrame) -> Result<GroupPendingEntry, RedisError> {
+  if let ProtocolFrame::Array(ref frames) = frame {
+    let _ = assert_frame_len(frames, 4)?;
+
+    Ok(GroupPendingEntry {
+      id: parse_stream_string(&frames[0])?,
+      consumer: parse_stream_string(&frames[1])?,
+      delivery_time: parse_stream_count(&frames[2])?,
+      delivery_count: parse_stream_count(&frames[3])?,
     })
+  } else {
+    Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array."))
   }
 }
 
-pub fn parse_georadius_result(
-  frame: ProtocolFrame,
-  withcoord: bool,
-  withdist: bool,
-  withhash: bool,
-) -> Result<Vec<GeoRadiusInfo>, RedisError> {
-  if let ProtocolFrame::Array(frames) = frame {
-    let mut out = Vec::with_capacity(frames.len());
-
-    for frame in frames.into_iter() {
-      out.push(parse_georadius_info(&frame, withcoord, withdist, withhash)?);
-    }
+fn parse_consumer_pending_entry(frame: ProtocolFrame) -> Result<ConsumerPendingEntry, RedisError> {
+  if let ProtocolFrame::Array(ref frames) = frame {
+    let _ = assert_frame_len(frames, 3)?;
 
-    Ok(out)
+    Ok(ConsumerPendingEntry {
+      id: parse_stream_string(&frames[0])?,
+      delivery_time: parse_stream_count(&frames[1])?,
+      delivery_count: parse_stream_count(&frames[2])?,
+    })
   } else {
     Err(RedisError::new(RedisErrorKind::ProtocolError, "Expected array."))
   }
 }
 
+fn parse_consumer_info_full(frame: ProtocolFrame) -> Result<ConsumerInfoFull, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
+  let pending = take_array(&mut map, "pending")?;
+
+  Ok(ConsumerInfoFull {
+    name: take_string(&mut map, "name")?.ok_or(missing_field("name"))?,
+    seen_time: take_u64(&mut map, "seen-time")?.unwrap_or(0),
+    active_time: take_i64(&mut map, "active-time")?,
+    pel_count: take_u64(&mut map, "pel-count")?.unwrap_or(0),
+    pending: pending
+      .into_iter()
+      .map(parse_consumer_pending_entry)
+      .collect::<Result<_, _>>()?,
+  })
+}
+
+fn parse_group_info_full(frame: ProtocolFrame) -> Result<GroupInfoFull, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
+  let pending = take_array(&mut map, "pending")?;
+  let consumers = take_array(&mut map, "consumers")?;
+
+  Ok(GroupInfoFull {
+    name: take_string(&mut map, "name")?.ok_or(missing_field("name"))?,
+    last_delivered_id: take_string(&mut map, "last-delivered-id")?.ok_or(missing_field("last-delivered-id"))?,
+    entries_read: take_u64(&mut map, "entries-read")?,
+    lag: take_u64(&mut map, "lag")?,
+    pel_count: take_u64(&mut map, "pel-count")?.unwrap_or(0),
+    pending: pending
+      .into_iter()
+      .map(parse_group_pending_entry)
+      .collect::<Result<_, _>>()?,
+    consumers: consumers
+      .into_iter()
+      .map(parse_consumer_info_full)
+      .collect::<Result<_, _>>()?,
+  })
+}
+
+/// Parse the response to `XINFO STREAM` with the `FULL` argument.
+pub fn frame_to_stream_info_full(frame: ProtocolFrame) -> Result<StreamInfoFull, RedisError> {
+  let mut map = frame_to_frame_map(frame)?;
+  let entries = take_frame(&mut map, "entries").unwrap_or(ProtocolFrame::Null);
+  let groups = take_array(&mut map, "groups")?;
+
+  Ok(StreamInfoFull {
+    length: take_u64(&mut map, "length")?.ok_or(missing_field("length"))?,
+    radix_tree_keys: take_u64(&mut map, "radix-tree-keys")?.unwrap_or(0),
+    radix_tree_nodes: take_u64(&mut map, "radix-tree-nodes")?.unwrap_or(0),
+    last_generated_id: take_string(&mut map, "last-generated-id")?.ok_or(missing_field("last-generated-id"))?,
+    max_deleted_entry_id: take_string(&mut map, "max-deleted-entry-id")?,
+    entries_added: take_u64(&mut map, "entries-added")?,
+    recorded_first_entry_id: take_string(&mut map, "recorded-first-entry-id")?,
+    entries: frame_to_stream_entries(entries)?,
+    groups: groups
+      .into_iter()
+      .map(parse_group_info_full)
+      .collect::<Result<_, _>>()?,
+  })
+}
+
 /// Convert a redis value to an array of (value, score) tuples.
 pub fn value_to_zset_result(value: RedisValue) -> Result<Vec<(RedisValue, f64)>, RedisError> {
   if let RedisValue::Array(mut values) = value {
@@ -1019,9 +2121,13 @@ pub fn arg_size(value: &RedisValue) -> usize {
   match value {
     RedisValue::Null => 3,
     RedisValue::Integer(ref i) => i64_size(*i),
-    RedisValue::String(ref s) => s.as_bytes().len(),
+    RedisValue::String(ref s) | RedisValue::BigNumber(ref s) | RedisValue::VerbatimString { data: ref s, .. } => {
+      s.as_bytes().len()
+    }
     RedisValue::Bytes(ref b) => b.len(),
-    RedisValue::Array(ref arr) => args_size(arr),
+    RedisValue::Double(ref f) => f.to_string().as_bytes().len(),
+    RedisValue::Boolean(ref b) => b.to_string().as_bytes().len(),
+    RedisValue::Array(ref arr) | RedisValue::Set(ref arr) => args_size(arr),
     RedisValue::Map(ref map) => map
       .inner
       .iter()
@@ -1099,6 +2205,20 @@ mod tests {
     d.into_iter().map(|s| s.to_owned()).collect()
   }
 
+  #[test]
+  fn should_keep_invalid_utf8_bulk_strings_as_bytes() {
+    let bytes = vec![0, 159, 146, 150];
+    let value = frame_to_single_result(ProtocolFrame::BulkString(bytes.clone())).unwrap();
+    assert_eq!(value, RedisValue::Bytes(bytes.clone()));
+
+    let value = frame_to_results(ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString(bytes.clone()),
+      str_to_bs("foo"),
+    ]))
+    .unwrap();
+    assert_eq!(value, RedisValue::Array(vec![RedisValue::Bytes(bytes), "foo".into()]));
+  }
+
   #[test]
   fn should_parse_memory_stats() {
     // better from()/into() interfaces for frames coming in the next redis-protocol version...
@@ -1760,6 +2880,177 @@ e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001 myself,master - 0 0 1 c
     assert_eq!(actual, expected);
   }
 
+  fn cluster_slots_node(host: &str, port: i64, id: &str) -> ProtocolFrame {
+    ProtocolFrame::Array(vec![
+      bulk(host),
+      ProtocolFrame::Integer(port),
+      bulk(id),
+    ])
+  }
+
+  #[test]
+  fn should_parse_cluster_slots() {
+    let frame = ProtocolFrame::Array(vec![
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::Integer(0),
+        ProtocolFrame::Integer(5460),
+        cluster_slots_node("127.0.0.1", 30001, "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca"),
+        cluster_slots_node("127.0.0.1", 30004, "4cf3b3d1b1b8b1c7c0d5f9e4b3f2b6a8e9d7c6b5"),
+      ]),
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::Integer(5461),
+        ProtocolFrame::Integer(16383),
+        cluster_slots_node("?", 30002, "67ed2db8d677e59ec4a4cefb06858cf2a1a89fa1"),
+      ]),
+    ]);
+
+    let expected = vec![
+      ClusterSlotRange {
+        start: 0,
+        end: 5460,
+        primary: ClusterSlotNode {
+          host: "127.0.0.1".into(),
+          port: 30001,
+          id: Some("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".into()),
+        },
+        replicas: vec![ClusterSlotNode {
+          host: "127.0.0.1".into(),
+          port: 30004,
+          id: Some("4cf3b3d1b1b8b1c7c0d5f9e4b3f2b6a8e9d7c6b5".into()),
+        }],
+      },
+      ClusterSlotRange {
+        start: 5461,
+        end: 16383,
+        primary: ClusterSlotNode {
+          host: "10.0.0.1".into(),
+          port: 30002,
+          id: Some("67ed2db8d677e59ec4a4cefb06858cf2a1a89fa1".into()),
+        },
+        replicas: vec![],
+      },
+    ];
+
+    let actual = match parse_cluster_slots(frame, "10.0.0.1") {
+      Ok(ranges) => ranges,
+      Err(e) => panic!("{}", e),
+    };
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn should_detect_cluster_failover_errors() {
+    let errors = vec![
+      "CLUSTERDOWN The cluster is down",
+      "READONLY You can't write against a read only replica.",
+    ];
+    for error in errors.into_iter() {
+      assert!(is_cluster_failover_error(&ProtocolFrame::Error(error.into())));
+    }
+
+    let try_again = ProtocolFrame::Error("TRYAGAIN Multiple keys request during rehashing of slot".into());
+    assert!(!is_cluster_failover_error(&try_again));
+    assert!(is_try_again_error(&try_again));
+    assert!(!is_cluster_failover_error(&ProtocolFrame::Error("ERR unknown command".into())));
+    assert!(!is_cluster_failover_error(&bulk("CLUSTERDOWN")));
+    assert!(!is_try_again_error(&bulk("TRYAGAIN")));
+  }
+
+  #[test]
+  fn should_detect_readonly_errors() {
+    let error = "READONLY You can't write against a read only replica.";
+    assert!(is_readonly_error(&ProtocolFrame::Error(error.into())));
+    assert!(!is_readonly_error(&ProtocolFrame::Error("CLUSTERDOWN The cluster is down".into())));
+    assert!(!is_readonly_error(&bulk("READONLY")));
+  }
+
+  #[test]
+  fn should_parse_cluster_shards() {
+    let frame = ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+      bulk("slots"),
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::Integer(0),
+        ProtocolFrame::Integer(5460),
+        ProtocolFrame::Integer(10923),
+        ProtocolFrame::Integer(10925),
+      ]),
+      bulk("nodes"),
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::Array(vec![
+          bulk("id"),
+          bulk("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca"),
+          bulk("port"),
+          ProtocolFrame::Integer(30001),
+          bulk("ip"),
+          bulk("127.0.0.1"),
+          bulk("endpoint"),
+          bulk("127.0.0.1"),
+          bulk("hostname"),
+          bulk(""),
+          bulk("role"),
+          bulk("master"),
+          bulk("replication-offset"),
+          ProtocolFrame::Integer(72156),
+          bulk("health"),
+          bulk("online"),
+        ]),
+        ProtocolFrame::Array(vec![
+          bulk("id"),
+          bulk("4cf3b3d1b1b8b1c7c0d5f9e4b3f2b6a8e9d7c6b5"),
+          bulk("tls-port"),
+          ProtocolFrame::Integer(30004),
+          bulk("ip"),
+          bulk("127.0.0.1"),
+          bulk("endpoint"),
+          bulk("127.0.0.1"),
+          bulk("hostname"),
+          bulk("replica.example.com"),
+          bulk("role"),
+          bulk("replica"),
+          bulk("replication-offset"),
+          ProtocolFrame::Integer(72156),
+          bulk("health"),
+          bulk("loading"),
+        ]),
+      ]),
+    ])]);
+
+    let expected = vec![ClusterShard {
+      slots: vec![(0, 5460), (10923, 10925)],
+      nodes: vec![
+        ClusterShardNode {
+          id: "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".into(),
+          endpoint: "127.0.0.1".into(),
+          ip: "127.0.0.1".into(),
+          hostname: None,
+          port: Some(30001),
+          tls_port: None,
+          role: ClusterNodeRole::Primary,
+          replication_offset: 72156,
+          health: ClusterNodeHealth::Online,
+        },
+        ClusterShardNode {
+          id: "4cf3b3d1b1b8b1c7c0d5f9e4b3f2b6a8e9d7c6b5".into(),
+          endpoint: "127.0.0.1".into(),
+          ip: "127.0.0.1".into(),
+          hostname: Some("replica.example.com".into()),
+          port: None,
+          tls_port: Some(30004),
+          role: ClusterNodeRole::Replica,
+          replication_offset: 72156,
+          health: ClusterNodeHealth::Loading,
+        },
+      ],
+    }];
+
+    let actual = match parse_cluster_shards(frame) {
+      Ok(shards) => shards,
+      Err(e) => panic!("{}", e),
+    };
+    assert_eq!(actual, expected);
+    assert_eq!(actual[0].primary().map(|node| node.port), Some(Some(30001)));
+  }
+
   #[test]
   fn should_parse_elasticache_cluster_node_status() {
     let status =
@@ -1852,4 +3143,587 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
     };
     assert_eq!(actual, expected);
   }
+
+  #[test]
+  fn should_parse_transaction_results_with_errors() {
+    let frame = ProtocolFrame::Array(vec![
+      str_to_f("OK"),
+      ProtocolFrame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into()),
+      ProtocolFrame::Array(vec![str_to_bs("a"), str_to_bs("b")]),
+    ]);
+    let results = frame_to_transaction_results(frame).unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &RedisValue::from("OK"));
+    assert!(results[1].is_err());
+    assert_eq!(
+      results[2].as_ref().unwrap(),
+      &RedisValue::Array(vec!["a".into(), "b".into()])
+    );
+  }
+
+  #[test]
+  fn should_parse_lcs_matches() {
+    let range = |start, end| ProtocolFrame::Array(vec![int_to_f(start), int_to_f(end)]);
+    let frames = vec![
+      str_to_bs("matches"),
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::Array(vec![range(4, 7), range(5, 8), int_to_f(4)]),
+        ProtocolFrame::Array(vec![range(2, 3), range(0, 1), int_to_f(2)]),
+      ]),
+      str_to_bs("len"),
+      int_to_f(6),
+    ];
+    let expected = LcsMatches {
+      matches: vec![
+        LcsMatch {
+          first: (4, 7),
+          second: (5, 8),
+          len: Some(4),
+        },
+        LcsMatch {
+          first: (2, 3),
+          second: (0, 1),
+          len: Some(2),
+        },
+      ],
+      len: 6,
+    };
+    assert_eq!(parse_lcs_matches(&frames).unwrap(), expected);
+
+    // a single match is not flattened
+    let frames = vec![
+      str_to_bs("matches"),
+      ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![range(0, 1), range(0, 1)])]),
+      str_to_bs("len"),
+      int_to_f(2),
+    ];
+    let result = parse_lcs_matches(&frames).unwrap();
+    assert_eq!(result.matches.len(), 1);
+    assert_eq!(result.matches[0].len, None);
+  }
+
+  #[test]
+  fn should_parse_aborted_transaction_results() {
+    let error = frame_to_transaction_results(ProtocolFrame::Null).unwrap_err();
+    assert_eq!(*error.kind(), RedisErrorKind::Canceled);
+  }
+
+  #[test]
+  fn should_parse_permission_errors() {
+    let error = pretty_error("NOPERM this user has no permissions to run the 'get' command");
+    assert_eq!(*error.kind(), RedisErrorKind::Permission);
+    let error = pretty_error("WRONGPASS invalid username-password pair");
+    assert_eq!(*error.kind(), RedisErrorKind::Auth);
+  }
+
+  #[test]
+  fn should_parse_no_script_errors() {
+    let error = pretty_error("NOSCRIPT No matching script. Please use EVAL.");
+    assert_eq!(*error.kind(), RedisErrorKind::NoScript);
+  }
+
+  #[test]
+  fn should_parse_busy_errors() {
+    let error = pretty_error(
+      "BUSY Redis is busy running a script. You can only call SCRIPT KILL or SHUTDOWN NOSAVE.",
+    );
+    assert_eq!(*error.kind(), RedisErrorKind::Busy);
+    assert!(error.is_busy());
+  }
+
+  #[test]
+  fn should_parse_execabort_errors() {
+    let error = pretty_error("EXECABORT Transaction discarded because of previous errors.");
+    assert_eq!(*error.kind(), RedisErrorKind::InvalidCommand);
+  }
+
+  #[test]
+  fn should_convert_resp3_frames_to_resp2() {
+    let mut map = redis_protocol::resp3::types::FrameMap::new();
+    map.insert(
+      Resp3Frame::SimpleString {
+        data: "foo".into(),
+        attributes: None,
+      },
+      Resp3Frame::Boolean {
+        data: true,
+        attributes: None,
+      },
+    );
+    let frame = Resp3Frame::Array {
+      data: vec![
+        Resp3Frame::Map {
+          data: map,
+          attributes: None,
+        },
+        Resp3Frame::Double {
+          data: 1.5,
+          attributes: None,
+        },
+        Resp3Frame::Null,
+      ],
+      attributes: None,
+    };
+
+    let expected = ProtocolFrame::Array(vec![
+      ProtocolFrame::Array(vec![ProtocolFrame::SimpleString("foo".into()), ProtocolFrame::Integer(1)]),
+      ProtocolFrame::BulkString("1.5".into()),
+      ProtocolFrame::Null,
+    ]);
+    assert_eq!(resp3_to_resp2(frame), expected);
+  }
+
+  #[test]
+  fn should_convert_resp3_frames_to_typed_values() {
+    let mut map = redis_protocol::resp3::types::FrameMap::new();
+    map.insert(
+      Resp3Frame::BlobString {
+        data: "foo".into(),
+        attributes: None,
+      },
+      Resp3Frame::Boolean {
+        data: false,
+        attributes: None,
+      },
+    );
+    let frame = Resp3Frame::Array {
+      data: vec![
+        Resp3Frame::Map {
+          data: map,
+          attributes: None,
+        },
+        Resp3Frame::Double {
+          data: 1.5,
+          attributes: None,
+        },
+        Resp3Frame::BigNumber {
+          data: "3492890328409238509324850943850943825024385".into(),
+          attributes: None,
+        },
+        Resp3Frame::VerbatimString {
+          data: "bar".into(),
+          format: VerbatimStringFormat::Text,
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+
+    let mut expected_map = RedisMap::new();
+    expected_map.insert("foo".into(), RedisValue::Boolean(false));
+    let expected = RedisValue::Array(vec![
+      RedisValue::Map(expected_map),
+      RedisValue::Double(1.5),
+      RedisValue::BigNumber("3492890328409238509324850943850943825024385".into()),
+      RedisValue::VerbatimString {
+        format: VerbatimStringFormat::Text,
+        data: "bar".into(),
+      },
+    ]);
+    let value = resp3_frame_to_value(frame).unwrap();
+    assert_eq!(value, expected);
+
+    let expected_resp2 = RedisValue::Array(vec![
+      RedisValue::Array(vec!["foo".into(), 0.into()]),
+      "1.5".into(),
+      "3492890328409238509324850943850943825024385".into(),
+      "bar".into(),
+    ]);
+    assert_eq!(value.into_resp2(), expected_resp2);
+  }
+
+  #[test]
+  fn should_build_bcast_tracking_args() {
+    let config = TrackingConfig {
+      mode: TrackingMode::Bcast {
+        prefixes: vec!["foo:".into(), "bar:".into()],
+      },
+      ..Default::default()
+    };
+    let expected: Vec<RedisValue> = vec![
+      "ON".into(),
+      "REDIRECT".into(),
+      10.into(),
+      "BCAST".into(),
+      "PREFIX".into(),
+      "foo:".into(),
+      "PREFIX".into(),
+      "bar:".into(),
+    ];
+
+    assert_eq!(tracking_args(&config, true, Some(10)), expected);
+  }
+
+  #[test]
+  fn should_build_optin_tracking_args() {
+    let config = TrackingConfig {
+      mode: TrackingMode::OptIn,
+      ..Default::default()
+    };
+    let expected: Vec<RedisValue> = vec!["ON".into(), "OPTIN".into()];
+
+    assert_eq!(tracking_args(&config, true, None), expected);
+  }
+
+  #[test]
+  fn should_only_track_keys_matching_bcast_prefixes() {
+    let mut config = TrackingConfig {
+      mode: TrackingMode::Bcast {
+        prefixes: vec!["foo:".into()],
+      },
+      ..Default::default()
+    };
+    assert!(is_tracked_key(&config, &"foo:1".into()));
+    assert!(!is_tracked_key(&config, &"bar:1".into()));
+
+    config.mode = TrackingMode::Bcast { prefixes: vec![] };
+    assert!(is_tracked_key(&config, &"bar:1".into()));
+    config.mode = TrackingMode::OptIn;
+    assert!(is_tracked_key(&config, &"bar:1".into()));
+  }
+
+  #[test]
+  fn should_convert_resp3_sets_to_arrays() {
+    let mut set = redis_protocol::resp3::types::FrameSet::new();
+    set.insert(Resp3Frame::Number {
+      data: 1,
+      attributes: None,
+    });
+    let value = resp3_frame_to_value(Resp3Frame::Set {
+      data: set,
+      attributes: None,
+    })
+    .unwrap();
+
+    assert!(value.is_set());
+    assert_eq!(value.into_resp2(), RedisValue::Array(vec![1.into()]));
+  }
+
+  #[test]
+  fn should_compare_doubles_by_bits() {
+    let mut set = std::collections::HashSet::new();
+    set.insert(RedisValue::Double(f64::NAN));
+    set.insert(RedisValue::Double(f64::NAN));
+    set.insert(RedisValue::Double(0.0));
+    set.insert(RedisValue::Double(-0.0));
+
+    assert_eq!(RedisValue::Double(f64::NAN), RedisValue::Double(f64::NAN));
+    assert_ne!(RedisValue::Double(0.0), RedisValue::Double(-0.0));
+    assert_eq!(set.len(), 3);
+  }
+
+  #[test]
+  fn should_read_typed_response_frames() {
+    let resp3 = Resp3Frame::Array {
+      data: vec![Resp3Frame::Boolean {
+        data: true,
+        attributes: None,
+      }],
+      attributes: None,
+    };
+    let frame = ResponseFrame {
+      frame: resp3_to_resp2(resp3.clone()),
+      resp3: Some(resp3),
+    };
+
+    assert_eq!(response_frame_to_results(frame.clone()).unwrap(), RedisValue::Boolean(true));
+    assert_eq!(frame_to_results(frame.into_resp2()).unwrap(), RedisValue::Integer(1));
+  }
+
+  fn stream_entry_frame(id: &str, field: &str, value: &str) -> ProtocolFrame {
+    ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString(id.as_bytes().to_vec()),
+      ProtocolFrame::Array(vec![
+        ProtocolFrame::BulkString(field.as_bytes().to_vec()),
+        ProtocolFrame::BulkString(value.as_bytes().to_vec()),
+      ]),
+    ])
+  }
+
+  #[test]
+  fn should_parse_stream_entries() {
+    let frame = ProtocolFrame::Array(vec![
+      stream_entry_frame("1-0", "foo", "bar"),
+      stream_entry_frame("2-0", "baz", "qux"),
+    ]);
+    let entries = frame_to_stream_entries(frame).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, "1-0");
+    assert_eq!(entries[0].fields.get("foo"), Some(&"bar".into()));
+    assert_eq!(entries[1].id, "2-0");
+    assert_eq!(entries[1].fields.get("baz"), Some(&"qux".into()));
+  }
+
+  #[test]
+  fn should_parse_resp2_and_resp3_xread_results() {
+    let resp2 = ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString("foo".into()),
+      ProtocolFrame::Array(vec![stream_entry_frame("1-0", "a", "b")]),
+    ])]);
+    let resp3 = ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString("foo".into()),
+      ProtocolFrame::Array(vec![stream_entry_frame("1-0", "a", "b")]),
+    ]);
+
+    let resp2 = frame_to_xread_result(resp2).unwrap();
+    let resp3 = frame_to_xread_result(resp3).unwrap();
+    assert_eq!(resp2, resp3);
+    assert_eq!(resp2.get("foo").unwrap()[0].id, "1-0");
+    assert!(frame_to_xread_result(ProtocolFrame::Null).unwrap().is_empty());
+  }
+
+  #[test]
+  fn should_build_xcap_args() {
+    let cap: XCap = (XCapKind::MinID, XCapTrim::AlmostExact, "1-0", Some(100)).into();
+    let expected: Vec<RedisValue> = vec!["MINID".into(), "~".into(), "1-0".into(), "LIMIT".into(), "100".into()];
+    assert_eq!(cap.into_args(), expected);
+
+    let cap: XCap = (XCapKind::MaxLen, 10).into();
+    let expected: Vec<RedisValue> = vec!["MAXLEN".into(), "=".into(), "10".into()];
+    assert_eq!(cap.into_args(), expected);
+  }
+
+  #[test]
+  fn should_parse_xpending_summary() {
+    let frame = ProtocolFrame::Array(vec![
+      ProtocolFrame::Integer(3),
+      ProtocolFrame::BulkString("1-0".into()),
+      ProtocolFrame::BulkString("3-0".into()),
+      ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+        ProtocolFrame::BulkString("alice".into()),
+        ProtocolFrame::BulkString("3".into()),
+      ])]),
+    ]);
+    let expected = XPendingSummary {
+      count: 3,
+      min_id: Some("1-0".into()),
+      max_id: Some("3-0".into()),
+      consumers: vec![("alice".into(), 3)],
+    };
+    assert_eq!(frame_to_xpending_summary(frame).unwrap(), expected);
+
+    let empty = ProtocolFrame::Array(vec![
+      ProtocolFrame::Integer(0),
+      ProtocolFrame::Null,
+      ProtocolFrame::Null,
+      ProtocolFrame::Null,
+    ]);
+    assert_eq!(frame_to_xpending_summary(empty).unwrap(), XPendingSummary::default());
+  }
+
+  #[test]
+  fn should_parse_xpending_entries() {
+    let frame = ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString("1-0".into()),
+      ProtocolFrame::BulkString("alice".into()),
+      ProtocolFrame::Integer(100),
+      ProtocolFrame::Integer(2),
+    ])]);
+    let expected = vec![XPendingEntry {
+      id: "1-0".into(),
+      consumer: "alice".into(),
+      idle: 100,
+      delivered: 2,
+    }];
+
+    assert_eq!(frame_to_xpending_entries(frame).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_parse_xautoclaim_results() {
+    let frame = ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString("0-0".into()),
+      ProtocolFrame::Array(vec![stream_entry_frame("1-0", "foo", "bar"), ProtocolFrame::Null]),
+      ProtocolFrame::Array(vec![ProtocolFrame::BulkString("2-0".into())]),
+    ]);
+    let result = frame_to_xautoclaim_result(frame, frame_to_stream_entries).unwrap();
+
+    assert!(result.is_complete());
+    assert_eq!(result.claimed.len(), 1);
+    assert_eq!(result.claimed[0].id, "1-0");
+    assert_eq!(result.deleted, vec!["2-0".to_owned()]);
+
+    let frame = ProtocolFrame::Array(vec![
+      ProtocolFrame::BulkString("3-0".into()),
+      ProtocolFrame::Array(vec![ProtocolFrame::BulkString("1-0".into())]),
+    ]);
+    let result = frame_to_xautoclaim_result(frame, frame_to_stream_ids).unwrap();
+
+    assert!(!result.is_complete());
+    assert_eq!(result.claimed, vec!["1-0".to_owned()]);
+    assert!(result.deleted.is_empty());
+  }
+
+  fn bulk(s: &str) -> ProtocolFrame {
+    ProtocolFrame::BulkString(s.as_bytes().to_vec())
+  }
+
+  #[test]
+  fn should_parse_xinfo_stream() {
+    let frame = ProtocolFrame::Array(vec![
+      bulk("length"),
+      ProtocolFrame::Integer(2),
+      bulk("radix-tree-keys"),
+      ProtocolFrame::Integer(1),
+      bulk("radix-tree-nodes"),
+      ProtocolFrame::Integer(2),
+      bulk("last-generated-id"),
+      bulk("2-0"),
+      bulk("groups"),
+      ProtocolFrame::Integer(1),
+      bulk("first-entry"),
+      stream_entry_frame("1-0", "foo", "bar"),
+      bulk("last-entry"),
+      stream_entry_frame("2-0", "foo", "baz"),
+    ]);
+    let info = frame_to_stream_info(frame).unwrap();
+
+    assert_eq!(info.length, 2);
+    assert_eq!(info.last_generated_id, "2-0");
+    assert_eq!(info.groups, 1);
+    assert!(info.entries_added.is_none());
+    assert_eq!(info.first_entry.unwrap().id, "1-0");
+    assert_eq!(info.last_entry.unwrap().id, "2-0");
+  }
+
+  #[test]
+  fn should_parse_xinfo_groups_and_consumers() {
+    let groups = ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+      bulk("name"),
+      bulk("group1"),
+      bulk("consumers"),
+      ProtocolFrame::Integer(1),
+      bulk("pending"),
+      ProtocolFrame::Integer(2),
+      bulk("last-delivered-id"),
+      bulk("2-0"),
+      bulk("entries-read"),
+      ProtocolFrame::Integer(2),
+      bulk("lag"),
+      ProtocolFrame::Null,
+    ])]);
+    let expected = vec![GroupInfo {
+      name: "group1".into(),
+      consumers: 1,
+      pending: 2,
+      last_delivered_id: "2-0".into(),
+      entries_read: Some(2),
+      lag: None,
+    }];
+    assert_eq!(frame_to_group_info(groups).unwrap(), expected);
+
+    let consumers = ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+      bulk("name"),
+      bulk("consumer1"),
+      bulk("pending"),
+      ProtocolFrame::Integer(2),
+      bulk("idle"),
+      ProtocolFrame::Integer(100),
+      bulk("inactive"),
+      ProtocolFrame::Integer(-1),
+    ])]);
+    let expected = vec![ConsumerInfo {
+      name: "consumer1".into(),
+      pending: 2,
+      idle: 100,
+      inactive: Some(-1),
+    }];
+    assert_eq!(frame_to_consumer_info(consumers).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_parse_xinfo_stream_full() {
+    let consumer = ProtocolFrame::Array(vec![
+      bulk("name"),
+      bulk("consumer1"),
+      bulk("seen-time"),
+      ProtocolFrame::Integer(1000),
+      bulk("pel-count"),
+      ProtocolFrame::Integer(1),
+      bulk("pending"),
+      ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+        bulk("1-0"),
+        ProtocolFrame::Integer(1000),
+        ProtocolFrame::Integer(1),
+      ])]),
+    ]);
+    let group = ProtocolFrame::Array(vec![
+      bulk("name"),
+      bulk("group1"),
+      bulk("last-delivered-id"),
+      bulk("1-0"),
+      bulk("pel-count"),
+      ProtocolFrame::Integer(1),
+      bulk("pending"),
+      ProtocolFrame::Array(vec![ProtocolFrame::Array(vec![
+        bulk("1-0"),
+        bulk("consumer1"),
+        ProtocolFrame::Integer(1000),
+        ProtocolFrame::Integer(1),
+      ])]),
+      bulk("consumers"),
+      ProtocolFrame::Array(vec![consumer]),
+    ]);
+    let frame = ProtocolFrame::Array(vec![
+      bulk("length"),
+      ProtocolFrame::Integer(1),
+      bulk("last-generated-id"),
+      bulk("1-0"),
+      bulk("entries"),
+      ProtocolFrame::Array(vec![stream_entry_frame("1-0", "foo", "bar")]),
+      bulk("groups"),
+      ProtocolFrame::Array(vec![group]),
+    ]);
+    let info = frame_to_stream_info_full(frame).unwrap();
+
+    assert_eq!(info.entries.len(), 1);
+    assert_eq!(info.groups.len(), 1);
+    assert_eq!(info.groups[0].pending[0].consumer, "consumer1");
+    assert_eq!(info.groups[0].consumers[0].pending, vec![ConsumerPendingEntry {
+      id: "1-0".into(),
+      delivery_time: 1000,
+      delivery_count: 1,
+    }]);
+  }
+
+  #[test]
+  fn should_parse_sharded_pubsub_message() {
+    let frame = ProtocolFrame::Array(vec![str_to_bs("smessage"), str_to_bs("foo"), str_to_bs("bar")]);
+    assert!(is_pubsub_message(&frame));
+
+    let (channel, message) = frame_to_pubsub(frame).unwrap();
+    assert_eq!(channel, "foo");
+    assert_eq!(message, RedisValue::String("bar".into()));
+  }
+
+  #[test]
+  fn should_parse_sunsubscribe_channel() {
+    let frame = ProtocolFrame::Array(vec![
+      str_to_bs("sunsubscribe"),
+      str_to_bs("foo"),
+      ProtocolFrame::Integer(0),
+    ]);
+    assert_eq!(parse_sunsubscribe_channel(&frame), Some("foo".into()));
+
+    let frame = ProtocolFrame::Array(vec![
+      str_to_bs("unsubscribe"),
+      str_to_bs("foo"),
+      ProtocolFrame::Integer(0),
+    ]);
+    assert_eq!(parse_sunsubscribe_channel(&frame), None);
+  }
+
+  #[test]
+  fn should_read_mapped_cluster_tls_domain() {
+    let mut config = RedisConfig::default();
+    config
+      .tls_server_names
+      .insert(("172.17.0.2".to_owned(), 6379), "redis.example.com".to_owned());
+    let inner = RedisClientInner::new(config);
+
+    assert_eq!(read_cluster_tls_domain(&inner, "172.17.0.2", 6379), "redis.example.com");
+    assert_eq!(read_cluster_tls_domain(&inner, "172.17.0.2", 6380), "172.17.0.2");
+  }
 }
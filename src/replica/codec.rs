@@ -0,0 +1,151 @@
+use crate::error::{RedisError, RedisErrorKind};
+use bytes::{Buf, BytesMut};
+use redis_protocol::resp2::decode::decode as resp2_decode;
+use redis_protocol::resp2::encode::encode_bytes as resp2_encode;
+use redis_protocol::resp2::types::Frame as ProtocolFrame;
+use std::cmp;
+use std::str;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A frame read from the replication stream.
+#[derive(Debug)]
+pub enum ReplicaFrame {
+  Rdb(Vec<u8>),
+  RdbEnd,
+  /// A frame from the command feed, and the number of bytes it used on the wire.
+  Frame(ProtocolFrame, usize),
+}
+
+#[derive(Debug)]
+enum State {
+  /// Waiting on the `$<len>` or `$EOF:<mark>` preamble before the RDB payload.
+  RdbHeader,
+  /// Reading the RDB payload with the provided number of bytes remaining.
+  Rdb(usize),
+  /// Reading the RDB payload until the provided end mark.
+  RdbEof(Vec<u8>),
+  /// Reading the command feed.
+  Stream,
+}
+
+/// A codec for the replication stream that follows a successful `PSYNC` response.
+#[derive(Debug)]
+pub struct ReplicaCodec {
+  state: State,
+}
+
+impl ReplicaCodec {
+  /// Create a codec that expects an RDB payload before the command feed.
+  pub fn full_resync() -> Self {
+    ReplicaCodec { state: State::RdbHeader }
+  }
+
+  /// Create a codec that expects the command feed immediately.
+  pub fn partial_resync() -> Self {
+    ReplicaCodec { state: State::Stream }
+  }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn parse_rdb_header(line: &[u8]) -> Result<State, RedisError> {
+  let line = str::from_utf8(line)?;
+
+  if !line.starts_with("$") {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      format!("Invalid RDB preamble: {}", line),
+    ));
+  }
+
+  if line.starts_with("$EOF:") {
+    let mark = line[5 ..].as_bytes().to_vec();
+    if mark.is_empty() {
+      return Err(RedisError::new(RedisErrorKind::ProtocolError, "Invalid RDB EOF mark."));
+    }
+
+    Ok(State::RdbEof(mark))
+  } else {
+    Ok(State::Rdb(line[1 ..].parse::<usize>()?))
+  }
+}
+
+impl Decoder for ReplicaCodec {
+  type Item = ReplicaFrame;
+  type Error = RedisError;
+
+  fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    loop {
+      match self.state {
+        State::RdbHeader => {
+          // the server sends newlines as a keepalive while it creates the RDB payload
+          while src.first() == Some(&b'\n') {
+            src.advance(1);
+          }
+
+          let idx = match find(src, b"\r\n") {
+            Some(idx) => idx,
+            None => return Ok(None),
+          };
+          self.state = parse_rdb_header(&src[.. idx])?;
+          src.advance(idx + 2);
+        }
+        State::Rdb(remaining) => {
+          if remaining == 0 {
+            self.state = State::Stream;
+            return Ok(Some(ReplicaFrame::RdbEnd));
+          }
+          if src.is_empty() {
+            return Ok(None);
+          }
+
+          let amt = cmp::min(remaining, src.len());
+          let chunk = src.split_to(amt);
+          self.state = State::Rdb(remaining - amt);
+          return Ok(Some(ReplicaFrame::Rdb(chunk.to_vec())));
+        }
+        State::RdbEof(ref mark) => match find(src, mark) {
+          Some(0) => {
+            src.advance(mark.len());
+            self.state = State::Stream;
+            return Ok(Some(ReplicaFrame::RdbEnd));
+          }
+          Some(idx) => return Ok(Some(ReplicaFrame::Rdb(src.split_to(idx).to_vec()))),
+          None => {
+            // keep enough bytes in the buffer to find a mark that spans multiple reads
+            if src.len() > mark.len() {
+              let amt = src.len() - mark.len();
+              return Ok(Some(ReplicaFrame::Rdb(src.split_to(amt).to_vec())));
+            } else {
+              return Ok(None);
+            }
+          }
+        },
+        State::Stream => {
+          if src.is_empty() {
+            return Ok(None);
+          }
+
+          return match resp2_decode(src)? {
+            Some((frame, amt)) => {
+              src.advance(amt);
+              Ok(Some(ReplicaFrame::Frame(frame, amt)))
+            }
+            None => Ok(None),
+          };
+        }
+      }
+    }
+  }
+}
+
+impl Encoder<ProtocolFrame> for ReplicaCodec {
+  type Error = RedisError;
+
+  fn encode(&mut self, item: ProtocolFrame, dst: &mut BytesMut) -> Result<(), Self::Error> {
+    let _ = resp2_encode(dst, &item)?;
+    Ok(())
+  }
+}
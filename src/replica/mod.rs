@@ -0,0 +1,118 @@
+use crate::error::RedisError;
+use crate::protocol::tls::TlsConfig;
+use crate::types::RedisValue;
+use futures::Stream;
+use std::fmt;
+
+mod codec;
+mod utils;
+
+/// A command parsed from the replication stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Command {
+  /// The command run by the server.
+  pub command: String,
+  /// Arguments passed to the command.
+  pub args: Vec<RedisValue>,
+  /// The database against which the command was run, as determined by the last `SELECT` in the replication stream.
+  pub db: u8,
+  /// The replication offset after processing the command.
+  ///
+  /// This can be used with [Config::offset] to resume the replication stream after reconnecting.
+  pub offset: i64,
+}
+
+impl fmt::Display for Command {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} [{}] \"{}\"", self.offset, self.db, self.command)?;
+
+    for arg in self.args.iter() {
+      write!(f, " \"{}\"", arg.as_str().unwrap_or("unknown".into()))?;
+    }
+
+    Ok(())
+  }
+}
+
+/// An event read from the replication stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+  /// The server will send a full copy of the dataset as an RDB payload, followed by the replication command feed starting at `offset`.
+  FullResync { replication_id: String, offset: i64 },
+  /// The server accepted the partial resynchronization request and will continue the replication command feed from the offset
+  /// in the [Config](Config).
+  Continue { replication_id: Option<String> },
+  /// A chunk of the RDB payload sent during a full resynchronization.
+  Rdb(Vec<u8>),
+  /// The end of the RDB payload.
+  RdbEnd,
+  /// A command from the replication command feed.
+  Command(Command),
+}
+
+/// Configuration options for a replica client.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+  pub host: String,
+  pub port: u16,
+  pub username: Option<String>,
+  pub password: Option<String>,
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  pub tls: Option<TlsConfig>,
+  /// The port to report to the primary node via `REPLCONF listening-port`. This only affects the output of `INFO` and `ROLE` on the primary.
+  ///
+  /// Default: `None`
+  pub listening_port: Option<u16>,
+  /// The replication ID to use when requesting a partial resynchronization. If `None` the client will request a full resynchronization.
+  ///
+  /// Default: `None`
+  pub replication_id: Option<String>,
+  /// The last replication offset processed by the caller, used when requesting a partial resynchronization.
+  ///
+  /// Default: `-1`
+  pub offset: i64,
+  /// The interval, in milliseconds, on which to send `REPLCONF ACK` to the primary node.
+  ///
+  /// Default: `1000`
+  pub ack_interval_ms: u64,
+}
+
+impl Default for Config {
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  fn default() -> Self {
+    Config {
+      host: "127.0.0.1".into(),
+      port: 6379,
+      username: None,
+      password: None,
+      tls: None,
+      listening_port: None,
+      replication_id: None,
+      offset: -1,
+      ack_interval_ms: 1000,
+    }
+  }
+
+  #[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
+  fn default() -> Self {
+    Config {
+      host: "127.0.0.1".into(),
+      port: 6379,
+      username: None,
+      password: None,
+      listening_port: None,
+      replication_id: None,
+      offset: -1,
+      ack_interval_ms: 1000,
+    }
+  }
+}
+
+/// Perform the `REPLCONF` and [PSYNC](https://redis.io/commands/psync) handshake with the provided server, returning a stream of
+/// replication events.
+///
+/// The stream will end after an error if the connection closes or the server sends an invalid frame. Callers that need to resume the
+/// replication stream should store the replication ID and offset from the events and use them to create a new client.
+pub async fn run(config: Config) -> Result<impl Stream<Item = Result<Event, RedisError>>, RedisError> {
+  utils::start(config).await
+}
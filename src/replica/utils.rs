@@ -0,0 +1,388 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::codec::RedisCodec;
+use crate::protocol::connection::{self, RedisTransport};
+use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::utils as protocol_utils;
+use crate::replica::codec::{ReplicaCodec, ReplicaFrame};
+use crate::replica::{Command, Config, Event};
+use crate::types::{RedisConfig, RedisValue, ServerConfig};
+use futures::sink::SinkExt;
+use futures::stream::{Stream, StreamExt};
+use redis_protocol::resp2::types::Frame as ProtocolFrame;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::interval as tokio_interval;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_util::codec::{Framed, FramedParts};
+
+static LISTENING_PORT: &'static str = "listening-port";
+static CAPA: &'static str = "capa";
+static EOF: &'static str = "eof";
+static PSYNC2: &'static str = "psync2";
+static ACK: &'static str = "ACK";
+static GETACK: &'static str = "GETACK";
+static FULLRESYNC: &'static str = "FULLRESYNC";
+static CONTINUE: &'static str = "CONTINUE";
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+fn create_client_inner(config: &Config) -> Arc<RedisClientInner> {
+  let config = RedisConfig {
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server: ServerConfig::Centralized {
+      host: config.host.clone(),
+      port: config.port,
+    },
+    tls: config.tls.clone(),
+    ..Default::default()
+  };
+
+  RedisClientInner::new(config)
+}
+
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
+fn create_client_inner(config: &Config) -> Arc<RedisClientInner> {
+  let config = RedisConfig {
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server: ServerConfig::Centralized {
+      host: config.host.clone(),
+      port: config.port,
+    },
+    ..Default::default()
+  };
+
+  RedisClientInner::new(config)
+}
+
+/// Parse the response to `PSYNC`, returning the first event in the replication stream.
+fn parse_psync_response(frame: ProtocolFrame) -> Result<Event, RedisError> {
+  let response = match frame {
+    ProtocolFrame::SimpleString(s) => s,
+    ProtocolFrame::Error(e) => return Err(protocol_utils::pretty_error(&e)),
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid PSYNC response.",
+      ))
+    }
+  };
+  let parts: Vec<&str> = response.split_whitespace().collect();
+
+  match parts.first() {
+    Some(kind) if *kind == FULLRESYNC && parts.len() == 3 => Ok(Event::FullResync {
+      replication_id: parts[1].to_owned(),
+      offset: parts[2].parse::<i64>()?,
+    }),
+    Some(kind) if *kind == CONTINUE => Ok(Event::Continue {
+      replication_id: parts.get(1).map(|s| (*s).to_owned()),
+    }),
+    _ => Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      format!("Invalid PSYNC response: {}", response),
+    )),
+  }
+}
+
+async fn request_ok<T>(
+  inner: &Arc<RedisClientInner>,
+  framed: Framed<T, RedisCodec>,
+  command: RedisCommand,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  _debug!(inner, "Sending {} command.", command.kind.to_str_debug());
+  let (frame, framed) = connection::request_response(framed, &command).await?;
+  let response = protocol_utils::frame_to_single_result(frame)?;
+  let _ = protocol_utils::expect_ok(&response)?;
+
+  Ok(framed)
+}
+
+/// Send the `REPLCONF` and `PSYNC` commands, returning the response to `PSYNC` and a transport using the replication codec.
+async fn handshake<T>(
+  inner: &Arc<RedisClientInner>,
+  config: &Config,
+  framed: Framed<T, RedisCodec>,
+) -> Result<(Event, Framed<T, ReplicaCodec>), RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let framed = if let Some(port) = config.listening_port {
+    let args = vec![LISTENING_PORT.into(), port.into()];
+    let command = RedisCommand::new(RedisCommandKind::Replconf, args, None);
+    request_ok(inner, framed, command).await?
+  } else {
+    framed
+  };
+
+  let args = vec![CAPA.into(), EOF.into(), CAPA.into(), PSYNC2.into()];
+  let command = RedisCommand::new(RedisCommandKind::Replconf, args, None);
+  let framed = request_ok(inner, framed, command).await?;
+
+  let args = match config.replication_id {
+    Some(ref id) => vec![id.clone().into(), (config.offset + 1).into()],
+    None => vec!["?".into(), (-1).into()],
+  };
+  let command = RedisCommand::new(RedisCommandKind::Psync, args, None);
+  let (frame, framed) = connection::request_response(framed, &command).await?;
+  _debug!(inner, "Recv PSYNC response: {:?}", frame);
+  let event = parse_psync_response(frame)?;
+
+  let codec = match event {
+    Event::FullResync { .. } => ReplicaCodec::full_resync(),
+    _ => ReplicaCodec::partial_resync(),
+  };
+  // any bytes already read from the socket belong to the replication stream, so they are moved to the new codec
+  let parts = framed.into_parts();
+  let mut new_parts = FramedParts::new::<ProtocolFrame>(parts.io, codec);
+  new_parts.read_buf = parts.read_buf;
+  new_parts.write_buf = parts.write_buf;
+
+  Ok((event, Framed::from_parts(new_parts)))
+}
+
+fn ack_frame(offset: i64) -> ProtocolFrame {
+  ProtocolFrame::Array(vec![
+    ProtocolFrame::BulkString(RedisCommandKind::Replconf.to_str_debug().as_bytes().to_vec()),
+    ProtocolFrame::BulkString(ACK.as_bytes().to_vec()),
+    ProtocolFrame::BulkString(offset.to_string().into_bytes()),
+  ])
+}
+
+/// Parse a command from the replication feed.
+fn parse_command(frame: ProtocolFrame) -> Result<(String, Vec<RedisValue>), RedisError> {
+  let mut frames = match frame {
+    ProtocolFrame::Array(frames) => frames,
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        format!("Invalid replication frame: {:?}", frame),
+      ))
+    }
+  };
+  if frames.is_empty() {
+    return Err(RedisError::new(RedisErrorKind::ProtocolError, "Empty replication frame."));
+  }
+
+  let command = match frames.remove(0) {
+    ProtocolFrame::BulkString(b) => String::from_utf8(b)?.to_uppercase(),
+    _ => return Err(RedisError::new(RedisErrorKind::ProtocolError, "Invalid command name.")),
+  };
+  let mut args = Vec::with_capacity(frames.len());
+  for frame in frames.into_iter() {
+    args.push(protocol_utils::frame_to_single_result(frame)?);
+  }
+
+  Ok((command, args))
+}
+
+/// Send `REPLCONF ACK` on an interval, or whenever an offset is received on `ack_rx`.
+async fn send_acks<S>(inner: Arc<RedisClientInner>, mut sink: S, mut ack_rx: UnboundedReceiver<i64>, interval_ms: u64)
+where
+  S: SinkExt<ProtocolFrame, Error = RedisError> + Unpin,
+{
+  let mut interval = tokio_interval(Duration::from_millis(interval_ms));
+  let mut offset = -1;
+
+  loop {
+    tokio::select! {
+      _ = interval.tick() => {},
+      next = ack_rx.recv() => match next {
+        Some(next) => offset = next,
+        None => break,
+      }
+    };
+
+    if offset >= 0 {
+      if let Err(e) = sink.send(ack_frame(offset)).await {
+        _warn!(inner, "Error sending replication ACK: {:?}", e);
+        break;
+      }
+    }
+  }
+
+  _debug!(inner, "Stopping replication ACK task.");
+}
+
+async fn process_stream<T>(
+  inner: &Arc<RedisClientInner>,
+  config: &Config,
+  tx: UnboundedSender<Result<Event, RedisError>>,
+  mut offset: i64,
+  framed: Framed<T, ReplicaCodec>,
+) where
+  T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+  _debug!(inner, "Starting replication stream processing...");
+  let (sink, mut stream) = framed.split();
+  let (ack_tx, ack_rx) = unbounded_channel();
+  let _ = tokio::spawn(send_acks(inner.clone(), sink, ack_rx, config.ack_interval_ms));
+  let mut db = 0;
+
+  while let Some(frame) = stream.next().await {
+    let event = match frame {
+      Ok(ReplicaFrame::Rdb(chunk)) => Event::Rdb(chunk),
+      Ok(ReplicaFrame::RdbEnd) => {
+        let _ = ack_tx.send(offset);
+        Event::RdbEnd
+      }
+      Ok(ReplicaFrame::Frame(frame, amt)) => {
+        let (command, args) = match parse_command(frame) {
+          Ok(parsed) => parsed,
+          Err(e) => {
+            let _ = tx.send(Err(e));
+            break;
+          }
+        };
+
+        if command == RedisCommandKind::Replconf.to_str_debug() {
+          if args.first().and_then(|a| a.as_str()).map(|s| s.eq_ignore_ascii_case(GETACK)) == Some(true) {
+            // the offset sent in response to GETACK does not include the GETACK command itself
+            let _ = ack_tx.send(offset);
+          }
+          offset += amt as i64;
+          continue;
+        }
+        if command == RedisCommandKind::Select.to_str_debug() {
+          if let Some(next_db) = args.first().and_then(|a| a.as_u64()) {
+            db = next_db as u8;
+          }
+        }
+
+        offset += amt as i64;
+        Event::Command(Command {
+          command,
+          args,
+          db,
+          offset,
+        })
+      }
+      Err(e) => {
+        let _ = tx.send(Err(e));
+        break;
+      }
+    };
+
+    if let Err(_) = tx.send(Ok(event)) {
+      _debug!(inner, "Replication stream receiver closed.");
+      break;
+    }
+  }
+
+  _warn!(inner, "Stopping replication stream.");
+}
+
+async fn start_stream<T>(
+  inner: Arc<RedisClientInner>,
+  config: Config,
+  framed: Framed<T, RedisCodec>,
+) -> Result<UnboundedReceiverStream<Result<Event, RedisError>>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+  let (event, framed) = handshake(&inner, &config, framed).await?;
+  let offset = match event {
+    Event::FullResync { offset, .. } => offset,
+    _ => config.offset,
+  };
+
+  // there is no mechanism to surface backpressure to the server, so a background task reads frames into a channel
+  // so that the server can keep sending data even if the stream consumer slows down.
+  let (tx, rx) = unbounded_channel();
+  let _ = tx.send(Ok(event));
+  let _ = tokio::spawn(async move {
+    process_stream(&inner, &config, tx, offset, framed).await;
+  });
+
+  Ok(UnboundedReceiverStream::new(rx))
+}
+
+pub async fn start(config: Config) -> Result<impl Stream<Item = Result<Event, RedisError>>, RedisError> {
+  let inner = create_client_inner(&config);
+  let connection = connection::create_centralized_connection(&inner).await?;
+
+  match connection {
+    RedisTransport::Tcp(framed) => start_stream(inner, config, framed).await,
+    RedisTransport::Tls(framed) => start_stream(inner, config, framed).await,
+    #[cfg(unix)]
+    RedisTransport::Unix(framed) => start_stream(inner, config, framed).await,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use bytes::BytesMut;
+  use tokio_util::codec::Decoder;
+
+  fn decode_all(codec: &mut ReplicaCodec, buf: &mut BytesMut) -> Vec<ReplicaFrame> {
+    let mut out = Vec::new();
+    while let Some(frame) = codec.decode(buf).unwrap() {
+      out.push(frame);
+    }
+    out
+  }
+
+  #[test]
+  fn should_parse_full_resync_response() {
+    let frame = ProtocolFrame::SimpleString("FULLRESYNC 8de1787ba490483314a4d30f1c628bc5025eb761 2443808505".into());
+    let expected = Event::FullResync {
+      replication_id: "8de1787ba490483314a4d30f1c628bc5025eb761".into(),
+      offset: 2443808505,
+    };
+
+    assert_eq!(parse_psync_response(frame).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_parse_continue_response() {
+    let frame = ProtocolFrame::SimpleString("CONTINUE".into());
+    let expected = Event::Continue { replication_id: None };
+
+    assert_eq!(parse_psync_response(frame).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_decode_rdb_with_length() {
+    let mut codec = ReplicaCodec::full_resync();
+    let mut buf = BytesMut::from(&b"\n\n$5\r\nREDIS*1\r\n$4\r\nPING\r\n"[..]);
+    let frames = decode_all(&mut codec, &mut buf);
+
+    assert_eq!(frames.len(), 3);
+    match frames[0] {
+      ReplicaFrame::Rdb(ref b) => assert_eq!(b, b"REDIS"),
+      _ => panic!("Expected RDB chunk."),
+    };
+    assert!(matches!(frames[1], ReplicaFrame::RdbEnd));
+    assert!(matches!(frames[2], ReplicaFrame::Frame(_, 14)));
+  }
+
+  #[test]
+  fn should_decode_rdb_with_eof_mark() {
+    let mark = "a".repeat(40);
+    let mut codec = ReplicaCodec::full_resync();
+    let mut buf = BytesMut::from(format!("$EOF:{}\r\nREDIS0009", mark).as_bytes());
+
+    let mut rdb = Vec::new();
+    for frame in decode_all(&mut codec, &mut buf) {
+      if let ReplicaFrame::Rdb(chunk) = frame {
+        rdb.extend(chunk);
+      }
+    }
+    buf.extend_from_slice(format!("xyz{}*1\r\n$4\r\nPING\r\n", mark).as_bytes());
+    let frames = decode_all(&mut codec, &mut buf);
+    for frame in frames.iter() {
+      if let ReplicaFrame::Rdb(ref chunk) = frame {
+        rdb.extend(chunk);
+      }
+    }
+
+    assert_eq!(rdb, b"REDIS0009xyz");
+    assert!(matches!(frames[frames.len() - 1], ReplicaFrame::Frame(_, 14)));
+  }
+}
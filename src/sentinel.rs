@@ -1,22 +1,25 @@
 use crate::client::RedisClient;
 use crate::commands;
-use crate::error::RedisError;
+use crate::error::{RedisError, RedisErrorKind};
 use crate::modules::inner::RedisClientInner;
+use crate::modules::pubsub;
 use crate::multiplexer::commands as multiplexer_commands;
 use crate::multiplexer::utils as multiplexer_utils;
 use crate::protocol::tls::TlsConfig;
 use crate::types::{
   AclRule, AclUser, Blocking, ClientKillFilter, ClientKillType, ClientPauseKind, ClientState, ConnectHandle,
-  InfoKind, MultipleStrings, ReconnectPolicy, RedisConfig, RedisKey, RedisMap, RedisResponse, RedisValue,
-  SentinelFailureKind, ServerConfig, ShutdownFlags, Stats,
+  InfoKind, MultipleStrings, ReconnectPolicy, RedisConfig, RedisKey, RedisMap, RedisResponse, RedisValue, RespVersion,
+  SentinelFailureKind, ServerConfig, ShutdownFlags, Stats, TcpConfig,
 };
 use crate::utils;
 use futures::{Stream, StreamExt};
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt;
 use std::net::IpAddr;
 use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 /// Configuration options for sentinel clients.
@@ -41,8 +44,8 @@ pub struct SentinelConfig {
   /// TLS configuration fields. If `None` the connection will not use TLS.
   ///
   /// Default: `None`
-  #[cfg(feature = "enable-tls")]
-  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "enable-tls", feature = "enable-rustls"))))]
   pub tls: Option<TlsConfig>,
   /// Whether or not to enable tracing for this client.
   ///
@@ -59,7 +62,7 @@ impl Default for SentinelConfig {
       port: 26379,
       username: None,
       password: None,
-      #[cfg(feature = "enable-tls")]
+      #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
       tls: None,
       #[cfg(feature = "partial-tracing")]
       tracing: false,
@@ -78,12 +81,29 @@ impl From<SentinelConfig> for RedisConfig {
       fail_fast: true,
       pipeline: false,
       blocking: Blocking::Block,
+      unblock_on_drop: false,
+      dedicated_blocking_streams: false,
+      unrecoverable_errors: vec![RedisErrorKind::Auth, RedisErrorKind::Permission],
+      version: RespVersion::RESP2,
+      tracking: None,
+      pubsub_buffer: None,
+      backpressure: None,
+      tcp: TcpConfig::default(),
+      unresponsive: None,
+      busy_retry: None,
       username: config.username,
       password: config.password,
-      #[cfg(feature = "enable-tls")]
+      database: None,
+      #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
       tls: config.tls,
       #[cfg(feature = "partial-tracing")]
       tracing: config.tracing,
+      service: None,
+      renamed_commands: HashMap::new(),
+      address_map: HashMap::new(),
+      tls_server_names: HashMap::new(),
+      split_cross_slot_keys: false,
+      default_command_timeout_ms: 0,
     }
   }
 }
@@ -165,7 +185,9 @@ impl SentinelClient {
       if let Err(ref e) = result {
         multiplexer_utils::emit_connect_error(&inner, e);
       }
-      utils::set_client_state(&inner.state, ClientState::Disconnected);
+      if utils::read_client_state(&inner.state) != ClientState::Closed {
+        utils::set_client_state(&inner, ClientState::Disconnected);
+      }
       result
     })
   }
@@ -178,6 +200,21 @@ impl SentinelClient {
     utils::wait_for_connect(&self.inner).await
   }
 
+  /// Listen for connection events.
+  ///
+  /// The stream receives `Ok(())` each time the client connects or reconnects, and an error each time a connection attempt fails
+  /// and the client gives up or waits to try again. Unlike [wait_for_connect](Self::wait_for_connect) this does not emit an event if the
+  /// client is already connected.
+  ///
+  /// Similar to [on_reconnect](Self::on_reconnect) and [on_error](Self::on_error), this function does not need to be called again if the
+  /// connection closes.
+  pub fn on_connect(&self) -> impl Stream<Item = Result<(), RedisError>> {
+    let (tx, rx) = unbounded_channel();
+    self.inner.connection_tx.write().push_back(tx);
+
+    UnboundedReceiverStream::new(rx)
+  }
+
   /// Listen for reconnection notifications.
   ///
   /// This function can be used to receive notifications whenever the client successfully reconnects.
@@ -205,10 +242,10 @@ impl SentinelClient {
   ///
   /// <https://redis.io/topics/sentinel#pubsub-messages>
   pub fn on_message(&self) -> impl Stream<Item = (String, RedisValue)> {
-    let (tx, rx) = unbounded_channel();
+    let (tx, rx) = pubsub::listener(&self.inner);
     self.inner.message_tx.write().push_back(tx);
 
-    UnboundedReceiverStream::new(rx)
+    rx
   }
 
   /// Whether or not the client has an active connection to the server(s).
@@ -216,6 +253,11 @@ impl SentinelClient {
     *self.inner.state.read() == ClientState::Connected
   }
 
+  /// Listen for changes to the state of the underlying connection.
+  pub fn watch_state(&self) -> watch::Receiver<ClientState> {
+    self.inner.state_tx.subscribe()
+  }
+
   /// Read the number of buffered commands that have not yet been sent to the server.
   pub fn command_queue_len(&self) -> usize {
     utils::read_atomic(&self.inner.cmd_buffer_len)
@@ -677,6 +719,9 @@ impl SentinelClient {
   /// not when the connection has been fully closed. Some time after this future resolves the future returned by [connect](Self::connect)
   /// will resolve which indicates that the connection has been fully closed.
   ///
+  /// Before sending `QUIT` the client stops accepting new commands and waits for any buffered or in-flight commands to finish, up
+  /// to the [quit timeout](crate::globals::get_quit_timeout_ms). Commands sent while the client is closing return a `Canceled` error.
+  ///
   /// This function will also close all error, pubsub message, and reconnection event streams.
   pub async fn quit(&self) -> Result<(), RedisError> {
     commands::server::quit(&self.inner).await
@@ -0,0 +1,195 @@
+use crate::client::RedisClient;
+use crate::error::RedisError;
+use crate::types::{ClientState, RedisKey};
+use crate::utils;
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// The prefix used for namespaces created via [new](TestNamespace::new).
+pub static DEFAULT_TEST_PREFIX: &'static str = "fred-test";
+
+/// A per-test key namespace that can be used to write hermetic tests against a shared Redis server.
+///
+/// Each namespace generates a unique key prefix wrapped in a hash tag, so every key created via [key](Self::key) maps to the
+/// same hash slot. This allows multi-key commands to work against a cluster and allows the namespace to be scanned on a
+/// single node.
+///
+/// Keys created via [key](Self::key) are tracked and deleted when [cleanup](Self::cleanup) is called. Callers should call
+/// `cleanup` at the end of each test. If the namespace is dropped without being cleaned up the keys will be deleted in a
+/// background task, but this is best effort only since the runtime may shut down before the task runs.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::testing::TestNamespace;
+/// async fn should_set_foo(client: RedisClient) -> Result<(), RedisError> {
+///   let ns = TestNamespace::new(&client);
+///
+///   let _: () = client.set(ns.key("foo"), "bar", None, None, false).await?;
+///   let foo: String = client.get(ns.key("foo")).await?;
+///   assert_eq!(foo, "bar");
+///
+///   let _ = ns.cleanup().await?;
+///   Ok(())
+/// }
+/// ```
+pub struct TestNamespace {
+  client: RedisClient,
+  prefix: String,
+  db: Option<u8>,
+  keys: Arc<Mutex<BTreeSet<String>>>,
+  flush_on_drop: bool,
+}
+
+impl fmt::Debug for TestNamespace {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("TestNamespace")
+      .field("prefix", &self.prefix)
+      .field("db", &self.db)
+      .field("keys", &self.keys.lock().len())
+      .finish()
+  }
+}
+
+impl TestNamespace {
+  /// Create a new namespace with a random prefix.
+  pub fn new(client: &RedisClient) -> Self {
+    let prefix = format!("{}-{}", DEFAULT_TEST_PREFIX, utils::random_string(16));
+    TestNamespace::with_prefix(client, prefix)
+  }
+
+  /// Create a new namespace with the provided prefix.
+  ///
+  /// The prefix is wrapped in a hash tag, so callers should not include `{` or `}` in the prefix.
+  pub fn with_prefix<S>(client: &RedisClient, prefix: S) -> Self
+  where
+    S: Into<String>,
+  {
+    TestNamespace {
+      client: client.clone(),
+      prefix: format!("{{{}}}:", prefix.into()),
+      db: None,
+      keys: Arc::new(Mutex::new(BTreeSet::new())),
+      flush_on_drop: true,
+    }
+  }
+
+  /// Create a new namespace with a random prefix after selecting the provided database on the client.
+  ///
+  /// Database selection is not supported by clustered servers.
+  pub async fn with_db(client: &RedisClient, db: u8) -> Result<Self, RedisError> {
+    let _ = client.select(db).await?;
+
+    let mut namespace = TestNamespace::new(client);
+    namespace.db = Some(db);
+    Ok(namespace)
+  }
+
+  /// Whether or not the namespace should delete its keys in a background task when dropped.
+  ///
+  /// Default: `true`
+  pub fn flush_on_drop(mut self, flush: bool) -> Self {
+    self.flush_on_drop = flush;
+    self
+  }
+
+  /// Read the client used by the namespace.
+  pub fn client(&self) -> &RedisClient {
+    &self.client
+  }
+
+  /// Read the key prefix used by the namespace, including the hash tag.
+  pub fn prefix(&self) -> &str {
+    &self.prefix
+  }
+
+  /// Read the database selected by the namespace, if any.
+  pub fn db(&self) -> Option<u8> {
+    self.db
+  }
+
+  /// Create a key inside the namespace, tracking it for cleanup later.
+  pub fn key<S>(&self, name: S) -> String
+  where
+    S: AsRef<str>,
+  {
+    let key = format!("{}{}", self.prefix, name.as_ref());
+    self.keys.lock().insert(key.clone());
+    key
+  }
+
+  /// Track a key that was created outside of [key](Self::key), such as one created by a command running on the server.
+  pub fn track<K>(&self, key: K)
+  where
+    K: Into<RedisKey>,
+  {
+    if let Some(key) = key.into().into_string() {
+      self.keys.lock().insert(key);
+    }
+  }
+
+  /// Read the keys tracked by the namespace.
+  pub fn keys(&self) -> Vec<String> {
+    self.keys.lock().iter().cloned().collect()
+  }
+
+  /// Delete all keys in the namespace, returning the number of keys deleted.
+  ///
+  /// This will delete the tracked keys and any other keys on the server that match the namespace prefix.
+  pub async fn cleanup(&self) -> Result<usize, RedisError> {
+    delete_namespace(&self.client, &self.prefix, &self.keys).await
+  }
+}
+
+impl Drop for TestNamespace {
+  fn drop(&mut self) {
+    if !self.flush_on_drop || self.keys.lock().is_empty() {
+      return;
+    }
+    if self.client.state() != ClientState::Connected {
+      return;
+    }
+
+    if let Ok(handle) = Handle::try_current() {
+      let (client, prefix, keys) = (self.client.clone(), self.prefix.clone(), self.keys.clone());
+
+      let _ = handle.spawn(async move {
+        if let Err(e) = delete_namespace(&client, &prefix, &keys).await {
+          warn!("Error cleaning up test namespace {}: {:?}", prefix, e);
+        }
+      });
+    }
+  }
+}
+
+async fn delete_namespace(
+  client: &RedisClient,
+  prefix: &str,
+  keys: &Arc<Mutex<BTreeSet<String>>>,
+) -> Result<usize, RedisError> {
+  let mut to_delete: BTreeSet<String> = keys.lock().iter().cloned().collect();
+
+  let mut scanner = client.scan(format!("{}*", prefix), Some(100), None);
+  while let Some(result) = scanner.next().await {
+    let mut page = result?;
+    if let Some(results) = page.take_results() {
+      to_delete.extend(results.into_iter().filter_map(|k| k.into_string()));
+    }
+    let _ = page.next()?;
+  }
+
+  let mut deleted = 0;
+  if !to_delete.is_empty() {
+    // all keys share a hash tag so this is safe to run against a cluster
+    let keys: Vec<String> = to_delete.into_iter().collect();
+    for chunk in keys.chunks(100) {
+      deleted += client.del::<usize, _>(chunk.to_vec()).await?;
+    }
+  }
+
+  keys.lock().clear();
+  Ok(deleted)
+}
@@ -0,0 +1,128 @@
+use fred::error::RedisErrorKind;
+use fred::prelude::*;
+use fred::types::{BitFieldEncoding, BitFieldOffset, BitFieldOps, BitFieldOverflow, BitOperation, BitUnit};
+
+pub async fn should_setbit_and_getbit(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let previous: u8 = client.setbit("foo", 7, true).await?;
+  assert_eq!(previous, 0);
+  let previous: u8 = client.setbit("foo", 7, true).await?;
+  assert_eq!(previous, 1);
+  let bit: bool = client.getbit("foo", 7).await?;
+  assert!(bit);
+  let bit: u8 = client.getbit("foo", 100).await?;
+  assert_eq!(bit, 0);
+
+  let value: Vec<u8> = client.get("foo").await?;
+  assert_eq!(value, vec![1]);
+  let _: u8 = client.setbit("foo", 7, false).await?;
+  let bit: u8 = client.getbit("foo", 7).await?;
+  assert_eq!(bit, 0);
+
+  Ok(())
+}
+
+pub async fn should_bitcount_and_bitpos(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "foobar", None, None, false).await?;
+
+  let count: i64 = client.bitcount("foo", None, None).await?;
+  assert_eq!(count, 26);
+  let count: i64 = client.bitcount("foo", Some((1, 1)), None).await?;
+  assert_eq!(count, 6);
+  let count: i64 = client.bitcount("foo", Some((5, 30)), Some(BitUnit::Bit)).await?;
+  assert_eq!(count, 17);
+
+  let pos: i64 = client.bitpos("foo", true, None, None, None).await?;
+  assert_eq!(pos, 1);
+  let pos: i64 = client.bitpos("foo", false, Some(1), None, None).await?;
+  assert_eq!(pos, 8);
+  let pos: i64 = client.bitpos("foo", true, Some(2), Some(20), Some(BitUnit::Bit)).await?;
+  assert_eq!(pos, 2);
+
+  let error = client
+    .bitcount::<i64, _>("foo", None, Some(BitUnit::Byte))
+    .await
+    .unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  let error = client
+    .bitpos::<i64, _>("foo", true, None, Some(1), None)
+    .await
+    .unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+
+  Ok(())
+}
+
+pub async fn should_bitop_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+  check_null!(client, "baz{1}");
+  let _: () = client.set("foo{1}", "abc", None, None, false).await?;
+  let _: () = client.set("bar{1}", "ab", None, None, false).await?;
+
+  let len: i64 = client.bitop(BitOperation::And, "baz{1}", vec!["foo{1}", "bar{1}"]).await?;
+  assert_eq!(len, 3);
+  let value: Vec<u8> = client.get("baz{1}").await?;
+  assert_eq!(value, b"ab\0");
+  let len: i64 = client.bitop(BitOperation::Or, "baz{1}", vec!["foo{1}", "bar{1}"]).await?;
+  assert_eq!(len, 3);
+  let value: String = client.get("baz{1}").await?;
+  assert_eq!(value, "abc");
+  let _: i64 = client.bitop(BitOperation::Xor, "baz{1}", vec!["foo{1}", "bar{1}"]).await?;
+  let value: Vec<u8> = client.get("baz{1}").await?;
+  assert_eq!(value, b"\0\0c");
+  let _: i64 = client.bitop(BitOperation::Not, "baz{1}", "bar{1}").await?;
+  let value: Vec<u8> = client.get("baz{1}").await?;
+  assert_eq!(value, vec![!b'a', !b'b']);
+
+  let error = client
+    .bitop::<i64, _, _>(BitOperation::Not, "baz{1}", vec!["foo{1}", "bar{1}"])
+    .await
+    .unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  if client.is_clustered() {
+    let error = client
+      .bitop::<i64, _, _>(BitOperation::And, "baz{1}", vec!["foo{1}", "bar{2}"])
+      .await
+      .unwrap_err();
+    assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  }
+
+  Ok(())
+}
+
+pub async fn should_run_bitfield_ops(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let u8 = BitFieldEncoding::Unsigned(8);
+
+  let ops = BitFieldOps::new()
+    .set(u8, BitFieldOffset::Index(0), 250)
+    .incr_by(u8, BitFieldOffset::Index(0), 10)
+    .overflow(BitFieldOverflow::Sat)
+    .incr_by(u8, BitFieldOffset::Index(1), 300)
+    .overflow(BitFieldOverflow::Fail)
+    .incr_by(u8, BitFieldOffset::Index(1), 1);
+  let values = client.bitfield("foo", ops).await?;
+  assert_eq!(values, vec![Some(0), Some(4), Some(255), None]);
+
+  let ops = BitFieldOps::new()
+    .get(u8, 0_u64)
+    .get(u8, 8_u64)
+    .get(BitFieldEncoding::Signed(16), 0_u64);
+  let values = client.bitfield_ro("foo", ops).await?;
+  assert_eq!(values, vec![Some(4), Some(255), Some(0x04ff)]);
+  let values = client
+    .bitfield_ro("foo", BitFieldOps::new().get(BitFieldEncoding::Signed(4), 12_u64))
+    .await?;
+  assert_eq!(values, vec![Some(-1)]);
+
+  let error = client
+    .bitfield_ro("foo", BitFieldOps::new().set(u8, 0_u64, 1))
+    .await
+    .unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+
+  Ok(())
+}
@@ -8,16 +8,32 @@ mod keys {
   centralized_test!(keys, should_mset_a_non_empty_map);
   centralized_test_panic!(keys, should_error_mset_empty_map);
   centralized_test!(keys, should_expire_key);
+  centralized_test!(keys, should_expire_key_with_duration_and_timestamp);
+  centralized_test!(keys, should_expire_key_with_options);
   centralized_test!(keys, should_persist_key);
+  centralized_test!(keys, should_read_object_info);
   centralized_test!(keys, should_check_ttl);
   centralized_test!(keys, should_check_pttl);
   centralized_test!(keys, should_dump_key);
   centralized_test!(keys, should_dump_and_restore_key);
+  centralized_test!(keys, should_dump_and_restore_binary_values);
+  centralized_test!(keys, should_migrate_missing_keys);
+  centralized_test!(keys, should_decode_dumped_values);
+  centralized_test!(keys, should_copy_keys_between_clients);
+  centralized_test!(keys, should_rename_cross_slot);
+  centralized_test!(keys, should_build_keyspace_report);
+  centralized_test!(keys, should_scan_and_delete_keys);
+  centralized_test!(keys, should_scan_and_expire_keys);
+  #[cfg(feature = "serde-json")]
+  centralized_test!(keys, should_get_and_set_json);
   centralized_test!(keys, should_modify_ranges);
+  centralized_test!(keys, should_append_values);
+  centralized_test!(keys, should_read_lcs);
   centralized_test!(keys, should_getset_value);
   centralized_test!(keys, should_getdel_value);
   centralized_test!(keys, should_get_strlen);
   centralized_test!(keys, should_mget_values);
+  centralized_test!(keys, should_mget_missing_values);
   centralized_test!(keys, should_msetnx_values);
   centralized_test!(keys, should_copy_values);
   centralized_test!(keys, should_get_keys_from_pool_in_a_stream);
@@ -26,6 +42,14 @@ mod keys {
 mod multi {
 
   centralized_test!(multi, should_run_get_set_trx);
+  centralized_test!(multi, should_run_trx_with_per_command_errors);
+  centralized_test!(multi, should_abort_trx_after_queue_error);
+  centralized_test!(multi, should_retry_trx_with_watch);
+  centralized_test!(multi, should_stop_retrying_trx_with_watch);
+  centralized_test!(multi, should_run_pipeline);
+  centralized_test!(multi, should_not_buffer_client_commands_in_pipeline);
+  centralized_test!(multi, should_run_pipeline_with_errors);
+  centralized_test!(multi, should_convert_typed_trx_results);
   centralized_test_panic!(multi, should_run_error_get_set_trx);
   centralized_test_panic!(multi, should_fail_with_blocking_cmd);
 }
@@ -34,16 +58,45 @@ mod other {
 
   #[cfg(all(not(feature = "chaos-monkey"), feature = "metrics"))]
   centralized_test!(other, should_track_size_stats);
+  #[cfg(feature = "metrics")]
+  centralized_test!(other, should_track_connection_stats);
 
   centralized_test!(other, should_automatically_unblock);
   centralized_test!(other, should_manually_unblock);
   centralized_test!(other, should_error_when_blocked);
+  centralized_test!(other, should_kill_blocked_connection);
+  centralized_test!(other, should_cancel_with_options);
+  centralized_test!(other, should_time_out_with_options);
+  centralized_test!(other, should_unblock_dropped_command);
+  centralized_test!(other, should_not_unblock_for_unsent_command);
+  centralized_test!(other, should_read_server_version);
+  centralized_test!(other, should_read_connected_addresses);
+  centralized_test!(other, should_run_command_hooks);
+  centralized_test!(other, should_use_resp3);
+  centralized_test!(other, should_select_database_on_connect);
+  centralized_test!(other, should_reconnect_unresponsive_connection);
+  centralized_test!(other, should_drain_commands_before_quit);
+  centralized_test!(other, should_connect_with_tcp_options);
+  centralized_test!(other, should_block_with_backpressure);
+  centralized_test!(other, should_stream_connection_events);
+  centralized_test!(other, should_invalidate_cached_values);
+  centralized_test!(other, should_invalidate_cached_values_with_resp2);
+  centralized_test!(other, should_invalidate_cached_values_with_optin);
+  centralized_test!(other, should_stream_bcast_invalidations);
+  centralized_test!(other, should_not_cache_keys_outside_bcast_prefixes);
+  centralized_test!(other, should_opt_in_to_caching);
+  centralized_test!(other, should_close_after_max_attempts);
+  centralized_test!(other, should_error_on_disabled_command);
+  centralized_test!(other, should_write_with_ack);
+  #[cfg(feature = "test-utils")]
+  centralized_test!(other, should_clean_up_test_namespace);
 }
 
 mod pool {
   centralized_test!(pool, should_connect_and_ping_static_pool_single_conn);
   centralized_test!(pool, should_connect_and_ping_static_pool_two_conn);
   centralized_test!(pool, should_connect_and_ping_dynamic_pool);
+  centralized_test!(pool, should_connect_all_and_round_robin_pool);
   #[cfg(feature = "fd-tests")]
   centralized_test!(pool, should_connect_and_ping_static_pool_many_conn);
   #[cfg(feature = "fd-tests")]
@@ -61,6 +114,7 @@ mod hashes {
   centralized_test!(hashes, should_get_keys);
   centralized_test!(hashes, should_hmset);
   centralized_test!(hashes, should_hmget);
+  centralized_test!(hashes, should_hmget_missing_fields);
   centralized_test!(hashes, should_hsetnx);
   centralized_test!(hashes, should_get_random_field);
   centralized_test!(hashes, should_get_strlen);
@@ -72,6 +126,12 @@ mod pubsub {
 
   centralized_test!(pubsub, should_publish_and_recv_messages);
   centralized_test!(pubsub, should_psubscribe_and_recv_messages);
+  centralized_test!(pubsub, should_track_subscriptions);
+  centralized_test!(pubsub, should_ssubscribe_and_recv_messages);
+  centralized_test!(pubsub, should_split_message_streams);
+  centralized_test!(pubsub, should_resubscribe_after_reconnecting);
+  centralized_test!(pubsub, should_emit_gap_after_reconnecting);
+  centralized_test!(pubsub, should_not_replay_expired_events_after_gap);
 }
 
 mod hyperloglog {
@@ -81,9 +141,19 @@ mod hyperloglog {
   centralized_test!(hyperloglog, should_pfmerge_elements);
 }
 
+mod bitmaps {
+
+  centralized_test!(bitmaps, should_setbit_and_getbit);
+  centralized_test!(bitmaps, should_bitcount_and_bitpos);
+  centralized_test!(bitmaps, should_bitop_values);
+  centralized_test!(bitmaps, should_run_bitfield_ops);
+}
+
 mod scanning {
 
   centralized_test!(scanning, should_scan_keyspace);
+  centralized_test!(scanning, should_cancel_scan);
+  centralized_test!(scanning, should_scan_buffered);
   centralized_test!(scanning, should_hscan_hash);
   centralized_test!(scanning, should_sscan_set);
   centralized_test!(scanning, should_zscan_sorted_set);
@@ -102,6 +172,7 @@ mod server {
   centralized_test!(server, should_read_server_info);
   centralized_test!(server, should_ping_server);
   centralized_test!(server, should_run_custom_command);
+  centralized_test!(server, should_route_custom_command);
   centralized_test!(server, should_read_last_save);
   centralized_test!(server, should_read_db_size);
   centralized_test!(server, should_start_bgsave);
@@ -143,6 +214,12 @@ pub mod lua {
   centralized_test!(lua, should_eval_get_script);
   centralized_test!(lua, should_evalsha_echo_script);
   centralized_test!(lua, should_evalsha_get_script);
+  centralized_test!(lua, should_eval_with_script_args);
+  centralized_test!(lua, should_eval_script_after_flush);
+  centralized_test!(lua, should_eval_included_script);
+  centralized_test!(lua, should_load_and_call_functions);
+  centralized_test!(lua, should_dump_and_restore_functions);
+  centralized_test!(lua, should_retry_busy_commands_after_script_kill);
 }
 
 pub mod sorted_sets {
@@ -160,6 +237,7 @@ pub mod sorted_sets {
   centralized_test!(sorted_sets, should_zlexcount);
   centralized_test!(sorted_sets, should_zpopmax);
   centralized_test!(sorted_sets, should_zpopmin);
+  centralized_test!(sorted_sets, should_zpopmin_into_tuples);
   centralized_test!(sorted_sets, should_zrandmember);
   centralized_test!(sorted_sets, should_zrangestore_values);
   centralized_test!(sorted_sets, should_zrangebylex);
@@ -204,6 +282,7 @@ pub mod lists {
   centralized_test!(lists, should_lmove_values);
   centralized_test!(lists, should_rpush_values);
   centralized_test!(lists, should_rpushx_values);
+  centralized_test!(lists, should_sort_values);
 }
 
 pub mod geo {
@@ -221,3 +300,25 @@ pub mod acl {
   centralized_test!(acl, should_auth_as_test_user);
   centralized_test!(acl, should_auth_as_test_user_via_config);
 }
+
+pub mod streams {
+  centralized_test!(streams, should_xadd_auto_id);
+  centralized_test!(streams, should_xadd_manual_id);
+  centralized_test!(streams, should_xadd_nomkstream);
+  centralized_test!(streams, should_xadd_with_cap);
+  centralized_test!(streams, should_xrange_values);
+  centralized_test!(streams, should_xrevrange_values);
+  centralized_test!(streams, should_xread_multiple_keys);
+  centralized_test!(streams, should_xread_block_timeout);
+  centralized_test!(streams, should_xgroup_create_and_destroy);
+  centralized_test!(streams, should_xgroup_create_and_delete_consumers);
+  centralized_test!(streams, should_xreadgroup_and_xack);
+  centralized_test!(streams, should_xpending_ext_values);
+  centralized_test!(streams, should_xclaim_entries);
+  centralized_test!(streams, should_xautoclaim_entries);
+  centralized_test!(streams, should_xinfo_stream);
+  centralized_test!(streams, should_xinfo_groups_and_consumers);
+  centralized_test!(streams, should_xread_on_dedicated_connection);
+  centralized_test!(streams, should_xtrim_values);
+  centralized_test!(streams, should_trim_streams_on_interval);
+}
@@ -0,0 +1,154 @@
+use fred::client::util::redis_keyslot;
+use fred::migrate::{self, ReshardOptions};
+use fred::prelude::*;
+use fred::types::CustomCommand;
+
+pub async fn should_follow_moved_redirection(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  // route the command to a node that doesn't own the key so the server responds with a MOVED error
+  let wrong_slot = (redis_keyslot("foo") + 8192) % 16384;
+  let cmd = CustomCommand {
+    cmd: "GET",
+    hash_slot: Some(wrong_slot),
+    is_blocking: false,
+  };
+  let value: String = client.custom(cmd, vec!["foo"]).await?;
+  assert_eq!(value, "bar");
+
+  Ok(())
+}
+
+pub async fn should_sync_cluster_state(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let before = client.cached_cluster_state().expect("Missing cluster state");
+  let _ = client.sync_cluster().await?;
+  let after = client.cached_cluster_state().expect("Missing cluster state");
+
+  assert_eq!(before.slots(), after.slots());
+  assert_eq!(after.slots().iter().map(|range| range.end - range.start + 1).sum::<u16>(), 16384);
+  Ok(())
+}
+
+pub async fn should_read_cluster_slots(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let slots = client.cluster_slots().await?;
+  let cached = client.cached_cluster_state().expect("Missing cluster state");
+
+  assert_eq!(slots.iter().map(|range| range.end - range.start + 1).sum::<u16>(), 16384);
+  for range in slots.iter() {
+    let server = cached.get_server(range.start).expect("Missing cached slot");
+    assert_eq!(*server.server, range.primary.server());
+  }
+
+  Ok(())
+}
+
+pub async fn should_mget_keys_split_by_slot(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let keys: Vec<String> = (0..20).map(|idx| format!("foo-{}", idx)).collect();
+  for key in keys.iter() {
+    let _: () = client.set(key.as_str(), key.as_str(), None, None, false).await?;
+  }
+
+  let groups = client.split_by_slot(keys.clone());
+  assert!(groups.len() > 1);
+  let mut count = 0;
+  for (slot, keys) in groups.into_iter() {
+    let owner = client.cached_slot_owner(slot).expect("Missing slot owner");
+    assert_eq!(keys[0].cluster_owner(&client), Some(owner));
+
+    let values: Vec<String> = client.mget(keys.clone()).await?;
+    for (key, value) in keys.iter().zip(values.iter()) {
+      assert_eq!(key.as_str(), Some(value.as_str()));
+    }
+    count += values.len();
+  }
+
+  assert_eq!(count, 20);
+  Ok(())
+}
+
+pub async fn should_run_commands_on_each_cluster_node(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..20 {
+    let _: () = client.set(format!("foo-{}", idx), idx, None, None, false).await?;
+  }
+
+  let sizes = client
+    .each_cluster_node(|node| async move { node.dbsize::<i64>().await })
+    .await?;
+  let primaries = client.cached_cluster_state().expect("Missing cluster state").unique_main_nodes();
+  assert_eq!(sizes.len(), primaries.len());
+  assert_eq!(sizes.values().sum::<i64>(), 20);
+
+  let node = client.with_cluster_node(primaries[0].as_str()).await?;
+  let size: i64 = node.dbsize().await?;
+  assert_eq!(sizes.get(primaries[0].as_str()), Some(&size));
+  let _ = node.quit().await?;
+
+  Ok(())
+}
+
+pub async fn should_split_cross_slot_keys(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.split_cross_slot_keys = true;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let keys: Vec<String> = (0..10).map(|idx| format!("foo-{}", idx)).collect();
+  let values: Vec<(String, RedisValue)> = keys.iter().map(|key| (key.clone(), key.as_str().into())).collect();
+  let _ = client.mset(values).await?;
+
+  let mut requested = keys.clone();
+  requested.insert(3, "missing".into());
+  let values: Vec<Option<String>> = client.mget(requested.clone()).await?;
+  for (key, value) in requested.iter().zip(values.into_iter()) {
+    if key == "missing" {
+      assert_eq!(value, None);
+    } else {
+      assert_eq!(value.as_ref(), Some(key));
+    }
+  }
+
+  let deleted: i64 = client.del(requested).await?;
+  assert_eq!(deleted, 10);
+  let _ = client.quit().await;
+  Ok(())
+}
+
+pub async fn should_reshard_slots(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "{foo}1");
+  check_null!(client, "{foo}2");
+  let _: () = client.set("{foo}1", "a", None, None, false).await?;
+  let _: () = client.set("{foo}2", "b", None, None, false).await?;
+
+  let slot = redis_keyslot("{foo}");
+  let source = client.cached_slot_owner(slot).expect("Missing slot owner");
+  let primaries = client.cached_cluster_state().expect("Missing cluster state").unique_main_nodes();
+  let dest = primaries.into_iter().find(|node| *node != source).expect("Missing other primary node");
+
+  let options = ReshardOptions {
+    batch_size: 1,
+    ..Default::default()
+  };
+  let mut reported = 0;
+  let progress =
+    migrate::reshard_slots(&client, slot, slot, dest.as_str(), options.clone(), |_| reported += 1).await?;
+  assert_eq!(progress.slots, 1);
+  assert_eq!(progress.keys, 2);
+  assert_eq!(reported, 3);
+  assert_eq!(client.cached_slot_owner(slot), Some(dest.clone()));
+  let value: String = client.get("{foo}2").await?;
+  assert_eq!(value, "b");
+
+  // slots that the destination already owns are skipped
+  let progress = migrate::reshard_slots(&client, slot, slot, dest.as_str(), options.clone(), |_| {}).await?;
+  assert_eq!(progress.slots, 0);
+  assert_eq!(progress.keys, 0);
+
+  let progress = migrate::reshard_slots(&client, slot, slot, source.as_str(), options, |_| {}).await?;
+  assert_eq!(progress.slots, 1);
+  assert_eq!(client.cached_slot_owner(slot), Some(source));
+  let value: String = client.get("{foo}1").await?;
+  assert_eq!(value, "a");
+
+  Ok(())
+}
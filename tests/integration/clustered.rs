@@ -8,16 +8,31 @@ mod keys {
   cluster_test!(keys, should_mset_a_non_empty_map);
   cluster_test_panic!(keys, should_error_mset_empty_map);
   cluster_test!(keys, should_expire_key);
+  cluster_test!(keys, should_expire_key_with_duration_and_timestamp);
+  cluster_test!(keys, should_expire_key_with_options);
   cluster_test!(keys, should_persist_key);
+  cluster_test!(keys, should_read_object_info);
   cluster_test!(keys, should_check_ttl);
   cluster_test!(keys, should_check_pttl);
   cluster_test!(keys, should_dump_key);
   cluster_test!(keys, should_dump_and_restore_key);
+  cluster_test!(keys, should_dump_and_restore_binary_values);
+  cluster_test!(keys, should_migrate_missing_keys);
+  cluster_test!(keys, should_decode_dumped_values);
+  cluster_test!(keys, should_rename_cross_slot);
+  cluster_test!(keys, should_build_keyspace_report);
+  cluster_test!(keys, should_scan_and_delete_keys);
+  cluster_test!(keys, should_scan_and_expire_keys);
+  #[cfg(feature = "serde-json")]
+  cluster_test!(keys, should_get_and_set_json);
   cluster_test!(keys, should_modify_ranges);
+  cluster_test!(keys, should_append_values);
+  cluster_test!(keys, should_read_lcs);
   cluster_test!(keys, should_getset_value);
   cluster_test!(keys, should_getdel_value);
   cluster_test!(keys, should_get_strlen);
   cluster_test!(keys, should_mget_values);
+  cluster_test!(keys, should_mget_missing_values);
   cluster_test!(keys, should_msetnx_values);
   cluster_test!(keys, should_copy_values);
   cluster_test!(keys, should_get_keys_from_pool_in_a_stream);
@@ -26,6 +41,15 @@ mod keys {
 mod multi {
 
   cluster_test!(multi, should_run_get_set_trx);
+  cluster_test!(multi, should_run_trx_with_per_command_errors);
+  cluster_test!(multi, should_abort_trx_after_queue_error);
+  cluster_test!(multi, should_retry_trx_with_watch);
+  cluster_test!(multi, should_stop_retrying_trx_with_watch);
+  cluster_test!(multi, should_run_pipeline);
+  cluster_test!(multi, should_not_buffer_client_commands_in_pipeline);
+  cluster_test!(multi, should_run_pipeline_with_errors);
+  cluster_test!(multi, should_convert_typed_trx_results);
+  cluster_test!(multi, should_fail_trx_with_cross_slot_keys);
   cluster_test_panic!(multi, should_fail_with_hashslot_error);
   cluster_test_panic!(multi, should_run_error_get_set_trx);
   cluster_test_panic!(multi, should_fail_with_blocking_cmd);
@@ -36,18 +60,44 @@ mod other {
 
   #[cfg(all(not(feature = "chaos-monkey"), feature = "metrics"))]
   cluster_test!(other, should_track_size_stats);
+  #[cfg(feature = "metrics")]
+  cluster_test!(other, should_track_connection_stats);
 
   cluster_test!(other, should_split_clustered_connection);
   cluster_test!(other, should_run_flushall_cluster);
   cluster_test!(other, should_automatically_unblock);
   cluster_test!(other, should_manually_unblock);
   cluster_test!(other, should_error_when_blocked);
+  cluster_test!(other, should_kill_blocked_connection);
+  cluster_test!(other, should_cancel_with_options);
+  cluster_test!(other, should_time_out_with_options);
+  cluster_test!(other, should_unblock_dropped_command);
+  cluster_test!(other, should_not_unblock_for_unsent_command);
+  cluster_test!(other, should_read_server_version);
+  cluster_test!(other, should_read_connected_addresses);
+  cluster_test!(other, should_run_command_hooks);
+  cluster_test!(other, should_use_resp3);
+  cluster_test!(other, should_drain_commands_before_quit);
+  cluster_test!(other, should_connect_with_tcp_options);
+  cluster_test!(other, should_block_with_backpressure);
+  cluster_test!(other, should_stream_connection_events);
+  cluster_test!(other, should_invalidate_cached_values);
+  cluster_test!(other, should_invalidate_cached_values_with_resp2);
+  cluster_test!(other, should_invalidate_cached_values_with_optin);
+  cluster_test!(other, should_stream_bcast_invalidations);
+  cluster_test!(other, should_not_cache_keys_outside_bcast_prefixes);
+  cluster_test!(other, should_opt_in_to_caching);
+  cluster_test!(other, should_error_on_disabled_command);
+  cluster_test!(other, should_write_with_ack);
+  #[cfg(feature = "test-utils")]
+  cluster_test!(other, should_clean_up_test_namespace);
 }
 
 mod pool {
   cluster_test!(pool, should_connect_and_ping_static_pool_single_conn);
   cluster_test!(pool, should_connect_and_ping_static_pool_two_conn);
   cluster_test!(pool, should_connect_and_ping_dynamic_pool);
+  cluster_test!(pool, should_connect_all_and_round_robin_pool);
   #[cfg(feature = "fd-tests")]
   cluster_test!(pool, should_connect_and_ping_static_pool_many_conn);
   #[cfg(feature = "fd-tests")]
@@ -65,6 +115,7 @@ mod hashes {
   cluster_test!(hashes, should_get_keys);
   cluster_test!(hashes, should_hmset);
   cluster_test!(hashes, should_hmget);
+  cluster_test!(hashes, should_hmget_missing_fields);
   cluster_test!(hashes, should_hsetnx);
   cluster_test!(hashes, should_get_random_field);
   cluster_test!(hashes, should_get_strlen);
@@ -76,6 +127,9 @@ mod pubsub {
 
   cluster_test!(pubsub, should_publish_and_recv_messages);
   cluster_test!(pubsub, should_psubscribe_and_recv_messages);
+  cluster_test!(pubsub, should_track_subscriptions);
+  cluster_test!(pubsub, should_ssubscribe_and_recv_messages);
+  cluster_test!(pubsub, should_split_message_streams);
 }
 
 mod hyperloglog {
@@ -85,9 +139,20 @@ mod hyperloglog {
   cluster_test!(hyperloglog, should_pfmerge_elements);
 }
 
+mod bitmaps {
+
+  cluster_test!(bitmaps, should_setbit_and_getbit);
+  cluster_test!(bitmaps, should_bitcount_and_bitpos);
+  cluster_test!(bitmaps, should_bitop_values);
+  cluster_test!(bitmaps, should_run_bitfield_ops);
+}
+
 mod scanning {
 
   cluster_test!(scanning, should_scan_keyspace);
+  cluster_test!(scanning, should_cancel_scan);
+  cluster_test!(scanning, should_scan_buffered);
+  cluster_test!(scanning, should_scan_cluster);
   cluster_test!(scanning, should_hscan_hash);
   cluster_test!(scanning, should_sscan_set);
   cluster_test!(scanning, should_zscan_sorted_set);
@@ -106,6 +171,7 @@ mod server {
   cluster_test!(server, should_read_server_info);
   cluster_test!(server, should_ping_server);
   cluster_test!(server, should_run_custom_command);
+  cluster_test!(server, should_route_custom_command);
   cluster_test!(server, should_read_last_save);
   cluster_test!(server, should_read_db_size);
   cluster_test!(server, should_start_bgsave);
@@ -148,6 +214,10 @@ pub mod lua {
   cluster_test!(lua, should_eval_get_script);
   cluster_test!(lua, should_evalsha_echo_script);
   cluster_test!(lua, should_evalsha_get_script);
+  cluster_test!(lua, should_eval_with_script_args);
+  cluster_test!(lua, should_eval_script_after_flush);
+  cluster_test!(lua, should_eval_included_script);
+  cluster_test!(lua, should_load_and_call_functions);
 }
 
 pub mod sorted_sets {
@@ -167,6 +237,7 @@ pub mod sorted_sets {
   cluster_test!(sorted_sets, should_zlexcount);
   cluster_test!(sorted_sets, should_zpopmax);
   cluster_test!(sorted_sets, should_zpopmin);
+  cluster_test!(sorted_sets, should_zpopmin_into_tuples);
   cluster_test!(sorted_sets, should_zrandmember);
   cluster_test!(sorted_sets, should_zrangestore_values);
   cluster_test!(sorted_sets, should_zrangebylex);
@@ -211,6 +282,7 @@ pub mod lists {
   cluster_test!(lists, should_lmove_values);
   cluster_test!(lists, should_rpush_values);
   cluster_test!(lists, should_rpushx_values);
+  cluster_test!(lists, should_sort_values);
 }
 
 pub mod geo {
@@ -223,3 +295,36 @@ pub mod geo {
   cluster_test!(geo, should_georadiusbymember_values);
   cluster_test!(geo, should_geosearch_values);
 }
+
+pub mod streams {
+  cluster_test!(streams, should_xadd_auto_id);
+  cluster_test!(streams, should_xadd_manual_id);
+  cluster_test!(streams, should_xadd_nomkstream);
+  cluster_test!(streams, should_xadd_with_cap);
+  cluster_test!(streams, should_xrange_values);
+  cluster_test!(streams, should_xrevrange_values);
+  cluster_test!(streams, should_xread_multiple_keys);
+  cluster_test!(streams, should_xread_block_timeout);
+  cluster_test!(streams, should_xgroup_create_and_destroy);
+  cluster_test!(streams, should_xgroup_create_and_delete_consumers);
+  cluster_test!(streams, should_xreadgroup_and_xack);
+  cluster_test!(streams, should_xpending_ext_values);
+  cluster_test!(streams, should_xclaim_entries);
+  cluster_test!(streams, should_xautoclaim_entries);
+  cluster_test!(streams, should_xinfo_stream);
+  cluster_test!(streams, should_xinfo_groups_and_consumers);
+  cluster_test!(streams, should_xread_on_dedicated_connection);
+  cluster_test!(streams, should_xtrim_values);
+  cluster_test!(streams, should_trim_streams_on_interval);
+}
+
+mod cluster {
+
+  cluster_test!(cluster, should_follow_moved_redirection);
+  cluster_test!(cluster, should_sync_cluster_state);
+  cluster_test!(cluster, should_read_cluster_slots);
+  cluster_test!(cluster, should_mget_keys_split_by_slot);
+  cluster_test!(cluster, should_run_commands_on_each_cluster_node);
+  cluster_test!(cluster, should_split_cross_slot_keys);
+  cluster_test!(cluster, should_reshard_slots);
+}
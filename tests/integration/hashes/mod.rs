@@ -163,6 +163,19 @@ pub async fn should_hmget(client: RedisClient, _: RedisConfig) -> Result<(), Red
   Ok(())
 }
 
+pub async fn should_hmget_missing_fields(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let _: () = client.hset("foo", ("a", 1.into())).await?;
+
+  let result: Vec<Option<i64>> = client.hmget("foo", vec!["a", "b"]).await?;
+  assert_eq!(result, vec![Some(1), None]);
+  let result: Vec<Option<i64>> = client.hmget("foo", "b").await?;
+  assert_eq!(result, vec![None]);
+
+  Ok(())
+}
+
 pub async fn should_hsetnx(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   check_null!(client, "foo");
 
@@ -1,12 +1,19 @@
+use fred::analytics::{self, ReportOptions};
+use fred::bulk::{self, BulkOptions};
 use fred::client::RedisClient;
-use fred::error::RedisError;
+use fred::dump::{self, DumpType};
+use fred::migrate::{self, CopyOptions, RenameOptions};
+use fred::error::{RedisError, RedisErrorKind};
 use fred::pool::StaticRedisPool;
 use fred::prelude::Expiration;
-use fred::types::{ReconnectPolicy, RedisConfig, RedisMap, RedisValue};
+use fred::types::{
+  ExpireOptions, LcsMatch, MigrateAuth, ObjectEncoding, ReconnectPolicy, RedisConfig, RedisMap, RedisValue,
+};
+use bytes::Bytes;
 use futures::pin_mut;
 use futures::StreamExt;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio;
 use tokio::time::sleep;
 
@@ -106,7 +113,7 @@ pub async fn should_expire_key(client: RedisClient, _config: RedisConfig) -> Res
   check_null!(client, "foo");
   let _: () = client.set("foo", "bar", None, None, false).await?;
 
-  let _: () = client.expire("foo", 1).await?;
+  let _: () = client.expire("foo", 1, None).await?;
   sleep(Duration::from_millis(1500)).await;
   let foo: Option<String> = client.get("foo").await?;
   assert!(foo.is_none());
@@ -114,6 +121,95 @@ pub async fn should_expire_key(client: RedisClient, _config: RedisConfig) -> Res
   Ok(())
 }
 
+pub async fn should_expire_key_with_duration_and_timestamp(
+  client: RedisClient,
+  _config: RedisConfig,
+) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client
+    .set("foo", "bar", Some(Duration::from_secs(30).into()), None, false)
+    .await?;
+  let ttl: Duration = client.ttl("foo").await?;
+  assert!(ttl > Duration::from_secs(0) && ttl <= Duration::from_secs(30));
+
+  let added: bool = client.expire("foo", Duration::from_secs(60), None).await?;
+  assert!(added);
+  let ttl: Duration = client.ttl("foo").await?;
+  assert!(ttl > Duration::from_secs(30) && ttl <= Duration::from_secs(60));
+
+  let added: bool = client
+    .expire_at("foo", SystemTime::now() + Duration::from_secs(120), None)
+    .await?;
+  assert!(added);
+  let ttl: Duration = client.ttl("foo").await?;
+  assert!(ttl > Duration::from_secs(60) && ttl <= Duration::from_secs(120));
+
+  let _: () = client.persist("foo").await?;
+  let result: Result<Duration, RedisError> = client.ttl("foo").await;
+  assert_eq!(result.unwrap_err().kind(), &RedisErrorKind::Parse);
+
+  Ok(())
+}
+
+pub async fn should_expire_key_with_options(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let added: bool = client.expire("foo", 60, Some(ExpireOptions::XX)).await?;
+  assert!(!added);
+  let added: bool = client.expire("foo", 60, Some(ExpireOptions::NX)).await?;
+  assert!(added);
+  let added: bool = client.pexpire("foo", 30_000, Some(ExpireOptions::GT)).await?;
+  assert!(!added);
+  let added: bool = client.pexpire("foo", 30_000, Some(ExpireOptions::LT)).await?;
+  assert!(added);
+
+  let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 + 120;
+  let added: bool = client.expire_at("foo", timestamp, Some(ExpireOptions::GT)).await?;
+  assert!(added);
+  let expire_time: i64 = client.expire_time("foo").await?;
+  assert_eq!(expire_time, timestamp);
+
+  let added: bool = client
+    .pexpire_at("foo", timestamp * 1000 + 500, Some(ExpireOptions::XX))
+    .await?;
+  assert!(added);
+  let expire_time: i64 = client.pexpire_time("foo").await?;
+  assert_eq!(expire_time, timestamp * 1000 + 500);
+
+  let _: () = client.persist("foo").await?;
+  let expire_time: i64 = client.expire_time("foo").await?;
+  assert_eq!(expire_time, -1);
+  let expire_time: i64 = client.pexpire_time("bar").await?;
+  assert_eq!(expire_time, -2);
+
+  Ok(())
+}
+
+pub async fn should_read_object_info(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  check_null!(client, "bar");
+
+  let encoding: Option<ObjectEncoding> = client.object_encoding("foo").await?;
+  assert!(encoding.is_none());
+  let freq: Option<u64> = client.object_freq("foo").await?;
+  assert!(freq.is_none());
+
+  let _: () = client.set("foo", 123, None, None, false).await?;
+  let _: () = client.set("bar", "baz", None, None, false).await?;
+  let encoding: ObjectEncoding = client.object_encoding("foo").await?;
+  assert_eq!(encoding, ObjectEncoding::Int);
+  let encoding: ObjectEncoding = client.object_encoding("bar").await?;
+  assert_eq!(encoding, ObjectEncoding::EmbStr);
+
+  let refcount: u64 = client.object_refcount("bar").await?;
+  assert!(refcount >= 1);
+  let idle: Duration = client.object_idletime("bar").await?;
+  assert!(idle < Duration::from_secs(60));
+
+  Ok(())
+}
+
 pub async fn should_persist_key(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
   check_null!(client, "foo");
   let _: () = client.set("foo", "bar", Some(Expiration::EX(5)), None, false).await?;
@@ -172,6 +268,246 @@ pub async fn should_dump_and_restore_key(client: RedisClient, _: RedisConfig) ->
   Ok(())
 }
 
+pub async fn should_dump_and_restore_binary_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+  check_null!(client, "baz{1}");
+  check_null!(client, "missing{1}");
+  let expected: &[u8] = &[0, 159, 146, 150, 255];
+
+  let _: () = client.set("foo{1}", expected, None, None, false).await?;
+  let dump = client.dump("foo{1}").await?;
+  assert!(dump.is_bytes());
+  let payload = Bytes::from(dump.as_bytes().unwrap().to_vec());
+
+  let _ = client.restore("bar{1}", 0, payload.clone(), false, false, None, None).await?;
+  let value: Vec<u8> = client.get("bar{1}").await?;
+  assert_eq!(value, expected);
+
+  let _ = client.restore("baz{1}", 0, &payload[..], false, false, Some(10), None).await?;
+  let value: Vec<u8> = client.get("baz{1}").await?;
+  assert_eq!(value, expected);
+
+  let missing = client.dump("missing{1}").await?;
+  assert!(missing.is_null());
+
+  Ok(())
+}
+
+pub async fn should_migrate_missing_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+
+  // the server replies with NOKEY before connecting to the destination
+  let result: String = client
+    .migrate("127.0.0.1", 1, "foo{1}", 0, 100, false, false, None)
+    .await?;
+  assert_eq!(result, "NOKEY");
+  let auth = MigrateAuth::UsernamePassword {
+    username: "foo".into(),
+    password: "bar".into(),
+  };
+  let result: String = client
+    .migrate("127.0.0.1", 1, vec!["foo{1}", "bar{1}"], 0, 100, true, true, Some(auth))
+    .await?;
+  assert_eq!(result, "NOKEY");
+
+  Ok(())
+}
+
+pub async fn should_decode_dumped_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+
+  let _: () = client.set("foo{1}", "abc123", None, None, false).await?;
+  let dump = client.dump("foo{1}").await?;
+  let value = dump::decode(dump.as_bytes().unwrap())?;
+  assert_eq!(value.kind, DumpType::String);
+  assert_eq!(value.value, "abc123".into());
+
+  let _: () = client.rpush("bar{1}", vec!["a", "b", "c"]).await?;
+  let dump = client.dump("bar{1}").await?;
+  let value = dump::decode(dump.as_bytes().unwrap())?;
+  assert_eq!(value.kind, DumpType::List);
+  assert_eq!(value.value, RedisValue::Array(vec!["a".into(), "b".into(), "c".into()]));
+
+  Ok(())
+}
+
+pub async fn should_copy_keys_between_clients(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "{foo}1");
+  check_null!(client, "{foo}2");
+  check_null!(client, "{foo}3");
+
+  // copy into another database so the destination keys are distinct from the source keys
+  config.database = Some(1);
+  let dest = RedisClient::new(config);
+  let _ = dest.connect(None);
+  let _ = dest.wait_for_connect().await?;
+  let _: () = dest.del(vec!["{foo}1", "{foo}2", "{foo}3"]).await?;
+
+  let _: () = client.set("{foo}1", "a", None, None, false).await?;
+  let _: () = client.set("{foo}2", "b", Some(Expiration::EX(100)), None, false).await?;
+
+  let mut reported = 0;
+  let progress = migrate::copy_keys(
+    &client,
+    &dest,
+    vec!["{foo}1", "{foo}2", "{foo}3"],
+    CopyOptions::default(),
+    |_| reported += 1,
+  )
+  .await?;
+  assert_eq!(progress.copied, 2);
+  assert_eq!(progress.skipped, 1);
+  assert_eq!(reported, 3);
+
+  let value: String = dest.get("{foo}2").await?;
+  assert_eq!(value, "b");
+  let ttl: i64 = dest.ttl("{foo}2").await?;
+  assert!(ttl > 0);
+  check_null!(dest, "{foo}3");
+
+  let _: () = dest.del(vec!["{foo}1", "{foo}2"]).await?;
+  let progress = migrate::copy_keys(&client, &dest, "{foo}*", CopyOptions::default(), |_| {}).await?;
+  assert_eq!(progress.copied, 2);
+  let value: String = dest.get("{foo}1").await?;
+  assert_eq!(value, "a");
+
+  // the source keys are copied, not moved
+  let value: String = client.get("{foo}1").await?;
+  assert_eq!(value, "a");
+
+  let _: () = dest.del(vec!["{foo}1", "{foo}2"]).await?;
+  let _ = dest.quit().await;
+  Ok(())
+}
+
+pub async fn should_rename_cross_slot(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  check_null!(client, "bar");
+  check_null!(client, "baz");
+
+  let _: () = client.set("foo", "abc", Some(Expiration::EX(100)), None, false).await?;
+  let options = RenameOptions {
+    lock_key: Some("baz".into()),
+    ..Default::default()
+  };
+  let _ = migrate::rename_cross_slot(&client, "foo", "bar", options).await?;
+
+  let foo: Option<String> = client.get("foo").await?;
+  assert!(foo.is_none());
+  let bar: String = client.get("bar").await?;
+  assert_eq!(bar, "abc");
+  let ttl: i64 = client.ttl("bar").await?;
+  assert!(ttl > 0);
+  let lock: Option<String> = client.get("baz").await?;
+  assert!(lock.is_none());
+
+  let result = migrate::rename_cross_slot(&client, "foo", "bar", RenameOptions::default()).await;
+  assert!(result.is_err());
+
+  let options = RenameOptions {
+    replace: true,
+    ..Default::default()
+  };
+  let _ = migrate::rename_cross_slot(&client, "bar", "bar", options).await?;
+  let bar: String = client.get("bar").await?;
+  assert_eq!(bar, "abc");
+  Ok(())
+}
+
+pub async fn should_build_keyspace_report(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for key in ["{report}:a:1", "{report}:a:2", "{report}:b:1"].iter() {
+    check_null!(client, *key);
+  }
+
+  let _: () = client.set("{report}:a:1", "foo", None, None, false).await?;
+  let _: () = client.set("{report}:a:2", "bar", Some(Expiration::EX(100)), None, false).await?;
+  let _: () = client.rpush("{report}:b:1", vec!["a", "b"]).await?;
+
+  let options = ReportOptions {
+    pattern: "{report}*".into(),
+    depth: 2,
+    ..Default::default()
+  };
+  let report = analytics::keyspace_report(&client, options).await?;
+  assert_eq!(report.keys, 3);
+
+  let a = report.prefixes.get("{report}:a").unwrap();
+  assert_eq!(a.keys, 2);
+  assert_eq!(a.ttl.none, 1);
+  assert_eq!(a.ttl.under_hour, 1);
+  assert_eq!(a.types.get("string"), Some(&2));
+  assert!(a.estimated_memory() > 0);
+  let b = report.prefixes.get("{report}:b").unwrap();
+  assert_eq!(b.types.get("list"), Some(&1));
+
+  Ok(())
+}
+
+pub async fn should_scan_and_delete_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..20 {
+    let _: () = client.set(format!("bulk-{}", idx), idx, None, None, false).await?;
+  }
+  let _: () = client.set("other", 1, None, None, false).await?;
+
+  let options = BulkOptions {
+    dry_run: true,
+    ..Default::default()
+  };
+  let progress = bulk::scan_and_delete(&client, "bulk-*", options, |_| {}).await?;
+  assert!(progress.matched >= 20);
+  assert_eq!(progress.changed, 0);
+  assert_eq!(client.exists::<i64, _>("bulk-0").await?, 1);
+
+  let options = BulkOptions {
+    batch_size: 7,
+    ..Default::default()
+  };
+  let mut calls = 0;
+  let progress = bulk::scan_and_delete(&client, "bulk-*", options, |_| calls += 1).await?;
+  assert_eq!(progress.changed, 20);
+  assert!(calls >= 3);
+  assert_eq!(client.exists::<i64, _>("bulk-0").await?, 0);
+  assert_eq!(client.exists::<i64, _>("other").await?, 1);
+
+  Ok(())
+}
+
+pub async fn should_scan_and_expire_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..20 {
+    let _: () = client.set(format!("bulk-{}", idx), idx, None, None, false).await?;
+  }
+
+  let progress = bulk::scan_and_expire(&client, "bulk-*", 100, BulkOptions::default(), |_| {}).await?;
+  assert_eq!(progress.changed, 20);
+  let ttl: i64 = client.ttl("bulk-0").await?;
+  assert!(ttl > 0 && ttl <= 100);
+
+  Ok(())
+}
+
+#[cfg(feature = "serde-json")]
+pub async fn should_get_and_set_json(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let missing: Option<HashMap<String, Vec<i64>>> = client.json_get("foo").await?;
+  assert!(missing.is_none());
+
+  let mut value = HashMap::new();
+  value.insert("a".to_owned(), vec![1, 2, 3]);
+  let _: () = client.json_set("foo", &value, None, None).await?;
+
+  let raw: String = client.get("foo").await?;
+  assert_eq!(raw, r#"{"a":[1,2,3]}"#);
+  let result: Option<HashMap<String, Vec<i64>>> = client.json_get("foo").await?;
+  assert_eq!(result, Some(value));
+
+  let _: () = client.set("bar", "not json", None, None, false).await?;
+  let error = client.json_get::<HashMap<String, Vec<i64>>, _>("bar").await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::Parse);
+  Ok(())
+}
+
 pub async fn should_modify_ranges(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   check_null!(client, "foo");
   let _: () = client.set("foo", "0123456789", None, None, false).await?;
@@ -182,6 +518,61 @@ pub async fn should_modify_ranges(client: RedisClient, _: RedisConfig) -> Result
   let _: () = client.setrange("foo", 4, "abc").await?;
   let value: String = client.get("foo").await?;
   assert_eq!(value, "0123abc789");
+  let range: String = client.getrange("foo", 8, 100).await?;
+  assert_eq!(range, "89");
+
+  check_null!(client, "bar");
+  let len: usize = client.setrange("bar", 3, "abc").await?;
+  assert_eq!(len, 6);
+  let value: Vec<u8> = client.get("bar").await?;
+  assert_eq!(value, b"\0\0\0abc");
+  let range: String = client.getrange("baz", 0, 10).await?;
+  assert_eq!(range, "");
+
+  Ok(())
+}
+
+pub async fn should_read_lcs(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+  let _: () = client.set("foo{1}", "ohmytext", None, None, false).await?;
+  let _: () = client.set("bar{1}", "mynewtext", None, None, false).await?;
+
+  let result: String = client.lcs("foo{1}", "bar{1}").await?;
+  assert_eq!(result, "mytext");
+  let result: u64 = client.lcs_len("foo{1}", "bar{1}").await?;
+  assert_eq!(result, 6);
+
+  let result = client.lcs_idx("foo{1}", "bar{1}", None, false).await?;
+  assert_eq!(result.len, 6);
+  assert_eq!(result.matches.len(), 2);
+  assert_eq!((result.matches[0].first, result.matches[0].second), ((4, 7), (5, 8)));
+  assert_eq!((result.matches[1].first, result.matches[1].second), ((2, 3), (0, 1)));
+  assert!(result.matches.iter().all(|m| m.len.is_none()));
+
+  let result = client.lcs_idx("foo{1}", "bar{1}", Some(4), true).await?;
+  assert_eq!(result.len, 6);
+  assert_eq!(
+    result.matches,
+    vec![LcsMatch {
+      first: (4, 7),
+      second: (5, 8),
+      len: Some(4),
+    }]
+  );
+
+  Ok(())
+}
+
+pub async fn should_append_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let len: usize = client.append("foo", "abc").await?;
+  assert_eq!(len, 3);
+  let len: usize = client.append("foo", 123).await?;
+  assert_eq!(len, 6);
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "abc123");
 
   Ok(())
 }
@@ -240,6 +631,24 @@ pub async fn should_mget_values(client: RedisClient, _: RedisConfig) -> Result<(
   Ok(())
 }
 
+pub async fn should_mget_missing_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "a{1}");
+  check_null!(client, "b{1}");
+  let _: () = client.set("a{1}", 1, None, None, false).await?;
+
+  let values: Vec<Option<i64>> = client.mget(vec!["a{1}", "b{1}"]).await?;
+  assert_eq!(values, vec![Some(1), None]);
+  let values: Vec<Option<i64>> = client.mget("b{1}").await?;
+  assert_eq!(values, vec![None]);
+  let value: Option<i64> = client.mget("b{1}").await?;
+  assert_eq!(value, None);
+
+  let result: Result<i64, RedisError> = client.get("b{1}").await;
+  assert!(result.unwrap_err().is_unexpected_nil());
+
+  Ok(())
+}
+
 pub async fn should_msetnx_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   check_null!(client, "a{1}");
   check_null!(client, "b{1}");
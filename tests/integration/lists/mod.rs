@@ -1,4 +1,6 @@
 use fred::prelude::*;
+use fred::error::RedisErrorKind;
+use fred::types::{SortOptions, SortOrder};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -238,7 +240,7 @@ pub async fn should_lrange_values(client: RedisClient, _: RedisConfig) -> Result
 
   for idx in 0..COUNT {
     let result: i64 = client.lrange("foo", idx, idx).await?;
-    assert_eq!(result, idx.into());
+    assert_eq!(result, idx);
   }
 
   Ok(())
@@ -371,3 +373,29 @@ pub async fn should_rpushx_values(client: RedisClient, _: RedisConfig) -> Result
 
   Ok(())
 }
+
+pub async fn should_sort_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.rpush("foo{1}", vec![3, 1, 2, 10]).await?;
+
+  let result: Vec<i64> = client.sort("foo{1}", SortOptions::new()).await?;
+  assert_eq!(result, vec![1, 2, 3, 10]);
+  let result: Vec<i64> = client
+    .sort_ro("foo{1}", SortOptions::new().order(SortOrder::Desc).limit(0, 2))
+    .await?;
+  assert_eq!(result, vec![10, 3]);
+  let result: Vec<String> = client.sort("foo{1}", SortOptions::new().alpha().get("#")).await?;
+  assert_eq!(result, vec!["1", "10", "2", "3"]);
+
+  let result: i64 = client.sort("foo{1}", SortOptions::new().store("bar{1}")).await?;
+  assert_eq!(result, 4);
+  let result: Vec<i64> = client.lrange("bar{1}", 0, -1).await?;
+  assert_eq!(result, vec![1, 2, 3, 10]);
+
+  let error = client
+    .sort_ro::<RedisValue, _>("foo{1}", SortOptions::new().store("bar{1}"))
+    .await
+    .unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+
+  Ok(())
+}
@@ -1,8 +1,16 @@
 use fred::client::util;
 use fred::prelude::*;
+use fred::script::Script;
+use std::time::Duration;
+use tokio::time::sleep;
 
 static ECHO_SCRIPT: &'static str = "return {KEYS[1],KEYS[2],ARGV[1],ARGV[2]}";
 static GET_SCRIPT: &'static str = "return redis.call('get', KEYS[1])";
+static INCLUDED_ECHO_SCRIPT: Script = fred::include_script!("echo.lua");
+static BUSY_SCRIPT: &'static str = "while true do end";
+static FUNCTION_LIBRARY: &'static str = "#!lua name=fredtest
+redis.register_function('echo_keys', function(keys, args) return {keys[1], args[1]} end)
+redis.register_function{function_name='get_ro', callback=function(keys) return redis.call('get', keys[1]) end, flags={'no-writes'}}";
 
 pub async fn load_script(client: &RedisClient, script: &str) -> Result<String, RedisError> {
   if client.is_clustered() {
@@ -12,6 +20,22 @@ pub async fn load_script(client: &RedisClient, script: &str) -> Result<String, R
   }
 }
 
+pub async fn load_functions(client: &RedisClient) -> Result<String, RedisError> {
+  if client.is_clustered() {
+    client.function_load_cluster(true, FUNCTION_LIBRARY).await
+  } else {
+    client.function_load(true, FUNCTION_LIBRARY).await
+  }
+}
+
+pub async fn flush_functions(client: &RedisClient) -> Result<(), RedisError> {
+  if client.is_clustered() {
+    client.function_flush_cluster(false).await
+  } else {
+    client.function_flush(false).await
+  }
+}
+
 pub async fn flush_scripts(client: &RedisClient) -> Result<(), RedisError> {
   if client.is_clustered() {
     client.script_flush_cluster(false).await
@@ -90,3 +114,158 @@ pub async fn should_eval_get_script(client: RedisClient, _: RedisConfig) -> Resu
   let _ = flush_scripts(&client).await?;
   Ok(())
 }
+
+pub async fn should_eval_with_script_args(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let args = ScriptArgs::new().key("a{1}").key("b{1}").arg("c{1}").arg("d{1}");
+  let result: Vec<String> = client.eval_with_args(ECHO_SCRIPT, args.clone()).await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+
+  let hash = util::sha1_hash(ECHO_SCRIPT);
+  let result: Vec<String> = client.evalsha_with_args(&hash, args).await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+
+  let result: i64 = client.eval_with_args("return 1", ScriptArgs::new()).await?;
+  assert_eq!(result, 1);
+
+  let _ = flush_scripts(&client).await?;
+  Ok(())
+}
+
+pub async fn should_eval_script_after_flush(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let script = Script::new(ECHO_SCRIPT);
+  assert_eq!(script.sha1(), util::sha1_hash(ECHO_SCRIPT));
+  let _ = flush_scripts(&client).await?;
+
+  let error = client
+    .evalsha_with_args::<RedisValue, _>(script.sha1(), ScriptArgs::new())
+    .await
+    .unwrap_err();
+  assert!(error.is_no_script());
+
+  let args = ScriptArgs::new().key("a{1}").key("b{1}").arg("c{1}").arg("d{1}");
+  let result: Vec<String> = script.eval(&client, args.clone()).await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+  let exists = client.script_exists(script.sha1()).await?;
+  assert_eq!(exists, vec![true]);
+
+  let _ = script.load(&client).await?;
+  let result: Vec<String> = script.eval(&client, args).await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+
+  let _ = flush_scripts(&client).await?;
+  Ok(())
+}
+
+pub async fn should_eval_included_script(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  assert_eq!(
+    INCLUDED_ECHO_SCRIPT.sha1(),
+    util::sha1_hash(INCLUDED_ECHO_SCRIPT.source())
+  );
+  let _ = flush_scripts(&client).await?;
+
+  let args = ScriptArgs::new().key("a{1}").key("b{1}").arg("c{1}").arg("d{1}");
+  let result: Vec<String> = INCLUDED_ECHO_SCRIPT.eval(&client, args).await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+  let exists = client.script_exists(INCLUDED_ECHO_SCRIPT.sha1()).await?;
+  assert_eq!(exists, vec![true]);
+
+  let _ = flush_scripts(&client).await?;
+  Ok(())
+}
+
+pub async fn should_load_and_call_functions(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let name = load_functions(&client).await?;
+  assert_eq!(name, "fredtest");
+
+  let result: (String, i64) = client.fcall("echo_keys", "foo", 1).await?;
+  assert_eq!(result, ("foo".to_owned(), 1));
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let result: Option<String> = client.fcall_ro("get_ro", "foo", ()).await?;
+  assert_eq!(result, Some("bar".to_owned()));
+
+  let libraries: Vec<RedisValue> = client.function_list(Some("fred*"), false).await?;
+  assert_eq!(libraries.len(), 1);
+  let stats: RedisValue = client.function_stats().await?;
+  assert!(!stats.is_null());
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
+
+pub async fn should_dump_and_restore_functions(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: String = client.function_load(true, FUNCTION_LIBRARY).await?;
+  let dump: Vec<u8> = client.function_dump().await?;
+  let _ = client.function_delete("fredtest").await?;
+  let libraries: Vec<RedisValue> = client.function_list(Some("fred*"), false).await?;
+  assert!(libraries.is_empty());
+
+  let _ = client.function_restore(dump.as_slice(), FnPolicy::Replace).await?;
+  let result: (String, i64) = client.fcall("echo_keys", "foo", 1).await?;
+  assert_eq!(result, ("foo".to_owned(), 1));
+
+  let _ = client.function_flush(false).await?;
+  Ok(())
+}
+
+async fn retry_busy_commands(client: &RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.set("foo", 1, None, None, false).await?;
+  let _: i64 = client.del("bar").await?;
+  let _ = client.config_set("lua-time-limit", 100).await?;
+  let plain = RedisClient::new(config.clone());
+  let _ = plain.connect(None);
+  let _ = plain.wait_for_connect().await?;
+  config.busy_retry = Some(BusyRetryConfig {
+    delay: Duration::from_millis(50),
+    max_attempts: 100,
+  });
+  let retrying = RedisClient::new(config);
+  let _ = retrying.connect(None);
+  let _ = retrying.wait_for_connect().await?;
+
+  let _client = client.clone();
+  let script_jh = tokio::spawn(async move {
+    _client
+      .eval_with_args::<RedisValue, _>(BUSY_SCRIPT, ScriptArgs::new())
+      .await
+  });
+  sleep(Duration::from_millis(300)).await;
+
+  let error = plain.get::<RedisValue, _>("foo").await.unwrap_err();
+  assert!(error.is_busy());
+  let _retrying = retrying.clone();
+  let retrying_jh = tokio::spawn(async move { _retrying.get::<i64, _>("foo").await });
+  let mut push_jhs = Vec::new();
+  for idx in 0 .. 5 {
+    let _retrying = retrying.clone();
+    push_jhs.push(tokio::spawn(async move { _retrying.rpush::<i64, _, _>("bar", idx).await }));
+    sleep(Duration::from_millis(10)).await;
+  }
+  sleep(Duration::from_millis(200)).await;
+
+  let _ = client.script_kill().await?;
+  assert!(script_jh.await.unwrap().is_err());
+  assert_eq!(retrying_jh.await.unwrap()?, 1);
+  for jh in push_jhs.into_iter() {
+    let _ = jh.await.unwrap()?;
+  }
+  // the retried commands run in the order they were sent
+  let values: Vec<i64> = client.lrange("bar", 0, -1).await?;
+  assert_eq!(values, vec![0, 1, 2, 3, 4]);
+
+  let _ = retrying.quit().await;
+  let _ = plain.quit().await;
+  Ok(())
+}
+
+pub async fn should_retry_busy_commands_after_script_kill(
+  client: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let lua_time_limit: Vec<String> = client.config_get("lua-time-limit").await?;
+  let result = retry_busy_commands(&client, config).await;
+  if let Some(value) = lua_time_limit.get(1) {
+    let _ = client.config_set("lua-time-limit", value.as_str()).await?;
+  }
+
+  result
+}
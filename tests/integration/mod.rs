@@ -2,6 +2,7 @@
 pub mod utils;
 
 mod acl;
+mod bitmaps;
 mod client;
 mod cluster;
 mod geo;
@@ -20,6 +21,7 @@ mod server;
 mod sets;
 mod slowlog;
 mod sorted_sets;
+mod streams;
 
 pub mod centralized;
 pub mod clustered;
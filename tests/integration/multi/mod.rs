@@ -1,6 +1,8 @@
 use fred::client::RedisClient;
-use fred::error::RedisError;
-use fred::types::{RedisConfig, RedisValue};
+use fred::error::{RedisError, RedisErrorKind};
+use fred::types::{CustomCommand, RedisConfig, RedisKey, RedisValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 pub async fn should_run_get_set_trx(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
   let trx = client.multi(true).await?;
@@ -23,6 +25,20 @@ pub async fn should_run_error_get_set_trx(client: RedisClient, _config: RedisCon
   Ok(())
 }
 
+pub async fn should_run_trx_with_per_command_errors(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let trx = client.multi(true).await?;
+  let _: () = trx.incr("foo").await?;
+  let _: () = trx.get("foo").await?;
+  let results = trx.exec_all().await?;
+
+  assert_eq!(results.len(), 2);
+  assert!(results[0].is_err());
+  assert_eq!(results[1].as_ref().unwrap(), &RedisValue::from("bar"));
+  Ok(())
+}
+
 pub async fn should_fail_with_hashslot_error(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
   let trx = client.multi(true).await?;
   let _: () = client.set("foo", "bar", None, None, false).await?;
@@ -51,3 +67,180 @@ pub async fn should_use_cluster_slot_with_publish(client: RedisClient, _: RedisC
   let _: () = trx.exec().await?;
   Ok(())
 }
+
+pub async fn should_abort_trx_after_queue_error(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let trx = client.multi(true).await?;
+  let _: () = trx.set("foo", "bar", None, None, false).await?;
+
+  let cmd = CustomCommand {
+    cmd: "SET",
+    hash_slot: Some(RedisKey::from("foo").cluster_hash()),
+    is_blocking: false,
+  };
+  let error = trx.custom::<RedisValue, _>(cmd, vec!["foo"]).await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::Unknown);
+  let error = trx.get::<RedisValue, _>("foo").await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::Canceled);
+  let error = trx.exec::<RedisValue>().await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidCommand);
+
+  assert!(!client.in_transaction());
+  let foo: Option<String> = client.get("foo").await?;
+  assert!(foo.is_none());
+  Ok(())
+}
+
+pub async fn should_retry_trx_with_watch(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let other = RedisClient::new(config);
+  let _ = other.connect(None);
+  let _ = other.wait_for_connect().await?;
+  let _: () = client.set("foo", 1, None, None, false).await?;
+
+  let attempts = Arc::new(AtomicUsize::new(0));
+  let _attempts = attempts.clone();
+  let _: () = client
+    .transact_with_watch("foo", 3, move |client| {
+      let (attempts, other) = (_attempts.clone(), other.clone());
+
+      async move {
+        let current: i64 = client.get("foo").await?;
+        if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+          // change the watched key so the first EXEC is aborted
+          let _: () = other.incr("foo").await?;
+        }
+
+        let trx = client.multi(true).await?;
+        let _: () = trx.set("foo", current + 10, None, None, false).await?;
+        Ok(trx)
+      }
+    })
+    .await?;
+
+  assert_eq!(attempts.load(Ordering::SeqCst), 2);
+  let foo: i64 = client.get("foo").await?;
+  assert_eq!(foo, 12);
+  Ok(())
+}
+
+pub async fn should_stop_retrying_trx_with_watch(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let other = RedisClient::new(config);
+  let _ = other.connect(None);
+  let _ = other.wait_for_connect().await?;
+  let _: () = client.set("foo", 1, None, None, false).await?;
+
+  let attempts = Arc::new(AtomicUsize::new(0));
+  let _attempts = attempts.clone();
+  let result: Result<(), RedisError> = client
+    .transact_with_watch("foo", 2, move |client| {
+      let (attempts, other) = (_attempts.clone(), other.clone());
+
+      async move {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        // change the watched key so every EXEC is aborted
+        let _: () = other.incr("foo").await?;
+
+        let trx = client.multi(true).await?;
+        let _: () = trx.set("foo", 0, None, None, false).await?;
+        Ok(trx)
+      }
+    })
+    .await;
+
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Canceled);
+  assert_eq!(attempts.load(Ordering::SeqCst), 2);
+  let foo: i64 = client.get("foo").await?;
+  assert_eq!(foo, 3);
+  Ok(())
+}
+
+pub async fn should_run_pipeline(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let pipeline = client.pipeline()?;
+  let res: RedisValue = pipeline.incr("foo").await?;
+  assert!(res.is_queued());
+  let _: () = pipeline.incr_by("foo", 2).await?;
+  let _: () = pipeline.set("bar", "baz", None, None, false).await?;
+  let _: () = pipeline.get("bar").await?;
+  assert_eq!(pipeline.len(), 4);
+
+  let results: Vec<RedisValue> = pipeline.all().await?;
+  assert_eq!(results, vec![1.into(), 3.into(), "OK".into(), "baz".into()]);
+  assert!(!client.in_pipeline());
+  Ok(())
+}
+
+pub async fn should_not_buffer_client_commands_in_pipeline(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  let pipeline = client.pipeline()?;
+  let other = client.pipeline()?;
+  assert!(pipeline.in_pipeline());
+  assert!(!client.in_pipeline());
+
+  let _: () = pipeline.incr("foo").await?;
+  let _: () = other.incr("bar").await?;
+  // commands sent on the client, including from other tasks, are not captured by either pipeline
+  let value: i64 = client.incr("foo").await?;
+  assert_eq!(value, 1);
+  let task_client = client.clone();
+  let value: i64 = tokio::spawn(async move { task_client.incr("bar").await }).await.unwrap()?;
+  assert_eq!(value, 1);
+  assert_eq!((pipeline.len(), other.len()), (1, 1));
+
+  let values: Vec<i64> = pipeline.all().await?;
+  assert_eq!(values, vec![2]);
+  let values: Vec<i64> = other.all().await?;
+  assert_eq!(values, vec![2]);
+  Ok(())
+}
+
+pub async fn should_run_pipeline_with_errors(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let pipeline = client.pipeline()?;
+  let _: () = pipeline.incr("foo").await?;
+  let _: () = pipeline.get("foo").await?;
+  let results = pipeline.try_all().await?;
+
+  assert_eq!(results.len(), 2);
+  assert!(results[0].is_err());
+  assert_eq!(results[1].as_ref().unwrap(), &RedisValue::from("bar"));
+  Ok(())
+}
+
+pub async fn should_fail_trx_with_cross_slot_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let trx = client.multi(true).await?;
+  let error = trx.ping().await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidCommand);
+
+  let _: () = trx.set("{foo}1", "bar", None, None, false).await?;
+  let error = trx.del::<RedisValue, _>(vec!["{foo}2", "bar"]).await.unwrap_err();
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  let _: () = trx.del(vec!["{foo}2", "{foo}3"]).await?;
+  assert_eq!(trx.hash_slot(), Some(RedisKey::from("foo").cluster_hash()));
+
+  let (_, deleted): (String, i64) = trx.exec().await?;
+  assert_eq!(deleted, 0);
+  Ok(())
+}
+
+pub async fn should_convert_typed_trx_results(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let trx = client.multi(true).await?;
+  let _: () = trx.incr("{foo}1").await?;
+  let _: () = trx.set("{foo}2", "bar", None, None, false).await?;
+  let _: () = trx.get("{foo}3").await?;
+  let results: (i64, String, Option<f64>) = trx.exec().await?;
+  assert_eq!(results, (1, "OK".to_owned(), None));
+
+  let trx = client.multi(true).await?;
+  let _: () = trx.get("{foo}2").await?;
+  let (bar,): (String,) = trx.exec().await?;
+  assert_eq!(bar, "bar");
+
+  let pipeline = client.pipeline()?;
+  let _: () = pipeline.incr("{foo}1").await?;
+  let _: () = pipeline.get("{foo}2").await?;
+  let results: (i64, String) = pipeline.all().await?;
+  assert_eq!(results, (2, "bar".to_owned()));
+  Ok(())
+}
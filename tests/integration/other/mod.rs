@@ -1,9 +1,18 @@
+use fred::client::util::redis_keyslot;
 use fred::client::RedisClient;
 use fred::error::{RedisError, RedisErrorKind};
 use fred::prelude::Blocking;
-use fred::types::{ClientUnblockFlag, RedisConfig, ServerConfig};
-use std::collections::BTreeSet;
-use std::time::Duration;
+use fred::types::{
+  BackpressureConfig, BackpressurePolicy, CancellationToken, ClientKillFilter, ClientPauseKind, ClientState,
+  ClientUnblockFlag, CommandHook, CustomCommand, Options, ReconnectPolicy, RedisConfig, RedisValue, RespVersion,
+  ServerConfig, TcpConfig, TrackingConfig, TrackingMode, UnresponsiveConfig,
+};
+use futures::future::{join, join_all};
+use futures::StreamExt;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 pub async fn should_automatically_unblock(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
@@ -66,6 +75,108 @@ pub async fn should_error_when_blocked(_: RedisClient, mut config: RedisConfig)
   Ok(())
 }
 
+pub async fn should_kill_blocked_connection(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let kill_client = client.clone();
+  let _ = tokio::spawn(async move {
+    sleep(Duration::from_secs(1)).await;
+    kill_client.kill_blocked_connection().await
+  });
+
+  let result = client.blpop::<(), _>("foo", 60.0).await;
+  assert!(result.is_err());
+  assert_ne!(*result.unwrap_err().kind(), RedisErrorKind::Timeout);
+  Ok(())
+}
+
+pub async fn should_cancel_with_options(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let options = Options {
+    deadline: Some(Instant::now() + Duration::from_millis(100)),
+    ..Default::default()
+  };
+  let result = client.with_options(options, client.blpop::<(), _>("baz", 1.0)).await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Canceled);
+
+  let token = CancellationToken::new();
+  token.cancel();
+  let options = Options {
+    cancel: Some(token),
+    ..Default::default()
+  };
+  let result = client.with_options(options, client.get::<String, _>("foo")).await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Canceled);
+
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "bar");
+  Ok(())
+}
+
+pub async fn should_time_out_with_options(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let options = Options {
+    timeout: Some(Duration::from_millis(100)),
+    ..Default::default()
+  };
+  let result = client.with_options(options, client.blpop::<(), _>("baz", 1.0)).await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Timeout);
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "bar");
+
+  config.default_command_timeout_ms = 100;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  let result = client.blpop::<(), _>("baz", 1.0).await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Timeout);
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "bar");
+
+  let _ = client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_unblock_dropped_command(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.unblock_on_drop = true;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let result = tokio::time::timeout(Duration::from_millis(500), client.blpop::<(), _>("foo", 60.0)).await;
+  assert!(result.is_err());
+
+  let result = tokio::time::timeout(Duration::from_secs(5), client.ping()).await;
+  assert!(result.is_ok());
+  let _ = client.quit().await;
+  Ok(())
+}
+
+pub async fn should_not_unblock_for_unsent_command(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.unblock_on_drop = true;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let _client = client.clone();
+  let blocked = tokio::spawn(async move { _client.blpop::<RedisValue, _>("foo", 1.0).await });
+  sleep(Duration::from_millis(100)).await;
+  // the second command waits behind the first blocking command, so dropping it should not unblock the connection
+  let result = tokio::time::timeout(Duration::from_millis(100), client.blpop::<(), _>("bar", 60.0)).await;
+  assert!(result.is_err());
+
+  let value = blocked.await.unwrap()?;
+  assert!(value.is_null());
+  let _ = client.quit().await;
+  Ok(())
+}
+
 pub async fn should_split_clustered_connection(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
   let clients = client.split_cluster().await?;
 
@@ -115,6 +226,19 @@ pub async fn should_track_size_stats(client: RedisClient, _config: RedisConfig)
   Ok(())
 }
 
+#[cfg(feature = "metrics")]
+pub async fn should_track_connection_stats(client: RedisClient, _config: RedisConfig) -> Result<(), RedisError> {
+  let before: u64 = client.read_connection_stats().values().map(|s| s.frames_encoded).sum();
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let stats = client.read_connection_stats();
+
+  assert!(!stats.is_empty());
+  assert!(stats.values().any(|s| s.connected && s.bytes_read > 0 && s.bytes_written > 0));
+  assert!(stats.values().map(|s| s.frames_encoded).sum::<u64>() > before);
+
+  Ok(())
+}
+
 pub async fn should_run_flushall_cluster(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let count: i64 = 200;
 
@@ -130,3 +254,460 @@ pub async fn should_run_flushall_cluster(client: RedisClient, _: RedisConfig) ->
 
   Ok(())
 }
+
+#[cfg(feature = "test-utils")]
+pub async fn should_clean_up_test_namespace(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::testing::TestNamespace;
+
+  let ns = TestNamespace::new(&client);
+  let (foo, bar) = (ns.key("foo"), ns.key("bar"));
+  let _ = client.mset(vec![(foo.clone(), 1.into()), (bar.clone(), 2.into())]).await?;
+  let _: () = client.set("baz", 3, None, None, false).await?;
+
+  let deleted = ns.cleanup().await?;
+  assert_eq!(deleted, 2);
+  assert!(ns.keys().is_empty());
+  let count: i64 = client.exists(vec![foo, bar]).await?;
+  assert_eq!(count, 0);
+  let baz: i64 = client.get("baz").await?;
+  assert_eq!(baz, 3);
+
+  Ok(())
+}
+
+pub async fn should_read_server_version(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let version = match client.server_version() {
+    Some(version) => version,
+    None => return_err!("Expected server version."),
+  };
+
+  assert!(version.major >= 5);
+  Ok(())
+}
+
+pub async fn should_read_connected_addresses(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let addresses = client.connected_addresses();
+
+  for (host, port) in config.server.hosts() {
+    if let Some(addr) = addresses.get(&format!("{}:{}", host, port)) {
+      assert!(addr.ip().is_loopback());
+    }
+  }
+  assert!(!addresses.is_empty());
+  Ok(())
+}
+
+#[derive(Default)]
+struct CountingHook {
+  sent: AtomicUsize,
+  received: AtomicUsize,
+}
+
+impl CommandHook for CountingHook {
+  fn before_send(&self, command: &str, args: &mut Vec<RedisValue>) {
+    if command == "SET" {
+      args[1] = "rewritten".into();
+    }
+    if command == "GET" && args[0].as_str().map(|key| key == "alias").unwrap_or(false) {
+      args[0] = "foo".into();
+    }
+    self.sent.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn after_response(
+    &self,
+    _: &str,
+    _: &[RedisValue],
+    server: &str,
+    _: Duration,
+    _: Result<&RedisValue, &RedisError>,
+  ) {
+    assert!(!server.is_empty());
+    self.received.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+pub async fn should_run_command_hooks(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let hook = Arc::new(CountingHook::default());
+  client.add_command_hook(hook.clone());
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = client.get("foo").await?;
+  let alias: String = client.get("alias").await?;
+  client.clear_command_hooks();
+
+  assert_eq!(value, "rewritten");
+  assert_eq!(alias, "rewritten");
+  assert_eq!(hook.sent.load(Ordering::SeqCst), 3);
+  assert_eq!(hook.received.load(Ordering::SeqCst), 3);
+  Ok(())
+}
+
+pub async fn should_close_after_max_attempts(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.server = ServerConfig::new_centralized("127.0.0.1", 1);
+  config.fail_fast = false;
+  let client = RedisClient::new(config);
+  let calls = Arc::new(AtomicUsize::new(0));
+  let _calls = calls.clone();
+  client.on_unrecoverable(move |error| {
+    assert_eq!(*error.kind(), RedisErrorKind::Unrecoverable);
+    _calls.fetch_add(1, Ordering::SeqCst);
+  });
+
+  let result = client.connect(Some(ReconnectPolicy::new_constant(2, 10))).await?;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Unrecoverable);
+  assert_eq!(client.state(), ClientState::Closed);
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+  let result = client.ping().await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::Unrecoverable);
+  Ok(())
+}
+
+pub async fn should_stream_connection_events(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let new_client = RedisClient::new(config);
+  let mut events = new_client.on_connect();
+
+  let _ = new_client.connect(Some(ReconnectPolicy::default()));
+  let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on connection event."))?;
+  assert!(matches!(event, Some(Ok(()))));
+  assert_eq!(new_client.state(), ClientState::Connected);
+
+  for (_, id) in new_client.connection_ids().await?.into_iter() {
+    let _: () = client.client_kill(vec![ClientKillFilter::ID(id.to_string())]).await?;
+  }
+  let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on reconnection event."))?;
+  assert!(matches!(event, Some(Ok(()))));
+
+  let _ = new_client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_block_with_backpressure(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.backpressure = Some(BackpressureConfig {
+    max_in_flight_commands: 1,
+    max_buffered_commands: 10,
+    policy: BackpressurePolicy::Block,
+  });
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  check_null!(client, "foo");
+
+  let tasks: Vec<_> = (0 .. 100)
+    .map(|_| {
+      let client = client.clone();
+      tokio::spawn(async move { client.incr::<i64, _>("foo").await })
+    })
+    .collect();
+  for task in tasks.into_iter() {
+    let _ = task.await??;
+  }
+
+  let count: i64 = client.get("foo").await?;
+  assert_eq!(count, 100);
+  let _ = client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_connect_with_tcp_options(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.tcp = TcpConfig {
+    nodelay: Some(true),
+    keepalive: Some(Duration::from_secs(30)),
+    connect_timeout: Some(Duration::from_secs(1)),
+  };
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let _: () = client.ping().await?;
+  let _ = client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_drain_commands_before_quit(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let commands = join_all((0 .. 100).map(|_| client.incr::<i64, _>("foo")));
+  let (results, quit) = join(commands, client.quit()).await;
+  let _ = quit?;
+  for result in results.into_iter() {
+    let _ = result?;
+  }
+
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  let count: i64 = client.get("foo").await?;
+  assert_eq!(count, 100);
+
+  let _ = client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_reconnect_unresponsive_connection(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  config.unresponsive = Some(UnresponsiveConfig {
+    max_timeout: Duration::from_millis(200),
+    interval: Duration::from_millis(50),
+  });
+  let unresponsive = RedisClient::new(config);
+  let _ = unresponsive.connect(Some(ReconnectPolicy::new_constant(0, 50)));
+  let _ = unresponsive.wait_for_connect().await?;
+  let mut reconnects = unresponsive.on_reconnect();
+
+  let _ = client.client_pause(1000, Some(ClientPauseKind::All)).await?;
+  let value: Option<String> = unresponsive.get("foo").await?;
+  assert!(value.is_none());
+  assert!(reconnects.next().await.is_some());
+
+  let _ = unresponsive.quit().await?;
+  Ok(())
+}
+
+pub async fn should_select_database_on_connect(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  config.database = Some(1);
+  let db_client = RedisClient::new(config);
+  let _ = db_client.connect(None);
+  let _ = db_client.wait_for_connect().await?;
+
+  let _: () = db_client.set("foo", "bar", None, None, false).await?;
+  let value: Option<String> = client.get("foo").await?;
+  assert!(value.is_none());
+  let value: String = db_client.get("foo").await?;
+  assert_eq!(value, "bar");
+
+  let _: i64 = db_client.del("foo").await?;
+  let _ = db_client.quit().await?;
+  Ok(())
+}
+
+pub async fn should_use_resp3(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+
+  let _: () = client.hset("foo{1}", ("a", 1.into())).await?;
+  let value: HashMap<String, i64> = client.hgetall("foo{1}").await?;
+  assert_eq!(value.get("a"), Some(&1));
+  let value: Option<String> = client.get("bar{1}").await?;
+  assert!(value.is_none());
+
+  let cmd = CustomCommand {
+    cmd: "HGETALL",
+    hash_slot: Some(redis_keyslot("foo{1}")),
+    is_blocking: false,
+  };
+  let value: RedisValue = client.custom(cmd, vec!["foo{1}"]).await?;
+  assert!(value.is_map());
+  assert_eq!(value.into_resp2(), RedisValue::Array(vec!["a".into(), "1".into()]));
+
+  let _ = client.quit().await;
+  Ok(())
+}
+
+pub async fn should_invalidate_cached_values(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  config.tracking = Some(TrackingConfig::default());
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  check_null!(client, "foo");
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "bar");
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "bar");
+  assert_eq!(tracking_client.cache_stats().hits, 1);
+
+  let _: () = client.set("foo", "baz", None, None, false).await?;
+  sleep(Duration::from_millis(100)).await;
+  assert_eq!(tracking_client.cache_stats().invalidations, 1);
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "baz");
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_invalidate_cached_values_with_resp2(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP2;
+  config.tracking = Some(TrackingConfig::default());
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  check_null!(client, "foo");
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "bar");
+
+  let _: () = client.set("foo", "baz", None, None, false).await?;
+  sleep(Duration::from_millis(100)).await;
+  assert_eq!(tracking_client.cache_stats().invalidations, 1);
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "baz");
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_stream_bcast_invalidations(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  config.tracking = Some(TrackingConfig {
+    mode: TrackingMode::Bcast {
+      prefixes: vec!["foo:".into()],
+    },
+    ..Default::default()
+  });
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  let mut invalidations = tracking_client.on_invalidation();
+
+  let _: () = client.set("bar:1", "a", None, None, false).await?;
+  let _: () = client.set("foo:1", "b", None, None, false).await?;
+  let key = invalidations.next().await.unwrap();
+  assert_eq!(key.as_str(), Some("foo:1"));
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_not_cache_keys_outside_bcast_prefixes(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  config.tracking = Some(TrackingConfig {
+    mode: TrackingMode::Bcast {
+      prefixes: vec!["foo:".into()],
+    },
+    ..Default::default()
+  });
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  check_null!(client, "bar:1");
+
+  let _: () = client.set("bar:1", "a", None, None, false).await?;
+  let value: String = tracking_client.cached_get("bar:1").await?;
+  assert_eq!(value, "a");
+  assert_eq!(tracking_client.cache_stats().size, 0);
+
+  // no invalidation message is sent for the key, so a stale value would be returned if it were cached
+  let _: () = client.set("bar:1", "b", None, None, false).await?;
+  let value: String = tracking_client.cached_get("bar:1").await?;
+  assert_eq!(value, "b");
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_opt_in_to_caching(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  config.tracking = Some(TrackingConfig {
+    mode: TrackingMode::OptIn,
+    ..Default::default()
+  });
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  let mut invalidations = tracking_client.on_invalidation();
+  check_null!(client, "foo");
+  check_null!(client, "bar");
+
+  let options = Options {
+    caching: Some(true),
+    ..Default::default()
+  };
+  let _: Option<String> = tracking_client.get("foo").await?;
+  let _: Option<String> = tracking_client.with_options(options, tracking_client.get("bar")).await?;
+  let _: () = client.set("foo", "a", None, None, false).await?;
+  let _: () = client.set("bar", "b", None, None, false).await?;
+
+  // only the key read with `CLIENT CACHING yes` is tracked
+  let key = invalidations.next().await.unwrap();
+  assert_eq!(key.as_str(), Some("bar"));
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_invalidate_cached_values_with_optin(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  config.version = RespVersion::RESP3;
+  config.tracking = Some(TrackingConfig {
+    mode: TrackingMode::OptIn,
+    ..Default::default()
+  });
+  let tracking_client = RedisClient::new(config);
+  let _ = tracking_client.connect(None);
+  let _ = tracking_client.wait_for_connect().await?;
+  check_null!(client, "foo");
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "bar");
+
+  let _: () = client.set("foo", "baz", None, None, false).await?;
+  sleep(Duration::from_millis(100)).await;
+  assert_eq!(tracking_client.cache_stats().invalidations, 1);
+  let value: String = tracking_client.cached_get("foo").await?;
+  assert_eq!(value, "baz");
+
+  let _ = tracking_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_error_on_disabled_command(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.renamed_commands.insert("config".into(), "".into());
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let result = client.config_get::<RedisValue, _>("maxmemory").await;
+  assert_eq!(result.unwrap_err().kind(), &RedisErrorKind::InvalidCommand);
+  let _: () = client.ping().await?;
+
+  let _ = client.quit().await;
+  Ok(())
+}
+
+pub async fn should_write_with_ack(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let (_, replicas): ((), i64) = client
+    .write_with_ack("foo", 0, 100, client.set("foo", "bar", None, None, false))
+    .await?;
+  assert!(replicas >= 0);
+
+  let result: Result<((), i64), RedisError> = client
+    .write_with_ack("foo", 100, 100, client.set("foo", "baz", None, None, false))
+    .await;
+  assert_eq!(*result.unwrap_err().kind(), RedisErrorKind::NotEnoughReplicas);
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "baz");
+
+  Ok(())
+}
@@ -1,6 +1,6 @@
 use fred::client::RedisClient;
 use fred::error::RedisError;
-use fred::pool::{DynamicRedisPool, StaticRedisPool};
+use fred::pool::{DynamicRedisPool, RedisPool, StaticRedisPool};
 use fred::types::RedisConfig;
 
 #[cfg(feature = "fd-tests")]
@@ -47,6 +47,22 @@ pub async fn should_connect_and_ping_static_pool_two_conn(
   create_and_ping_pool(&config, 2).await
 }
 
+pub async fn should_connect_all_and_round_robin_pool(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let pool = RedisPool::new(config, 3)?;
+  let _ = pool.connect_all(None).await?;
+
+  for client in pool.clients().iter() {
+    assert!(client.is_connected());
+  }
+  let first = pool.next().id().to_owned();
+  let second = pool.next().id().to_owned();
+  assert_ne!(first, second);
+
+  let _ = pool.ping().await?;
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
 pub async fn should_connect_and_ping_dynamic_pool(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
   create_and_ping_dynamic_pool(&config, 5).await
 }
@@ -1,3 +1,4 @@
+use fred::error::RedisErrorKind;
 use fred::prelude::*;
 use futures::StreamExt;
 use std::time::Duration;
@@ -100,3 +101,219 @@ pub async fn should_psubscribe_and_recv_messages(client: RedisClient, _: RedisCo
 
   Ok(())
 }
+
+pub async fn should_emit_gap_after_reconnecting(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let _ = subscriber_client.connect(Some(ReconnectPolicy::default()));
+  let _ = subscriber_client.wait_for_connect().await?;
+  let _ = subscriber_client.subscribe(CHANNEL1).await?;
+  let mut events = subscriber_client.on_pubsub_event();
+
+  let _: () = client.publish(CHANNEL1, FAKE_MESSAGE).await?;
+  let event = events.next().await.unwrap();
+  assert_eq!(
+    event,
+    PubsubEvent::Message {
+      channel: CHANNEL1.into(),
+      message: FAKE_MESSAGE.into()
+    }
+  );
+
+  for (_, id) in subscriber_client.connection_ids().await?.into_iter() {
+    let _: () = client.client_kill(vec![ClientKillFilter::ID(id.to_string())]).await?;
+  }
+
+  let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on gap."))?
+    .unwrap();
+  match event {
+    PubsubEvent::GapDetected { from, to } => assert!(to >= from),
+    _ => panic!("Expected gap event, found {:?}", event),
+  };
+  // the gap ends once the subscriptions are restored
+  let event = events.next().await.unwrap();
+  assert!(matches!(event, PubsubEvent::Restored { .. }), "Expected restored event, found {:?}", event);
+
+  let _ = subscriber_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_not_replay_expired_events_after_gap(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  let _: () = client.config_set("notify-keyspace-events", "Ex").await?;
+  let subscriber_client = client.clone_new();
+  let _ = subscriber_client.connect(Some(ReconnectPolicy::new_constant(0, 1000)));
+  let _ = subscriber_client.wait_for_connect().await?;
+  let _ = subscriber_client.subscribe("__keyevent@0__:expired").await?;
+  let mut events = subscriber_client.on_pubsub_event();
+
+  let _: () = client
+    .set("foo", "bar", Some(Expiration::PX(200)), None, false)
+    .await?;
+  for (_, id) in subscriber_client.connection_ids().await?.into_iter() {
+    let _: () = client.client_kill(vec![ClientKillFilter::ID(id.to_string())]).await?;
+  }
+  // the key expires while the subscriber waits to reconnect
+  sleep(Duration::from_millis(500)).await;
+  let exists: i64 = client.exists("foo").await?;
+  assert_eq!(exists, 0);
+
+  let mut found_gap = false;
+  while let Ok(Some(event)) = tokio::time::timeout(Duration::from_secs(2), events.next()).await {
+    match event {
+      PubsubEvent::GapDetected { .. } => found_gap = true,
+      PubsubEvent::Keyspace(ref keyspace) if keyspace.operation == "expired" => {
+        panic!("Unexpected expired event after gap: {:?}", event)
+      },
+      PubsubEvent::Message { ref channel, .. } if channel.ends_with(":expired") => {
+        panic!("Unexpected expired event after gap: {:?}", event)
+      },
+      _ => {},
+    }
+  }
+  assert!(found_gap);
+
+  let _: () = client.config_set("notify-keyspace-events", "").await?;
+  let _ = subscriber_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_track_subscriptions(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let policy = client.client_reconnect_policy();
+  let _ = subscriber_client.connect(policy);
+  let _ = subscriber_client.wait_for_connect().await?;
+
+  let _ = subscriber_client.subscribe(CHANNEL1).await?;
+  let _ = subscriber_client.subscribe(CHANNEL2).await?;
+  let _ = subscriber_client.psubscribe(vec![CHANNEL3]).await?;
+  let channels: Vec<String> = subscriber_client.tracked_channels().into_iter().collect();
+  let patterns: Vec<String> = subscriber_client.tracked_patterns().into_iter().collect();
+  assert_eq!(channels, vec![CHANNEL2.to_owned(), CHANNEL1.to_owned()]);
+  assert_eq!(patterns, vec![CHANNEL3.to_owned()]);
+
+  let _ = subscriber_client.unsubscribe(CHANNEL1).await?;
+  let _ = subscriber_client.punsubscribe(Vec::<String>::new()).await?;
+  let channels: Vec<String> = subscriber_client.tracked_channels().into_iter().collect();
+  assert_eq!(channels, vec![CHANNEL2.to_owned()]);
+  assert!(subscriber_client.tracked_patterns().is_empty());
+
+  let _ = subscriber_client.quit().await?;
+  assert!(subscriber_client.tracked_channels().is_empty());
+  Ok(())
+}
+
+pub async fn should_ssubscribe_and_recv_messages(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let policy = client.client_reconnect_policy();
+  let _ = subscriber_client.connect(policy);
+  let _ = subscriber_client.wait_for_connect().await?;
+  let _ = subscriber_client.ssubscribe(CHANNEL1).await?;
+  assert!(subscriber_client.tracked_shard_channels().contains(CHANNEL1));
+
+  let subscriber_jh = tokio::spawn(async move {
+    let mut message_stream = subscriber_client.on_message();
+
+    let mut count = 0;
+    while count < NUM_MESSAGES {
+      if let Some((channel, message)) = message_stream.next().await {
+        let message: String = message.convert().unwrap();
+
+        assert_eq!(CHANNEL1, channel);
+        if ASSERT_COUNT {
+          assert_eq!(format!("{}-{}", FAKE_MESSAGE, count), message);
+        }
+        count += 1;
+      }
+    }
+
+    let _ = subscriber_client.sunsubscribe(CHANNEL1).await?;
+    assert!(subscriber_client.tracked_shard_channels().is_empty());
+    Ok::<_, RedisError>(())
+  });
+
+  for idx in 0..NUM_MESSAGES + EXTRA_MESSAGES {
+    let _: () = client.spublish(CHANNEL1, format!("{}-{}", FAKE_MESSAGE, idx)).await?;
+    sleep(Duration::from_millis(50)).await;
+  }
+  let _ = subscriber_jh.await?;
+
+  Ok(())
+}
+
+pub async fn should_resubscribe_after_reconnecting(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let _ = subscriber_client.connect(Some(ReconnectPolicy::default()));
+  let _ = subscriber_client.wait_for_connect().await?;
+  let _ = subscriber_client.subscribe(CHANNEL1).await?;
+  let _ = subscriber_client.psubscribe(vec![CHANNEL2]).await?;
+  let mut events = subscriber_client.on_pubsub_event();
+
+  for (_, id) in subscriber_client.connection_ids().await?.into_iter() {
+    let _: () = client.client_kill(vec![ClientKillFilter::ID(id.to_string())]).await?;
+  }
+
+  let restored = tokio::time::timeout(Duration::from_secs(5), async {
+    while let Some(event) = events.next().await {
+      if let PubsubEvent::Restored { channels, patterns, .. } = event {
+        return Some((channels, patterns));
+      }
+    }
+    None
+  })
+  .await
+  .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on restored subscriptions."))?
+  .unwrap();
+  assert!(restored.0.contains(CHANNEL1));
+  assert!(restored.1.contains(CHANNEL2));
+
+  let _: () = client.publish(CHANNEL1, FAKE_MESSAGE).await?;
+  let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on message."))?
+    .unwrap();
+  assert_eq!(
+    event,
+    PubsubEvent::Message {
+      channel: CHANNEL1.into(),
+      message: FAKE_MESSAGE.into()
+    }
+  );
+
+  let _ = subscriber_client.quit().await;
+  Ok(())
+}
+
+pub async fn should_split_message_streams(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let policy = client.client_reconnect_policy();
+  let _ = subscriber_client.connect(policy);
+  let _ = subscriber_client.wait_for_connect().await?;
+
+  let mut channel1_stream = subscriber_client.message_stream(CHANNEL1);
+  let mut pattern_stream = subscriber_client.message_stream("ba*");
+  let _ = subscriber_client.subscribe(CHANNEL1).await?;
+  let _ = subscriber_client.psubscribe(vec!["ba*"]).await?;
+
+  let _: () = client.publish(CHANNEL2, FAKE_MESSAGE).await?;
+  let _: () = client.publish(CHANNEL1, FAKE_MESSAGE).await?;
+  let _: () = client.publish(CHANNEL3, FAKE_MESSAGE).await?;
+
+  let (channel, message) = channel1_stream.next().await.unwrap();
+  assert_eq!(channel, CHANNEL1);
+  assert_eq!(message.as_str().unwrap(), FAKE_MESSAGE);
+
+  let mut channels = Vec::with_capacity(2);
+  for _ in 0..2 {
+    let (channel, _) = pattern_stream.next().await.unwrap();
+    channels.push(channel);
+  }
+  channels.sort();
+  assert_eq!(channels, vec![CHANNEL2.to_owned(), CHANNEL3.to_owned()]);
+
+  let _ = subscriber_client.quit().await;
+  Ok(())
+}
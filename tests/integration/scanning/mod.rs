@@ -34,6 +34,63 @@ pub async fn should_scan_keyspace(client: RedisClient, _: RedisConfig) -> Result
   Ok(())
 }
 
+pub async fn should_cancel_scan(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..SCAN_KEYS {
+    let _: () = client
+      .set(format!("foo-{}-{}", idx, "{1}"), idx, None, None, false)
+      .await?;
+  }
+
+  let mut pages = 0;
+  let mut scanner = client.scan("foo*{1}", Some(10), None);
+  while let Some(mut result) = scanner.try_next().await? {
+    pages += 1;
+    assert!(result.take_results().is_some());
+    result.cancel();
+  }
+
+  assert_eq!(pages, 1);
+  Ok(())
+}
+
+pub async fn should_scan_buffered(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..SCAN_KEYS {
+    let _: () = client
+      .set(format!("foo-{}-{}", idx, "{1}"), idx, None, None, false)
+      .await?;
+  }
+
+  let keys: Vec<RedisKey> = client.scan_buffered("foo*{1}", Some(10), None).try_collect().await?;
+  let mut keys: Vec<String> = keys.into_iter().map(|key| key.as_str().unwrap().to_owned()).collect();
+  keys.sort();
+  keys.dedup();
+
+  assert_eq!(keys.len() as i64, SCAN_KEYS);
+  Ok(())
+}
+
+pub async fn should_scan_cluster(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  for idx in 0..SCAN_KEYS {
+    let _: () = client.set(format!("foo-{}", idx), idx, None, None, false).await?;
+  }
+
+  let count = client
+    .scan_cluster("foo*", Some(10), None)
+    .try_fold(0, |mut count, mut result| async move {
+      if let Some(results) = result.take_results() {
+        count += results.len() as i64;
+      }
+
+      let _ = result.next()?;
+      Ok(count)
+    })
+    .await?;
+
+  // SCAN can return keys more than once, but keys are spread across all the primary nodes
+  assert!(count >= SCAN_KEYS);
+  Ok(())
+}
+
 pub async fn should_hscan_hash(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   for idx in 0..SCAN_KEYS {
     let value = (format!("bar-{}", idx), idx.into());
@@ -35,6 +35,24 @@ pub async fn should_run_custom_command(_client: RedisClient, _: RedisConfig) ->
   Ok(())
 }
 
+pub async fn should_route_custom_command(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::types::{CustomCommand, KeyPosition};
+
+  check_null!(client, "foo");
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  client.set_custom_routing("object", KeyPosition::Index(1));
+
+  let cmd = CustomCommand {
+    cmd: "OBJECT",
+    hash_slot: None,
+    is_blocking: false,
+  };
+  let encoding: String = client.custom(cmd, vec!["ENCODING", "foo"]).await?;
+  assert_eq!(encoding, "embstr");
+
+  Ok(())
+}
+
 pub async fn should_read_last_save(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let lastsave: Option<i64> = client.lastsave().await?;
   assert!(lastsave.is_some());
@@ -416,6 +416,17 @@ pub async fn should_zpopmin(client: RedisClient, _: RedisConfig) -> Result<(), R
   Ok(())
 }
 
+pub async fn should_zpopmin_into_tuples(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = create_count_data(&client, "foo").await?;
+
+  let result: Vec<(String, f64)> = client.zpopmin("foo", Some(2)).await?;
+  assert_eq!(result, vec![("0".to_owned(), 0.0), ("1".to_owned(), 1.0)]);
+  let (member, score): (String, f64) = client.zpopmin("foo", None).await?;
+  assert_eq!((member, score), ("2".to_owned(), 2.0));
+
+  Ok(())
+}
+
 pub async fn should_zrandmember(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let _ = create_count_data(&client, "foo").await?;
 
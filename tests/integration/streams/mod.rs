@@ -0,0 +1,345 @@
+use fred::prelude::*;
+use std::time::Duration;
+use tokio::time::sleep;
+
+async fn create_fake_data(client: &RedisClient, key: &str, count: u64) -> Result<Vec<String>, RedisError> {
+  let mut ids = Vec::with_capacity(count as usize);
+  for idx in 0 .. count {
+    let id: String = client
+      .xadd(key, false, None, "*", vec![("count", (idx as i64).into())])
+      .await?;
+    ids.push(id);
+  }
+
+  Ok(ids)
+}
+
+pub async fn should_xadd_auto_id(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let id: String = client.xadd("foo", false, None, "*", ("a", "b".into())).await?;
+  assert!(!id.is_empty());
+
+  let len: u64 = client.xlen("foo").await?;
+  assert_eq!(len, 1);
+  Ok(())
+}
+
+pub async fn should_xadd_manual_id(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let id: String = client.xadd("foo", false, None, (1, 0), ("a", "b".into())).await?;
+  assert_eq!(id, "1-0");
+
+  let result: Result<String, RedisError> = client.xadd("foo", false, None, (1, 0), ("a", "b".into())).await;
+  assert!(result.is_err());
+  Ok(())
+}
+
+pub async fn should_xadd_nomkstream(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let id: Option<String> = client.xadd("foo", true, None, "*", ("a", "b".into())).await?;
+  assert!(id.is_none());
+
+  let len: u64 = client.xlen("foo").await?;
+  assert_eq!(len, 0);
+  Ok(())
+}
+
+pub async fn should_xadd_with_cap(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = create_fake_data(&client, "foo", 5).await?;
+  let _: String = client
+    .xadd("foo", false, Some((XCapKind::MaxLen, 2).into()), "*", ("a", "b".into()))
+    .await?;
+
+  let len: u64 = client.xlen("foo").await?;
+  assert_eq!(len, 2);
+
+  let cap = (XCapKind::MaxLen, XCapTrim::AlmostExact, 1, Some(10)).into();
+  let _: String = client.xadd("foo", false, Some(cap), "*", ("a", "b".into())).await?;
+  Ok(())
+}
+
+pub async fn should_xrange_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_fake_data(&client, "foo", 3).await?;
+
+  let entries = client.xrange("foo", "-", "+", None).await?;
+  assert_eq!(entries.len(), 3);
+  for (idx, entry) in entries.iter().enumerate() {
+    assert_eq!(entry.id, ids[idx]);
+    assert_eq!(entry.fields.get("count"), Some(&idx.to_string().into()));
+  }
+
+  let entries = client.xrange("foo", "-", "+", Some(1)).await?;
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].id, ids[0]);
+  Ok(())
+}
+
+pub async fn should_xrevrange_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_fake_data(&client, "foo", 3).await?;
+
+  let entries = client.xrevrange("foo", "+", "-", None).await?;
+  let entry_ids: Vec<String> = entries.into_iter().map(|e| e.id).collect();
+  let mut expected = ids.clone();
+  expected.reverse();
+  assert_eq!(entry_ids, expected);
+  Ok(())
+}
+
+pub async fn should_xread_multiple_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let foo_ids = create_fake_data(&client, "{foo}1", 2).await?;
+  let bar_ids = create_fake_data(&client, "{foo}2", 1).await?;
+
+  let result = client
+    .xread(None, None, vec!["{foo}1", "{foo}2"], vec!["0", "0"])
+    .await?;
+  assert_eq!(result.len(), 2);
+  assert_eq!(result.get("{foo}1").unwrap().len(), foo_ids.len());
+  assert_eq!(result.get("{foo}2").unwrap()[0].id, bar_ids[0]);
+
+  let result = client.xread(Some(1), None, "{foo}1", foo_ids[0].as_str()).await?;
+  assert_eq!(result.get("{foo}1").unwrap()[0].id, foo_ids[1]);
+  Ok(())
+}
+
+pub async fn should_xread_block_timeout(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = create_fake_data(&client, "foo", 1).await?;
+
+  let result = client.xread(None, Some(100), "foo", "$").await?;
+  assert!(result.is_empty());
+  Ok(())
+}
+
+pub async fn should_xgroup_create_and_destroy(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let result: Result<(), RedisError> = client.xgroup_create("foo", "group1", "$", false).await;
+  assert!(result.is_err());
+
+  let _ = client.xgroup_create("foo", "group1", "$", true).await?;
+  let _ = client.xgroup_setid("foo", "group1", "0").await?;
+  let destroyed: u64 = client.xgroup_destroy("foo", "group1").await?;
+  assert_eq!(destroyed, 1);
+  Ok(())
+}
+
+pub async fn should_xgroup_create_and_delete_consumers(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  let _ = client.xgroup_create("foo", "group1", "$", true).await?;
+
+  let created: u64 = client.xgroup_createconsumer("foo", "group1", "consumer1").await?;
+  assert_eq!(created, 1);
+  let pending: u64 = client.xgroup_delconsumer("foo", "group1", "consumer1").await?;
+  assert_eq!(pending, 0);
+  Ok(())
+}
+
+pub async fn should_xreadgroup_and_xack(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = client.xgroup_create("{foo}1", "group1", "$", true).await?;
+  let _ = client.xgroup_create("{foo}2", "group1", "$", true).await?;
+  let foo_ids = create_fake_data(&client, "{foo}1", 2).await?;
+  let bar_ids = create_fake_data(&client, "{foo}2", 1).await?;
+
+  let result = client
+    .xreadgroup(
+      "group1",
+      "consumer1",
+      None,
+      None,
+      false,
+      vec!["{foo}1", "{foo}2"],
+      vec![XID::NewInGroup, XID::NewInGroup],
+    )
+    .await?;
+  assert_eq!(result.get("{foo}1").unwrap().len(), foo_ids.len());
+  assert_eq!(result.get("{foo}2").unwrap()[0].id, bar_ids[0]);
+
+  let summary = client.xpending("{foo}1", "group1").await?;
+  assert_eq!(summary.count, 2);
+  assert_eq!(summary.min_id, Some(foo_ids[0].clone()));
+  assert_eq!(summary.consumers, vec![("consumer1".to_owned(), 2)]);
+
+  let acked: u64 = client.xack("{foo}1", "group1", foo_ids.clone()).await?;
+  assert_eq!(acked, 2);
+  let summary = client.xpending("{foo}1", "group1").await?;
+  assert_eq!(summary.count, 0);
+  assert!(summary.min_id.is_none());
+  Ok(())
+}
+
+pub async fn should_xpending_ext_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = client.xgroup_create("foo", "group1", "$", true).await?;
+  let ids = create_fake_data(&client, "foo", 2).await?;
+  let _ = client
+    .xreadgroup("group1", "consumer1", None, None, false, "foo", ">")
+    .await?;
+
+  let pending = client.xpending_ext("foo", "group1", None, "-", "+", 10, None).await?;
+  assert_eq!(pending.len(), 2);
+  assert_eq!(pending[0].id, ids[0]);
+  assert_eq!(pending[0].consumer, "consumer1");
+  assert_eq!(pending[0].delivered, 1);
+
+  let pending = client
+    .xpending_ext("foo", "group1", None, "-", "+", 10, Some("consumer2".into()))
+    .await?;
+  assert!(pending.is_empty());
+  Ok(())
+}
+
+async fn create_pending_entries(client: &RedisClient, key: &str, count: u64) -> Result<Vec<String>, RedisError> {
+  let _ = client.xgroup_create(key, "group1", "$", true).await?;
+  let ids = create_fake_data(client, key, count).await?;
+  let _ = client
+    .xreadgroup("group1", "consumer1", None, None, false, key, ">")
+    .await?;
+
+  Ok(ids)
+}
+
+pub async fn should_xclaim_entries(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_pending_entries(&client, "foo", 2).await?;
+
+  let options = XClaimOptions {
+    retry_count: Some(5),
+    ..Default::default()
+  };
+  let entries = client
+    .xclaim("foo", "group1", "consumer2", 0, ids[0].as_str(), options)
+    .await?;
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].id, ids[0]);
+
+  let claimed = client
+    .xclaim_justid("foo", "group1", "consumer2", 0, ids.clone(), XClaimOptions::default())
+    .await?;
+  assert_eq!(claimed, ids);
+
+  let pending = client
+    .xpending_ext("foo", "group1", None, "-", "+", 10, Some("consumer2".into()))
+    .await?;
+  assert_eq!(pending.len(), 2);
+  assert_eq!(pending[0].delivered, 5);
+  Ok(())
+}
+
+pub async fn should_xautoclaim_entries(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_pending_entries(&client, "foo", 3).await?;
+
+  let mut claimed = Vec::new();
+  let mut start = "0-0".to_owned();
+  loop {
+    let result = client
+      .xautoclaim("foo", "group1", "consumer2", 0, start.as_str(), Some(1))
+      .await?;
+    claimed.extend(result.claimed.into_iter().map(|entry| entry.id));
+
+    if result.next_id == "0-0" {
+      break;
+    }
+    start = result.next_id;
+  }
+  assert_eq!(claimed, ids);
+
+  let result = client
+    .xautoclaim_justid("foo", "group1", "consumer1", 0, "0-0", None)
+    .await?;
+  assert!(result.is_complete());
+  assert_eq!(result.claimed, ids);
+  Ok(())
+}
+
+pub async fn should_xinfo_stream(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_pending_entries(&client, "foo", 2).await?;
+
+  let info = client.xinfo_stream("foo").await?;
+  assert_eq!(info.length, 2);
+  assert_eq!(info.groups, 1);
+  assert_eq!(info.last_generated_id, ids[1]);
+  assert_eq!(info.first_entry.unwrap().id, ids[0]);
+  assert_eq!(info.last_entry.unwrap().id, ids[1]);
+
+  let info = client.xinfo_stream_full("foo", None).await?;
+  assert_eq!(info.length, 2);
+  assert_eq!(info.entries.len(), 2);
+  assert_eq!(info.groups[0].name, "group1");
+  assert_eq!(info.groups[0].pel_count, 2);
+  assert_eq!(info.groups[0].consumers[0].name, "consumer1");
+  assert_eq!(info.groups[0].consumers[0].pending.len(), 2);
+  Ok(())
+}
+
+pub async fn should_xinfo_groups_and_consumers(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_pending_entries(&client, "foo", 2).await?;
+
+  let groups = client.xinfo_groups("foo").await?;
+  assert_eq!(groups.len(), 1);
+  assert_eq!(groups[0].name, "group1");
+  assert_eq!(groups[0].consumers, 1);
+  assert_eq!(groups[0].pending, 2);
+  assert_eq!(groups[0].last_delivered_id, ids[1]);
+
+  let consumers = client.xinfo_consumers("foo", "group1").await?;
+  assert_eq!(consumers.len(), 1);
+  assert_eq!(consumers[0].name, "consumer1");
+  assert_eq!(consumers[0].pending, 2);
+  Ok(())
+}
+
+pub async fn should_xread_on_dedicated_connection(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  config.blocking = Blocking::Error;
+  config.dedicated_blocking_streams = true;
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let writer = client.clone();
+  let jh = tokio::spawn(async move {
+    sleep(Duration::from_secs(1)).await;
+
+    // the shared connection is not blocked, so this does not return a `Blocking::Error` error
+    let _: () = writer.ping().await?;
+    let id: String = writer.xadd("foo", false, None, "*", ("a", "b".into())).await?;
+    Ok::<_, RedisError>(id)
+  });
+
+  let result = client.xread(None, Some(10_000), "foo", "$").await?;
+  let id = jh.await.unwrap()?;
+  assert_eq!(result.get("foo").unwrap()[0].id, id);
+
+  // the dedicated connection is reused by the next blocking read
+  let result = client.xread(None, Some(100), "foo", "$").await?;
+  assert!(result.is_empty());
+
+  let _ = client.quit().await;
+  Ok(())
+}
+
+pub async fn should_xtrim_values(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let ids = create_fake_data(&client, "foo", 5).await?;
+
+  let removed: u64 = client.xtrim("foo", (XCapKind::MaxLen, 3)).await?;
+  assert_eq!(removed, 2);
+  let removed: u64 = client.xtrim("foo", (XCapKind::MinID, ids[4].as_str())).await?;
+  assert_eq!(removed, 2);
+
+  let len: u64 = client.xlen("foo").await?;
+  assert_eq!(len, 1);
+  Ok(())
+}
+
+pub async fn should_trim_streams_on_interval(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = create_fake_data(&client, "foo", 10).await?;
+  let _ = client.take_trimmed_count();
+
+  let trim_client = client.clone();
+  let jh = tokio::spawn(async move {
+    trim_client
+      .enable_stream_trimming(Duration::from_millis(100), vec![("foo", (XCapKind::MaxLen, 2))])
+      .await
+  });
+  sleep(Duration::from_millis(500)).await;
+  jh.abort();
+
+  let len: u64 = client.xlen("foo").await?;
+  assert_eq!(len, 2);
+  assert_eq!(client.take_trimmed_count(), 8);
+  assert_eq!(client.read_trimmed_count(), 0);
+  Ok(())
+}
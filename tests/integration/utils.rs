@@ -70,6 +70,8 @@ where
       // TODO fix this so sentinel-tests can run without sentinel-auth
       username: None,
       password: Some(read_sentinel_password()),
+      #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+      tls: None,
     },
     pipeline,
     password: Some(read_redis_password()),
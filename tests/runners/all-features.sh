@@ -12,7 +12,7 @@ done
 
 # cant use all-features here or it'll run chaos monkey and then the tests will take forever
 cargo test --release --lib --tests --features \
-  "index-map network-logs pool-prefer-active enable-tls vendored-tls
+  "index-map network-logs pool-prefer-active enable-tls vendored-tls enable-rustls
   custom-reconnect-errors ignore-auth-error blocking-encoding full-tracing
-  reconnect-on-auth-error monitor metrics sentinel-client" \
+  reconnect-on-auth-error monitor metrics sentinel-client test-utils replica serde-json chrono uuid" \
   -- --test-threads=1 "$@"
\ No newline at end of file